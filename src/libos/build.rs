@@ -0,0 +1,14 @@
+use std::env;
+use std::fs;
+use std::path::Path;
+
+// The LibOS config built into the enclave is libos.conf by default, or the
+// file given by OCCLUM_LIBOS_CONFIG, e.g., the config of the tests
+fn main() {
+    let config_path =
+        env::var("OCCLUM_LIBOS_CONFIG").unwrap_or_else(|_| "libos.conf".to_string());
+    println!("cargo:rerun-if-env-changed=OCCLUM_LIBOS_CONFIG");
+    println!("cargo:rerun-if-changed={}", config_path);
+    let out_path = Path::new(&env::var("OUT_DIR").unwrap()).join("libos.conf");
+    fs::copy(&config_path, &out_path).expect("Failed to copy the LibOS config");
+}
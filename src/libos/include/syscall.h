@@ -6,6 +6,10 @@
 
 struct iovec;
 struct timeval;
+struct timespec;
+struct pollfd;
+struct epoll_event;
+struct stat;
 
 #ifdef __cplusplus
 extern "C" {
@@ -17,10 +21,46 @@ extern ssize_t occlum_read(int fd, void* buf, size_t size);
 extern ssize_t occlum_readv(int fd, struct iovec* iov, int count);
 extern ssize_t occlum_write(int fd, const void* buf, size_t size);
 extern ssize_t occlum_writev(int fd, const struct iovec* iov, int count);
+extern ssize_t occlum_pread(int fd, void* buf, size_t size, off_t offset);
+extern ssize_t occlum_pwrite(int fd, const void* buf, size_t size, off_t offset);
+extern ssize_t occlum_preadv(int fd, struct iovec* iov, int count, off_t offset);
+extern ssize_t occlum_pwritev(int fd, const struct iovec* iov, int count, off_t offset);
+extern ssize_t occlum_sendfile(int out_fd, int in_fd, off_t* offset, size_t count);
+extern ssize_t occlum_splice(int fd_in, off_t* off_in, int fd_out, off_t* off_out, size_t len,
+                             unsigned int flags);
+extern ssize_t occlum_tee(int fd_in, int fd_out, size_t len, unsigned int flags);
+extern ssize_t occlum_copy_file_range(int fd_in, off_t* off_in, int fd_out, off_t* off_out,
+                                      size_t len, unsigned int flags);
 extern off_t occlum_lseek(int fd, off_t offset, int whence);
 
+extern int occlum_fsync(int fd);
+extern int occlum_fdatasync(int fd);
 extern int occlum_sync(void);
 
+extern int occlum_mkdir(const char* path, int mode);
+extern int occlum_rmdir(const char* path);
+extern int occlum_getdents64(int fd, void* buf, unsigned int buf_size);
+extern int occlum_chdir(const char* path);
+extern int occlum_fchdir(int fd);
+extern int occlum_getcwd(char* buf, size_t buf_size);
+
+extern int occlum_stat(const char* path, struct stat* buf);
+extern int occlum_fstat(int fd, struct stat* buf);
+extern int occlum_lstat(const char* path, struct stat* buf);
+extern int occlum_fstatat(int dirfd, const char* path, struct stat* buf, int flags);
+
+extern int occlum_truncate(const char* path, off_t len);
+extern int occlum_ftruncate(int fd, off_t len);
+extern int occlum_fallocate(int fd, int mode, off_t offset, off_t len);
+
+extern int occlum_unlink(const char* path);
+extern int occlum_unlinkat(int dirfd, const char* path, int flags);
+extern int occlum_rename(const char* old_path, const char* new_path);
+extern int occlum_renameat(int old_dirfd, const char* old_path, int new_dirfd, const char* new_path);
+extern int occlum_link(const char* old_path, const char* new_path);
+extern int occlum_symlink(const char* target, const char* link_path);
+extern ssize_t occlum_readlink(const char* path, char* buf, size_t buf_size);
+
 extern int occlum_pipe(int fds[2]);
 extern int occlum_pipe2(int fds[2], int flags);
 
@@ -28,6 +68,44 @@ extern int occlum_dup(int old_fd);
 extern int occlum_dup2(int old_fd, int new_fd);
 extern int occlum_dup3(int old_fd, int new_fd, int flags);
 
+extern int occlum_fcntl(int fd, int cmd, unsigned long arg);
+extern int occlum_ioctl(int fd, unsigned int cmd, void* argp);
+
+extern int occlum_poll(struct pollfd* fds, unsigned long nfds, int timeout);
+extern int occlum_ppoll(struct pollfd* fds, unsigned long nfds, const struct timespec* timeout,
+                        const void* sigmask);
+extern int occlum_select(int nfds, void* readfds, void* writefds, void* exceptfds,
+                         const struct timeval* timeout);
+
+extern int occlum_eventfd(unsigned int init_val);
+extern int occlum_eventfd2(unsigned int init_val, int flags);
+
+extern int occlum_socket(int domain, int type, int protocol);
+extern int occlum_socketpair(int domain, int type, int protocol, int* sv);
+extern int occlum_bind(int fd, const void* addr, unsigned int addr_len);
+extern int occlum_listen(int fd, int backlog);
+extern int occlum_accept4(int fd, void* addr, unsigned int* addr_len, int flags);
+extern int occlum_connect(int fd, const void* addr, unsigned int addr_len);
+extern int occlum_getsockname(int fd, void* addr, unsigned int* addr_len);
+extern int occlum_getpeername(int fd, void* addr, unsigned int* addr_len);
+extern ssize_t occlum_sendto(int fd, const void* buf, size_t len, int flags,
+                             const void* addr, unsigned int addr_len);
+extern ssize_t occlum_recvfrom(int fd, void* buf, size_t len, int flags,
+                               void* addr, unsigned int* addr_len);
+extern ssize_t occlum_sendmsg(int fd, const void* msg, int flags);
+extern ssize_t occlum_recvmsg(int fd, void* msg, int flags);
+extern int occlum_getsockopt(int fd, int level, int name, void* value,
+                             unsigned int* value_len);
+extern int occlum_setsockopt(int fd, int level, int name, const void* value,
+                             unsigned int value_len);
+extern int occlum_shutdown(int fd, int how);
+
+extern int occlum_epoll_create(int size);
+extern int occlum_epoll_create1(int flags);
+extern int occlum_epoll_ctl(int epfd, int op, int fd, const struct epoll_event* event);
+extern int occlum_epoll_pwait(int epfd, struct epoll_event* events, int maxevents, int timeout,
+                              const void* sigmask);
+
 extern int occlum_spawn(int* child_pid, const char* path,
                         const char** argv, const char** envp,
                         void* file_actions);
@@ -38,6 +116,7 @@ extern unsigned int occlum_getppid(void);
 
 extern void *occlum_mmap(void *addr, size_t length, int prot, int flags, int fd, off_t offset);
 extern int occlum_munmap(void *addr, size_t length);
+extern int occlum_msync(void *addr, size_t length, int flags);
 extern void *occlum_mremap(void *old_address, size_t old_size, size_t new_size, int flags, void *new_address);
 extern void* occlum_brk(void* addr);
 
@@ -6,6 +6,7 @@
 #define SYS__llseek __NR__llseek
 #define SYS__newselect __NR__newselect
 #define SYS__sysctl __NR__sysctl
+#define SYS_accept __NR_accept
 #define SYS_accept4 __NR_accept4
 #define SYS_access __NR_access
 #define SYS_acct __NR_acct
@@ -33,6 +34,7 @@
 #define SYS_clone __NR_clone
 #define SYS_close __NR_close
 #define SYS_connect __NR_connect
+#define SYS_copy_file_range __NR_copy_file_range
 #define SYS_creat __NR_creat
 #define SYS_create_module __NR_create_module
 #define SYS_delete_module __NR_delete_module
@@ -192,6 +194,7 @@
 #define SYS_munmap __NR_munmap
 #define SYS_name_to_handle_at __NR_name_to_handle_at
 #define SYS_nanosleep __NR_nanosleep
+#define SYS_newfstatat __NR_newfstatat
 #define SYS_nfsservctl __NR_nfsservctl
 #define SYS_nice __NR_nice
 #define SYS_oldfstat __NR_oldfstat
@@ -710,6 +713,7 @@
 #define __NR_userfaultfd 323
 #define __NR_membarrier 324
 #define __NR_mlock2 325
+#define __NR_copy_file_range 326
 
 #define __NR_spawn 360
 
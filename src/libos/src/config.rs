@@ -0,0 +1,194 @@
+use super::*;
+use fs::SgxKeySource;
+
+/// The configuration of the LibOS, which is built into the enclave
+#[derive(Debug)]
+pub struct Config {
+    pub mounts: Vec<ConfigMount>,
+    pub keys: Vec<ConfigKey>,
+    /// The max total size of the page cache of protected files
+    pub page_cache_size: Option<usize>,
+}
+
+#[derive(Debug)]
+pub struct ConfigMount {
+    pub type_: ConfigMountFsType,
+    pub mount_point: String,
+    pub options: ConfigMountOptions,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ConfigMountFsType {
+    SgxFS,
+    TmpFS,
+    HostFS,
+    DevFS,
+}
+
+#[derive(Debug, Default)]
+pub struct ConfigMountOptions {
+    pub root: Option<String>,
+    pub size: Option<usize>,
+    pub manifest: Option<String>,
+    pub manifest_hash: Option<sgx_sha256_hash_t>,
+    pub key: Option<SgxKeySource>,
+}
+
+/// The key of the protected files under a path prefix
+#[derive(Debug)]
+pub struct ConfigKey {
+    pub path_prefix: String,
+    pub source: SgxKeySource,
+}
+
+lazy_static! {
+    pub static ref LIBOS_CONFIG: Config = {
+        let config_str = include_str!(concat!(env!("OUT_DIR"), "/libos.conf"));
+        match parse_config(config_str) {
+            Ok(config) => config,
+            Err(_) => panic!("Failed to parse the LibOS config"),
+        }
+    };
+}
+
+fn parse_config(config_str: &str) -> Result<Config, Error> {
+    let mut mounts = Vec::new();
+    let mut keys = Vec::new();
+    let mut page_cache_size = None;
+    for line in config_str.lines() {
+        let line = match line.find('#') {
+            Some(comment_start) => &line[..comment_start],
+            None => line,
+        };
+        let mut tokens = line.split_whitespace();
+        let directive = match tokens.next() {
+            Some(directive) => directive,
+            None => continue,
+        };
+        let args: Vec<&str> = tokens.collect();
+        match directive {
+            "mount" => mounts.push(parse_mount(&args)?),
+            "key" => keys.push(parse_key(&args)?),
+            "page_cache" => page_cache_size = Some(parse_page_cache(&args)?),
+            _ => return errno!(EINVAL, "Unknown config directive"),
+        }
+    }
+    Ok(Config {
+        mounts,
+        keys,
+        page_cache_size,
+    })
+}
+
+fn parse_mount(args: &[&str]) -> Result<ConfigMount, Error> {
+    if args.len() < 2 || args.len() > 3 {
+        return errno!(EINVAL, "Invalid number of arguments for mount");
+    }
+
+    let type_ = match args[0] {
+        "sgxfs" => ConfigMountFsType::SgxFS,
+        "tmpfs" => ConfigMountFsType::TmpFS,
+        "hostfs" => ConfigMountFsType::HostFS,
+        "devfs" => ConfigMountFsType::DevFS,
+        _ => return errno!(EINVAL, "Unsupported file system type"),
+    };
+
+    let mount_point = args[1].to_owned();
+    if !mount_point.starts_with("/") {
+        return errno!(EINVAL, "Mount point must be an absolute path");
+    }
+
+    let mut options: ConfigMountOptions = Default::default();
+    if args.len() == 3 {
+        for option in args[2].split(',') {
+            let (key, value) = match option.find('=') {
+                Some(eq_i) => (&option[..eq_i], &option[eq_i + 1..]),
+                None => return errno!(EINVAL, "Invalid mount option"),
+            };
+            match key {
+                "root" => options.root = Some(value.to_owned()),
+                "size" => options.size = Some(parse_size(value)?),
+                "manifest" => options.manifest = Some(value.to_owned()),
+                "manifest_hash" => options.manifest_hash = Some(fs::parse_sha256_hash(value)?),
+                "key" => options.key = Some(parse_key_source(value)?),
+                _ => return errno!(EINVAL, "Unknown mount option"),
+            }
+        }
+    }
+
+    if options.key.is_some() && type_ != ConfigMountFsType::SgxFS {
+        return errno!(EINVAL, "The key option is only supported by sgxfs");
+    }
+    match type_ {
+        ConfigMountFsType::SgxFS => {
+            if options.root.is_none() {
+                return errno!(EINVAL, "The root option is required by sgxfs");
+            }
+        }
+        ConfigMountFsType::TmpFS | ConfigMountFsType::DevFS => {}
+        ConfigMountFsType::HostFS => {
+            if options.root.is_none()
+                || options.manifest.is_none()
+                || options.manifest_hash.is_none()
+            {
+                return errno!(
+                    EINVAL,
+                    "The root, manifest and manifest_hash options are required by hostfs"
+                );
+            }
+        }
+    }
+
+    Ok(ConfigMount {
+        type_,
+        mount_point,
+        options,
+    })
+}
+
+fn parse_key(args: &[&str]) -> Result<ConfigKey, Error> {
+    if args.len() != 2 {
+        return errno!(EINVAL, "Invalid number of arguments for key");
+    }
+    let path_prefix = args[0].to_owned();
+    if !path_prefix.starts_with("/") {
+        return errno!(EINVAL, "Path prefix of key must be an absolute path");
+    }
+    let source = parse_key_source(args[1])?;
+    Ok(ConfigKey {
+        path_prefix,
+        source,
+    })
+}
+
+fn parse_page_cache(args: &[&str]) -> Result<usize, Error> {
+    if args.len() != 1 {
+        return errno!(EINVAL, "Invalid number of arguments for page_cache");
+    }
+    parse_size(args[0])
+}
+
+fn parse_key_source(source_str: &str) -> Result<SgxKeySource, Error> {
+    match source_str {
+        "zero" => Ok(SgxKeySource::Zero),
+        "mrenclave" => Ok(SgxKeySource::MrEnclave),
+        "mrsigner" => Ok(SgxKeySource::MrSigner),
+        "boot" => Ok(SgxKeySource::Boot),
+        _ => errno!(EINVAL, "Unknown key source"),
+    }
+}
+
+/// Parse a size like "4096", "64K", "16M" or "1G"
+fn parse_size(size_str: &str) -> Result<usize, Error> {
+    let (num_str, unit) = match size_str.chars().last() {
+        Some('K') | Some('k') => (&size_str[..size_str.len() - 1], 1024),
+        Some('M') | Some('m') => (&size_str[..size_str.len() - 1], 1024 * 1024),
+        Some('G') | Some('g') => (&size_str[..size_str.len() - 1], 1024 * 1024 * 1024),
+        _ => (size_str, 1),
+    };
+    let num = num_str
+        .parse::<usize>()
+        .map_err(|_| Error::new(EINVAL, "Invalid size"))?;
+    num.checked_mul(unit)
+        .ok_or_else(|| Error::new(EINVAL, "Size is too large"))
+}
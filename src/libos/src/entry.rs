@@ -34,6 +34,17 @@ pub extern "C" fn libos_run() -> i32 {
     .unwrap_or(EXIT_STATUS_INTERNAL_ERROR)
 }
 
+/// Supply the key of the protected files whose key source is "boot"
+#[no_mangle]
+pub extern "C" fn libos_set_sgxfs_key(key_buf: *const uint8_t) -> i32 {
+    let mut key: sgx_key_128bit_t = [0 as uint8_t; 16];
+    key.copy_from_slice(unsafe { std::slice::from_raw_parts(key_buf, 16) });
+    match fs::set_sgxfs_boot_key(&key) {
+        Ok(()) => 0,
+        Err(_) => EXIT_STATUS_INTERNAL_ERROR,
+    }
+}
+
 // Use 127 as a special value to indicate internal error from libos, not from
 // user programs, although it is completely ok for a user program to return 127.
 const EXIT_STATUS_INTERNAL_ERROR: i32 = 127;
@@ -64,6 +75,11 @@ fn parse_arguments(
 // TODO: make sure do_boot can only be called once
 fn do_boot(path_str: &str, argv: &Vec<CString>) -> Result<(), Error> {
     util::mpx_util::mpx_enable()?;
+    let config = &config::LIBOS_CONFIG;
+    fs::mount_from_config(&config.mounts, &config.keys)?;
+    if let Some(page_cache_size) = config.page_cache_size {
+        fs::set_page_cache_size(page_cache_size);
+    }
 
     let envp = std::vec::Vec::new();
     let file_actions = Vec::new();
@@ -78,12 +78,108 @@ pub enum Errno {
     EDEADLK = 35,
     ENAMETOOLONG = 36,
     ENOLCK = 37,
+    ENOSYS = 38,
+    ENOTEMPTY = 39,
+    ELOOP = 40,
+    ENOTSOCK = 88,
+    EDESTADDRREQ = 89,
+    EMSGSIZE = 90,
+    EPROTOTYPE = 91,
+    ENOPROTOOPT = 92,
+    EPROTONOSUPPORT = 93,
+    ESOCKTNOSUPPORT = 94,
+    EOPNOTSUPP = 95,
+    EAFNOSUPPORT = 97,
+    EADDRINUSE = 98,
+    EADDRNOTAVAIL = 99,
+    ENETDOWN = 100,
+    ENETUNREACH = 101,
+    ECONNABORTED = 103,
+    ECONNRESET = 104,
+    ENOBUFS = 105,
+    EISCONN = 106,
+    ENOTCONN = 107,
+    ETIMEDOUT = 110,
+    ECONNREFUSED = 111,
+    EHOSTUNREACH = 113,
+    EALREADY = 114,
+    EINPROGRESS = 115,
 }
 
 impl Errno {
     pub fn as_retval(&self) -> i32 {
         -(*self as i32)
     }
+
+    /// Convert an errno returned from the host into an Errno
+    pub fn from_errno(errno: i32) -> Errno {
+        match errno {
+            1 => EPERM,
+            2 => ENOENT,
+            3 => ESRCH,
+            4 => EINTR,
+            5 => EIO,
+            6 => ENXIO,
+            7 => E2BIG,
+            8 => ENOEXEC,
+            9 => EBADF,
+            10 => ECHILD,
+            11 => EAGAIN,
+            12 => ENOMEM,
+            13 => EACCES,
+            14 => EFAULT,
+            15 => ENOTBLK,
+            16 => EBUSY,
+            17 => EEXIST,
+            18 => EXDEV,
+            19 => ENODEV,
+            20 => ENOTDIR,
+            21 => EISDIR,
+            22 => EINVAL,
+            23 => ENFILE,
+            24 => EMFILE,
+            25 => ENOTTY,
+            26 => ETXTBSY,
+            27 => EFBIG,
+            28 => ENOSPC,
+            29 => ESPIPE,
+            30 => EROFS,
+            31 => EMLINK,
+            32 => EPIPE,
+            33 => EDOM,
+            34 => ERANGE,
+            35 => EDEADLK,
+            36 => ENAMETOOLONG,
+            37 => ENOLCK,
+            38 => ENOSYS,
+            39 => ENOTEMPTY,
+            40 => ELOOP,
+            88 => ENOTSOCK,
+            89 => EDESTADDRREQ,
+            90 => EMSGSIZE,
+            91 => EPROTOTYPE,
+            92 => ENOPROTOOPT,
+            93 => EPROTONOSUPPORT,
+            94 => ESOCKTNOSUPPORT,
+            95 => EOPNOTSUPP,
+            97 => EAFNOSUPPORT,
+            98 => EADDRINUSE,
+            99 => EADDRNOTAVAIL,
+            100 => ENETDOWN,
+            101 => ENETUNREACH,
+            103 => ECONNABORTED,
+            104 => ECONNRESET,
+            105 => ENOBUFS,
+            106 => EISCONN,
+            107 => ENOTCONN,
+            110 => ETIMEDOUT,
+            111 => ECONNREFUSED,
+            113 => EHOSTUNREACH,
+            114 => EALREADY,
+            115 => EINPROGRESS,
+            _ => EUNDEF,
+        }
+    }
 }
 
 impl fmt::Display for Errno {
@@ -130,6 +226,32 @@ impl fmt::Display for Errno {
                 Errno::EDEADLK => "Resource deadlock would occur",
                 Errno::ENAMETOOLONG => "File name too long",
                 Errno::ENOLCK => "No record locks available",
+                Errno::ENOSYS => "Function not implemented",
+                Errno::ENOTEMPTY => "Directory not empty",
+                Errno::ELOOP => "Too many symbolic links encountered",
+                Errno::ENOTSOCK => "Socket operation on non-socket",
+                Errno::EDESTADDRREQ => "Destination address required",
+                Errno::EMSGSIZE => "Message too long",
+                Errno::EPROTOTYPE => "Protocol wrong type for socket",
+                Errno::ENOPROTOOPT => "Protocol not available",
+                Errno::EPROTONOSUPPORT => "Protocol not supported",
+                Errno::ESOCKTNOSUPPORT => "Socket type not supported",
+                Errno::EOPNOTSUPP => "Operation not supported",
+                Errno::EAFNOSUPPORT => "Address family not supported by protocol",
+                Errno::EADDRINUSE => "Address already in use",
+                Errno::EADDRNOTAVAIL => "Cannot assign requested address",
+                Errno::ENETDOWN => "Network is down",
+                Errno::ENETUNREACH => "Network is unreachable",
+                Errno::ECONNABORTED => "Software caused connection abort",
+                Errno::ECONNRESET => "Connection reset by peer",
+                Errno::ENOBUFS => "No buffer space available",
+                Errno::EISCONN => "Transport endpoint is already connected",
+                Errno::ENOTCONN => "Transport endpoint is not connected",
+                Errno::ETIMEDOUT => "Connection timed out",
+                Errno::ECONNREFUSED => "Connection refused",
+                Errno::EHOSTUNREACH => "No route to host",
+                Errno::EALREADY => "Operation already in progress",
+                Errno::EINPROGRESS => "Operation now in progress",
                 _ => "Unknown error",
             },
         )
@@ -0,0 +1,286 @@
+use super::*;
+
+/// A file system of the built-in device files, i.e., null, zero, full, random
+/// and urandom
+#[derive(Debug)]
+pub struct DevFS;
+
+impl DevFS {
+    pub fn new() -> DevFS {
+        DevFS
+    }
+}
+
+impl FileSystem for DevFS {
+    fn root_inode(&self) -> INodeRef {
+        Arc::new(DevINode::Root)
+    }
+
+    fn sync(&self) -> Result<(), Error> {
+        // Nothing to write back
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DevType {
+    /// Discards all data written and reads nothing
+    Null,
+    /// Discards all data written and reads zeros
+    Zero,
+    /// Fails all writes with ENOSPC and reads zeros
+    Full,
+    /// Reads random bytes from the RNG of the CPU, through sgx_read_rand, so
+    /// the host cannot tamper with the randomness. Both random and urandom
+    /// never block.
+    Random,
+}
+
+// The devices, in the order they are listed
+const DEVICES: [(&str, DevType); 5] = [
+    ("null", DevType::Null),
+    ("zero", DevType::Zero),
+    ("full", DevType::Full),
+    ("random", DevType::Random),
+    ("urandom", DevType::Random),
+];
+
+/// Either the root directory or a device of DevFS
+#[derive(Clone, Copy, Debug)]
+pub enum DevINode {
+    Root,
+    // The index of the device in DEVICES
+    Device(usize),
+}
+
+impl DevINode {
+    fn get_ino(&self) -> u64 {
+        match *self {
+            DevINode::Root => 1,
+            DevINode::Device(dev_i) => dev_i as u64 + 2,
+        }
+    }
+
+    fn get_file_type(&self) -> FileType {
+        match *self {
+            DevINode::Root => FileType::Dir,
+            DevINode::Device(_) => FileType::CharDevice,
+        }
+    }
+
+    fn check_is_dir(&self) -> Result<(), Error> {
+        match *self {
+            DevINode::Root => Ok(()),
+            DevINode::Device(_) => errno!(ENOTDIR, "Not a directory"),
+        }
+    }
+}
+
+impl INode for DevINode {
+    fn open(&self, flags: u32) -> Result<FileRef, Error> {
+        let dev_type = match *self {
+            DevINode::Root => return errno!(EISDIR, "Is a directory"),
+            DevINode::Device(dev_i) => DEVICES[dev_i].1,
+        };
+        let file_ref: Arc<Box<File>> = Arc::new(Box::new(DevFile {
+            inode: *self,
+            dev_type,
+            status_flags: StatusFlags::new(flags),
+        }));
+        Ok(file_ref)
+    }
+
+    fn find(&self, name: &str) -> Result<INodeRef, Error> {
+        self.check_is_dir()?;
+        match DEVICES.iter().position(|&(dev_name, _)| dev_name == name) {
+            Some(dev_i) => Ok(Arc::new(DevINode::Device(dev_i))),
+            None => errno!(ENOENT, "No such device"),
+        }
+    }
+
+    fn create(&self, name: &str, mode: u32) -> Result<INodeRef, Error> {
+        errno!(EPERM, "DevFS does not support creating files")
+    }
+
+    fn mkdir(&self, name: &str, mode: u32) -> Result<INodeRef, Error> {
+        errno!(EPERM, "DevFS does not support creating directories")
+    }
+
+    fn rmdir(&self, name: &str) -> Result<(), Error> {
+        // All the entries are devices
+        self.find(name)?;
+        errno!(ENOTDIR, "Not a directory")
+    }
+
+    fn list(&self) -> Result<Vec<DirEntry>, Error> {
+        self.check_is_dir()?;
+        Ok(DEVICES
+            .iter()
+            .enumerate()
+            .map(|(dev_i, &(dev_name, _))| DirEntry {
+                ino: DevINode::Device(dev_i).get_ino(),
+                type_: FileType::CharDevice,
+                name: dev_name.to_owned(),
+            })
+            .collect())
+    }
+
+    fn get_type(&self) -> Result<FileType, Error> {
+        Ok(self.get_file_type())
+    }
+
+    fn metadata(&self) -> Result<Metadata, Error> {
+        let (mode, nlinks) = match *self {
+            DevINode::Root => (0o755, 2),
+            DevINode::Device(_) => (0o666, 1),
+        };
+        Ok(Metadata {
+            dev: 0,
+            ino: self.get_ino(),
+            size: 0,
+            type_: self.get_file_type(),
+            mode,
+            nlinks,
+            atime: Default::default(),
+            mtime: Default::default(),
+            ctime: Default::default(),
+        })
+    }
+
+    fn unlink(&self, name: &str) -> Result<(), Error> {
+        errno!(EPERM, "DevFS does not support removing files")
+    }
+
+    fn link(&self, name: &str, target: &INodeRef) -> Result<(), Error> {
+        errno!(EPERM, "DevFS does not support creating files")
+    }
+
+    fn rename(&self, old_name: &str, target_dir: &INodeRef, new_name: &str) -> Result<(), Error> {
+        errno!(EPERM, "DevFS does not support renaming files")
+    }
+
+    fn symlink(&self, name: &str, target: &str) -> Result<INodeRef, Error> {
+        errno!(EPERM, "DevFS does not support creating files")
+    }
+
+    fn read_link(&self) -> Result<String, Error> {
+        errno!(EINVAL, "Not a symbolic link")
+    }
+
+    fn as_any(&self) -> &Any {
+        self
+    }
+}
+
+/// An opened device file
+#[derive(Debug)]
+pub struct DevFile {
+    inode: DevINode,
+    dev_type: DevType,
+    status_flags: StatusFlags,
+}
+
+impl DevFile {
+    fn do_read(&self, buf: &mut [u8]) -> Result<usize, Error> {
+        match self.dev_type {
+            DevType::Null => Ok(0),
+            DevType::Zero | DevType::Full => {
+                for byte in buf.iter_mut() {
+                    *byte = 0;
+                }
+                Ok(buf.len())
+            }
+            DevType::Random => {
+                let sgx_status = unsafe { sgx_read_rand(buf.as_mut_ptr(), buf.len()) };
+                if sgx_status != sgx_status_t::SGX_SUCCESS {
+                    return errno!(EIO, "Failed to generate random bytes");
+                }
+                Ok(buf.len())
+            }
+        }
+    }
+
+    fn do_write(&self, buf: &[u8]) -> Result<usize, Error> {
+        match self.dev_type {
+            DevType::Full => errno!(ENOSPC, "No space left on /dev/full"),
+            // The data written to random devices is not mixed into the RNG
+            DevType::Null | DevType::Zero | DevType::Random => Ok(buf.len()),
+        }
+    }
+}
+
+impl File for DevFile {
+    fn read(&self, buf: &mut [u8]) -> Result<usize, Error> {
+        self.do_read(buf)
+    }
+
+    fn write(&self, buf: &[u8]) -> Result<usize, Error> {
+        self.do_write(buf)
+    }
+
+    fn readv<'a, 'b>(&self, bufs: &'a mut [&'b mut [u8]]) -> Result<usize, Error> {
+        let mut total_bytes = 0;
+        for buf in bufs {
+            total_bytes += self.do_read(buf)?;
+        }
+        Ok(total_bytes)
+    }
+
+    fn writev<'a, 'b>(&self, bufs: &'a [&'b [u8]]) -> Result<usize, Error> {
+        let mut total_bytes = 0;
+        for buf in bufs {
+            total_bytes += self.do_write(buf)?;
+        }
+        Ok(total_bytes)
+    }
+
+    fn read_at(&self, offset: usize, buf: &mut [u8]) -> Result<usize, Error> {
+        self.do_read(buf)
+    }
+
+    fn write_at(&self, offset: usize, buf: &[u8]) -> Result<usize, Error> {
+        self.do_write(buf)
+    }
+
+    fn seek(&self, pos: SeekFrom) -> Result<off_t, Error> {
+        // Device files have no position, so seeking always succeeds
+        Ok(0)
+    }
+
+    fn set_len(&self, len: u64) -> Result<(), Error> {
+        errno!(EINVAL, "Cannot truncate a device file")
+    }
+
+    fn allocate(&self, offset: u64, len: u64) -> Result<(), Error> {
+        errno!(ENODEV, "Cannot allocate space for a device file")
+    }
+
+    fn metadata(&self) -> Result<Metadata, Error> {
+        self.inode.metadata()
+    }
+
+    fn get_status_flags(&self) -> Result<u32, Error> {
+        Ok(self.status_flags.get())
+    }
+
+    fn set_status_flags(&self, flags: u32) -> Result<(), Error> {
+        self.status_flags.set(flags);
+        Ok(())
+    }
+
+    fn poll(&self) -> Result<u32, Error> {
+        Ok(POLL_ALWAYS_READY)
+    }
+
+    fn poll_queue(&self) -> Option<&IoWaitQueue> {
+        None
+    }
+
+    fn ioctl(&self, cmd: &mut IoctlCmd) -> Result<(), Error> {
+        errno!(ENOTTY, "Device file does not support ioctl")
+    }
+
+    fn as_any(&self) -> &Any {
+        self
+    }
+}
@@ -0,0 +1,226 @@
+use super::*;
+use std::fmt;
+
+/// An opened directory, whose entries can be read by getdents64
+pub struct DirFile {
+    inode: INodeRef,
+    path: String,
+    inner: SgxMutex<DirFileInner>,
+    status_flags: StatusFlags,
+}
+
+struct DirFileInner {
+    pos: usize,
+    // A snapshot of the entries, taken when reading from the beginning
+    entries: Option<Vec<DirEntry>>,
+}
+
+impl DirFile {
+    pub fn new(inode: INodeRef, path: &str, flags: u32) -> DirFile {
+        DirFile {
+            inode,
+            path: path.to_owned(),
+            inner: SgxMutex::new(DirFileInner {
+                pos: 0,
+                entries: None,
+            }),
+            status_flags: StatusFlags::new(flags),
+        }
+    }
+
+    /// Get the absolute path of the directory
+    pub fn get_path(&self) -> &str {
+        &self.path
+    }
+
+    /// Read as many entries as the buffer can hold, in the format of
+    /// struct linux_dirent64, and return the number of bytes written
+    pub fn read_entries(&self, buf: &mut [u8]) -> Result<usize, Error> {
+        let mut inner_guard = self.inner.lock().unwrap();
+        let inner = &mut *inner_guard;
+        if inner.entries.is_none() {
+            inner.entries = Some(self.list_entries()?);
+        }
+        let entries = inner.entries.as_ref().unwrap();
+
+        let mut writer = DirentWriter::new(buf);
+        while inner.pos < entries.len() {
+            let next_pos = inner.pos + 1;
+            if !writer.write(&entries[inner.pos], next_pos)? {
+                break;
+            }
+            inner.pos = next_pos;
+        }
+        if writer.written_len() == 0 && inner.pos < entries.len() {
+            return errno!(EINVAL, "The buffer is too small for a directory entry");
+        }
+        Ok(writer.written_len())
+    }
+
+    fn list_entries(&self) -> Result<Vec<DirEntry>, Error> {
+        let parent_path = match self.path.rfind('/') {
+            Some(0) | None => "/",
+            Some(slash_i) => &self.path[..slash_i],
+        };
+        let parent_inode = vfs::lookup(parent_path)?;
+        let mut entries = vec![
+            DirEntry {
+                ino: self.inode.metadata()?.ino,
+                type_: FileType::Dir,
+                name: ".".to_owned(),
+            },
+            DirEntry {
+                ino: parent_inode.metadata()?.ino,
+                type_: FileType::Dir,
+                name: "..".to_owned(),
+            },
+        ];
+        entries.extend(self.inode.list()?);
+        Ok(entries)
+    }
+}
+
+impl File for DirFile {
+    fn read(&self, buf: &mut [u8]) -> Result<usize, Error> {
+        errno!(EISDIR, "Cannot read a directory")
+    }
+
+    fn write(&self, buf: &[u8]) -> Result<usize, Error> {
+        errno!(EBADF, "Cannot write a directory")
+    }
+
+    fn readv<'a, 'b>(&self, bufs: &'a mut [&'b mut [u8]]) -> Result<usize, Error> {
+        errno!(EISDIR, "Cannot read a directory")
+    }
+
+    fn writev<'a, 'b>(&self, bufs: &'a [&'b [u8]]) -> Result<usize, Error> {
+        errno!(EBADF, "Cannot write a directory")
+    }
+
+    fn read_at(&self, offset: usize, buf: &mut [u8]) -> Result<usize, Error> {
+        errno!(EISDIR, "Cannot read a directory")
+    }
+
+    fn write_at(&self, offset: usize, buf: &[u8]) -> Result<usize, Error> {
+        errno!(EBADF, "Cannot write a directory")
+    }
+
+    fn seek(&self, pos: SeekFrom) -> Result<off_t, Error> {
+        let mut inner = self.inner.lock().unwrap();
+        match pos {
+            SeekFrom::Start(offset) => {
+                inner.pos = offset as usize;
+                // Rewinding a directory makes new entries visible
+                if offset == 0 {
+                    inner.entries = None;
+                }
+            }
+            SeekFrom::Current(0) => {}
+            _ => return errno!(EINVAL, "Invalid seek position for a directory"),
+        }
+        Ok(inner.pos as off_t)
+    }
+
+    fn set_len(&self, len: u64) -> Result<(), Error> {
+        errno!(EISDIR, "Cannot truncate a directory")
+    }
+
+    fn allocate(&self, offset: u64, len: u64) -> Result<(), Error> {
+        errno!(EISDIR, "Cannot allocate space for a directory")
+    }
+
+    fn metadata(&self) -> Result<Metadata, Error> {
+        self.inode.metadata()
+    }
+
+    fn get_status_flags(&self) -> Result<u32, Error> {
+        Ok(self.status_flags.get())
+    }
+
+    fn set_status_flags(&self, flags: u32) -> Result<(), Error> {
+        self.status_flags.set(flags);
+        Ok(())
+    }
+
+    fn poll(&self) -> Result<u32, Error> {
+        Ok(POLL_ALWAYS_READY)
+    }
+
+    fn poll_queue(&self) -> Option<&IoWaitQueue> {
+        None
+    }
+
+    fn ioctl(&self, cmd: &mut IoctlCmd) -> Result<(), Error> {
+        errno!(ENOTTY, "Directory does not support ioctl")
+    }
+
+    fn as_any(&self) -> &Any {
+        self
+    }
+}
+
+impl Debug for DirFile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "DirFile {{ path: {} }}", self.path)
+    }
+}
+
+/// Writes directory entries into a buffer in the format of
+/// struct linux_dirent64
+struct DirentWriter<'a> {
+    buf: &'a mut [u8],
+    written_len: usize,
+}
+
+// The offset of d_name in struct linux_dirent64
+const DIRENT64_NAME_OFFSET: usize = 19;
+
+impl<'a> DirentWriter<'a> {
+    fn new(buf: &'a mut [u8]) -> DirentWriter<'a> {
+        DirentWriter {
+            buf,
+            written_len: 0,
+        }
+    }
+
+    /// Write an entry, or return false if there is no room for it
+    fn write(&mut self, entry: &DirEntry, next_pos: usize) -> Result<bool, Error> {
+        let name = entry.name.as_bytes();
+        let reclen = align_up(DIRENT64_NAME_OFFSET + name.len() + 1, 8);
+        if self.written_len + reclen > self.buf.len() {
+            return Ok(false);
+        }
+        if reclen > std::u16::MAX as usize {
+            return errno!(ENAMETOOLONG, "File name too long");
+        }
+
+        let dirent = &mut self.buf[self.written_len..self.written_len + reclen];
+        dirent[0..8].copy_from_slice(&entry.ino.to_ne_bytes());
+        dirent[8..16].copy_from_slice(&(next_pos as i64).to_ne_bytes());
+        dirent[16..18].copy_from_slice(&(reclen as u16).to_ne_bytes());
+        dirent[18] = dtype_from_file_type(entry.type_);
+        dirent[DIRENT64_NAME_OFFSET..DIRENT64_NAME_OFFSET + name.len()].copy_from_slice(name);
+        for byte in &mut dirent[DIRENT64_NAME_OFFSET + name.len()..] {
+            *byte = 0;
+        }
+        self.written_len += reclen;
+        Ok(true)
+    }
+
+    fn written_len(&self) -> usize {
+        self.written_len
+    }
+}
+
+fn dtype_from_file_type(type_: FileType) -> u8 {
+    match type_ {
+        FileType::File => DT_REG,
+        FileType::Dir => DT_DIR,
+        FileType::SymLink => DT_LNK,
+        FileType::CharDevice => DT_CHR,
+        FileType::BlockDevice => DT_BLK,
+        FileType::NamedPipe => DT_FIFO,
+        FileType::Socket => DT_SOCK,
+        FileType::Anonymous => DT_UNKNOWN,
+    }
+}
@@ -0,0 +1,423 @@
+use super::*;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Weak;
+
+// The events of epoll, which are the same as those of poll
+pub const EPOLLIN: u32 = POLLIN;
+pub const EPOLLPRI: u32 = POLLPRI;
+pub const EPOLLOUT: u32 = POLLOUT;
+pub const EPOLLERR: u32 = POLLERR;
+pub const EPOLLHUP: u32 = POLLHUP;
+pub const EPOLLRDNORM: u32 = POLLRDNORM;
+pub const EPOLLWRNORM: u32 = POLLWRNORM;
+pub const EPOLLRDHUP: u32 = 0x2000;
+// The flags of the events of epoll
+pub const EPOLLEXCLUSIVE: u32 = 1 << 28;
+pub const EPOLLWAKEUP: u32 = 1 << 29;
+pub const EPOLLONESHOT: u32 = 1 << 30;
+pub const EPOLLET: u32 = 1 << 31;
+
+// The events that epoll reports even if they are not requested
+const EPOLL_ALWAYS_REPORTED: u32 = EPOLLERR | EPOLLHUP;
+
+pub const EPOLL_CLOEXEC: u32 = O_CLOEXEC;
+
+// The max depth of epoll files watching epoll files, as on Linux
+const EPOLL_MAX_NESTS: usize = 4;
+
+lazy_static! {
+    /// Taken while an epoll file is added to another, so that two epoll files
+    /// cannot be added to each other at the same time
+    static ref EPOLL_NESTING_LOCK: SgxMutex<()> = SgxMutex::new(());
+}
+
+// The operations of epoll_ctl
+pub const EPOLL_CTL_ADD: i32 = 1;
+pub const EPOLL_CTL_DEL: i32 = 2;
+pub const EPOLL_CTL_MOD: i32 = 3;
+
+/// The struct epoll_event of Linux, which is packed on x86-64
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone, Default)]
+pub struct EpollEvent {
+    pub events: u32,
+    pub data: u64,
+}
+
+#[derive(Debug)]
+pub enum EpollCtlCmd {
+    Add(FileDesc, EpollEvent),
+    Del(FileDesc),
+    Mod(FileDesc, EpollEvent),
+}
+
+impl EpollCtlCmd {
+    pub fn from_raw(
+        op: i32,
+        fd: FileDesc,
+        event: Option<EpollEvent>,
+    ) -> Result<EpollCtlCmd, Error> {
+        let get_event = || event.ok_or_else(|| Error::new(EFAULT, "The event is null"));
+        Ok(match op {
+            EPOLL_CTL_ADD => EpollCtlCmd::Add(fd, get_event()?),
+            EPOLL_CTL_DEL => EpollCtlCmd::Del(fd),
+            EPOLL_CTL_MOD => EpollCtlCmd::Mod(fd, get_event()?),
+            _ => return errno!(EINVAL, "Unsupported epoll_ctl operation"),
+        })
+    }
+}
+
+/// An epoll file, which watches the events of the files in its interest list
+pub struct EpollFile {
+    entries: SgxMutex<BTreeMap<FileDesc, EpollEntry>>,
+    // Woken whenever the interest list changes, so that the threads waiting
+    // for the events also wait for the files added
+    ctl_queue: IoWaitQueue,
+    status_flags: StatusFlags,
+}
+
+struct EpollEntry {
+    // Not to keep the file open, so that it is closed as usual, after which
+    // the entry is removed
+    file: Weak<Box<File>>,
+    event: EpollEvent,
+    // Disabled after reporting the events with EPOLLONESHOT, until modified
+    is_disabled: bool,
+    // The number of changes of the file and the events last reported, by
+    // which EPOLLET tells whether the events are new
+    last_num_changes: Option<usize>,
+    last_events: u32,
+}
+
+impl EpollEntry {
+    fn new(file: &FileRef, event: EpollEvent) -> EpollEntry {
+        EpollEntry {
+            file: Arc::downgrade(file),
+            event,
+            is_disabled: false,
+            last_num_changes: None,
+            last_events: 0,
+        }
+    }
+
+    /// Get the events to report, and update the state of the entry if they
+    /// are reported
+    fn get_events(&mut self, file: &FileRef, is_reported: bool) -> Result<u32, Error> {
+        if self.is_disabled {
+            return Ok(0);
+        }
+        // Get the number of changes before the events, so that a change in
+        // between is reported again
+        let num_changes = file.get_num_changes();
+        let events = file.poll()? & (self.event.events | EPOLL_ALWAYS_REPORTED);
+        if self.event.events & EPOLLET != 0 {
+            let is_new = events != self.last_events || num_changes != self.last_num_changes;
+            if is_reported || events == 0 {
+                self.last_events = events;
+                self.last_num_changes = num_changes;
+            }
+            if !is_new {
+                return Ok(0);
+            }
+        }
+        if is_reported && events != 0 && self.event.events & EPOLLONESHOT != 0 {
+            self.is_disabled = true;
+        }
+        Ok(events)
+    }
+}
+
+impl EpollFile {
+    pub fn new(flags: u32) -> EpollFile {
+        EpollFile {
+            entries: SgxMutex::new(BTreeMap::new()),
+            ctl_queue: IoWaitQueue::new(),
+            status_flags: StatusFlags::new(O_RDWR | flags),
+        }
+    }
+
+    /// Add, modify or delete the entry of the fd, where `file` is the file
+    /// that the fd refers to
+    pub fn control(&self, cmd: &EpollCtlCmd, file: &FileRef) -> Result<(), Error> {
+        let mut entries = self.entries.lock().unwrap();
+        match *cmd {
+            EpollCtlCmd::Add(fd, event) => {
+                // The entry of a closed file can be replaced
+                let is_added = match entries.get(&fd) {
+                    Some(entry) => entry.file.upgrade().is_some(),
+                    None => false,
+                };
+                if is_added {
+                    return errno!(EEXIST, "The fd is already added");
+                }
+                entries.insert(fd, EpollEntry::new(file, event));
+            }
+            EpollCtlCmd::Del(fd) => {
+                entries
+                    .remove(&fd)
+                    .ok_or_else(|| Error::new(ENOENT, "The fd is not added"))?;
+            }
+            EpollCtlCmd::Mod(fd, event) => {
+                let entry = entries
+                    .get_mut(&fd)
+                    .ok_or_else(|| Error::new(ENOENT, "The fd is not added"))?;
+                *entry = EpollEntry::new(file, event);
+            }
+        }
+        drop(entries);
+        self.ctl_queue.wake_all();
+        Ok(())
+    }
+
+    /// Wait until some events are ready or the timeout elapses, and then
+    /// return the number of the events written into the buffer
+    pub fn wait(&self, events: &mut [EpollEvent], timeout_us: Option<u64>) -> Result<usize, Error> {
+        let deadline_us =
+            timeout_us.map(|timeout_us| time::do_gettimeofday().as_usec() + timeout_us as i64);
+        loop {
+            let num_ctl_changes = self.ctl_queue.get_num_changes();
+            let files = self.get_files();
+            let mut wait_queues: Vec<&IoWaitQueue> =
+                files.iter().filter_map(|file| file.poll_queue()).collect();
+            let has_unnotified = wait_queues.len() < files.len();
+            wait_queues.push(&self.ctl_queue);
+
+            let timeout_us = deadline_us.map(|deadline_us| {
+                let remaining_us = deadline_us - time::do_gettimeofday().as_usec();
+                if remaining_us > 0 {
+                    remaining_us as u64
+                } else {
+                    0
+                }
+            });
+            let result = wait_on_queues(&wait_queues, has_unnotified, timeout_us, || {
+                let num_events = self.get_ready_events(events)?;
+                if num_events > 0 || self.ctl_queue.get_num_changes() != num_ctl_changes {
+                    Ok(Some(num_events))
+                } else {
+                    Ok(None)
+                }
+            })?;
+            match result {
+                // The interest list has changed, so wait again for the new files
+                Some(0) => continue,
+                Some(num_events) => return Ok(num_events),
+                None => return Ok(0),
+            }
+        }
+    }
+
+    /// Check that this epoll file can watch `target`, which must not watch
+    /// this epoll file in turn, directly or through other epoll files
+    ///
+    /// The files watched by an epoll file are polled with its interest list
+    /// locked, so a cycle would deadlock.
+    fn check_nesting(&self, target: &EpollFile, depth: usize) -> Result<(), Error> {
+        if target as *const EpollFile == self as *const EpollFile {
+            return errno!(ELOOP, "Epoll files cannot watch each other");
+        }
+        if depth > EPOLL_MAX_NESTS {
+            return errno!(ELOOP, "Epoll files are nested too deeply");
+        }
+        for file in target.get_files() {
+            if let Some(nested_target) = file.as_any().downcast_ref::<EpollFile>() {
+                self.check_nesting(nested_target, depth + 1)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn get_files(&self) -> Vec<FileRef> {
+        let entries = self.entries.lock().unwrap();
+        entries
+            .values()
+            .filter_map(|entry| entry.file.upgrade())
+            .collect()
+    }
+
+    fn get_ready_events(&self, events: &mut [EpollEvent]) -> Result<usize, Error> {
+        let mut entries = self.entries.lock().unwrap();
+        let mut num_events = 0;
+        let mut closed_fds = Vec::new();
+        for (&fd, entry) in entries.iter_mut() {
+            if num_events == events.len() {
+                break;
+            }
+            let file = match entry.file.upgrade() {
+                Some(file) => file,
+                None => {
+                    closed_fds.push(fd);
+                    continue;
+                }
+            };
+            let ready_events = entry.get_events(&file, true)?;
+            if ready_events != 0 {
+                events[num_events] = EpollEvent {
+                    events: ready_events,
+                    data: entry.event.data,
+                };
+                num_events += 1;
+            }
+        }
+        for fd in closed_fds {
+            entries.remove(&fd);
+        }
+        Ok(num_events)
+    }
+}
+
+impl File for EpollFile {
+    fn read(&self, buf: &mut [u8]) -> Result<usize, Error> {
+        errno!(EINVAL, "Epoll file does not support read")
+    }
+
+    fn write(&self, buf: &[u8]) -> Result<usize, Error> {
+        errno!(EINVAL, "Epoll file does not support write")
+    }
+
+    fn readv<'a, 'b>(&self, bufs: &'a mut [&'b mut [u8]]) -> Result<usize, Error> {
+        errno!(EINVAL, "Epoll file does not support read")
+    }
+
+    fn writev<'a, 'b>(&self, bufs: &'a [&'b [u8]]) -> Result<usize, Error> {
+        errno!(EINVAL, "Epoll file does not support write")
+    }
+
+    fn read_at(&self, offset: usize, buf: &mut [u8]) -> Result<usize, Error> {
+        errno!(ESPIPE, "Epoll file does not support read_at")
+    }
+
+    fn write_at(&self, offset: usize, buf: &[u8]) -> Result<usize, Error> {
+        errno!(ESPIPE, "Epoll file does not support write_at")
+    }
+
+    fn seek(&self, pos: SeekFrom) -> Result<off_t, Error> {
+        errno!(ESPIPE, "Epoll file does not support seek")
+    }
+
+    fn set_len(&self, len: u64) -> Result<(), Error> {
+        errno!(EINVAL, "Epoll file does not support truncate")
+    }
+
+    fn allocate(&self, offset: u64, len: u64) -> Result<(), Error> {
+        errno!(ESPIPE, "Epoll file does not support allocate")
+    }
+
+    fn metadata(&self) -> Result<Metadata, Error> {
+        Ok(Metadata {
+            dev: 0,
+            ino: 0,
+            size: 0,
+            type_: FileType::Anonymous,
+            mode: 0o600,
+            nlinks: 1,
+            atime: Default::default(),
+            mtime: Default::default(),
+            ctime: Default::default(),
+        })
+    }
+
+    fn get_status_flags(&self) -> Result<u32, Error> {
+        Ok(self.status_flags.get())
+    }
+
+    fn set_status_flags(&self, flags: u32) -> Result<(), Error> {
+        self.status_flags.set(flags);
+        Ok(())
+    }
+
+    fn poll(&self) -> Result<u32, Error> {
+        // Readable if epoll_wait would report some events, which are checked
+        // without being reported
+        let mut entries = self.entries.lock().unwrap();
+        for entry in entries.values_mut() {
+            if let Some(file) = entry.file.upgrade() {
+                if entry.get_events(&file, false)? != 0 {
+                    return Ok(POLLIN | POLLRDNORM);
+                }
+            }
+        }
+        Ok(0)
+    }
+
+    fn poll_queue(&self) -> Option<&IoWaitQueue> {
+        // The changes of the files in the interest list are not notified
+        None
+    }
+
+    fn ioctl(&self, cmd: &mut IoctlCmd) -> Result<(), Error> {
+        errno!(ENOTTY, "Epoll file does not support ioctl")
+    }
+
+    fn as_any(&self) -> &Any {
+        self
+    }
+}
+
+impl Debug for EpollFile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "EpollFile")
+    }
+}
+
+unsafe impl Send for EpollFile {}
+unsafe impl Sync for EpollFile {}
+
+pub fn do_epoll_create1(flags: u32) -> Result<FileDesc, Error> {
+    if flags & !EPOLL_CLOEXEC != 0 {
+        return errno!(EINVAL, "Invalid flags for epoll_create1");
+    }
+    let current_ref = process::get_current();
+    let mut current = current_ref.lock().unwrap();
+    let epoll_file: FileRef = Arc::new(Box::new(EpollFile::new(0)));
+    let close_on_spawn = flags & EPOLL_CLOEXEC != 0;
+    Ok(current.get_files_mut().put(epoll_file, close_on_spawn))
+}
+
+pub fn do_epoll_ctl(epfd: FileDesc, cmd: &EpollCtlCmd) -> Result<(), Error> {
+    let fd = match *cmd {
+        EpollCtlCmd::Add(fd, _) | EpollCtlCmd::Del(fd) | EpollCtlCmd::Mod(fd, _) => fd,
+    };
+    let (epoll_file_ref, file_ref) = {
+        let current_ref = process::get_current();
+        let current = current_ref.lock().unwrap();
+        let file_table = current.get_files();
+        (file_table.get(epfd)?, file_table.get(fd)?)
+    };
+    let epoll_file = get_epoll_file(&epoll_file_ref)?;
+    // The fds may differ but refer to the same file, e.g., by dup
+    if Arc::ptr_eq(&epoll_file_ref, &file_ref) {
+        return errno!(EINVAL, "An epoll file cannot watch itself");
+    }
+    match file_ref.as_any().downcast_ref::<EpollFile>() {
+        Some(target) => {
+            let _nesting_guard = EPOLL_NESTING_LOCK.lock().unwrap();
+            if let EpollCtlCmd::Add(..) | EpollCtlCmd::Mod(..) = *cmd {
+                epoll_file.check_nesting(target, 1)?;
+            }
+            epoll_file.control(cmd, &file_ref)
+        }
+        None => epoll_file.control(cmd, &file_ref),
+    }
+}
+
+pub fn do_epoll_wait(
+    epfd: FileDesc,
+    events: &mut [EpollEvent],
+    timeout_us: Option<u64>,
+) -> Result<usize, Error> {
+    if events.is_empty() {
+        return errno!(EINVAL, "maxevents must be greater than zero");
+    }
+    let epoll_file_ref = get_file(epfd)?;
+    let epoll_file = get_epoll_file(&epoll_file_ref)?;
+    epoll_file.wait(events, timeout_us)
+}
+
+fn get_epoll_file(file_ref: &FileRef) -> Result<&EpollFile, Error> {
+    file_ref
+        .as_any()
+        .downcast_ref::<EpollFile>()
+        .ok_or_else(|| Error::new(EINVAL, "The fd is not an epoll file"))
+}
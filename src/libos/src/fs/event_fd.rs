@@ -0,0 +1,199 @@
+use super::*;
+use std::fmt;
+
+pub const EFD_SEMAPHORE: u32 = 0x1;
+pub const EFD_NONBLOCK: u32 = O_NONBLOCK;
+pub const EFD_CLOEXEC: u32 = O_CLOEXEC;
+
+// The counter can never reach u64::MAX
+const MAX_COUNTER: u64 = ::std::u64::MAX - 1;
+
+/// An eventfd, whose counter is added to by write and taken by read
+pub struct EventFd {
+    counter: SgxMutex<u64>,
+    // Read takes only one from the counter in the semaphore mode, instead of
+    // the whole counter
+    is_semaphore: bool,
+    wait_queue: IoWaitQueue,
+    status_flags: StatusFlags,
+}
+
+impl EventFd {
+    pub fn new(init_val: u64, flags: u32) -> EventFd {
+        EventFd {
+            counter: SgxMutex::new(init_val),
+            is_semaphore: flags & EFD_SEMAPHORE != 0,
+            wait_queue: IoWaitQueue::new(),
+            status_flags: StatusFlags::new(O_RDWR | (flags & EFD_NONBLOCK)),
+        }
+    }
+
+    /// Take from the counter, sleeping until it is not zero
+    fn read_counter(&self) -> Result<u64, Error> {
+        let is_nonblocking = self.status_flags.is_nonblocking();
+        let val = self.wait_queue.wait_until(is_nonblocking, || {
+            let mut counter = self.counter.lock().unwrap();
+            if *counter == 0 {
+                return Ok(None);
+            }
+            let val = if self.is_semaphore { 1 } else { *counter };
+            *counter -= val;
+            Ok(Some(val))
+        })?;
+        // So that the writers sleeping on an overflow can add to the counter
+        self.wait_queue.wake_all();
+        Ok(val)
+    }
+
+    /// Add to the counter, sleeping until it would not overflow
+    fn write_counter(&self, val: u64) -> Result<(), Error> {
+        if val > MAX_COUNTER {
+            return errno!(EINVAL, "The value is too large for eventfd");
+        }
+        let is_nonblocking = self.status_flags.is_nonblocking();
+        self.wait_queue.wait_until(is_nonblocking, || {
+            let mut counter = self.counter.lock().unwrap();
+            if *counter > MAX_COUNTER - val {
+                return Ok(None);
+            }
+            *counter += val;
+            Ok(Some(()))
+        })?;
+        if val > 0 {
+            self.wait_queue.wake_all();
+        }
+        Ok(())
+    }
+}
+
+const COUNTER_SIZE: usize = 8;
+
+impl File for EventFd {
+    fn read(&self, buf: &mut [u8]) -> Result<usize, Error> {
+        self.readv(&mut [buf])
+    }
+
+    fn write(&self, buf: &[u8]) -> Result<usize, Error> {
+        self.writev(&[buf])
+    }
+
+    fn readv<'a, 'b>(&self, bufs: &'a mut [&'b mut [u8]]) -> Result<usize, Error> {
+        let total_len: usize = bufs.iter().map(|buf| buf.len()).sum();
+        if total_len < COUNTER_SIZE {
+            return errno!(EINVAL, "The buffer is too small for eventfd");
+        }
+        let val_bytes = self.read_counter()?.to_ne_bytes();
+        let mut val_bytes = &val_bytes[..];
+        for buf in bufs {
+            let this_len = buf.len().min(val_bytes.len());
+            buf[..this_len].copy_from_slice(&val_bytes[..this_len]);
+            val_bytes = &val_bytes[this_len..];
+        }
+        Ok(COUNTER_SIZE)
+    }
+
+    fn writev<'a, 'b>(&self, bufs: &'a [&'b [u8]]) -> Result<usize, Error> {
+        let val_bytes: Vec<u8> = bufs
+            .iter()
+            .flat_map(|buf| buf.iter().cloned())
+            .take(COUNTER_SIZE)
+            .collect();
+        if val_bytes.len() < COUNTER_SIZE {
+            return errno!(EINVAL, "The buffer is too small for eventfd");
+        }
+        let mut val = [0; COUNTER_SIZE];
+        val.copy_from_slice(&val_bytes);
+        self.write_counter(u64::from_ne_bytes(val))?;
+        Ok(COUNTER_SIZE)
+    }
+
+    fn read_at(&self, offset: usize, buf: &mut [u8]) -> Result<usize, Error> {
+        errno!(ESPIPE, "Eventfd does not support read_at")
+    }
+
+    fn write_at(&self, offset: usize, buf: &[u8]) -> Result<usize, Error> {
+        errno!(ESPIPE, "Eventfd does not support write_at")
+    }
+
+    fn seek(&self, pos: SeekFrom) -> Result<off_t, Error> {
+        errno!(ESPIPE, "Eventfd does not support seek")
+    }
+
+    fn set_len(&self, len: u64) -> Result<(), Error> {
+        errno!(EINVAL, "Eventfd does not support truncate")
+    }
+
+    fn allocate(&self, offset: u64, len: u64) -> Result<(), Error> {
+        errno!(ESPIPE, "Eventfd does not support allocate")
+    }
+
+    fn metadata(&self) -> Result<Metadata, Error> {
+        Ok(Metadata {
+            dev: 0,
+            ino: 0,
+            size: 0,
+            type_: FileType::Anonymous,
+            mode: 0o600,
+            nlinks: 1,
+            atime: Default::default(),
+            mtime: Default::default(),
+            ctime: Default::default(),
+        })
+    }
+
+    fn get_status_flags(&self) -> Result<u32, Error> {
+        Ok(self.status_flags.get())
+    }
+
+    fn set_status_flags(&self, flags: u32) -> Result<(), Error> {
+        self.status_flags.set(flags);
+        Ok(())
+    }
+
+    fn poll(&self) -> Result<u32, Error> {
+        let counter = *self.counter.lock().unwrap();
+        let mut events = 0;
+        if counter > 0 {
+            events |= POLLIN | POLLRDNORM;
+        }
+        if counter < MAX_COUNTER {
+            events |= POLLOUT | POLLWRNORM;
+        }
+        Ok(events)
+    }
+
+    fn poll_queue(&self) -> Option<&IoWaitQueue> {
+        Some(&self.wait_queue)
+    }
+
+    fn ioctl(&self, cmd: &mut IoctlCmd) -> Result<(), Error> {
+        errno!(ENOTTY, "Eventfd does not support ioctl")
+    }
+
+    fn as_any(&self) -> &Any {
+        self
+    }
+}
+
+impl Debug for EventFd {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "EventFd {{ is_semaphore: {} }}", self.is_semaphore)
+    }
+}
+
+unsafe impl Send for EventFd {}
+unsafe impl Sync for EventFd {}
+
+pub fn do_eventfd2(init_val: u32, flags: u32) -> Result<FileDesc, Error> {
+    if flags & !(EFD_SEMAPHORE | EFD_NONBLOCK | EFD_CLOEXEC) != 0 {
+        return errno!(EINVAL, "Invalid flags for eventfd");
+    }
+    let current_ref = process::get_current();
+    let mut current = current_ref.lock().unwrap();
+    let event_fd: FileRef = Arc::new(Box::new(EventFd::new(
+        init_val as u64,
+        flags & (EFD_SEMAPHORE | EFD_NONBLOCK),
+    )));
+    let close_on_spawn = flags & EFD_CLOEXEC != 0;
+    Ok(current.get_files_mut().put(event_fd, close_on_spawn))
+}
@@ -1,34 +1,101 @@
+use super::page_cache::ProtectedFile;
 use super::*;
 use std;
 use std::borrow::BorrowMut;
 use std::fmt;
+use std::sync::atomic::{AtomicU32, Ordering};
 
 pub trait File: Debug + Sync + Send {
     fn read(&self, buf: &mut [u8]) -> Result<usize, Error>;
     fn write(&self, buf: &[u8]) -> Result<usize, Error>;
     fn readv<'a, 'b>(&self, bufs: &'a mut [&'b mut [u8]]) -> Result<usize, Error>;
     fn writev<'a, 'b>(&self, bufs: &'a [&'b [u8]]) -> Result<usize, Error>;
+    /// Read at the given offset without changing the file offset
+    fn read_at(&self, offset: usize, buf: &mut [u8]) -> Result<usize, Error>;
+    /// Write at the given offset without changing the file offset
+    fn write_at(&self, offset: usize, buf: &[u8]) -> Result<usize, Error>;
     fn seek(&self, pos: SeekFrom) -> Result<off_t, Error>;
+    /// Truncate or extend the file to the given length
+    fn set_len(&self, len: u64) -> Result<(), Error>;
+    /// Make sure the file is large enough to hold the given range
+    fn allocate(&self, offset: u64, len: u64) -> Result<(), Error>;
+    fn metadata(&self) -> Result<Metadata, Error>;
+    /// Get the access mode and the status flags of the open file
+    fn get_status_flags(&self) -> Result<u32, Error>;
+    /// Set the status flags that can be changed after open, i.e., O_APPEND
+    /// and O_NONBLOCK, while the others are ignored
+    fn set_status_flags(&self, flags: u32) -> Result<(), Error>;
+    /// Get the events of poll that are ready now, e.g., POLLIN and POLLOUT
+    fn poll(&self) -> Result<u32, Error>;
+    /// Get the queue that is woken whenever the ready events may change, or
+    /// None if the changes are not notified
+    fn poll_queue(&self) -> Option<&IoWaitQueue>;
+    /// Get the number of times that the ready events may have changed, by
+    /// which edge-triggered epoll tells whether the events are new, or None
+    /// if the changes are not counted
+    fn get_num_changes(&self) -> Option<usize> {
+        self.poll_queue()
+            .map(|wait_queue| wait_queue.get_num_changes())
+    }
+    /// Do an ioctl command that depends on the type of the file, or fail with
+    /// ENOTTY if not supported
+    fn ioctl(&self, cmd: &mut IoctlCmd) -> Result<(), Error>;
+    fn as_any(&self) -> &Any;
 }
 
 pub type FileRef = Arc<Box<File>>;
 
+/// The access mode and the status flags of an open file, which are shared by
+/// all the file descriptors duplicated from the same one
+#[derive(Debug)]
+pub struct StatusFlags {
+    flags: AtomicU32,
+}
+
+// The status flags that can be changed by fcntl
+const SETTABLE_STATUS_FLAGS: u32 = O_APPEND | O_NONBLOCK;
+
+impl StatusFlags {
+    pub fn new(open_flags: u32) -> StatusFlags {
+        StatusFlags {
+            flags: AtomicU32::new(open_flags & (O_ACCMODE | SETTABLE_STATUS_FLAGS)),
+        }
+    }
+
+    pub fn get(&self) -> u32 {
+        self.flags.load(Ordering::SeqCst)
+    }
+
+    pub fn set(&self, flags: u32) {
+        let access_mode = self.get() & O_ACCMODE;
+        let new_flags = access_mode | (flags & SETTABLE_STATUS_FLAGS);
+        self.flags.store(new_flags, Ordering::SeqCst);
+    }
+
+    pub fn is_append(&self) -> bool {
+        self.get() & O_APPEND != 0
+    }
+
+    pub fn is_nonblocking(&self) -> bool {
+        self.get() & O_NONBLOCK != 0
+    }
+}
+
 #[derive(Debug)]
 #[repr(C)]
 pub struct SgxFile {
     inner: SgxMutex<SgxFileInner>,
+    status_flags: StatusFlags,
 }
 
 impl SgxFile {
-    pub fn new(
-        file: Arc<SgxMutex<fs_impl::SgxFile>>,
-        is_readable: bool,
-        is_writable: bool,
-        is_append: bool,
-    ) -> Result<SgxFile, Error> {
-        if !is_readable && !is_writable {
-            return Err(Error::new(Errno::EINVAL, "Invalid permissions"));
-        }
+    pub fn new(file: Arc<SgxMutex<ProtectedFile>>, flags: u32) -> Result<SgxFile, Error> {
+        let (is_readable, is_writable) = match flags & O_ACCMODE {
+            O_RDONLY => (true, false),
+            O_WRONLY => (false, true),
+            O_RDWR => (true, true),
+            _ => return Err(Error::new(Errno::EINVAL, "Invalid permissions")),
+        };
 
         Ok(SgxFile {
             inner: SgxMutex::new(SgxFileInner {
@@ -36,10 +103,18 @@ impl SgxFile {
                 file: file,
                 is_readable,
                 is_writable,
-                is_append,
             }),
+            status_flags: StatusFlags::new(flags),
         })
     }
+
+    /// Write the data of the file that is cached in the enclave back to the
+    /// protected file
+    pub fn sync(&self) -> Result<(), Error> {
+        let mut inner_guard = self.inner.lock().unwrap();
+        let inner = inner_guard.borrow_mut();
+        inner.sync()
+    }
 }
 
 impl File for SgxFile {
@@ -52,7 +127,7 @@ impl File for SgxFile {
     fn write(&self, buf: &[u8]) -> Result<usize, Error> {
         let mut inner_guard = self.inner.lock().unwrap();
         let inner = inner_guard.borrow_mut();
-        inner.write(buf)
+        inner.write(buf, self.status_flags.is_append())
     }
 
     fn readv<'a, 'b>(&self, bufs: &'a mut [&'b mut [u8]]) -> Result<usize, Error> {
@@ -64,7 +139,19 @@ impl File for SgxFile {
     fn writev<'a, 'b>(&self, bufs: &'a [&'b [u8]]) -> Result<usize, Error> {
         let mut inner_guard = self.inner.lock().unwrap();
         let inner = inner_guard.borrow_mut();
-        inner.writev(bufs)
+        inner.writev(bufs, self.status_flags.is_append())
+    }
+
+    fn read_at(&self, offset: usize, buf: &mut [u8]) -> Result<usize, Error> {
+        let mut inner_guard = self.inner.lock().unwrap();
+        let inner = inner_guard.borrow_mut();
+        inner.read_at(offset, buf)
+    }
+
+    fn write_at(&self, offset: usize, buf: &[u8]) -> Result<usize, Error> {
+        let mut inner_guard = self.inner.lock().unwrap();
+        let inner = inner_guard.borrow_mut();
+        inner.write_at(offset, buf)
     }
 
     fn seek(&self, pos: SeekFrom) -> Result<off_t, Error> {
@@ -72,6 +159,49 @@ impl File for SgxFile {
         let inner = inner_guard.borrow_mut();
         inner.seek(pos)
     }
+
+    fn set_len(&self, len: u64) -> Result<(), Error> {
+        let mut inner_guard = self.inner.lock().unwrap();
+        let inner = inner_guard.borrow_mut();
+        inner.set_len(len as usize)
+    }
+
+    fn allocate(&self, offset: u64, len: u64) -> Result<(), Error> {
+        let mut inner_guard = self.inner.lock().unwrap();
+        let inner = inner_guard.borrow_mut();
+        inner.allocate(get_range_end(offset, len)?)
+    }
+
+    fn metadata(&self) -> Result<Metadata, Error> {
+        let mut inner_guard = self.inner.lock().unwrap();
+        let inner = inner_guard.borrow_mut();
+        inner.metadata()
+    }
+
+    fn get_status_flags(&self) -> Result<u32, Error> {
+        Ok(self.status_flags.get())
+    }
+
+    fn set_status_flags(&self, flags: u32) -> Result<(), Error> {
+        self.status_flags.set(flags);
+        Ok(())
+    }
+
+    fn poll(&self) -> Result<u32, Error> {
+        Ok(POLL_ALWAYS_READY)
+    }
+
+    fn poll_queue(&self) -> Option<&IoWaitQueue> {
+        None
+    }
+
+    fn ioctl(&self, cmd: &mut IoctlCmd) -> Result<(), Error> {
+        errno!(ENOTTY, "Regular file does not support ioctl")
+    }
+
+    fn as_any(&self) -> &Any {
+        self
+    }
 }
 
 #[derive(Clone)]
@@ -79,119 +209,122 @@ impl File for SgxFile {
 struct SgxFileInner {
     //    perms: FilePerms,
     pos: usize,
-    file: Arc<SgxMutex<fs_impl::SgxFile>>,
+    file: Arc<SgxMutex<ProtectedFile>>,
     is_readable: bool,
     is_writable: bool,
-    is_append: bool,
 }
 
 impl SgxFileInner {
-    pub fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+    pub fn write(&mut self, buf: &[u8], is_append: bool) -> Result<usize, Error> {
+        self.writev(&[buf], is_append)
+    }
+
+    pub fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        self.readv(&mut [buf])
+    }
+
+    pub fn write_at(&mut self, offset: usize, buf: &[u8]) -> Result<usize, Error> {
         if !self.is_writable {
-            return Err(Error::new(Errno::EINVAL, "File not writable"));
+            return Err(Error::new(Errno::EBADF, "File not writable"));
         }
 
         let mut file_guard = self.file.lock().unwrap();
         let file = file_guard.borrow_mut();
+        file.write_at(offset, buf)
+    }
 
-        let seek_pos = if !self.is_append {
-            SeekFrom::Start(self.pos as u64)
-        } else {
-            SeekFrom::End(0)
-        };
-        // TODO: recover from error
-        file.seek(seek_pos)
-            .map_err(|e| Error::new(Errno::EINVAL, "Failed to seek to a position"))?;
+    pub fn read_at(&mut self, offset: usize, buf: &mut [u8]) -> Result<usize, Error> {
+        if !self.is_readable {
+            return Err(Error::new(Errno::EBADF, "File not readable"));
+        }
 
-        let write_len = {
-            file.write(buf)
-                .map_err(|e| Error::new(Errno::EINVAL, "Failed to write"))?
-        };
+        let mut file_guard = self.file.lock().unwrap();
+        let file = file_guard.borrow_mut();
+        file.read_at(offset, buf)
+    }
 
-        if !self.is_append {
-            self.pos += write_len;
+    /// Change the position, which may be beyond the end of the file
+    pub fn seek(&mut self, pos: SeekFrom) -> Result<off_t, Error> {
+        let new_pos = match pos {
+            SeekFrom::Start(absolute_offset) => absolute_offset as i64,
+            SeekFrom::End(relative_offset) => self.get_size()? as i64 + relative_offset,
+            SeekFrom::Current(relative_offset) => self.pos as i64 + relative_offset,
+        };
+        if new_pos < 0 {
+            return Err(Error::new(Errno::EINVAL, "Invalid seek position"));
         }
-        Ok(write_len)
+        self.pos = new_pos as usize;
+        Ok(self.pos as off_t)
     }
 
-    pub fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
-        if !self.is_readable {
-            return Err(Error::new(Errno::EINVAL, "File not readable"));
+    pub fn set_len(&mut self, len: usize) -> Result<(), Error> {
+        if !self.is_writable {
+            return Err(Error::new(Errno::EINVAL, "File not writable"));
         }
 
         let mut file_guard = self.file.lock().unwrap();
         let file = file_guard.borrow_mut();
+        sgx_fs::set_protected_file_len(file, len)
+    }
 
-        let seek_pos = SeekFrom::Start(self.pos as u64);
-        file.seek(seek_pos)
-            .map_err(|e| Error::new(Errno::EINVAL, "Failed to seek to a position"))?;
+    pub fn allocate(&mut self, end: usize) -> Result<(), Error> {
+        if !self.is_writable {
+            return Err(Error::new(Errno::EBADF, "File not writable"));
+        }
 
-        let read_len = {
-            file.read(buf)
-                .map_err(|e| Error::new(Errno::EINVAL, "Failed to write"))?
-        };
+        let mut file_guard = self.file.lock().unwrap();
+        let file = file_guard.borrow_mut();
+        if end > file.get_size() {
+            sgx_fs::set_protected_file_len(file, end)?;
+        }
+        Ok(())
+    }
 
-        self.pos += read_len;
-        Ok(read_len)
+    pub fn get_size(&mut self) -> Result<usize, Error> {
+        let file_guard = self.file.lock().unwrap();
+        Ok(file_guard.get_size())
     }
 
-    pub fn seek(&mut self, pos: SeekFrom) -> Result<off_t, Error> {
+    pub fn metadata(&mut self) -> Result<Metadata, Error> {
+        let file_guard = self.file.lock().unwrap();
+        sgx_fs::get_protected_file_metadata(&file_guard)
+    }
+
+    /// Write the cached data of the file back to the protected file
+    pub fn sync(&mut self) -> Result<(), Error> {
         let mut file_guard = self.file.lock().unwrap();
         let file = file_guard.borrow_mut();
-
-        let pos = match pos {
-            SeekFrom::Start(absolute_offset) => pos,
-            SeekFrom::End(relative_offset) => pos,
-            SeekFrom::Current(relative_offset) => {
-                if relative_offset >= 0 {
-                    SeekFrom::Start((self.pos + relative_offset as usize) as u64)
-                } else {
-                    let backward_offset = (-relative_offset) as usize;
-                    if self.pos < backward_offset {
-                        // underflow
-                        return Err(Error::new(Errno::EINVAL, "Invalid seek position"));
-                    }
-                    SeekFrom::Start((self.pos - backward_offset) as u64)
-                }
-            }
-        };
-
-        self.pos = file
-            .seek(pos)
-            .map_err(|e| Error::new(Errno::EINVAL, "Failed to seek to a position"))?
-            as usize;
-        Ok(self.pos as off_t)
+        file.sync()
     }
 
-    pub fn writev<'a, 'b>(&mut self, bufs: &'a [&'b [u8]]) -> Result<usize, Error> {
+    pub fn writev<'a, 'b>(
+        &mut self,
+        bufs: &'a [&'b [u8]],
+        is_append: bool,
+    ) -> Result<usize, Error> {
         if !self.is_writable {
-            return Err(Error::new(Errno::EINVAL, "File not writable"));
+            return Err(Error::new(Errno::EBADF, "File not writable"));
         }
 
         let mut file_guard = self.file.lock().unwrap();
         let file = file_guard.borrow_mut();
 
-        let seek_pos = if !self.is_append {
-            SeekFrom::Start(self.pos as u64)
+        let offset = if !is_append {
+            self.pos
         } else {
-            SeekFrom::End(0)
+            file.get_size()
         };
-        file.seek(seek_pos)
-            .map_err(|e| Error::new(Errno::EINVAL, "Failed to seek to a position"))?;
 
         let mut total_bytes = 0;
         for buf in bufs {
-            match file.write(buf) {
+            match file.write_at(offset + total_bytes, buf) {
                 Ok(this_bytes) => {
                     total_bytes += this_bytes;
-                    if this_bytes < buf.len() {
-                        break;
-                    }
                 }
                 Err(e) => {
                     match total_bytes {
                         // a complete failure
-                        0 => return Err(Error::new(Errno::EINVAL, "Failed to write")),
+                        0 => return Err(e),
                         // a partially failure
                         _ => break,
                     }
@@ -199,25 +332,23 @@ impl SgxFileInner {
             }
         }
 
-        self.pos += total_bytes;
+        if !is_append {
+            self.pos += total_bytes;
+        }
         Ok(total_bytes)
     }
 
     fn readv<'a, 'b>(&mut self, bufs: &'a mut [&'b mut [u8]]) -> Result<usize, Error> {
         if !self.is_readable {
-            return Err(Error::new(Errno::EINVAL, "File not readable"));
+            return Err(Error::new(Errno::EBADF, "File not readable"));
         }
 
         let mut file_guard = self.file.lock().unwrap();
         let file = file_guard.borrow_mut();
 
-        let seek_pos = SeekFrom::Start(self.pos as u64);
-        file.seek(seek_pos)
-            .map_err(|e| Error::new(Errno::EINVAL, "Failed to seek to a position"))?;
-
         let mut total_bytes = 0;
         for buf in bufs {
-            match file.read(buf) {
+            match file.read_at(self.pos + total_bytes, buf) {
                 Ok(this_bytes) => {
                     total_bytes += this_bytes;
                     if this_bytes < buf.len() {
@@ -227,7 +358,7 @@ impl SgxFileInner {
                 Err(e) => {
                     match total_bytes {
                         // a complete failure
-                        0 => return Err(Error::new(Errno::EINVAL, "Failed to write")),
+                        0 => return Err(e),
                         // a partially failure
                         _ => break,
                     }
@@ -249,14 +380,37 @@ impl Debug for SgxFileInner {
     }
 }
 
+// The fds of stdin, stdout and stderr on the host, where the terminal
+// commands of ioctl are forwarded to
+const HOST_STDIN_FD: c_int = 0;
+const HOST_STDOUT_FD: c_int = 1;
+const HOST_STDERR_FD: c_int = 2;
+
+/// The metadata of stdin, stdout and stderr, which are treated as terminals
+fn tty_metadata() -> Metadata {
+    Metadata {
+        dev: 0,
+        ino: 0,
+        size: 0,
+        type_: FileType::CharDevice,
+        mode: 0o620,
+        nlinks: 1,
+        atime: Default::default(),
+        mtime: Default::default(),
+        ctime: Default::default(),
+    }
+}
+
 pub struct StdoutFile {
     inner: std::io::Stdout,
+    status_flags: StatusFlags,
 }
 
 impl StdoutFile {
     pub fn new() -> StdoutFile {
         StdoutFile {
             inner: std::io::stdout(),
+            status_flags: StatusFlags::new(O_WRONLY),
         }
     }
 }
@@ -304,9 +458,63 @@ impl File for StdoutFile {
         Err(Error::new(Errno::EBADF, "Stdout does not support read"))
     }
 
+    fn read_at(&self, offset: usize, buf: &mut [u8]) -> Result<usize, Error> {
+        Err(Error::new(Errno::ESPIPE, "Stdout does not support read_at"))
+    }
+
+    fn write_at(&self, offset: usize, buf: &[u8]) -> Result<usize, Error> {
+        Err(Error::new(
+            Errno::ESPIPE,
+            "Stdout does not support write_at",
+        ))
+    }
+
     fn seek(&self, seek_pos: SeekFrom) -> Result<off_t, Error> {
         Err(Error::new(Errno::ESPIPE, "Stdout does not support seek"))
     }
+
+    fn set_len(&self, len: u64) -> Result<(), Error> {
+        Err(Error::new(
+            Errno::EINVAL,
+            "Stdout does not support truncate",
+        ))
+    }
+
+    fn allocate(&self, offset: u64, len: u64) -> Result<(), Error> {
+        Err(Error::new(
+            Errno::ESPIPE,
+            "Stdout does not support allocate",
+        ))
+    }
+
+    fn metadata(&self) -> Result<Metadata, Error> {
+        Ok(tty_metadata())
+    }
+
+    fn get_status_flags(&self) -> Result<u32, Error> {
+        Ok(self.status_flags.get())
+    }
+
+    fn set_status_flags(&self, flags: u32) -> Result<(), Error> {
+        self.status_flags.set(flags);
+        Ok(())
+    }
+
+    fn poll(&self) -> Result<u32, Error> {
+        Ok(POLLOUT | POLLWRNORM)
+    }
+
+    fn poll_queue(&self) -> Option<&IoWaitQueue> {
+        None
+    }
+
+    fn ioctl(&self, cmd: &mut IoctlCmd) -> Result<(), Error> {
+        ioctl_host_tty(HOST_STDOUT_FD, cmd)
+    }
+
+    fn as_any(&self) -> &Any {
+        self
+    }
 }
 
 impl Debug for StdoutFile {
@@ -318,64 +526,270 @@ impl Debug for StdoutFile {
 unsafe impl Send for StdoutFile {}
 unsafe impl Sync for StdoutFile {}
 
+/// The standard error, which writes to the stderr of the host.
+///
+/// Unlike stdout, stderr is not buffered inside the enclave. Every write goes
+/// to the host through its own OCall before returning, so the error messages
+/// printed right before a crash are not lost.
+pub struct StderrFile {
+    status_flags: StatusFlags,
+}
+
+impl StderrFile {
+    pub fn new() -> StderrFile {
+        StderrFile {
+            status_flags: StatusFlags::new(O_WRONLY),
+        }
+    }
+
+    fn write_to_host(&self, buf: &[u8]) -> Result<usize, Error> {
+        let mut total_bytes = 0;
+        // The buffer of an OCall is copied onto the untrusted stack, so a big
+        // buffer is written in chunks
+        for chunk in buf.chunks(MAX_STDERR_WRITE_SIZE) {
+            let mut ret: int64_t = 0;
+            let sgx_status = unsafe {
+                ocall_write_stderr(&mut ret, chunk.as_ptr() as *const c_void, chunk.len())
+            };
+            if sgx_status != sgx_status_t::SGX_SUCCESS || ret < 0 || ret as usize > chunk.len() {
+                match total_bytes {
+                    // a complete failure
+                    0 => return errno!(EIO, "Failed to write to the stderr of the host"),
+                    // a partially failure
+                    _ => break,
+                }
+            }
+            total_bytes += ret as usize;
+            if (ret as usize) < chunk.len() {
+                break;
+            }
+        }
+        Ok(total_bytes)
+    }
+}
+
+const MAX_STDERR_WRITE_SIZE: usize = 4096;
+
+impl File for StderrFile {
+    fn write(&self, buf: &[u8]) -> Result<usize, Error> {
+        self.write_to_host(buf)
+    }
+
+    fn read(&self, buf: &mut [u8]) -> Result<usize, Error> {
+        Err(Error::new(Errno::EBADF, "Stderr does not support read"))
+    }
+
+    fn writev<'a, 'b>(&self, bufs: &'a [&'b [u8]]) -> Result<usize, Error> {
+        // Gather the buffers so that a message written by writev, e.g., by
+        // fprintf, is not interleaved with others
+        let buf: Vec<u8> = bufs.iter().flat_map(|buf| buf.iter().cloned()).collect();
+        self.write_to_host(&buf)
+    }
+
+    fn readv<'a, 'b>(&self, bufs: &'a mut [&'b mut [u8]]) -> Result<usize, Error> {
+        Err(Error::new(Errno::EBADF, "Stderr does not support read"))
+    }
+
+    fn read_at(&self, offset: usize, buf: &mut [u8]) -> Result<usize, Error> {
+        Err(Error::new(Errno::ESPIPE, "Stderr does not support read_at"))
+    }
+
+    fn write_at(&self, offset: usize, buf: &[u8]) -> Result<usize, Error> {
+        Err(Error::new(
+            Errno::ESPIPE,
+            "Stderr does not support write_at",
+        ))
+    }
+
+    fn seek(&self, seek_pos: SeekFrom) -> Result<off_t, Error> {
+        Err(Error::new(Errno::ESPIPE, "Stderr does not support seek"))
+    }
+
+    fn set_len(&self, len: u64) -> Result<(), Error> {
+        Err(Error::new(
+            Errno::EINVAL,
+            "Stderr does not support truncate",
+        ))
+    }
+
+    fn allocate(&self, offset: u64, len: u64) -> Result<(), Error> {
+        Err(Error::new(
+            Errno::ESPIPE,
+            "Stderr does not support allocate",
+        ))
+    }
+
+    fn metadata(&self) -> Result<Metadata, Error> {
+        Ok(tty_metadata())
+    }
+
+    fn get_status_flags(&self) -> Result<u32, Error> {
+        Ok(self.status_flags.get())
+    }
+
+    fn set_status_flags(&self, flags: u32) -> Result<(), Error> {
+        self.status_flags.set(flags);
+        Ok(())
+    }
+
+    fn poll(&self) -> Result<u32, Error> {
+        Ok(POLLOUT | POLLWRNORM)
+    }
+
+    fn poll_queue(&self) -> Option<&IoWaitQueue> {
+        None
+    }
+
+    fn ioctl(&self, cmd: &mut IoctlCmd) -> Result<(), Error> {
+        ioctl_host_tty(HOST_STDERR_FD, cmd)
+    }
+
+    fn as_any(&self) -> &Any {
+        self
+    }
+}
+
+impl Debug for StderrFile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "StderrFile")
+    }
+}
+
+extern "C" {
+    fn ocall_write_stderr(ret: *mut int64_t, buf: *const c_void, buf_size: size_t) -> sgx_status_t;
+    fn ocall_read_stdin(
+        ret: *mut int64_t,
+        buf: *mut c_void,
+        buf_size: size_t,
+        is_nonblocking: c_int,
+    ) -> sgx_status_t;
+    fn ocall_poll_stdin(ret: *mut c_int) -> sgx_status_t;
+}
+
 pub struct StdinFile {
-    inner: std::io::Stdin,
+    status_flags: StatusFlags,
+    io_changes: IoChangeCounter,
 }
 
 impl StdinFile {
     pub fn new() -> StdinFile {
         StdinFile {
-            inner: std::io::stdin(),
+            status_flags: StatusFlags::new(O_RDONLY),
+            io_changes: IoChangeCounter::new(),
         }
     }
+
+    /// Read from the stdin of the host directly, without any buffering in the
+    /// enclave, so that the host knows whether there is more data to read
+    fn read_from_host(&self, buf: &mut [u8]) -> Result<usize, Error> {
+        // The buffer of an OCall is copied onto the untrusted stack, so at
+        // most one chunk is read at a time
+        let read_len = buf.len().min(MAX_STDIN_READ_SIZE);
+        let is_nonblocking = self.status_flags.is_nonblocking() as c_int;
+        let mut ret: int64_t = 0;
+        let sgx_status = unsafe {
+            ocall_read_stdin(
+                &mut ret,
+                buf.as_mut_ptr() as *mut c_void,
+                read_len,
+                is_nonblocking,
+            )
+        };
+        let result = check_host_len(sgx_status, ret, read_len);
+        self.io_changes.count_io(&result, read_len);
+        result
+    }
 }
 
+const MAX_STDIN_READ_SIZE: usize = 4096;
+
 impl File for StdinFile {
     fn read(&self, buf: &mut [u8]) -> Result<usize, Error> {
-        let read_len = {
-            self.inner
-                .lock()
-                .read(buf)
-                .map_err(|e| (Errno::EINVAL, "Failed to read"))?
-        };
-        Ok(read_len)
+        self.read_from_host(buf)
     }
 
     fn write(&self, buf: &[u8]) -> Result<usize, Error> {
         Err(Error::new(Errno::EBADF, "Stdin does not support write"))
     }
 
+    fn read_at(&self, offset: usize, buf: &mut [u8]) -> Result<usize, Error> {
+        Err(Error::new(Errno::ESPIPE, "Stdin does not support read_at"))
+    }
+
+    fn write_at(&self, offset: usize, buf: &[u8]) -> Result<usize, Error> {
+        Err(Error::new(Errno::ESPIPE, "Stdin does not support write_at"))
+    }
+
     fn seek(&self, pos: SeekFrom) -> Result<off_t, Error> {
         Err(Error::new(Errno::ESPIPE, "Stdin does not support seek"))
     }
 
+    fn set_len(&self, len: u64) -> Result<(), Error> {
+        Err(Error::new(Errno::EINVAL, "Stdin does not support truncate"))
+    }
+
+    fn allocate(&self, offset: u64, len: u64) -> Result<(), Error> {
+        Err(Error::new(Errno::ESPIPE, "Stdin does not support allocate"))
+    }
+
+    fn metadata(&self) -> Result<Metadata, Error> {
+        Ok(tty_metadata())
+    }
+
     fn readv<'a, 'b>(&self, bufs: &'a mut [&'b mut [u8]]) -> Result<usize, Error> {
-        let mut guard = self.inner.lock();
-        let mut total_bytes = 0;
-        for buf in bufs {
-            match guard.read(buf) {
-                Ok(this_len) => {
-                    total_bytes += this_len;
-                    if this_len < buf.len() {
-                        break;
-                    }
-                }
-                Err(e) => {
-                    match total_bytes {
-                        // a complete failure
-                        0 => return Err(Error::new(Errno::EINVAL, "Failed to write")),
-                        // a partially failure
-                        _ => break,
-                    }
-                }
+        // Read once and scatter the data, so that a read does not block while
+        // some data is already read into the first buffers
+        let total_len: usize = bufs.iter().map(|buf| buf.len()).sum();
+        let mut buf = vec![0; total_len.min(MAX_STDIN_READ_SIZE)];
+        let read_len = self.read_from_host(&mut buf)?;
+        let mut data = &buf[..read_len];
+        for dst_buf in bufs {
+            if data.is_empty() {
+                break;
             }
+            let this_len = dst_buf.len().min(data.len());
+            dst_buf[..this_len].copy_from_slice(&data[..this_len]);
+            data = &data[this_len..];
         }
-        Ok(total_bytes)
+        Ok(read_len)
     }
 
     fn writev<'a, 'b>(&self, bufs: &'a [&'b [u8]]) -> Result<usize, Error> {
         Err(Error::new(Errno::EBADF, "Stdin does not support write"))
     }
+
+    fn get_status_flags(&self) -> Result<u32, Error> {
+        Ok(self.status_flags.get())
+    }
+
+    fn set_status_flags(&self, flags: u32) -> Result<(), Error> {
+        self.status_flags.set(flags);
+        Ok(())
+    }
+
+    fn poll(&self) -> Result<u32, Error> {
+        let mut ret: c_int = 0;
+        let sgx_status = unsafe { ocall_poll_stdin(&mut ret) };
+        let events = check_host_retval(sgx_status, ret as i64)?;
+        Ok(events as u32)
+    }
+
+    fn poll_queue(&self) -> Option<&IoWaitQueue> {
+        // The host does not notify when the stdin becomes readable
+        None
+    }
+
+    fn get_num_changes(&self) -> Option<usize> {
+        Some(self.io_changes.get_num_changes())
+    }
+
+    fn ioctl(&self, cmd: &mut IoctlCmd) -> Result<(), Error> {
+        ioctl_host_tty(HOST_STDIN_FD, cmd)
+    }
+
+    fn as_any(&self) -> &Any {
+        self
+    }
 }
 
 impl Debug for StdinFile {
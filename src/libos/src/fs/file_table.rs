@@ -26,21 +26,30 @@ impl FileTable {
     }
 
     pub fn put(&mut self, file: FileRef, close_on_spawn: bool) -> FileDesc {
+        self.put_above(0, file, close_on_spawn)
+    }
+
+    /// Put a file at the lowest free fd that is not less than min_fd
+    pub fn put_above(&mut self, min_fd: FileDesc, file: FileRef, close_on_spawn: bool) -> FileDesc {
         let mut table = &mut self.table;
 
-        let min_free_fd = if self.num_fds < table.len() {
+        let min_fd = min_fd as usize;
+        let min_free_fd = if min_fd < table.len() {
             table
                 .iter()
                 .enumerate()
+                .skip(min_fd)
                 .find(|&(idx, opt)| opt.is_none())
-                .unwrap()
-                .0
+                .map(|(idx, _)| idx)
+                .unwrap_or(table.len())
         } else {
-            table.push(None);
-            table.len() - 1
+            min_fd
         };
+        if min_free_fd >= table.len() {
+            table.resize(min_free_fd + 1, None);
+        }
 
-        table[min_free_fd as usize] = Some(FileTableEntry::new(file, close_on_spawn));
+        table[min_free_fd] = Some(FileTableEntry::new(file, close_on_spawn));
         self.num_fds += 1;
 
         min_free_fd as FileDesc
@@ -70,6 +79,31 @@ impl FileTable {
         }
     }
 
+    /// Get whether the fd is closed when spawning a new process, i.e., the
+    /// FD_CLOEXEC flag of the fd
+    pub fn get_close_on_spawn(&self, fd: FileDesc) -> Result<bool, Error> {
+        Ok(self.get_entry(fd)?.close_on_spawn)
+    }
+
+    pub fn set_close_on_spawn(&mut self, fd: FileDesc, close_on_spawn: bool) -> Result<(), Error> {
+        self.get_entry_mut(fd)?.close_on_spawn = close_on_spawn;
+        Ok(())
+    }
+
+    fn get_entry(&self, fd: FileDesc) -> Result<&FileTableEntry, Error> {
+        match self.table.get(fd as usize) {
+            Some(Some(table_entry)) => Ok(table_entry),
+            _ => errno!(EBADF, "Invalid file descriptor"),
+        }
+    }
+
+    fn get_entry_mut(&mut self, fd: FileDesc) -> Result<&mut FileTableEntry, Error> {
+        match self.table.get_mut(fd as usize) {
+            Some(Some(table_entry)) => Ok(table_entry),
+            _ => errno!(EBADF, "Invalid file descriptor"),
+        }
+    }
+
     pub fn del(&mut self, fd: FileDesc) -> Result<FileRef, Error> {
         if fd as usize >= self.table.len() {
             return errno!(EBADF, "Invalid file descriptor");
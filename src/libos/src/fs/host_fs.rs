@@ -0,0 +1,511 @@
+use super::*;
+use std::collections::BTreeMap;
+use std::ffi::CString;
+use std::fmt;
+
+/// A read-only file system of plain files on the host, whose integrity (but
+/// not confidentiality) is protected by a manifest
+///
+/// The manifest lists every file that is visible in the file system, together
+/// with the SHA-256 hash of each of its blocks. The manifest itself is trusted
+/// by checking its hash, which is given in the (trusted) LibOS config.
+///
+/// The format of a manifest is one file per line:
+///     <path relative to the root> <file size> <hash of block 0> <hash of block 1> ...
+/// where each hash is in hex and a block is HOSTFS_BLOCK_SIZE bytes.
+#[derive(Debug)]
+pub struct HostFS {
+    root: HostINode,
+}
+
+pub const HOSTFS_BLOCK_SIZE: usize = 4096;
+
+impl HostFS {
+    pub fn new(
+        host_root: &str,
+        manifest_path: &str,
+        manifest_hash: &sgx_sha256_hash_t,
+    ) -> Result<HostFS, Error> {
+        let manifest = {
+            let manifest_file = HostFileHandle::open(manifest_path)?;
+            let mut manifest = Vec::new();
+            let mut buf = vec![0 as u8; HOSTFS_BLOCK_SIZE];
+            loop {
+                let read_len = manifest_file.read_at(manifest.len(), &mut buf)?;
+                if read_len == 0 {
+                    break;
+                }
+                manifest.extend_from_slice(&buf[..read_len]);
+            }
+            if sha256(&manifest)? != *manifest_hash {
+                return errno!(EACCES, "The hash of the HostFS manifest does not match");
+            }
+            String::from_utf8(manifest).map_err(|_| Error::new(EINVAL, "Invalid manifest"))?
+        };
+
+        let root = HostINode::new_dir();
+        for line in manifest.lines() {
+            let mut tokens = line.split_whitespace();
+            let path = match tokens.next() {
+                Some(path) => path,
+                None => continue,
+            };
+            let size = tokens
+                .next()
+                .and_then(|size_str| size_str.parse::<usize>().ok())
+                .ok_or_else(|| Error::new(EINVAL, "Invalid file size in manifest"))?;
+            let block_hashes = tokens
+                .map(|hash_str| parse_sha256_hash(hash_str))
+                .collect::<Result<Vec<sgx_sha256_hash_t>, Error>>()?;
+            if block_hashes.len() != (size + HOSTFS_BLOCK_SIZE - 1) / HOSTFS_BLOCK_SIZE {
+                return errno!(EINVAL, "Wrong number of block hashes in manifest");
+            }
+
+            let host_path = format!("{}/{}", host_root.trim_right_matches('/'), path);
+            let file = HostINode::new_file(host_path, size, block_hashes);
+            root.add_by_path(path, file)?;
+        }
+        Ok(HostFS { root })
+    }
+}
+
+impl FileSystem for HostFS {
+    fn root_inode(&self) -> INodeRef {
+        Arc::new(self.root.clone())
+    }
+
+    fn sync(&self) -> Result<(), Error> {
+        // Read-only
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct HostINode {
+    inner: Arc<HostINodeInner>,
+}
+
+enum HostINodeInner {
+    File {
+        host_path: String,
+        size: usize,
+        block_hashes: Vec<sgx_sha256_hash_t>,
+    },
+    Dir(SgxRwLock<BTreeMap<String, HostINode>>),
+}
+
+impl HostINode {
+    fn new_dir() -> HostINode {
+        HostINode {
+            inner: Arc::new(HostINodeInner::Dir(SgxRwLock::new(BTreeMap::new()))),
+        }
+    }
+
+    fn new_file(host_path: String, size: usize, block_hashes: Vec<sgx_sha256_hash_t>) -> HostINode {
+        HostINode {
+            inner: Arc::new(HostINodeInner::File {
+                host_path,
+                size,
+                block_hashes,
+            }),
+        }
+    }
+
+    /// Add a file into the directory tree, creating intermediate directories
+    /// when necessary
+    fn add_by_path(&self, path: &str, file: HostINode) -> Result<(), Error> {
+        let mut names: Vec<&str> = path.split('/').filter(|name| !name.is_empty()).collect();
+        let file_name = names
+            .pop()
+            .ok_or_else(|| Error::new(EINVAL, "Invalid path in manifest"))?;
+
+        let mut dir = self.clone();
+        for name in names {
+            let sub_dir = {
+                let entries = dir.get_entries()?;
+                let mut entries = entries.write().unwrap();
+                entries
+                    .entry(name.to_owned())
+                    .or_insert_with(|| HostINode::new_dir())
+                    .clone()
+            };
+            dir = sub_dir;
+        }
+        let entries = dir.get_entries()?;
+        let mut entries = entries.write().unwrap();
+        if entries.contains_key(file_name) {
+            return errno!(EEXIST, "Duplicated path in manifest");
+        }
+        entries.insert(file_name.to_owned(), file);
+        Ok(())
+    }
+
+    fn get_ino(&self) -> u64 {
+        // The address of the inner object is unique while the inode is alive
+        &*self.inner as *const HostINodeInner as usize as u64
+    }
+
+    fn get_file_type(&self) -> FileType {
+        match *self.inner {
+            HostINodeInner::File { .. } => FileType::File,
+            HostINodeInner::Dir(_) => FileType::Dir,
+        }
+    }
+
+    fn get_entries(&self) -> Result<&SgxRwLock<BTreeMap<String, HostINode>>, Error> {
+        match *self.inner {
+            HostINodeInner::Dir(ref entries) => Ok(entries),
+            HostINodeInner::File { .. } => errno!(ENOTDIR, "Not a directory"),
+        }
+    }
+}
+
+impl INode for HostINode {
+    fn open(&self, flags: u32) -> Result<FileRef, Error> {
+        let is_writable = (flags & O_WRONLY != 0) || (flags & O_RDWR != 0);
+        if is_writable || (flags & O_TRUNC) != 0 {
+            return errno!(EROFS, "HostFS is read-only");
+        }
+
+        let host_file = match *self.inner {
+            HostINodeInner::File { ref host_path, .. } => Some(HostFileHandle::open(host_path)?),
+            HostINodeInner::Dir(_) => None,
+        };
+        let file_ref: Arc<Box<File>> = Arc::new(Box::new(HostFile {
+            inode: self.clone(),
+            host_file,
+            pos: SgxMutex::new(0),
+            status_flags: StatusFlags::new(flags),
+        }));
+        Ok(file_ref)
+    }
+
+    fn find(&self, name: &str) -> Result<INodeRef, Error> {
+        let entries = self.get_entries()?.read().unwrap();
+        match entries.get(name) {
+            Some(child) => Ok(Arc::new(child.clone())),
+            None => errno!(ENOENT, "No such file or directory"),
+        }
+    }
+
+    fn create(&self, name: &str, mode: u32) -> Result<INodeRef, Error> {
+        errno!(EROFS, "HostFS is read-only")
+    }
+
+    fn mkdir(&self, name: &str, mode: u32) -> Result<INodeRef, Error> {
+        errno!(EROFS, "HostFS is read-only")
+    }
+
+    fn rmdir(&self, name: &str) -> Result<(), Error> {
+        errno!(EROFS, "HostFS is read-only")
+    }
+
+    fn list(&self) -> Result<Vec<DirEntry>, Error> {
+        let entries = self.get_entries()?.read().unwrap();
+        Ok(entries
+            .iter()
+            .map(|(name, child)| DirEntry {
+                ino: child.get_ino(),
+                type_: child.get_file_type(),
+                name: name.clone(),
+            })
+            .collect())
+    }
+
+    fn get_type(&self) -> Result<FileType, Error> {
+        Ok(self.get_file_type())
+    }
+
+    fn metadata(&self) -> Result<Metadata, Error> {
+        let (size, mode, nlinks) = match *self.inner {
+            HostINodeInner::File { size, .. } => (size, 0o444, 1),
+            HostINodeInner::Dir(_) => (0, 0o555, 2),
+        };
+        // The timestamps of host files are not trusted, thus not exposed
+        Ok(Metadata {
+            dev: 0,
+            ino: self.get_ino(),
+            size,
+            type_: self.get_file_type(),
+            mode,
+            nlinks,
+            atime: Default::default(),
+            mtime: Default::default(),
+            ctime: Default::default(),
+        })
+    }
+
+    fn unlink(&self, name: &str) -> Result<(), Error> {
+        errno!(EROFS, "HostFS is read-only")
+    }
+
+    fn link(&self, name: &str, target: &INodeRef) -> Result<(), Error> {
+        errno!(EROFS, "HostFS is read-only")
+    }
+
+    fn rename(&self, old_name: &str, target_dir: &INodeRef, new_name: &str) -> Result<(), Error> {
+        errno!(EROFS, "HostFS is read-only")
+    }
+
+    fn symlink(&self, name: &str, target: &str) -> Result<INodeRef, Error> {
+        errno!(EROFS, "HostFS is read-only")
+    }
+
+    fn read_link(&self) -> Result<String, Error> {
+        errno!(EINVAL, "Not a symbolic link")
+    }
+
+    fn as_any(&self) -> &Any {
+        self
+    }
+}
+
+impl Debug for HostINodeInner {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            HostINodeInner::File {
+                ref host_path,
+                size,
+                ..
+            } => write!(
+                f,
+                "HostINodeInner::File {{ host_path: {}, size: {} }}",
+                host_path, size
+            ),
+            HostINodeInner::Dir(_) => write!(f, "HostINodeInner::Dir"),
+        }
+    }
+}
+
+/// An opened file of HostFS
+#[derive(Debug)]
+pub struct HostFile {
+    inode: HostINode,
+    host_file: Option<HostFileHandle>,
+    pos: SgxMutex<usize>,
+    status_flags: StatusFlags,
+}
+
+impl HostFile {
+    /// Read from the host file and check the integrity of every block read
+    fn read_checked_at(&self, offset: usize, buf: &mut [u8]) -> Result<usize, Error> {
+        let (size, block_hashes) = match *self.inode.inner {
+            HostINodeInner::File {
+                size,
+                ref block_hashes,
+                ..
+            } => (size, block_hashes),
+            HostINodeInner::Dir(_) => return errno!(EISDIR, "Cannot read a directory"),
+        };
+        let host_file = self.host_file.as_ref().unwrap();
+
+        let end = std::cmp::min(offset + buf.len(), size);
+        let mut pos = offset;
+        let mut block_buf = vec![0 as u8; HOSTFS_BLOCK_SIZE];
+        while pos < end {
+            let block_i = pos / HOSTFS_BLOCK_SIZE;
+            let block_start = block_i * HOSTFS_BLOCK_SIZE;
+            let block_len = std::cmp::min(HOSTFS_BLOCK_SIZE, size - block_start);
+            let block = &mut block_buf[..block_len];
+
+            let read_len = host_file.read_at(block_start, block)?;
+            if read_len != block_len || sha256(block)? != block_hashes[block_i] {
+                return errno!(EIO, "HostFS file is tampered with");
+            }
+
+            let copy_start = pos - block_start;
+            let copy_len = std::cmp::min(block_len - copy_start, end - pos);
+            let buf_pos = pos - offset;
+            buf[buf_pos..buf_pos + copy_len]
+                .copy_from_slice(&block[copy_start..copy_start + copy_len]);
+            pos += copy_len;
+        }
+        Ok(pos - offset)
+    }
+
+    fn get_size(&self) -> usize {
+        match *self.inode.inner {
+            HostINodeInner::File { size, .. } => size,
+            HostINodeInner::Dir(_) => 0,
+        }
+    }
+}
+
+impl File for HostFile {
+    fn read(&self, buf: &mut [u8]) -> Result<usize, Error> {
+        let mut pos = self.pos.lock().unwrap();
+        let read_len = self.read_checked_at(*pos, buf)?;
+        *pos += read_len;
+        Ok(read_len)
+    }
+
+    fn write(&self, buf: &[u8]) -> Result<usize, Error> {
+        errno!(EBADF, "HostFS is read-only")
+    }
+
+    fn readv<'a, 'b>(&self, bufs: &'a mut [&'b mut [u8]]) -> Result<usize, Error> {
+        let mut pos = self.pos.lock().unwrap();
+        let mut total_bytes = 0;
+        for buf in bufs {
+            match self.read_checked_at(*pos, buf) {
+                Ok(this_len) => {
+                    *pos += this_len;
+                    total_bytes += this_len;
+                    if this_len < buf.len() {
+                        break;
+                    }
+                }
+                Err(e) => {
+                    match total_bytes {
+                        // a complete failure
+                        0 => return Err(e),
+                        // a partially failure
+                        _ => break,
+                    }
+                }
+            }
+        }
+        Ok(total_bytes)
+    }
+
+    fn writev<'a, 'b>(&self, bufs: &'a [&'b [u8]]) -> Result<usize, Error> {
+        errno!(EBADF, "HostFS is read-only")
+    }
+
+    fn read_at(&self, offset: usize, buf: &mut [u8]) -> Result<usize, Error> {
+        self.read_checked_at(offset, buf)
+    }
+
+    fn write_at(&self, offset: usize, buf: &[u8]) -> Result<usize, Error> {
+        errno!(EBADF, "HostFS is read-only")
+    }
+
+    fn seek(&self, seek_pos: SeekFrom) -> Result<off_t, Error> {
+        let mut pos = self.pos.lock().unwrap();
+        let new_pos = match seek_pos {
+            SeekFrom::Start(offset) => offset as i64,
+            SeekFrom::End(offset) => self.get_size() as i64 + offset,
+            SeekFrom::Current(offset) => *pos as i64 + offset,
+        };
+        if new_pos < 0 {
+            return errno!(EINVAL, "Invalid seek position");
+        }
+        *pos = new_pos as usize;
+        Ok(new_pos as off_t)
+    }
+
+    fn set_len(&self, len: u64) -> Result<(), Error> {
+        errno!(EINVAL, "HostFS is read-only")
+    }
+
+    fn allocate(&self, offset: u64, len: u64) -> Result<(), Error> {
+        errno!(EBADF, "HostFS is read-only")
+    }
+
+    fn metadata(&self) -> Result<Metadata, Error> {
+        self.inode.metadata()
+    }
+
+    fn get_status_flags(&self) -> Result<u32, Error> {
+        Ok(self.status_flags.get())
+    }
+
+    fn set_status_flags(&self, flags: u32) -> Result<(), Error> {
+        self.status_flags.set(flags);
+        Ok(())
+    }
+
+    fn poll(&self) -> Result<u32, Error> {
+        Ok(POLL_ALWAYS_READY)
+    }
+
+    fn poll_queue(&self) -> Option<&IoWaitQueue> {
+        None
+    }
+
+    fn ioctl(&self, cmd: &mut IoctlCmd) -> Result<(), Error> {
+        errno!(ENOTTY, "Regular file does not support ioctl")
+    }
+
+    fn as_any(&self) -> &Any {
+        self
+    }
+}
+
+/// A file descriptor of the host, which is closed when dropped
+#[derive(Debug)]
+struct HostFileHandle {
+    host_fd: c_int,
+}
+
+impl HostFileHandle {
+    fn open(host_path: &str) -> Result<HostFileHandle, Error> {
+        let host_path =
+            CString::new(host_path).map_err(|_| Error::new(EINVAL, "Invalid host path"))?;
+        let mut host_fd: c_int = -1;
+        let sgx_status = unsafe { ocall_open_host_file(&mut host_fd, host_path.as_ptr()) };
+        if sgx_status != sgx_status_t::SGX_SUCCESS || host_fd < 0 {
+            return errno!(EIO, "Failed to open the host file");
+        }
+        Ok(HostFileHandle { host_fd })
+    }
+
+    fn read_at(&self, offset: usize, buf: &mut [u8]) -> Result<usize, Error> {
+        let mut ret: int64_t = -1;
+        let sgx_status = unsafe {
+            ocall_pread_host_file(
+                &mut ret,
+                self.host_fd,
+                buf.as_mut_ptr() as *mut c_void,
+                buf.len(),
+                offset as int64_t,
+            )
+        };
+        if sgx_status != sgx_status_t::SGX_SUCCESS || ret < 0 || ret as usize > buf.len() {
+            return errno!(EIO, "Failed to read the host file");
+        }
+        Ok(ret as usize)
+    }
+}
+
+impl Drop for HostFileHandle {
+    fn drop(&mut self) {
+        unsafe {
+            ocall_close_host_file(self.host_fd);
+        }
+    }
+}
+
+fn sha256(data: &[u8]) -> Result<sgx_sha256_hash_t, Error> {
+    let mut hash: sgx_sha256_hash_t = [0; 32];
+    let sgx_status = unsafe { sgx_sha256_msg(data.as_ptr(), data.len() as uint32_t, &mut hash) };
+    if sgx_status != sgx_status_t::SGX_SUCCESS {
+        return errno!(EIO, "Failed to calculate SHA-256");
+    }
+    Ok(hash)
+}
+
+pub fn parse_sha256_hash(hash_str: &str) -> Result<sgx_sha256_hash_t, Error> {
+    if hash_str.len() != 64 || !hash_str.is_ascii() {
+        return errno!(EINVAL, "Invalid SHA-256 hash");
+    }
+    let mut hash: sgx_sha256_hash_t = [0; 32];
+    for (byte_i, byte) in hash.iter_mut().enumerate() {
+        let byte_str = &hash_str[byte_i * 2..byte_i * 2 + 2];
+        *byte = u8::from_str_radix(byte_str, 16)
+            .map_err(|_| Error::new(EINVAL, "Invalid SHA-256 hash"))?;
+    }
+    Ok(hash)
+}
+
+extern "C" {
+    fn ocall_open_host_file(ret: *mut c_int, path: *const c_char) -> sgx_status_t;
+    fn ocall_pread_host_file(
+        ret: *mut int64_t,
+        fd: c_int,
+        buf: *mut c_void,
+        buf_size: size_t,
+        offset: int64_t,
+    ) -> sgx_status_t;
+    fn ocall_close_host_file(fd: c_int) -> sgx_status_t;
+}
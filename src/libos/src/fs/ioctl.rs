@@ -0,0 +1,97 @@
+use super::*;
+
+// The commands of ioctl
+pub const TCGETS: u32 = 0x5401;
+pub const TIOCGWINSZ: u32 = 0x5413;
+pub const FIONREAD: u32 = 0x541B;
+pub const FIONBIO: u32 = 0x5421;
+
+/// The struct termios of Linux, which is a prefix of that of libc
+#[allow(non_camel_case_types)]
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct termios_t {
+    pub c_iflag: u32,
+    pub c_oflag: u32,
+    pub c_cflag: u32,
+    pub c_lflag: u32,
+    pub c_line: u8,
+    pub c_cc: [u8; 19],
+}
+
+#[allow(non_camel_case_types)]
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct winsize_t {
+    pub ws_row: u16,
+    pub ws_col: u16,
+    pub ws_xpixel: u16,
+    pub ws_ypixel: u16,
+}
+
+/// A command of ioctl together with its argument, which refers to the memory
+/// of the user when the result is written back
+#[derive(Debug)]
+pub enum IoctlCmd<'a> {
+    /// Get the attributes of the terminal
+    TcGets(&'a mut termios_t),
+    /// Get the window size of the terminal
+    TiocGWinSz(&'a mut winsize_t),
+    /// Get the number of bytes that can be read
+    FionRead(&'a mut i32),
+    /// Set O_NONBLOCK if the arg is not zero, or clear it otherwise
+    FionBio(i32),
+}
+
+pub fn do_ioctl(fd: FileDesc, cmd: &mut IoctlCmd) -> Result<(), Error> {
+    let file_ref = get_file(fd)?;
+    // FIONBIO works for all files, as fcntl does
+    if let IoctlCmd::FionBio(is_nonblocking) = *cmd {
+        let flags = file_ref.get_status_flags()?;
+        let new_flags = if is_nonblocking != 0 {
+            flags | O_NONBLOCK
+        } else {
+            flags & !O_NONBLOCK
+        };
+        return file_ref.set_status_flags(new_flags);
+    }
+    file_ref.ioctl(cmd)
+}
+
+/// Forward a command of the terminal to the fd of the host, which fails with
+/// ENOTTY if the host fd is not a terminal
+pub fn ioctl_host_tty(host_fd: c_int, cmd: &mut IoctlCmd) -> Result<(), Error> {
+    match *cmd {
+        IoctlCmd::TcGets(ref mut termios) => ioctl_host_fd(host_fd, TCGETS, &mut **termios),
+        IoctlCmd::TiocGWinSz(ref mut winsize) => ioctl_host_fd(host_fd, TIOCGWINSZ, &mut **winsize),
+        IoctlCmd::FionRead(ref mut len) => ioctl_host_fd(host_fd, FIONREAD, &mut **len),
+        _ => errno!(ENOTTY, "Terminal does not support the ioctl command"),
+    }
+}
+
+/// Do an ioctl command on the fd of the host, whose argument is only written
+/// by the host
+pub fn ioctl_host_fd<T: Copy>(host_fd: c_int, cmd: u32, arg: &mut T) -> Result<(), Error> {
+    let mut ret: c_int = 0;
+    let sgx_status = unsafe {
+        ocall_ioctl_host_fd(
+            &mut ret,
+            host_fd,
+            cmd,
+            arg as *mut T as *mut c_void,
+            std::mem::size_of::<T>(),
+        )
+    };
+    check_host_retval(sgx_status, ret as i64)?;
+    Ok(())
+}
+
+extern "C" {
+    fn ocall_ioctl_host_fd(
+        ret: *mut c_int,
+        fd: c_int,
+        cmd: uint32_t,
+        arg: *mut c_void,
+        arg_size: size_t,
+    ) -> sgx_status_t;
+}
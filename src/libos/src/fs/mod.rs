@@ -1,24 +1,92 @@
 use super::*;
+use config::{ConfigKey, ConfigMount, ConfigMountFsType};
 use prelude::*;
 use std::sgxfs as fs_impl;
+use time::timespec_t;
 use {process, std};
 
+mod dev_fs;
+mod dir_file;
+mod epoll;
+mod event_fd;
 mod file;
 mod file_table;
+mod host_fs;
+mod ioctl;
+mod page_cache;
 mod pipe;
+mod poll;
+mod sgx_fs;
+mod sgx_key;
+mod splice;
+mod tmp_fs;
+mod vfs;
 
-pub use self::file::{File, FileRef, SgxFile, StdinFile, StdoutFile};
+pub use self::dev_fs::DevFS;
+pub use self::dir_file::DirFile;
+pub use self::epoll::{
+    do_epoll_create1, do_epoll_ctl, do_epoll_wait, EpollCtlCmd, EpollEvent, EpollFile,
+};
+pub use self::event_fd::{do_eventfd2, EventFd};
+pub use self::file::{File, FileRef, SgxFile, StatusFlags, StderrFile, StdinFile, StdoutFile};
 pub use self::file_table::{FileDesc, FileTable};
+pub use self::host_fs::{parse_sha256_hash, HostFS, HostFile, HostINode};
+pub use self::ioctl::{
+    do_ioctl, ioctl_host_fd, ioctl_host_tty, termios_t, winsize_t, IoctlCmd, FIONBIO, FIONREAD,
+    TCGETS, TIOCGWINSZ,
+};
+pub use self::page_cache::set_page_cache_size;
 pub use self::pipe::Pipe;
+pub use self::poll::{
+    do_poll, do_select, wait_on_queues, FdSet, IoChangeCounter, IoWaitQueue, PollFd, FD_SETSIZE,
+    POLLERR, POLLHUP, POLLIN, POLLNVAL, POLLOUT, POLLPRI, POLLRDNORM, POLLWRNORM,
+    POLL_ALWAYS_READY,
+};
+pub use self::sgx_fs::{SgxFS, SgxINode};
+pub use self::sgx_key::{set_sgxfs_boot_key, SgxKeySource, SgxKeyTable};
+pub use self::splice::{do_copy_file_range, do_sendfile, do_splice, do_tee};
+pub use self::tmp_fs::{TmpFS, TmpFile, TmpINode};
+pub use self::vfs::{
+    get_host_cwd, mount, to_abs_path, umount, DirEntry, FileSystem, FileSystemRef, FileType, INode,
+    INodeRef, Metadata,
+};
 
 pub const O_RDONLY: u32 = 0x00000000;
 pub const O_WRONLY: u32 = 0x00000001;
 pub const O_RDWR: u32 = 0x00000002;
+pub const O_ACCMODE: u32 = 0x00000003;
 pub const O_CREAT: u32 = 0x00000040;
+pub const O_EXCL: u32 = 0x00000080;
 pub const O_TRUNC: u32 = 0x00000200;
 pub const O_APPEND: u32 = 0x00000400;
+pub const O_NONBLOCK: u32 = 0x00000800;
+pub const O_DIRECTORY: u32 = 0x00010000;
+pub const O_NOFOLLOW: u32 = 0x00020000;
 pub const O_CLOEXEC: u32 = 0x00080000;
 
+// File types in the mode of a file
+pub const S_IFMT: u32 = 0o170000;
+pub const S_IFSOCK: u32 = 0o140000;
+pub const S_IFLNK: u32 = 0o120000;
+pub const S_IFREG: u32 = 0o100000;
+pub const S_IFBLK: u32 = 0o060000;
+pub const S_IFDIR: u32 = 0o040000;
+pub const S_IFCHR: u32 = 0o020000;
+pub const S_IFIFO: u32 = 0o010000;
+// The permission bits in the mode of a file, including setuid, setgid and
+// sticky bits
+pub const S_IPERM: u32 = 0o007777;
+
+// File types in directory entries
+pub const DT_UNKNOWN: u8 = 0;
+pub const DT_FIFO: u8 = 1;
+pub const DT_CHR: u8 = 2;
+pub const DT_DIR: u8 = 4;
+pub const DT_BLK: u8 = 6;
+pub const DT_REG: u8 = 8;
+pub const DT_LNK: u8 = 10;
+pub const DT_SOCK: u8 = 12;
+
 // TODO: use the type defined in Rust libc.
 //
 // However, off_t is defined as u64 in the current Rust SGX SDK, which is
@@ -26,38 +94,137 @@ pub const O_CLOEXEC: u32 = 0x00080000;
 #[allow(non_camel_case_types)]
 pub type off_t = i64;
 
-pub fn do_open(path: &str, flags: u32, mode: u32) -> Result<FileDesc, Error> {
-    let open_options = {
-        let mut open_options = fs_impl::OpenOptions::new();
+// Flags and special values for the *at family of syscalls
+pub const AT_FDCWD: i32 = -100;
+pub const AT_SYMLINK_NOFOLLOW: u32 = 0x100;
+pub const AT_REMOVEDIR: u32 = 0x200;
+
+// Commands of fcntl
+pub const F_DUPFD: u32 = 0;
+pub const F_GETFD: u32 = 1;
+pub const F_SETFD: u32 = 2;
+pub const F_GETFL: u32 = 3;
+pub const F_SETFL: u32 = 4;
+pub const F_DUPFD_CLOEXEC: u32 = 1030;
+
+// Flags of file descriptors
+pub const FD_CLOEXEC: u32 = 0x1;
+
+// Modes of fallocate
+pub const FALLOC_FL_KEEP_SIZE: u32 = 0x1;
+pub const AT_EMPTY_PATH: u32 = 0x1000;
+
+/// The max size of a file, beyond which a file cannot be extended
+pub const MAX_FILE_SIZE: u64 = 1 << 40;
+
+/// Get the end of a range of a file, or fail with EFBIG if the range goes
+/// beyond the max size of a file
+pub fn get_range_end(offset: u64, len: u64) -> Result<usize, Error> {
+    match offset.checked_add(len) {
+        Some(end) if end <= MAX_FILE_SIZE => Ok(end as usize),
+        _ => errno!(EFBIG, "The range is beyond the max file size"),
+    }
+}
 
-        if ((flags & O_TRUNC) != 0 || (flags & O_CREAT) != 0) {
-            open_options.write(true);
-        } else {
-            open_options.read(true);
+/// The struct stat of x86-64 Linux
+#[repr(C)]
+#[derive(Debug)]
+pub struct Stat {
+    dev: u64,
+    ino: u64,
+    nlink: u64,
+    mode: u32,
+    uid: u32,
+    gid: u32,
+    __pad0: u32,
+    rdev: u64,
+    size: i64,
+    blksize: i64,
+    blocks: i64,
+    atime: timespec_t,
+    mtime: timespec_t,
+    ctime: timespec_t,
+    __unused: [i64; 3],
+}
+
+impl From<Metadata> for Stat {
+    fn from(metadata: Metadata) -> Stat {
+        let type_mode = match metadata.type_ {
+            FileType::File => S_IFREG,
+            FileType::Dir => S_IFDIR,
+            FileType::SymLink => S_IFLNK,
+            FileType::CharDevice => S_IFCHR,
+            FileType::BlockDevice => S_IFBLK,
+            FileType::NamedPipe => S_IFIFO,
+            FileType::Socket => S_IFSOCK,
+            FileType::Anonymous => 0,
+        };
+        Stat {
+            dev: metadata.dev,
+            ino: metadata.ino,
+            nlink: metadata.nlinks as u64,
+            mode: type_mode | metadata.mode,
+            uid: 0,
+            gid: 0,
+            __pad0: 0,
+            rdev: 0,
+            size: metadata.size as i64,
+            blksize: 4096,
+            blocks: ((metadata.size + 511) / 512) as i64,
+            atime: metadata.atime,
+            mtime: metadata.mtime,
+            ctime: metadata.ctime,
+            __unused: [0; 3],
         }
-        open_options.update(true).binary(true);
+    }
+}
 
-        open_options
-    };
+/// Mount the file systems specified in the LibOS config
+pub fn mount_from_config(
+    config_mounts: &[ConfigMount],
+    config_keys: &[ConfigKey],
+) -> Result<(), Error> {
+    for config_mount in config_mounts {
+        let fs: FileSystemRef = match config_mount.type_ {
+            ConfigMountFsType::SgxFS => {
+                let host_root = config_mount.options.root.as_ref().unwrap();
+                // Unlike the executables in the root file system, the data is
+                // sealed by default
+                let default_key = config_mount.options.key.unwrap_or(SgxKeySource::MrSigner);
+                let keys = SgxKeyTable::from_config(
+                    &config_mount.mount_point,
+                    host_root,
+                    default_key,
+                    config_keys,
+                );
+                let sgx_fs = SgxFS::new(host_root, keys);
+                sgx_fs.create_host_root()?;
+                Arc::new(sgx_fs)
+            }
+            ConfigMountFsType::TmpFS => {
+                let capacity = config_mount.options.size.unwrap_or(TMPFS_DEFAULT_CAPACITY);
+                Arc::new(TmpFS::new(capacity))
+            }
+            ConfigMountFsType::HostFS => {
+                let options = &config_mount.options;
+                Arc::new(HostFS::new(
+                    options.root.as_ref().unwrap(),
+                    options.manifest.as_ref().unwrap(),
+                    options.manifest_hash.as_ref().unwrap(),
+                )?)
+            }
+            ConfigMountFsType::DevFS => Arc::new(DevFS::new()),
+        };
+        vfs::mount(&config_mount.mount_point, fs)?;
+    }
+    Ok(())
+}
 
-    let mut sgx_file = {
-        let key: sgx_key_128bit_t = [0 as uint8_t; 16];
-        // TODO: what if two processes open the same underlying SGX file?
-        let sgx_file = open_options
-            .open_ex(path, &key)
-            .map_err(|e| (Errno::ENOENT, "Failed to open the SGX-protected file"))?;
-        Arc::new(SgxMutex::new(sgx_file))
-    };
+const TMPFS_DEFAULT_CAPACITY: usize = 16 * 1024 * 1024;
 
-    let is_readable = (flags & O_WRONLY) == 0;
-    let is_writable = (flags & O_WRONLY != 0) || (flags & O_RDWR != 0);
-    let is_append = (flags & O_APPEND != 0);
-    let file_ref: Arc<Box<File>> = Arc::new(Box::new(SgxFile::new(
-        sgx_file,
-        is_readable,
-        is_writable,
-        is_append,
-    )?));
+pub fn do_open(path: &str, flags: u32, mode: u32) -> Result<FileDesc, Error> {
+    let path = resolve_path(path)?;
+    let file_ref = open_file(&path, flags, mode)?;
 
     let fd = {
         let current_ref = process::get_current();
@@ -68,53 +235,403 @@ pub fn do_open(path: &str, flags: u32, mode: u32) -> Result<FileDesc, Error> {
     Ok(fd)
 }
 
-pub fn do_write(fd: FileDesc, buf: &[u8]) -> Result<usize, Error> {
+/// Open a file by its absolute path in the file system namespace
+pub fn open_file(path: &str, flags: u32, mode: u32) -> Result<FileRef, Error> {
+    if (flags & O_ACCMODE) == O_ACCMODE {
+        return errno!(EINVAL, "Invalid access mode");
+    }
+    let is_creat = (flags & O_CREAT) != 0;
+    let is_excl = is_creat && (flags & O_EXCL) != 0;
+
+    // With O_CREAT | O_EXCL, a symlink is never followed but counts as an
+    // existing file
+    let lookup_result = if is_excl || (flags & O_NOFOLLOW) != 0 {
+        vfs::lookup_no_follow(path)
+    } else {
+        vfs::lookup(path)
+    };
+    let inode = match lookup_result {
+        Ok(_) if is_excl => return errno!(EEXIST, "File exists"),
+        Ok(inode) => inode,
+        Err(e) if e.errno == ENOENT && is_creat => {
+            if path.ends_with("/") {
+                return errno!(EISDIR, "Cannot create a directory by open");
+            }
+            // A dangling symlink is followed to create its target
+            let (dir_inode, file_name) = vfs::lookup_parent_to_create(path)?;
+            match dir_inode.create(&file_name, mode & S_IPERM) {
+                Ok(inode) => inode,
+                // Created by others since the lookup
+                Err(e) if e.errno == EEXIST && !is_excl => vfs::lookup(path)?,
+                Err(e) => return Err(e),
+            }
+        }
+        Err(e) => return Err(e),
+    };
+
+    let type_ = inode.get_type()?;
+    if type_ == FileType::SymLink {
+        return errno!(ELOOP, "Cannot open a symbolic link with O_NOFOLLOW");
+    }
+    if type_ == FileType::Dir {
+        let is_writable = (flags & O_WRONLY != 0) || (flags & O_RDWR != 0);
+        if is_writable || is_creat {
+            return errno!(EISDIR, "Cannot open a directory for write");
+        }
+        // Keep the path without symlinks, so that it can be used as the cwd
+        let (inode, real_path) = vfs::lookup_real_path(path)?;
+        let file_ref: Arc<Box<File>> = Arc::new(Box::new(DirFile::new(inode, &real_path, flags)));
+        return Ok(file_ref);
+    }
+    if (flags & O_DIRECTORY) != 0 {
+        return errno!(ENOTDIR, "Not a directory");
+    }
+    inode.open(flags)
+}
+
+/// Convert a path into an absolute one, resolving relative paths against the
+/// working directory of the current process
+fn resolve_path(path: &str) -> Result<String, Error> {
+    resolve_path_at(AT_FDCWD, path)
+}
+
+/// Convert a path into an absolute one, resolving relative paths against the
+/// directory referred by dirfd, or the cwd if dirfd is AT_FDCWD
+fn resolve_path_at(dirfd: i32, path: &str) -> Result<String, Error> {
+    let current_ref = process::get_current();
+    let current = current_ref.lock().unwrap();
+    if path.starts_with("/") || dirfd == AT_FDCWD {
+        return vfs::to_abs_path(current.get_cwd(), path);
+    }
+    let file_ref = current.get_files().get(dirfd as FileDesc)?;
+    let dir_file = file_ref
+        .as_any()
+        .downcast_ref::<DirFile>()
+        .ok_or_else(|| Error::new(ENOTDIR, "dirfd is not a directory"))?;
+    vfs::to_abs_path(dir_file.get_path(), path)
+}
+
+pub fn do_stat(path: &str) -> Result<Stat, Error> {
+    let path = resolve_path(path)?;
+    let inode = vfs::lookup(&path)?;
+    Ok(Stat::from(inode.metadata()?))
+}
+
+pub fn do_lstat(path: &str) -> Result<Stat, Error> {
+    let path = resolve_path(path)?;
+    let inode = vfs::lookup_no_follow(&path)?;
+    Ok(Stat::from(inode.metadata()?))
+}
+
+pub fn do_fstat(fd: FileDesc) -> Result<Stat, Error> {
+    let file_ref = get_file(fd)?;
+    Ok(Stat::from(file_ref.metadata()?))
+}
+
+pub fn do_fstatat(dirfd: i32, path: &str, flags: u32) -> Result<Stat, Error> {
+    if path.is_empty() && (flags & AT_EMPTY_PATH) != 0 {
+        if dirfd == AT_FDCWD {
+            return do_stat(".");
+        }
+        return do_fstat(dirfd as FileDesc);
+    }
+    let path = resolve_path_at(dirfd, path)?;
+    if (flags & AT_SYMLINK_NOFOLLOW) != 0 {
+        do_lstat(&path)
+    } else {
+        do_stat(&path)
+    }
+}
+
+pub fn do_mkdir(path: &str, mode: u32) -> Result<(), Error> {
+    let path = resolve_path(path)?;
+    let (dir_inode, dir_name) = vfs::lookup_parent(&path)?;
+    dir_inode.mkdir(&dir_name, mode & S_IPERM)?;
+    Ok(())
+}
+
+pub fn do_rmdir(path: &str) -> Result<(), Error> {
+    let path = resolve_path(path)?;
+    let (dir_inode, dir_name) = vfs::lookup_parent(&path)?;
+    if dir_inode.find(&dir_name)?.get_type()? != FileType::Dir {
+        return errno!(ENOTDIR, "Not a directory");
+    }
+    dir_inode.rmdir(&dir_name)
+}
+
+pub fn do_unlink(path: &str) -> Result<(), Error> {
+    do_unlinkat(AT_FDCWD, path, 0)
+}
+
+pub fn do_unlinkat(dirfd: i32, path: &str, flags: u32) -> Result<(), Error> {
+    let path = resolve_path_at(dirfd, path)?;
+    let (dir_inode, file_name) = vfs::lookup_parent(&path)?;
+    let is_dir = dir_inode.find(&file_name)?.get_type()? == FileType::Dir;
+    if (flags & AT_REMOVEDIR) != 0 {
+        if !is_dir {
+            return errno!(ENOTDIR, "Not a directory");
+        }
+        return dir_inode.rmdir(&file_name);
+    }
+    if is_dir {
+        return errno!(EISDIR, "Cannot unlink a directory");
+    }
+    dir_inode.unlink(&file_name)
+}
+
+pub fn do_rename(old_path: &str, new_path: &str) -> Result<(), Error> {
+    do_renameat(AT_FDCWD, old_path, AT_FDCWD, new_path)
+}
+
+pub fn do_renameat(
+    old_dirfd: i32,
+    old_path: &str,
+    new_dirfd: i32,
+    new_path: &str,
+) -> Result<(), Error> {
+    let old_path = resolve_path_at(old_dirfd, old_path)?;
+    let new_path = resolve_path_at(new_dirfd, new_path)?;
+    vfs::rename(&old_path, &new_path)
+}
+
+pub fn do_link(old_path: &str, new_path: &str) -> Result<(), Error> {
+    let old_path = resolve_path(old_path)?;
+    let new_path = resolve_path(new_path)?;
+    vfs::link(&old_path, &new_path)
+}
+
+pub fn do_symlink(target: &str, link_path: &str) -> Result<(), Error> {
+    if target.is_empty() {
+        return errno!(ENOENT, "The target of a symlink cannot be empty");
+    }
+    let link_path = resolve_path(link_path)?;
+    let (dir_inode, link_name) = vfs::lookup_parent(&link_path)?;
+    dir_inode.symlink(&link_name, target)?;
+    Ok(())
+}
+
+pub fn do_readlink(path: &str) -> Result<String, Error> {
+    let path = resolve_path(path)?;
+    vfs::lookup_no_follow(&path)?.read_link()
+}
+
+pub fn do_truncate(path: &str, len: off_t) -> Result<(), Error> {
+    if len < 0 {
+        return errno!(EINVAL, "The length is negative");
+    }
+    get_range_end(0, len as u64)?;
+    let path = resolve_path(path)?;
+    let inode = vfs::lookup(&path)?;
+    match inode.get_type()? {
+        FileType::File => {}
+        FileType::Dir => return errno!(EISDIR, "Cannot truncate a directory"),
+        _ => return errno!(EINVAL, "Not a regular file"),
+    }
+    inode.open(O_WRONLY)?.set_len(len as u64)
+}
+
+pub fn do_ftruncate(fd: FileDesc, len: off_t) -> Result<(), Error> {
+    if len < 0 {
+        return errno!(EINVAL, "The length is negative");
+    }
+    get_range_end(0, len as u64)?;
+    let file_ref = get_file(fd)?;
+    file_ref.set_len(len as u64)
+}
+
+pub fn do_fallocate(fd: FileDesc, mode: u32, offset: off_t, len: off_t) -> Result<(), Error> {
+    if offset < 0 || len <= 0 {
+        return errno!(EINVAL, "Invalid range to allocate");
+    }
+    get_range_end(offset as u64, len as u64)?;
+    let file_ref = get_file(fd)?;
+    match mode {
+        0 => file_ref.allocate(offset as u64, len as u64),
+        // Space is not reserved in advance, so there is nothing to do as long
+        // as the file size is kept
+        FALLOC_FL_KEEP_SIZE => {
+            if file_ref.metadata()?.type_ != FileType::File {
+                return errno!(ENODEV, "Not a regular file");
+            }
+            Ok(())
+        }
+        _ => errno!(EOPNOTSUPP, "The fallocate mode is not supported"),
+    }
+}
+
+pub fn do_getdents64(fd: FileDesc, buf: &mut [u8]) -> Result<usize, Error> {
+    let file_ref = get_file(fd)?;
+    let dir_file = file_ref
+        .as_any()
+        .downcast_ref::<DirFile>()
+        .ok_or_else(|| Error::new(ENOTDIR, "Not a directory"))?;
+    dir_file.read_entries(buf)
+}
+
+pub fn do_chdir(path: &str) -> Result<(), Error> {
+    let path = resolve_path(path)?;
+    let (inode, real_path) = vfs::lookup_real_path(&path)?;
+    if inode.get_type()? != FileType::Dir {
+        return errno!(ENOTDIR, "Not a directory");
+    }
+    let current_ref = process::get_current();
+    let mut current = current_ref.lock().unwrap();
+    current.set_cwd(real_path);
+    Ok(())
+}
+
+pub fn do_fchdir(fd: FileDesc) -> Result<(), Error> {
     let current_ref = process::get_current();
-    let current_process = current_ref.lock().unwrap();
-    let file_ref = current_process.get_files().get(fd)?;
+    let mut current = current_ref.lock().unwrap();
+    let path = {
+        let file_ref = current.get_files().get(fd)?;
+        let dir_file = file_ref
+            .as_any()
+            .downcast_ref::<DirFile>()
+            .ok_or_else(|| Error::new(ENOTDIR, "Not a directory"))?;
+        dir_file.get_path().to_owned()
+    };
+    current.set_cwd(path);
+    Ok(())
+}
+
+pub fn do_getcwd() -> String {
+    let current_ref = process::get_current();
+    let current = current_ref.lock().unwrap();
+    current.get_cwd().to_owned()
+}
+
+/// Get the file of an fd of the current process
+pub fn get_file(fd: FileDesc) -> Result<FileRef, Error> {
+    // The process is unlocked before the I/O, which may block
+    let current_ref = process::get_current();
+    let current = current_ref.lock().unwrap();
+    current.get_files().get(fd)
+}
+
+pub fn do_write(fd: FileDesc, buf: &[u8]) -> Result<usize, Error> {
+    let file_ref = get_file(fd)?;
     file_ref.write(buf)
 }
 
 pub fn do_read(fd: FileDesc, buf: &mut [u8]) -> Result<usize, Error> {
-    let current_ref = process::get_current();
-    let current_process = current_ref.lock().unwrap();
-    let file_ref = current_process.get_files().get(fd)?;
+    let file_ref = get_file(fd)?;
     file_ref.read(buf)
 }
 
 pub fn do_writev<'a, 'b>(fd: FileDesc, bufs: &'a [&'b [u8]]) -> Result<usize, Error> {
-    let current_ref = process::get_current();
-    let current_process = current_ref.lock().unwrap();
-    let file_ref = current_process.get_files().get(fd)?;
+    let file_ref = get_file(fd)?;
     file_ref.writev(bufs)
 }
 
 pub fn do_readv<'a, 'b>(fd: FileDesc, bufs: &'a mut [&'b mut [u8]]) -> Result<usize, Error> {
-    let current_ref = process::get_current();
-    let current_process = current_ref.lock().unwrap();
-    let file_ref = current_process.get_files().get(fd)?;
+    let file_ref = get_file(fd)?;
     file_ref.readv(bufs)
 }
 
+pub fn do_pread(fd: FileDesc, buf: &mut [u8], offset: off_t) -> Result<usize, Error> {
+    let file_ref = get_file_for_positional_io(fd, offset)?;
+    file_ref.read_at(offset as usize, buf)
+}
+
+pub fn do_pwrite(fd: FileDesc, buf: &[u8], offset: off_t) -> Result<usize, Error> {
+    let file_ref = get_file_for_positional_io(fd, offset)?;
+    file_ref.write_at(offset as usize, buf)
+}
+
+pub fn do_preadv<'a, 'b>(
+    fd: FileDesc,
+    bufs: &'a mut [&'b mut [u8]],
+    offset: off_t,
+) -> Result<usize, Error> {
+    let file_ref = get_file_for_positional_io(fd, offset)?;
+    let mut total_bytes = 0;
+    for buf in bufs {
+        match file_ref.read_at(offset as usize + total_bytes, buf) {
+            Ok(this_len) => {
+                total_bytes += this_len;
+                if this_len < buf.len() {
+                    break;
+                }
+            }
+            Err(e) => {
+                match total_bytes {
+                    // a complete failure
+                    0 => return Err(e),
+                    // a partially failure
+                    _ => break,
+                }
+            }
+        }
+    }
+    Ok(total_bytes)
+}
+
+pub fn do_pwritev<'a, 'b>(
+    fd: FileDesc,
+    bufs: &'a [&'b [u8]],
+    offset: off_t,
+) -> Result<usize, Error> {
+    let file_ref = get_file_for_positional_io(fd, offset)?;
+    let mut total_bytes = 0;
+    for buf in bufs {
+        match file_ref.write_at(offset as usize + total_bytes, buf) {
+            Ok(this_len) => {
+                total_bytes += this_len;
+                if this_len < buf.len() {
+                    break;
+                }
+            }
+            Err(e) => {
+                match total_bytes {
+                    // a complete failure
+                    0 => return Err(e),
+                    // a partially failure
+                    _ => break,
+                }
+            }
+        }
+    }
+    Ok(total_bytes)
+}
+
+fn get_file_for_positional_io(fd: FileDesc, offset: off_t) -> Result<FileRef, Error> {
+    if offset < 0 {
+        return errno!(EINVAL, "The offset is negative");
+    }
+    get_file(fd)
+}
+
 pub fn do_lseek<'a, 'b>(fd: FileDesc, offset: SeekFrom) -> Result<off_t, Error> {
-    let current_ref = process::get_current();
-    let current_process = current_ref.lock().unwrap();
-    let file_ref = current_process.get_files().get(fd)?;
+    let file_ref = get_file(fd)?;
     file_ref.seek(offset)
 }
 
 pub fn do_close(fd: FileDesc) -> Result<(), Error> {
-    let current_ref = process::get_current();
-    let mut current_process = current_ref.lock().unwrap();
-    let file_table = current_process.get_files_mut();
-    file_table.del(fd)?;
+    let file_ref = {
+        let current_ref = process::get_current();
+        let mut current_process = current_ref.lock().unwrap();
+        let file_table = current_process.get_files_mut();
+        file_table.del(fd)?
+    };
+    // As Linux flushes a file on close, the cached data is written back here,
+    // so that a failure is reported rather than lost when the file is dropped.
+    // The fd is closed even if this fails.
+    if let Some(sgx_file) = file_ref.as_any().downcast_ref::<SgxFile>() {
+        if file_ref.get_status_flags()? & O_ACCMODE != O_RDONLY {
+            sgx_file.sync()?;
+        }
+    }
     Ok(())
 }
 
 pub fn do_pipe2(flags: u32) -> Result<[FileDesc; 2], Error> {
+    if flags & !(O_CLOEXEC | O_NONBLOCK) != 0 {
+        return errno!(EINVAL, "Invalid flags for pipe2");
+    }
     let current_ref = process::get_current();
     let mut current = current_ref.lock().unwrap();
-    let pipe = Pipe::new()?;
+    let pipe = Pipe::new(flags)?;
 
     let mut file_table = current.get_files_mut();
     let close_on_spawn = flags & O_CLOEXEC != 0;
@@ -156,7 +673,90 @@ pub fn do_dup3(old_fd: FileDesc, new_fd: FileDesc, flags: u32) -> Result<FileDes
     Ok(new_fd)
 }
 
+#[derive(Debug)]
+pub enum FcntlCmd {
+    /// Duplicate the fd to the lowest free fd that is not less than the arg
+    DupFd(FileDesc),
+    /// Like DupFd, but set FD_CLOEXEC on the new fd
+    DupFdCloexec(FileDesc),
+    /// Get the flags of the fd, i.e., FD_CLOEXEC
+    GetFd,
+    /// Set the flags of the fd
+    SetFd(u32),
+    /// Get the access mode and the status flags of the open file
+    GetFl,
+    /// Set the status flags of the open file
+    SetFl(u32),
+}
+
+impl FcntlCmd {
+    pub fn from_raw(cmd: u32, arg: u64) -> Result<FcntlCmd, Error> {
+        Ok(match cmd {
+            F_DUPFD | F_DUPFD_CLOEXEC => {
+                let min_fd = arg as i32;
+                if min_fd < 0 {
+                    return errno!(EINVAL, "Invalid file descriptor");
+                }
+                if cmd == F_DUPFD {
+                    FcntlCmd::DupFd(min_fd as FileDesc)
+                } else {
+                    FcntlCmd::DupFdCloexec(min_fd as FileDesc)
+                }
+            }
+            F_GETFD => FcntlCmd::GetFd,
+            F_SETFD => FcntlCmd::SetFd(arg as u32),
+            F_GETFL => FcntlCmd::GetFl,
+            F_SETFL => FcntlCmd::SetFl(arg as u32),
+            _ => return errno!(EINVAL, "Unsupported fcntl command"),
+        })
+    }
+}
+
+pub fn do_fcntl(fd: FileDesc, cmd: &FcntlCmd) -> Result<isize, Error> {
+    let current_ref = process::get_current();
+    let mut current = current_ref.lock().unwrap();
+    let file_table = current.get_files_mut();
+    let ret = match *cmd {
+        FcntlCmd::DupFd(min_fd) => {
+            let file = file_table.get(fd)?;
+            file_table.put_above(min_fd, file, false) as isize
+        }
+        FcntlCmd::DupFdCloexec(min_fd) => {
+            let file = file_table.get(fd)?;
+            file_table.put_above(min_fd, file, true) as isize
+        }
+        FcntlCmd::GetFd => {
+            if file_table.get_close_on_spawn(fd)? {
+                FD_CLOEXEC as isize
+            } else {
+                0
+            }
+        }
+        FcntlCmd::SetFd(fd_flags) => {
+            file_table.set_close_on_spawn(fd, fd_flags & FD_CLOEXEC != 0)?;
+            0
+        }
+        FcntlCmd::GetFl => file_table.get(fd)?.get_status_flags()? as isize,
+        FcntlCmd::SetFl(flags) => {
+            file_table.get(fd)?.set_status_flags(flags)?;
+            0
+        }
+    };
+    Ok(ret)
+}
+
+/// Write back the data of the file that is cached in the enclave, which only
+/// protected files have
+pub fn do_fsync(fd: FileDesc) -> Result<(), Error> {
+    let file_ref = get_file(fd)?;
+    if let Some(sgx_file) = file_ref.as_any().downcast_ref::<SgxFile>() {
+        sgx_file.sync()?;
+    }
+    Ok(())
+}
+
 pub fn do_sync() -> Result<(), Error> {
+    vfs::sync_all()?;
     unsafe {
         ocall_sync();
     }
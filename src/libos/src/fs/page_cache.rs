@@ -0,0 +1,463 @@
+use super::sgx_fs::HostFileVersion;
+use super::*;
+use std::collections::{BTreeMap, HashSet};
+
+// The size of a page in the page cache
+const PAGE_SIZE: usize = 4096;
+
+// The max total size of the pages in the page cache by default
+const PAGE_CACHE_DEFAULT_SIZE: usize = 16 * 1024 * 1024;
+
+lazy_static! {
+    /// The page cache shared by all the opened protected files
+    static ref PAGE_CACHE: SgxMutex<PageCache> =
+        SgxMutex::new(PageCache::new(PAGE_CACHE_DEFAULT_SIZE / PAGE_SIZE));
+}
+
+/// Set the max total size of the pages in the page cache, where zero disables
+/// the cache
+pub fn set_page_cache_size(size: usize) {
+    PAGE_CACHE.lock().unwrap().capacity = size / PAGE_SIZE;
+}
+
+/// Drop the cached pages of the closed files at `host_path` or under it, which
+/// are unlinked, moved or replaced on the host
+pub fn forget_closed_files(host_path: &str) {
+    PAGE_CACHE.lock().unwrap().forget_closed_files(host_path);
+}
+
+/// The id of a file in the page cache, which is unique among all the opened
+/// protected files and the closed ones whose pages are kept
+type FileId = u64;
+
+/// A page that is no longer dirty in the cache, whose data is to be written
+/// back at its index in the file
+type DirtyPage = (usize, Vec<u8>);
+
+/// A cache of the pages of files, which are indexed by the file and the index
+/// of the page in the file
+///
+/// When there are more pages than the capacity, the least recently used ones
+/// are evicted. The lock of the cache is always taken after that of a file,
+/// so only the dirty pages of the file that is locked by the caller can be
+/// evicted, which are written back by the caller.
+///
+/// The clean pages of a file are kept after it is closed, and used again when
+/// the file at the same host path is reopened, unless the host file has been
+/// changed since.
+struct PageCache {
+    pages: HashMap<(FileId, usize), CachedPage>,
+    /// The pages ordered by the time of their last use
+    lru_list: BTreeMap<u64, (FileId, usize)>,
+    /// The closed files whose pages are kept, indexed by their host paths
+    closed_files: HashMap<String, (FileId, HostFileVersion)>,
+    next_use_time: u64,
+    next_file_id: FileId,
+    capacity: usize,
+}
+
+struct CachedPage {
+    data: Vec<u8>,
+    is_dirty: bool,
+    use_time: u64,
+}
+
+impl PageCache {
+    fn new(capacity: usize) -> PageCache {
+        PageCache {
+            pages: HashMap::new(),
+            lru_list: BTreeMap::new(),
+            closed_files: HashMap::new(),
+            next_use_time: 0,
+            next_file_id: 0,
+            capacity,
+        }
+    }
+
+    fn new_file_id(&mut self) -> FileId {
+        let file_id = self.next_file_id;
+        self.next_file_id += 1;
+        file_id
+    }
+
+    /// Get the id of a file that is opened, which is that of the file when it
+    /// was closed if the host file is not changed since
+    fn open_file(&mut self, host_path: &str, version: &HostFileVersion) -> FileId {
+        // The pages of the other paths of the same host file, i.e., its hard
+        // links, become stale once the file is written through this path
+        let linked_paths: Vec<String> = self
+            .closed_files
+            .iter()
+            .filter(|&(path, &(_, ref closed_version))| {
+                path != host_path && closed_version.is_same_file(version)
+            })
+            .map(|(path, _)| path.clone())
+            .collect();
+        for path in linked_paths {
+            let (file_id, _) = self.closed_files.remove(&path).unwrap();
+            self.remove_file(file_id);
+        }
+
+        match self.closed_files.remove(host_path) {
+            Some((file_id, closed_version)) if closed_version == *version => file_id,
+            Some((file_id, _)) => {
+                self.remove_file(file_id);
+                self.new_file_id()
+            }
+            None => self.new_file_id(),
+        }
+    }
+
+    /// Keep the pages of a file that is closed, whose dirty pages are written
+    /// back
+    fn close_file(&mut self, host_path: &str, file_id: FileId, version: HostFileVersion) {
+        if let Some((old_file_id, _)) = self
+            .closed_files
+            .insert(host_path.to_owned(), (file_id, version))
+        {
+            self.remove_file(old_file_id);
+        }
+        // The files whose pages are all evicted are forgotten at times, so
+        // that there are not more closed files than cached pages
+        if self.closed_files.len() > self.capacity {
+            let cached_file_ids: HashSet<FileId> =
+                self.pages.keys().map(|&(file_id, _)| file_id).collect();
+            self.closed_files
+                .retain(|_, &mut (file_id, _)| cached_file_ids.contains(&file_id));
+        }
+    }
+
+    fn forget_closed_files(&mut self, host_path: &str) {
+        let forgotten_paths: Vec<String> = self
+            .closed_files
+            .keys()
+            .filter(|path| sgx_fs::is_same_or_under(path, host_path))
+            .cloned()
+            .collect();
+        for path in forgotten_paths {
+            let (file_id, _) = self.closed_files.remove(&path).unwrap();
+            self.remove_file(file_id);
+        }
+    }
+
+    /// Get a cached page, which becomes the most recently used one
+    fn use_page(&mut self, file_id: FileId, page_i: usize) -> Option<&mut CachedPage> {
+        let use_time = self.next_use_time;
+        let page = self.pages.get_mut(&(file_id, page_i))?;
+        self.next_use_time += 1;
+        self.lru_list.remove(&page.use_time);
+        self.lru_list.insert(use_time, (file_id, page_i));
+        page.use_time = use_time;
+        Some(page)
+    }
+
+    /// Add a page as the most recently used one, and return the dirty pages
+    /// of the file that are evicted
+    fn insert(
+        &mut self,
+        file_id: FileId,
+        page_i: usize,
+        data: Vec<u8>,
+        is_dirty: bool,
+    ) -> Vec<DirtyPage> {
+        let use_time = self.next_use_time;
+        self.next_use_time += 1;
+        let new_page = CachedPage {
+            data,
+            is_dirty,
+            use_time,
+        };
+        if let Some(old_page) = self.pages.insert((file_id, page_i), new_page) {
+            self.lru_list.remove(&old_page.use_time);
+        }
+        self.lru_list.insert(use_time, (file_id, page_i));
+        self.evict(file_id)
+    }
+
+    /// Evict the least recently used pages until the cache is within its
+    /// capacity, skipping the dirty pages of other files than the given one
+    fn evict(&mut self, file_id: FileId) -> Vec<DirtyPage> {
+        let mut dirty_pages = Vec::new();
+        while self.pages.len() > self.capacity {
+            let victim = {
+                let pages = &self.pages;
+                self.lru_list
+                    .iter()
+                    .find(|&(_, key)| key.0 == file_id || !pages[key].is_dirty)
+                    .map(|(&use_time, &key)| (use_time, key))
+            };
+            let (use_time, key) = match victim {
+                Some(victim) => victim,
+                None => break,
+            };
+            self.lru_list.remove(&use_time);
+            let page = self.pages.remove(&key).unwrap();
+            if page.is_dirty {
+                dirty_pages.push((key.1, page.data));
+            }
+        }
+        dirty_pages
+    }
+
+    /// Mark all the pages of the file clean, and return copies of the dirty
+    /// ones in the order of their indexes
+    fn clean_file(&mut self, file_id: FileId) -> Vec<DirtyPage> {
+        let mut dirty_pages: Vec<DirtyPage> = self
+            .pages
+            .iter_mut()
+            .filter(|&(key, ref page)| key.0 == file_id && page.is_dirty)
+            .map(|(key, page)| {
+                page.is_dirty = false;
+                (key.1, page.data.clone())
+            })
+            .collect();
+        dirty_pages.sort_by_key(|&(page_i, _)| page_i);
+        dirty_pages
+    }
+
+    /// Drop all the pages of the file, including the dirty ones
+    fn remove_file(&mut self, file_id: FileId) {
+        let keys: Vec<(FileId, usize)> = self
+            .pages
+            .keys()
+            .filter(|key| key.0 == file_id)
+            .cloned()
+            .collect();
+        for key in keys {
+            let page = self.pages.remove(&key).unwrap();
+            self.lru_list.remove(&page.use_time);
+        }
+    }
+}
+
+/// An opened SGX protected file, whose data is read and written through the
+/// page cache
+///
+/// The data written stays in the cache until the dirty pages are evicted or
+/// the file is synced. The pages of the file are kept in the cache when it is
+/// closed, unless it is no longer at its host path.
+pub struct ProtectedFile {
+    file: fs_impl::SgxFile,
+    /// The current path of the file on the host, which follows renames
+    host_path: String,
+    /// Whether the file is unlinked or replaced at its host path
+    is_forgotten: bool,
+    key: sgx_key_128bit_t,
+    id: FileId,
+    /// The size of the file, including the data not yet written back
+    size: usize,
+}
+
+impl ProtectedFile {
+    pub fn new(
+        mut file: fs_impl::SgxFile,
+        host_path: &str,
+        key: &sgx_key_128bit_t,
+    ) -> Result<ProtectedFile, Error> {
+        let size = get_raw_size(&mut file)?;
+        let version = sgx_fs::get_host_file_version(host_path)?;
+        let id = PAGE_CACHE.lock().unwrap().open_file(host_path, &version);
+        Ok(ProtectedFile {
+            file,
+            host_path: host_path.to_owned(),
+            is_forgotten: false,
+            key: *key,
+            id,
+            size,
+        })
+    }
+
+    pub fn get_size(&self) -> usize {
+        self.size
+    }
+
+    pub fn get_host_path(&self) -> &str {
+        &self.host_path
+    }
+
+    pub fn get_key(&self) -> &sgx_key_128bit_t {
+        &self.key
+    }
+
+    /// Record that the file is moved on the host, where the key changes if
+    /// the file is re-encrypted
+    pub fn set_host_path(&mut self, host_path: &str, key: &sgx_key_128bit_t) {
+        self.host_path = host_path.to_owned();
+        self.key = *key;
+    }
+
+    /// Record that the file is unlinked or replaced on the host, so that its
+    /// pages are dropped when it is closed
+    pub fn forget_host_path(&mut self) {
+        self.is_forgotten = true;
+    }
+
+    pub fn read_at(&mut self, offset: usize, buf: &mut [u8]) -> Result<usize, Error> {
+        if offset >= self.size {
+            return Ok(0);
+        }
+        let end = std::cmp::min(offset + buf.len(), self.size);
+        let mut pos = offset;
+        while pos < end {
+            let (page_i, page_offset) = (pos / PAGE_SIZE, pos % PAGE_SIZE);
+            let len = std::cmp::min(PAGE_SIZE - page_offset, end - pos);
+            let dst = &mut buf[pos - offset..pos - offset + len];
+            let is_cached = match PAGE_CACHE.lock().unwrap().use_page(self.id, page_i) {
+                Some(page) => {
+                    dst.copy_from_slice(&page.data[page_offset..page_offset + len]);
+                    true
+                }
+                None => false,
+            };
+            if !is_cached {
+                let data = self.load_page(page_i)?;
+                dst.copy_from_slice(&data[page_offset..page_offset + len]);
+                self.insert_page(page_i, data, false)?;
+            }
+            pos += len;
+        }
+        Ok(end - offset)
+    }
+
+    /// Write all the data at the offset, where a gap after the end of the
+    /// file reads as zeros
+    pub fn write_at(&mut self, offset: usize, buf: &[u8]) -> Result<usize, Error> {
+        let end = offset + buf.len();
+        let mut pos = offset;
+        while pos < end {
+            let (page_i, page_offset) = (pos / PAGE_SIZE, pos % PAGE_SIZE);
+            let len = std::cmp::min(PAGE_SIZE - page_offset, end - pos);
+            let src = &buf[pos - offset..pos - offset + len];
+            // The size covers the data before the page may be written back
+            self.size = std::cmp::max(self.size, pos + len);
+            let is_cached = match PAGE_CACHE.lock().unwrap().use_page(self.id, page_i) {
+                Some(page) => {
+                    page.data[page_offset..page_offset + len].copy_from_slice(src);
+                    page.is_dirty = true;
+                    true
+                }
+                None => false,
+            };
+            if !is_cached {
+                // A page that is fully overwritten need not be loaded
+                let mut data = if len == PAGE_SIZE {
+                    vec![0; PAGE_SIZE]
+                } else {
+                    self.load_page(page_i)?
+                };
+                data[page_offset..page_offset + len].copy_from_slice(src);
+                self.insert_page(page_i, data, true)?;
+            }
+            pos += len;
+        }
+        Ok(buf.len())
+    }
+
+    /// Write all the dirty pages back to the protected file, and flush it
+    pub fn sync(&mut self) -> Result<(), Error> {
+        self.write_back_all()?;
+        self.file
+            .flush()
+            .map_err(|e| Error::new(EIO, "Failed to flush the SGX-protected file"))
+    }
+
+    /// Access the protected file directly by `access_fn`, e.g., to change its
+    /// size, after the pages of the file are written back and dropped
+    pub fn with_raw_file<F, T>(&mut self, access_fn: F) -> Result<T, Error>
+    where
+        F: FnOnce(&mut fs_impl::SgxFile) -> Result<T, Error>,
+    {
+        self.write_back_all()?;
+        PAGE_CACHE.lock().unwrap().remove_file(self.id);
+        let result = access_fn(&mut self.file);
+        self.size = get_raw_size(&mut self.file)?;
+        result
+    }
+
+    /// Read a page from the protected file, where the part beyond its end is
+    /// filled with zeros
+    fn load_page(&mut self, page_i: usize) -> Result<Vec<u8>, Error> {
+        let mut data = vec![0; PAGE_SIZE];
+        let start = page_i * PAGE_SIZE;
+        let raw_size = get_raw_size(&mut self.file)?;
+        if start < raw_size {
+            let len = std::cmp::min(PAGE_SIZE, raw_size - start);
+            self.file
+                .seek(SeekFrom::Start(start as u64))
+                .map_err(|e| Error::new(EIO, "Failed to seek the SGX-protected file"))?;
+            self.file
+                .read_exact(&mut data[..len])
+                .map_err(|e| Error::new(EIO, "Failed to read the SGX-protected file"))?;
+        }
+        Ok(data)
+    }
+
+    fn insert_page(&mut self, page_i: usize, data: Vec<u8>, is_dirty: bool) -> Result<(), Error> {
+        let evicted_pages = PAGE_CACHE
+            .lock()
+            .unwrap()
+            .insert(self.id, page_i, data, is_dirty);
+        self.write_back(evicted_pages)
+    }
+
+    fn write_back_all(&mut self) -> Result<(), Error> {
+        let dirty_pages = PAGE_CACHE.lock().unwrap().clean_file(self.id);
+        self.write_back(dirty_pages)
+    }
+
+    /// Write the pages to the protected file, where the part of a page beyond
+    /// the size of the file is not written
+    fn write_back(&mut self, dirty_pages: Vec<DirtyPage>) -> Result<(), Error> {
+        for (page_i, data) in dirty_pages {
+            let start = page_i * PAGE_SIZE;
+            if start >= self.size {
+                continue;
+            }
+            let len = std::cmp::min(PAGE_SIZE, self.size - start);
+            // A protected file cannot seek beyond its end, so the gap is
+            // filled with zeros
+            let raw_size = get_raw_size(&mut self.file)?;
+            if start > raw_size {
+                sgx_fs::write_zeros(&mut self.file, start - raw_size)?;
+            } else {
+                self.file
+                    .seek(SeekFrom::Start(start as u64))
+                    .map_err(|e| Error::new(EIO, "Failed to seek the SGX-protected file"))?;
+            }
+            self.file
+                .write_all(&data[..len])
+                .map_err(|e| Error::new(EIO, "Failed to write the SGX-protected file"))?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for ProtectedFile {
+    fn drop(&mut self) {
+        // The version of the host file is taken after all the data is flushed.
+        // The data is synced on close, so a failure here is only reported.
+        let version = self
+            .sync()
+            .and_then(|_| sgx_fs::get_host_file_version(&self.host_path));
+        if let Err(ref e) = version {
+            println!(
+                "[WARNING] Failed to write back the SGX-protected file {}: {}",
+                self.host_path, e
+            );
+        }
+        let mut page_cache = PAGE_CACHE.lock().unwrap();
+        match version {
+            Ok(version) if !self.is_forgotten => {
+                page_cache.close_file(&self.host_path, self.id, version)
+            }
+            _ => page_cache.remove_file(self.id),
+        }
+    }
+}
+
+fn get_raw_size(file: &mut fs_impl::SgxFile) -> Result<usize, Error> {
+    let size = file
+        .seek(SeekFrom::End(0))
+        .map_err(|e| Error::new(EIO, "Failed to get the size of the SGX-protected file"))?;
+    Ok(size as usize)
+}
@@ -1,9 +1,10 @@
 use super::*;
 use util::ring_buf::*;
 
-// TODO: Use Waiter and WaitQueue infrastructure to sleep when blocking
-
 pub const PIPE_BUF_SIZE: usize = 2 * 1024 * 1024;
+// Writes of at most PIPE_BUF bytes are atomic, i.e., not interleaved with
+// other writes
+pub const PIPE_BUF: usize = 4096;
 
 #[derive(Debug)]
 pub struct Pipe {
@@ -12,52 +13,120 @@ pub struct Pipe {
 }
 
 impl Pipe {
-    pub fn new() -> Result<Pipe, Error> {
+    pub fn new(flags: u32) -> Result<Pipe, Error> {
         let mut ring_buf = RingBuf::new(PIPE_BUF_SIZE);
+        let wait_queues = Arc::new(PipeWaitQueues::new());
         Ok(Pipe {
             reader: PipeReader {
                 inner: SgxMutex::new(ring_buf.reader),
+                status_flags: StatusFlags::new(O_RDONLY | (flags & O_NONBLOCK)),
+                wait_queues: wait_queues.clone(),
             },
             writer: PipeWriter {
                 inner: SgxMutex::new(ring_buf.writer),
+                status_flags: StatusFlags::new(O_WRONLY | (flags & O_NONBLOCK)),
+                wait_queues: wait_queues,
             },
         })
     }
 }
 
+/// The reader and the writer of a pipe that sleep until the pipe becomes
+/// readable or writable, or that poll the pipe
+#[derive(Debug)]
+struct PipeWaitQueues {
+    readers: IoWaitQueue,
+    writers: IoWaitQueue,
+}
+
+impl PipeWaitQueues {
+    fn new() -> PipeWaitQueues {
+        PipeWaitQueues {
+            readers: IoWaitQueue::new(),
+            writers: IoWaitQueue::new(),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct PipeReader {
     inner: SgxMutex<RingBufReader>,
+    status_flags: StatusFlags,
+    wait_queues: Arc<PipeWaitQueues>,
+}
+
+impl PipeReader {
+    /// Wait until the pipe has some data or the writer is closed, and then
+    /// pass at most `max_len` bytes at the start of the pipe to `consume_fn`,
+    /// which returns how many of them are consumed. The pipe is locked during
+    /// `consume_fn`, so it must not sleep.
+    pub fn consume_with<F>(
+        &self,
+        max_len: usize,
+        is_nonblocking: bool,
+        mut consume_fn: F,
+    ) -> Result<usize, Error>
+    where
+        F: FnMut(&[u8]) -> Result<usize, Error>,
+    {
+        if max_len == 0 {
+            return Ok(0);
+        }
+        let consumed_len = self.wait_queues.readers.wait_until(is_nonblocking, || {
+            let ringbuf = self.inner.lock().unwrap();
+            let is_writer_closed = ringbuf.is_peer_closed();
+            let mut buf = vec![0; max_len.min(ringbuf.bytes_to_read())];
+            if buf.is_empty() {
+                return if is_writer_closed {
+                    Ok(Some(0))
+                } else {
+                    Ok(None)
+                };
+            }
+            ringbuf.peek(&mut buf);
+            let consumed_len = consume_fn(&buf)?;
+            Ok(Some(ringbuf.skip(consumed_len)))
+        })?;
+        if consumed_len > 0 {
+            self.wait_queues.writers.wake_all();
+        }
+        Ok(consumed_len)
+    }
+
+    /// Whether the writer is of the same pipe as this reader
+    pub fn is_same_pipe(&self, writer: &PipeWriter) -> bool {
+        Arc::ptr_eq(&self.wait_queues, &writer.wait_queues)
+    }
 }
 
 impl File for PipeReader {
     fn read(&self, buf: &mut [u8]) -> Result<usize, Error> {
-        let ringbuf = self.inner.lock().unwrap();
-        ringbuf.read(buf)
+        self.readv(&mut [buf])
     }
 
+    /// Read the available data, or sleep until some data is written. Only
+    /// when the writer has been closed is an empty read the end of file.
     fn readv<'a, 'b>(&self, bufs: &'a mut [&'b mut [u8]]) -> Result<usize, Error> {
-        let mut ringbuf = self.inner.lock().unwrap();
-        let mut total_bytes = 0;
-        for buf in bufs {
-            match ringbuf.read(buf) {
-                Ok(this_len) => {
-                    total_bytes += this_len;
-                    if this_len < buf.len() {
-                        break;
-                    }
-                }
-                Err(e) => {
-                    match total_bytes {
-                        // a complete failure
-                        0 => return Err(e),
-                        // a partially failure
-                        _ => break,
-                    }
-                }
+        if bufs.iter().all(|buf| buf.len() == 0) {
+            return Ok(0);
+        }
+        let is_nonblocking = self.status_flags.is_nonblocking();
+        let read_len = self.wait_queues.readers.wait_until(is_nonblocking, || {
+            let ringbuf = self.inner.lock().unwrap();
+            // Checked before reading, so the data written right before the
+            // writer is closed is not missed
+            let is_writer_closed = ringbuf.is_peer_closed();
+            let read_len = ringbuf.readv(bufs)?;
+            if read_len > 0 || is_writer_closed {
+                Ok(Some(read_len))
+            } else {
+                Ok(None)
             }
+        })?;
+        if read_len > 0 {
+            self.wait_queues.writers.wake_all();
         }
-        Ok(total_bytes)
+        Ok(read_len)
     }
 
     fn write(&self, buf: &[u8]) -> Result<usize, Error> {
@@ -74,9 +143,76 @@ impl File for PipeReader {
         ))
     }
 
+    fn read_at(&self, offset: usize, buf: &mut [u8]) -> Result<usize, Error> {
+        Err(Error::new(Errno::ESPIPE, "Pipe does not support read_at"))
+    }
+
+    fn write_at(&self, offset: usize, buf: &[u8]) -> Result<usize, Error> {
+        Err(Error::new(Errno::ESPIPE, "Pipe does not support write_at"))
+    }
+
     fn seek(&self, pos: SeekFrom) -> Result<off_t, Error> {
         Err(Error::new(Errno::ESPIPE, "Pipe does not support seek"))
     }
+
+    fn set_len(&self, len: u64) -> Result<(), Error> {
+        Err(Error::new(Errno::EINVAL, "Pipe does not support truncate"))
+    }
+
+    fn allocate(&self, offset: u64, len: u64) -> Result<(), Error> {
+        Err(Error::new(Errno::ESPIPE, "Pipe does not support allocate"))
+    }
+
+    fn metadata(&self) -> Result<Metadata, Error> {
+        Ok(pipe_metadata())
+    }
+
+    fn get_status_flags(&self) -> Result<u32, Error> {
+        Ok(self.status_flags.get())
+    }
+
+    fn set_status_flags(&self, flags: u32) -> Result<(), Error> {
+        self.status_flags.set(flags);
+        Ok(())
+    }
+
+    fn poll(&self) -> Result<u32, Error> {
+        let ringbuf = self.inner.lock().unwrap();
+        let mut events = 0;
+        if ringbuf.bytes_to_read() > 0 {
+            events |= POLLIN | POLLRDNORM;
+        }
+        if ringbuf.is_peer_closed() {
+            events |= POLLHUP;
+        }
+        Ok(events)
+    }
+
+    fn poll_queue(&self) -> Option<&IoWaitQueue> {
+        Some(&self.wait_queues.readers)
+    }
+
+    fn ioctl(&self, cmd: &mut IoctlCmd) -> Result<(), Error> {
+        match *cmd {
+            IoctlCmd::FionRead(ref mut len) => {
+                **len = self.inner.lock().unwrap().bytes_to_read() as i32;
+                Ok(())
+            }
+            _ => errno!(ENOTTY, "Pipe does not support the ioctl command"),
+        }
+    }
+
+    fn as_any(&self) -> &Any {
+        self
+    }
+}
+
+impl Drop for PipeReader {
+    fn drop(&mut self) {
+        self.inner.lock().unwrap().close();
+        // So that the sleeping writer gets EPIPE
+        self.wait_queues.writers.wake_all();
+    }
 }
 
 unsafe impl Send for PipeReader {}
@@ -85,36 +221,53 @@ unsafe impl Sync for PipeReader {}
 #[derive(Debug)]
 pub struct PipeWriter {
     inner: SgxMutex<RingBufWriter>,
+    status_flags: StatusFlags,
+    wait_queues: Arc<PipeWaitQueues>,
+}
+
+impl PipeWriter {
+    /// Write all the data, sleeping whenever the pipe is full. If the reader
+    /// has been closed, the data written so far is returned, or EPIPE if none.
+    /// In non-blocking mode, only the data that fits is written.
+    pub fn write_bufs(&self, bufs: &[&[u8]], is_nonblocking: bool) -> Result<usize, Error> {
+        let total_len: usize = bufs.iter().map(|buf| buf.len()).sum();
+        let mut written_len = 0;
+        self.wait_queues.writers.wait_until(is_nonblocking, || {
+            let ringbuf = self.inner.lock().unwrap();
+            if ringbuf.is_peer_closed() {
+                return match written_len {
+                    0 => errno!(EPIPE, "Reader has been closed"),
+                    _ => Ok(Some(written_len)),
+                };
+            }
+            if total_len <= PIPE_BUF && ringbuf.bytes_to_write() < total_len {
+                return Ok(None);
+            }
+            let this_len = match ringbuf.writev(bufs, written_len) {
+                Ok(this_len) => this_len,
+                Err(e) if written_len == 0 => return Err(e),
+                Err(_) => return Ok(Some(written_len)),
+            };
+            if this_len > 0 {
+                written_len += this_len;
+                self.wait_queues.readers.wake_all();
+            }
+            if written_len == total_len || (is_nonblocking && written_len > 0) {
+                Ok(Some(written_len))
+            } else {
+                Ok(None)
+            }
+        })
+    }
 }
 
 impl File for PipeWriter {
     fn write(&self, buf: &[u8]) -> Result<usize, Error> {
-        let ringbuf = self.inner.lock().unwrap();
-        ringbuf.write(buf)
+        self.writev(&[buf])
     }
 
     fn writev<'a, 'b>(&self, bufs: &'a [&'b [u8]]) -> Result<usize, Error> {
-        let ringbuf = self.inner.lock().unwrap();
-        let mut total_bytes = 0;
-        for buf in bufs {
-            match ringbuf.write(buf) {
-                Ok(this_len) => {
-                    total_bytes += this_len;
-                    if this_len < buf.len() {
-                        break;
-                    }
-                }
-                Err(e) => {
-                    match total_bytes {
-                        // a complete failure
-                        0 => return Err(e),
-                        // a partially failure
-                        _ => break,
-                    }
-                }
-            }
-        }
-        Ok(total_bytes)
+        self.write_bufs(bufs, self.status_flags.is_nonblocking())
     }
 
     fn read(&self, buf: &mut [u8]) -> Result<usize, Error> {
@@ -125,10 +278,95 @@ impl File for PipeWriter {
         Err(Error::new(Errno::EBADF, "PipeWriter does not support read"))
     }
 
+    fn read_at(&self, offset: usize, buf: &mut [u8]) -> Result<usize, Error> {
+        Err(Error::new(Errno::ESPIPE, "Pipe does not support read_at"))
+    }
+
+    fn write_at(&self, offset: usize, buf: &[u8]) -> Result<usize, Error> {
+        Err(Error::new(Errno::ESPIPE, "Pipe does not support write_at"))
+    }
+
     fn seek(&self, seek_pos: SeekFrom) -> Result<off_t, Error> {
         Err(Error::new(Errno::ESPIPE, "Pipe does not support seek"))
     }
+
+    fn set_len(&self, len: u64) -> Result<(), Error> {
+        Err(Error::new(Errno::EINVAL, "Pipe does not support truncate"))
+    }
+
+    fn allocate(&self, offset: u64, len: u64) -> Result<(), Error> {
+        Err(Error::new(Errno::ESPIPE, "Pipe does not support allocate"))
+    }
+
+    fn metadata(&self) -> Result<Metadata, Error> {
+        Ok(pipe_metadata())
+    }
+
+    fn get_status_flags(&self) -> Result<u32, Error> {
+        Ok(self.status_flags.get())
+    }
+
+    fn set_status_flags(&self, flags: u32) -> Result<(), Error> {
+        self.status_flags.set(flags);
+        Ok(())
+    }
+
+    fn poll(&self) -> Result<u32, Error> {
+        let ringbuf = self.inner.lock().unwrap();
+        if ringbuf.is_peer_closed() {
+            return Ok(POLLERR);
+        }
+        // Writable only if an atomic write does not block
+        if ringbuf.bytes_to_write() >= PIPE_BUF {
+            Ok(POLLOUT | POLLWRNORM)
+        } else {
+            Ok(0)
+        }
+    }
+
+    fn poll_queue(&self) -> Option<&IoWaitQueue> {
+        Some(&self.wait_queues.writers)
+    }
+
+    /// FIONREAD gets the number of bytes in the pipe as on Linux
+    fn ioctl(&self, cmd: &mut IoctlCmd) -> Result<(), Error> {
+        match *cmd {
+            IoctlCmd::FionRead(ref mut len) => {
+                let bytes_to_write = self.inner.lock().unwrap().bytes_to_write();
+                // One byte of the ring buffer is always kept free
+                **len = (PIPE_BUF_SIZE - 1 - bytes_to_write) as i32;
+                Ok(())
+            }
+            _ => errno!(ENOTTY, "Pipe does not support the ioctl command"),
+        }
+    }
+
+    fn as_any(&self) -> &Any {
+        self
+    }
+}
+
+impl Drop for PipeWriter {
+    fn drop(&mut self) {
+        self.inner.lock().unwrap().close();
+        // So that the sleeping reader gets EOF
+        self.wait_queues.readers.wake_all();
+    }
 }
 
 unsafe impl Send for PipeWriter {}
 unsafe impl Sync for PipeWriter {}
+
+fn pipe_metadata() -> Metadata {
+    Metadata {
+        dev: 0,
+        ino: 0,
+        size: 0,
+        type_: FileType::NamedPipe,
+        mode: 0o600,
+        nlinks: 1,
+        atime: Default::default(),
+        mtime: Default::default(),
+        ctime: Default::default(),
+    }
+}
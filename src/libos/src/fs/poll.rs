@@ -0,0 +1,353 @@
+use super::*;
+use process::{WaitQueue, Waiter};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// The events of poll
+pub const POLLIN: u32 = 0x001;
+pub const POLLPRI: u32 = 0x002;
+pub const POLLOUT: u32 = 0x004;
+pub const POLLERR: u32 = 0x008;
+pub const POLLHUP: u32 = 0x010;
+pub const POLLNVAL: u32 = 0x020;
+pub const POLLRDNORM: u32 = 0x040;
+pub const POLLWRNORM: u32 = 0x100;
+
+// The events of a file that can always be read and written without blocking,
+// e.g., a regular file
+pub const POLL_ALWAYS_READY: u32 = POLLIN | POLLOUT | POLLRDNORM | POLLWRNORM;
+// The events that poll reports even if they are not requested
+const POLL_ALWAYS_REPORTED: u32 = POLLERR | POLLHUP | POLLNVAL;
+
+// The interval to check the files whose events are not notified, e.g., stdin
+const POLL_INTERVAL_US: u64 = 10_000;
+
+pub const FD_SETSIZE: usize = 1024;
+
+/// The threads waiting for the events of a file, including the threads that
+/// sleep in reading or writing the file and those polling the file
+#[derive(Debug)]
+pub struct IoWaitQueue {
+    waiters: SgxMutex<WaitQueue<(), ()>>,
+    // Incremented whenever the events may have changed, by which edge-triggered
+    // epoll tells whether to report the events again
+    num_changes: AtomicUsize,
+}
+
+impl IoWaitQueue {
+    pub fn new() -> IoWaitQueue {
+        IoWaitQueue {
+            waiters: SgxMutex::new(WaitQueue::new()),
+            num_changes: AtomicUsize::new(0),
+        }
+    }
+
+    /// Sleep until `try_fn` returns a result, or fail with EAGAIN instead of
+    /// sleeping in non-blocking mode
+    ///
+    /// The wait queue is locked while `try_fn` is called, so a wakeup after a
+    /// failed try is never missed.
+    pub fn wait_until<T, F>(&self, is_nonblocking: bool, mut try_fn: F) -> Result<T, Error>
+    where
+        F: FnMut() -> Result<Option<T>, Error>,
+    {
+        loop {
+            let waiter = {
+                let mut waiters = self.waiters.lock().unwrap();
+                if let Some(result) = try_fn()? {
+                    return Ok(result);
+                }
+                if is_nonblocking {
+                    return errno!(EAGAIN, "The operation would block");
+                }
+                let waiter = Waiter::new(&());
+                waiters.add_waiter(&waiter);
+                waiter
+            };
+            Waiter::sleep_until_woken_with_result(waiter);
+        }
+    }
+
+    pub fn get_num_changes(&self) -> usize {
+        self.num_changes.load(Ordering::SeqCst)
+    }
+
+    pub fn add_waiter(&self, waiter: &Waiter<(), ()>) {
+        self.waiters.lock().unwrap().add_waiter(waiter);
+    }
+
+    pub fn del_waiter(&self, waiter: &Waiter<(), ()>) {
+        self.waiters.lock().unwrap().del_waiter(waiter);
+    }
+
+    /// Wake all the waiting threads, as the events of the file may have
+    /// changed
+    pub fn wake_all(&self) {
+        self.num_changes.fetch_add(1, Ordering::SeqCst);
+        self.waiters
+            .lock()
+            .unwrap()
+            .del_and_wake_all_waiters(|_| Some(()));
+    }
+}
+
+/// The changes of the events of a file whose changes are not notified, which
+/// are counted when its I/O would block or is short
+///
+/// After the app has read all the data or filled all the space, the events
+/// may become ready again before they are checked, so edge-triggered epoll
+/// relies on this count instead of the events to tell whether they are new.
+#[derive(Debug)]
+pub struct IoChangeCounter {
+    num_changes: AtomicUsize,
+}
+
+impl IoChangeCounter {
+    pub fn new() -> IoChangeCounter {
+        IoChangeCounter {
+            num_changes: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn get_num_changes(&self) -> usize {
+        self.num_changes.load(Ordering::SeqCst)
+    }
+
+    /// Count a change if the I/O of `requested_len` bytes would block or is
+    /// short
+    pub fn count_io(&self, result: &Result<usize, Error>, requested_len: usize) {
+        let is_exhausted = match *result {
+            Ok(len) => len < requested_len,
+            Err(ref e) => e.errno == EAGAIN,
+        };
+        if is_exhausted {
+            self.num_changes.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct PollFd {
+    pub fd: i32,
+    pub events: u16,
+    pub revents: u16,
+}
+
+pub fn do_poll(poll_fds: &mut [PollFd], timeout_us: Option<u64>) -> Result<usize, Error> {
+    // Negative fds are ignored, while invalid fds get POLLNVAL
+    let files: Vec<Option<Result<FileRef, Error>>> = {
+        let current_ref = process::get_current();
+        let current = current_ref.lock().unwrap();
+        let file_table = current.get_files();
+        poll_fds
+            .iter()
+            .map(|poll_fd| match poll_fd.fd {
+                fd if fd < 0 => None,
+                fd => Some(file_table.get(fd as FileDesc)),
+            })
+            .collect()
+    };
+    let interests: Vec<(FileRef, u32)> = files
+        .iter()
+        .zip(poll_fds.iter())
+        .filter_map(|(file, poll_fd)| match *file {
+            Some(Ok(ref file)) => {
+                Some((file.clone(), poll_fd.events as u32 | POLL_ALWAYS_REPORTED))
+            }
+            _ => None,
+        })
+        .collect();
+
+    let has_invalid_fd = files.iter().any(|file| match *file {
+        Some(Err(_)) => true,
+        _ => false,
+    });
+    // Do not sleep if some fd is already known to be invalid
+    let timeout_us = if has_invalid_fd { Some(0) } else { timeout_us };
+    let mut ready_events = wait_for_events(&interests, timeout_us)?.into_iter();
+
+    let mut num_ready_fds = 0;
+    for (file, poll_fd) in files.iter().zip(poll_fds.iter_mut()) {
+        poll_fd.revents = match *file {
+            None => 0,
+            Some(Err(_)) => POLLNVAL as u16,
+            Some(Ok(_)) => ready_events.next().unwrap() as u16,
+        };
+        if poll_fd.revents != 0 {
+            num_ready_fds += 1;
+        }
+    }
+    Ok(num_ready_fds)
+}
+
+/// The fd set of select, which is a bitmap of FD_SETSIZE fds
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct FdSet {
+    bits: [u64; FD_SETSIZE / 64],
+}
+
+impl FdSet {
+    pub fn is_set(&self, fd: FileDesc) -> bool {
+        let fd = fd as usize;
+        self.bits[fd / 64] & (1 << (fd % 64)) != 0
+    }
+
+    pub fn set(&mut self, fd: FileDesc) {
+        let fd = fd as usize;
+        self.bits[fd / 64] |= 1 << (fd % 64);
+    }
+
+    pub fn clear_all(&mut self) {
+        self.bits = [0; FD_SETSIZE / 64];
+    }
+}
+
+// The events of poll that make an fd in each set of select ready
+const SELECT_READ_EVENTS: u32 = POLLIN | POLLRDNORM | POLLHUP | POLLERR;
+const SELECT_WRITE_EVENTS: u32 = POLLOUT | POLLWRNORM | POLLERR;
+const SELECT_EXCEPT_EVENTS: u32 = POLLPRI;
+
+pub fn do_select(
+    nfds: usize,
+    mut readfds: Option<&mut FdSet>,
+    mut writefds: Option<&mut FdSet>,
+    mut exceptfds: Option<&mut FdSet>,
+    timeout_us: Option<u64>,
+) -> Result<usize, Error> {
+    if nfds > FD_SETSIZE {
+        return errno!(EINVAL, "nfds is too large");
+    }
+
+    let is_set = |fds: &Option<&mut FdSet>, fd: FileDesc| match *fds {
+        Some(ref fds) => fds.is_set(fd),
+        None => false,
+    };
+    let mut fds = Vec::new();
+    let mut interests = Vec::new();
+    {
+        let current_ref = process::get_current();
+        let current = current_ref.lock().unwrap();
+        let file_table = current.get_files();
+        for fd in 0..nfds as FileDesc {
+            let mut events = 0;
+            if is_set(&readfds, fd) {
+                events |= SELECT_READ_EVENTS;
+            }
+            if is_set(&writefds, fd) {
+                events |= SELECT_WRITE_EVENTS;
+            }
+            if is_set(&exceptfds, fd) {
+                events |= SELECT_EXCEPT_EVENTS;
+            }
+            if events == 0 {
+                continue;
+            }
+            fds.push(fd);
+            interests.push((file_table.get(fd)?, events));
+        }
+    }
+
+    let ready_events = wait_for_events(&interests, timeout_us)?;
+
+    let mut num_ready_fds = 0;
+    let mut update_fds = |fds_opt: &mut Option<&mut FdSet>, ready_mask: u32| {
+        let fds_to_update = match *fds_opt {
+            Some(ref mut fds_to_update) => fds_to_update,
+            None => return,
+        };
+        let requested = **fds_to_update;
+        fds_to_update.clear_all();
+        for (&fd, &events) in fds.iter().zip(ready_events.iter()) {
+            if requested.is_set(fd) && events & ready_mask != 0 {
+                fds_to_update.set(fd);
+                num_ready_fds += 1;
+            }
+        }
+    };
+    update_fds(&mut readfds, SELECT_READ_EVENTS);
+    update_fds(&mut writefds, SELECT_WRITE_EVENTS);
+    update_fds(&mut exceptfds, SELECT_EXCEPT_EVENTS);
+    Ok(num_ready_fds)
+}
+
+/// Wait until any of the files is ready for its events of interest, or the
+/// timeout elapses, and then return the ready events of each file
+fn wait_for_events(
+    interests: &[(FileRef, u32)],
+    timeout_us: Option<u64>,
+) -> Result<Vec<u32>, Error> {
+    let wait_queues: Vec<&IoWaitQueue> = interests
+        .iter()
+        .filter_map(|&(ref file, _)| file.poll_queue())
+        .collect();
+    let has_unnotified = wait_queues.len() < interests.len();
+    let ready_events = wait_on_queues(&wait_queues, has_unnotified, timeout_us, || {
+        let ready_events = get_ready_events(interests)?;
+        if ready_events.iter().any(|&events| events != 0) {
+            Ok(Some(ready_events))
+        } else {
+            Ok(None)
+        }
+    })?;
+    Ok(ready_events.unwrap_or_else(|| vec![0; interests.len()]))
+}
+
+fn get_ready_events(interests: &[(FileRef, u32)]) -> Result<Vec<u32>, Error> {
+    interests
+        .iter()
+        .map(|&(ref file, events)| Ok(file.poll()? & events))
+        .collect()
+}
+
+/// Sleep on the wait queues until `try_fn` returns a result, or return None
+/// once the timeout elapses. If some of the events waited for are not
+/// notified through any wait queue, `try_fn` is retried regularly.
+pub fn wait_on_queues<T, F>(
+    wait_queues: &[&IoWaitQueue],
+    has_unnotified: bool,
+    timeout_us: Option<u64>,
+    mut try_fn: F,
+) -> Result<Option<T>, Error>
+where
+    F: FnMut() -> Result<Option<T>, Error>,
+{
+    let deadline_us =
+        timeout_us.map(|timeout_us| time::do_gettimeofday().as_usec() + timeout_us as i64);
+    loop {
+        // Add the waiter before trying, so that no change of the events
+        // after the try is missed
+        let waiter = Waiter::new(&());
+        for wait_queue in wait_queues {
+            wait_queue.add_waiter(&waiter);
+        }
+
+        let result = try_fn();
+        let is_done = match result {
+            Ok(Some(_)) | Err(_) => true,
+            Ok(None) => false,
+        };
+        let remaining_us =
+            deadline_us.map(|deadline_us| deadline_us - time::do_gettimeofday().as_usec());
+        let is_timeout = match remaining_us {
+            Some(remaining_us) => remaining_us <= 0,
+            None => false,
+        };
+        if !is_done && !is_timeout {
+            let sleep_us = match (remaining_us, has_unnotified) {
+                (Some(remaining_us), false) => Some(remaining_us as u64),
+                (Some(remaining_us), true) => Some((remaining_us as u64).min(POLL_INTERVAL_US)),
+                (None, false) => None,
+                (None, true) => Some(POLL_INTERVAL_US),
+            };
+            Waiter::sleep_until_woken_with_timeout(&waiter, sleep_us);
+        }
+
+        for wait_queue in wait_queues {
+            wait_queue.del_waiter(&waiter);
+        }
+        if is_done || is_timeout {
+            return result;
+        }
+    }
+}
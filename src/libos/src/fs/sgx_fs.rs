@@ -0,0 +1,876 @@
+use super::page_cache::{self, ProtectedFile};
+use super::*;
+use std::collections::BTreeMap;
+use std::ffi::{CStr, CString};
+use std::sync::Weak;
+use time::timespec_t;
+
+/// A file system of SGX protected files, which are stored in a directory of
+/// the host
+#[derive(Debug)]
+pub struct SgxFS {
+    host_root: String,
+    keys: Arc<SgxKeyTable>,
+}
+
+impl SgxFS {
+    pub fn new(host_root: &str, keys: SgxKeyTable) -> SgxFS {
+        SgxFS {
+            host_root: host_root.to_owned(),
+            keys: Arc::new(keys),
+        }
+    }
+
+    /// Create the host directory of the file system if it does not exist
+    pub fn create_host_root(&self) -> Result<(), Error> {
+        match mkdir_host_dir(&self.host_root, 0o755) {
+            Err(e) if e.errno != EEXIST => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl FileSystem for SgxFS {
+    fn root_inode(&self) -> INodeRef {
+        Arc::new(SgxINode {
+            host_path: self.host_root.clone(),
+            type_: FileType::Dir,
+            keys: self.keys.clone(),
+        })
+    }
+
+    fn sync(&self) -> Result<(), Error> {
+        // The files that are closed have been written back when their last
+        // handles were dropped
+        sync_opened_protected_files(&self.host_root)
+    }
+}
+
+/// An SGX protected file (or a directory that contains such files)
+///
+/// As the host is the source of truth about which files exist, an SgxINode is
+/// merely a path, whose type is checked when the inode is found. Directories
+/// are plain directories of the host, so the names of files are not protected.
+#[derive(Debug, Clone)]
+pub struct SgxINode {
+    host_path: String,
+    type_: FileType,
+    keys: Arc<SgxKeyTable>,
+}
+
+impl SgxINode {
+    fn new_child(&self, name: &str, type_: FileType) -> SgxINode {
+        SgxINode {
+            host_path: self.get_child_path(name),
+            type_,
+            keys: self.keys.clone(),
+        }
+    }
+
+    fn get_child_path(&self, name: &str) -> String {
+        if self.host_path.ends_with("/") {
+            format!("{}{}", self.host_path, name)
+        } else {
+            format!("{}/{}", self.host_path, name)
+        }
+    }
+
+    fn check_is_dir(&self) -> Result<(), Error> {
+        if self.type_ != FileType::Dir {
+            return errno!(ENOTDIR, "Not a directory");
+        }
+        Ok(())
+    }
+
+    /// Rename a protected file in this directory, or move one from another
+    /// directory, under a new name
+    ///
+    /// As the name of a protected file is bound to its content, the file is
+    /// re-encrypted under the new name, with the key of the new path. The
+    /// open files of the old file are moved to the new one, as on Linux.
+    fn rename_protected_file(&self, old_path: &str, new_name: &str) -> Result<(), Error> {
+        let new_path = self.get_child_path(new_name);
+        let old_key = self.keys.get_key(old_path)?;
+        let new_key = self.keys.get_key(&new_path)?;
+        let mode = stat_host_file(old_path)?.mode & !S_IFMT;
+
+        // The table stays locked, so that the old file is not opened again
+        // while it is being moved
+        let mut opened_files = OPENED_PROTECTED_FILES.lock().unwrap();
+        let opened_file = opened_files
+            .get(old_path)
+            .and_then(|opened_file| opened_file.upgrade());
+        match opened_file {
+            Some(opened_file) => {
+                // An opened file is locked by its handle, so the data is read
+                // through it, and the handle is reopened on the new file
+                let mut file_guard = opened_file.lock().unwrap();
+                file_guard.with_raw_file(|file| {
+                    file.seek(SeekFrom::Start(0))
+                        .map_err(|e| Error::new(EIO, "Failed to seek the SGX-protected file"))?;
+                    replace_protected_file(&new_path, mode, |tmp_path| {
+                        let mut dst_file =
+                            open_protected_file(tmp_path, &new_key, &write_options())?;
+                        copy_protected_data(file, &mut dst_file, std::usize::MAX)
+                    })?;
+                    unlink_host_file(old_path)?;
+                    *file = open_protected_file(&new_path, &new_key, &update_options())?;
+                    Ok(())
+                })?;
+                file_guard.set_host_path(&new_path, &new_key);
+            }
+            None => {
+                let mut src_file = open_protected_file(old_path, &old_key, &read_options())?;
+                replace_protected_file(&new_path, mode, |tmp_path| {
+                    let mut dst_file = open_protected_file(tmp_path, &new_key, &write_options())?;
+                    copy_protected_data(&mut src_file, &mut dst_file, std::usize::MAX)
+                })?;
+                unlink_host_file(old_path)?;
+            }
+        }
+        // The open files of the replaced file keep using it, as if it were
+        // unlinked
+        if let Some(replaced_file) = opened_files
+            .remove(&new_path)
+            .and_then(|opened_file| opened_file.upgrade())
+        {
+            replaced_file.lock().unwrap().forget_host_path();
+        }
+        page_cache::forget_closed_files(old_path);
+        if let Some(opened_file) = opened_files.remove(old_path) {
+            opened_files.insert(new_path, opened_file);
+        }
+        Ok(())
+    }
+}
+
+/// Get the metadata of a file on the host, where the size is that of the
+/// underlying host file
+fn get_host_metadata(host_path: &str, type_: FileType) -> Result<Metadata, Error> {
+    let host_stat = stat_host_file(host_path)?;
+    Ok(Metadata {
+        dev: host_stat.dev,
+        ino: host_stat.ino,
+        size: host_stat.size as usize,
+        type_,
+        mode: host_stat.mode & !S_IFMT,
+        nlinks: host_stat.nlink as usize,
+        atime: timespec_t::new(host_stat.atime_sec, host_stat.atime_nsec),
+        mtime: timespec_t::new(host_stat.mtime_sec, host_stat.mtime_nsec),
+        ctime: timespec_t::new(host_stat.ctime_sec, host_stat.ctime_nsec),
+    })
+}
+
+/// Get the metadata of an opened protected file, whose size includes the
+/// data not yet written back
+pub fn get_protected_file_metadata(file: &ProtectedFile) -> Result<Metadata, Error> {
+    let mut metadata = get_host_metadata(file.get_host_path(), FileType::File)?;
+    metadata.size = file.get_size();
+    Ok(metadata)
+}
+
+/// Change the size of an opened protected file
+///
+/// A protected file can grow by writing zeros at its end, but there is no way
+/// to shrink it in place. So the data to keep is re-encrypted into a new file,
+/// which then replaces the old one, and the file is reopened.
+pub fn set_protected_file_len(file: &mut ProtectedFile, len: usize) -> Result<(), Error> {
+    let host_path = file.get_host_path().to_owned();
+    let key = *file.get_key();
+    file.with_raw_file(|file| {
+        let size = file
+            .seek(SeekFrom::End(0))
+            .map_err(|e| Error::new(EIO, "Failed to seek the SGX-protected file"))?
+            as usize;
+        if len >= size {
+            return write_zeros(file, len - size);
+        }
+
+        // The file is locked by its handle, so the data is read through it
+        file.seek(SeekFrom::Start(0))
+            .map_err(|e| Error::new(EIO, "Failed to seek the SGX-protected file"))?;
+        let mode = stat_host_file(&host_path)?.mode & !S_IFMT;
+        replace_protected_file(&host_path, mode, |tmp_path| {
+            let mut dst_file = open_protected_file(tmp_path, &key, &write_options())?;
+            copy_protected_data(file, &mut dst_file, len)
+        })?;
+        *file = open_protected_file(&host_path, &key, &update_options())?;
+        Ok(())
+    })
+}
+
+impl INode for SgxINode {
+    fn open(&self, flags: u32) -> Result<FileRef, Error> {
+        let is_writable = (flags & O_WRONLY != 0) || (flags & O_RDWR != 0);
+
+        let is_truncated = is_writable && (flags & O_TRUNC) != 0;
+
+        let sgx_file = {
+            let mut opened_files = OPENED_PROTECTED_FILES.lock().unwrap();
+            let opened_file = opened_files
+                .get(&self.host_path)
+                .and_then(|opened_file| opened_file.upgrade());
+            match opened_file {
+                Some(sgx_file) => {
+                    if is_truncated {
+                        set_protected_file_len(&mut sgx_file.lock().unwrap(), 0)?;
+                    }
+                    sgx_file
+                }
+                None => {
+                    // A file that is created but not yet opened is empty on the
+                    // host, which is never the case for a protected file
+                    let is_new = match stat_host_file(&self.host_path) {
+                        Ok(host_stat) => host_stat.size == 0,
+                        Err(e) if e.errno == ENOENT => true,
+                        Err(e) => return Err(e),
+                    };
+                    let open_options = if is_new || is_truncated {
+                        write_options()
+                    } else {
+                        update_options()
+                    };
+                    let key = self.keys.get_key(&self.host_path)?;
+                    let sgx_file = Arc::new(SgxMutex::new(ProtectedFile::new(
+                        open_protected_file(&self.host_path, &key, &open_options)?,
+                        &self.host_path,
+                        &key,
+                    )?));
+                    retain_opened_files(&mut opened_files, |_, opened_file| {
+                        opened_file.upgrade().is_some()
+                    });
+                    opened_files.insert(self.host_path.clone(), Arc::downgrade(&sgx_file));
+                    sgx_file
+                }
+            }
+        };
+
+        let file_ref: Arc<Box<File>> = Arc::new(Box::new(SgxFile::new(sgx_file, flags)?));
+        Ok(file_ref)
+    }
+
+    fn find(&self, name: &str) -> Result<INodeRef, Error> {
+        self.check_is_dir()?;
+        let type_ = {
+            let host_stat = stat_host_file(&self.get_child_path(name))?;
+            file_type_from_mode(host_stat.mode)
+        };
+        Ok(Arc::new(self.new_child(name, type_)))
+    }
+
+    fn create(&self, name: &str, mode: u32) -> Result<INodeRef, Error> {
+        self.check_is_dir()?;
+        // The file is created empty on the host, so that its permissions are
+        // kept, and filled as a protected file when it is opened
+        let child = self.new_child(name, FileType::File);
+        create_host_file(&child.host_path, mode)?;
+        Ok(Arc::new(child))
+    }
+
+    fn mkdir(&self, name: &str, mode: u32) -> Result<INodeRef, Error> {
+        self.check_is_dir()?;
+        let child = self.new_child(name, FileType::Dir);
+        mkdir_host_dir(&child.host_path, mode)?;
+        Ok(Arc::new(child))
+    }
+
+    fn rmdir(&self, name: &str) -> Result<(), Error> {
+        self.check_is_dir()?;
+        rmdir_host_dir(&self.get_child_path(name))
+    }
+
+    fn list(&self) -> Result<Vec<DirEntry>, Error> {
+        self.check_is_dir()?;
+        let c_path = to_host_cstring(&self.host_path)?;
+        let mut buf = vec![0 as u8; 4096];
+        loop {
+            let mut ret: int64_t = 0;
+            let sgx_status = unsafe {
+                ocall_read_host_dir(
+                    &mut ret,
+                    c_path.as_ptr(),
+                    buf.as_mut_ptr() as *mut c_void,
+                    buf.len(),
+                )
+            };
+            let total_len = check_host_retval(sgx_status, ret)?;
+            if total_len > buf.len() {
+                // The buffer is too small; retry with a large enough one
+                buf.resize(total_len, 0);
+                continue;
+            }
+            return parse_host_dir_entries(&buf[..total_len]);
+        }
+    }
+
+    fn get_type(&self) -> Result<FileType, Error> {
+        Ok(self.type_)
+    }
+
+    fn metadata(&self) -> Result<Metadata, Error> {
+        let mut metadata = get_host_metadata(&self.host_path, self.type_)?;
+        if self.type_ == FileType::File {
+            // The size of a protected file is that of its plaintext
+            let get_size = |sgx_file: &mut fs_impl::SgxFile| {
+                sgx_file
+                    .seek(SeekFrom::End(0))
+                    .map_err(|e| Error::new(Errno::EIO, "Failed to get the size of the file"))
+            };
+            metadata.size = match get_opened_protected_file(&self.host_path) {
+                // Including the data in the page cache
+                Some(sgx_file) => sgx_file.lock().unwrap().get_size(),
+                // Created but not yet filled as a protected file
+                None if metadata.size == 0 => 0,
+                None => {
+                    let key = self.keys.get_key(&self.host_path)?;
+                    get_size(&mut open_protected_file(
+                        &self.host_path,
+                        &key,
+                        &read_options(),
+                    )?)? as usize
+                }
+            };
+        }
+        Ok(metadata)
+    }
+
+    fn unlink(&self, name: &str) -> Result<(), Error> {
+        self.check_is_dir()?;
+        let host_path = self.get_child_path(name);
+        unlink_host_file(&host_path)?;
+        // The opened file is still usable, but a new file of the same name is
+        // a different one
+        forget_opened_protected_files(&host_path);
+        page_cache::forget_closed_files(&host_path);
+        Ok(())
+    }
+
+    fn link(&self, name: &str, target: &INodeRef) -> Result<(), Error> {
+        self.check_is_dir()?;
+        let target = target
+            .as_any()
+            .downcast_ref::<SgxINode>()
+            .ok_or_else(|| Error::new(EXDEV, "Not in the same file system"))?;
+        // As the name of a protected file is bound to its content, a hard link
+        // must have the same name as the file
+        let target_name = target.host_path.rsplit('/').next().unwrap();
+        if target.type_ == FileType::File && target_name != name {
+            return errno!(
+                EPERM,
+                "A hard link to a protected file must have the same name"
+            );
+        }
+        // Nor can a hard link have a different key, which would have to
+        // re-encrypt the file
+        let new_path = self.get_child_path(name);
+        if target.type_ == FileType::File
+            && self.keys.get_source(&target.host_path) != self.keys.get_source(&new_path)
+        {
+            return errno!(
+                EXDEV,
+                "A hard link to a protected file must use the same key"
+            );
+        }
+        link_host_file(&target.host_path, &new_path)
+    }
+
+    fn rename(&self, old_name: &str, target_dir: &INodeRef, new_name: &str) -> Result<(), Error> {
+        self.check_is_dir()?;
+        let target_dir = target_dir
+            .as_any()
+            .downcast_ref::<SgxINode>()
+            .ok_or_else(|| Error::new(EXDEV, "Not in the same file system"))?;
+        target_dir.check_is_dir()?;
+
+        let old_path = self.get_child_path(old_name);
+        let new_path = target_dir.get_child_path(new_name);
+        let old_type = file_type_from_mode(stat_host_file(&old_path)?.mode);
+        let is_key_changed = self.keys.get_source(&old_path) != self.keys.get_source(&new_path);
+        match old_type {
+            FileType::File if old_name != new_name || is_key_changed => {
+                target_dir.rename_protected_file(&old_path, new_name)
+            }
+            // The protected files in a directory would not be re-encrypted
+            FileType::Dir
+                if is_key_changed
+                    || self.keys.has_prefix_inside(&old_path)
+                    || self.keys.has_prefix_inside(&new_path) =>
+            {
+                errno!(EXDEV, "Cannot move a directory to a path of other keys")
+            }
+            _ => {
+                rename_host_file(&old_path, &new_path)?;
+                // The handles of the opened files are moved with the files
+                forget_opened_protected_files(&new_path);
+                move_opened_protected_files(&old_path, &new_path);
+                page_cache::forget_closed_files(&old_path);
+                page_cache::forget_closed_files(&new_path);
+                Ok(())
+            }
+        }
+    }
+
+    fn symlink(&self, name: &str, target: &str) -> Result<INodeRef, Error> {
+        self.check_is_dir()?;
+        let child = self.new_child(name, FileType::SymLink);
+        symlink_host_file(target, &child.host_path)?;
+        Ok(Arc::new(child))
+    }
+
+    fn read_link(&self) -> Result<String, Error> {
+        if self.type_ != FileType::SymLink {
+            return errno!(EINVAL, "Not a symbolic link");
+        }
+        readlink_host_file(&self.host_path)
+    }
+
+    fn as_any(&self) -> &Any {
+        self
+    }
+}
+
+fn open_protected_file(
+    host_path: &str,
+    key: &sgx_key_128bit_t,
+    open_options: &fs_impl::OpenOptions,
+) -> Result<fs_impl::SgxFile, Error> {
+    open_options
+        .open_ex(host_path, key)
+        .map_err(|e| Error::new(Errno::ENOENT, "Failed to open the SGX-protected file"))
+}
+
+/// The options to open an existing protected file for both read and write
+fn update_options() -> fs_impl::OpenOptions {
+    let mut open_options = fs_impl::OpenOptions::new();
+    open_options.read(true).update(true).binary(true);
+    open_options
+}
+
+/// The options to create or truncate a protected file for both read and write
+fn write_options() -> fs_impl::OpenOptions {
+    let mut open_options = fs_impl::OpenOptions::new();
+    open_options.write(true).update(true).binary(true);
+    open_options
+}
+
+fn read_options() -> fs_impl::OpenOptions {
+    let mut open_options = fs_impl::OpenOptions::new();
+    open_options.read(true).binary(true);
+    open_options
+}
+
+/// Replace the protected file at `host_path` with a new one, which is written
+/// by `write_fn` to the given temporary path
+///
+/// The temporary file is in a new directory under the same name, as the name
+/// of a protected file is bound to its content. It is moved into place by an
+/// atomic rename on the host, so a crash never leaves a half-written file. The
+/// new file has the given permissions.
+fn replace_protected_file<F>(host_path: &str, mode: u32, write_fn: F) -> Result<(), Error>
+where
+    F: FnOnce(&str) -> Result<(), Error>,
+{
+    let (dir_path, file_name) = match host_path.rfind('/') {
+        Some(slash_i) => (&host_path[..slash_i], &host_path[slash_i + 1..]),
+        None => (".", host_path),
+    };
+    let tmp_dir_path = format!("{}/.sgxfs-tmp-{:016x}", dir_path, random_u64()?);
+    mkdir_host_dir(&tmp_dir_path, 0o700)?;
+    let tmp_path = format!("{}/{}", tmp_dir_path, file_name);
+
+    let result = write_fn(&tmp_path)
+        .and_then(|_| chmod_host_file(&tmp_path, mode))
+        .and_then(|_| rename_host_file(&tmp_path, host_path));
+    if result.is_err() {
+        unlink_host_file(&tmp_path);
+    } else {
+        page_cache::forget_closed_files(host_path);
+    }
+    rmdir_host_dir(&tmp_dir_path);
+    result
+}
+
+lazy_static! {
+    /// The handles of the opened protected files, indexed by their host paths
+    ///
+    /// A protected file caches its data in the enclave and is locked on the
+    /// host by its handle, so a file must not be opened twice. Instead, all
+    /// the open files of a protected file, in any process, share one handle,
+    /// while each of them keeps its own offset.
+    static ref OPENED_PROTECTED_FILES: SgxMutex<OpenedFiles> = SgxMutex::new(BTreeMap::new());
+}
+
+type OpenedFiles = BTreeMap<String, Weak<SgxMutex<ProtectedFile>>>;
+
+fn retain_opened_files<F>(opened_files: &mut OpenedFiles, f: F)
+where
+    F: Fn(&str, &Weak<SgxMutex<ProtectedFile>>) -> bool,
+{
+    let removed_paths: Vec<String> = opened_files
+        .iter()
+        .filter(|&(path, opened_file)| !f(path, opened_file))
+        .map(|(path, _)| path.clone())
+        .collect();
+    for path in removed_paths {
+        opened_files.remove(&path);
+    }
+}
+
+fn get_opened_protected_file(host_path: &str) -> Option<Arc<SgxMutex<ProtectedFile>>> {
+    OPENED_PROTECTED_FILES
+        .lock()
+        .unwrap()
+        .get(host_path)
+        .and_then(|opened_file| opened_file.upgrade())
+}
+
+/// Write back the opened files at `host_path` or under it
+fn sync_opened_protected_files(host_path: &str) -> Result<(), Error> {
+    // The table is unlocked before the files are written back, which may be
+    // slow
+    let opened_files: Vec<Arc<SgxMutex<ProtectedFile>>> = OPENED_PROTECTED_FILES
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|&(path, _)| is_same_or_under(path, host_path))
+        .filter_map(|(_, opened_file)| opened_file.upgrade())
+        .collect();
+    for opened_file in opened_files {
+        opened_file.lock().unwrap().sync()?;
+    }
+    Ok(())
+}
+
+/// Forget the handles of the file at `host_path` or the files under it, as
+/// well as those of the files that are closed
+fn forget_opened_protected_files(host_path: &str) {
+    let mut opened_files = OPENED_PROTECTED_FILES.lock().unwrap();
+    for (_, opened_file) in opened_files
+        .iter()
+        .filter(|&(path, _)| is_same_or_under(path, host_path))
+    {
+        if let Some(opened_file) = opened_file.upgrade() {
+            opened_file.lock().unwrap().forget_host_path();
+        }
+    }
+    retain_opened_files(&mut opened_files, |path, opened_file| {
+        !is_same_or_under(path, host_path) && opened_file.upgrade().is_some()
+    });
+}
+
+/// Move the handles of the file at `old_host_path` or the files under it,
+/// which are renamed on the host under the same keys
+fn move_opened_protected_files(old_host_path: &str, new_host_path: &str) {
+    let mut opened_files = OPENED_PROTECTED_FILES.lock().unwrap();
+    let moved_paths: Vec<String> = opened_files
+        .keys()
+        .filter(|path| is_same_or_under(path, old_host_path))
+        .cloned()
+        .collect();
+    for old_path in moved_paths {
+        let new_path = format!("{}{}", new_host_path, &old_path[old_host_path.len()..]);
+        let opened_file = opened_files.remove(&old_path).unwrap();
+        if let Some(opened_file) = opened_file.upgrade() {
+            let mut file_guard = opened_file.lock().unwrap();
+            let key = *file_guard.get_key();
+            file_guard.set_host_path(&new_path, &key);
+        }
+        opened_files.insert(new_path, opened_file);
+    }
+}
+
+pub fn is_same_or_under(path: &str, dir_path: &str) -> bool {
+    path.starts_with(dir_path)
+        && (path.len() == dir_path.len() || path[dir_path.len()..].starts_with('/'))
+}
+
+/// Copy at most `max_len` bytes from the current position of one protected
+/// file to another, and flush the destination
+fn copy_protected_data(
+    src_file: &mut fs_impl::SgxFile,
+    dst_file: &mut fs_impl::SgxFile,
+    max_len: usize,
+) -> Result<(), Error> {
+    let mut buf = vec![0 as u8; 16 * 1024];
+    let mut remaining_len = max_len;
+    while remaining_len > 0 {
+        let buf_len = std::cmp::min(buf.len(), remaining_len);
+        let read_len = src_file
+            .read(&mut buf[..buf_len])
+            .map_err(|e| Error::new(EIO, "Failed to read the SGX-protected file"))?;
+        if read_len == 0 {
+            break;
+        }
+        dst_file
+            .write_all(&buf[..read_len])
+            .map_err(|e| Error::new(EIO, "Failed to write the SGX-protected file"))?;
+        remaining_len -= read_len;
+    }
+    dst_file
+        .flush()
+        .map_err(|e| Error::new(EIO, "Failed to flush the SGX-protected file"))?;
+    Ok(())
+}
+
+/// Append zeros to the end of a protected file
+pub fn write_zeros(file: &mut fs_impl::SgxFile, len: usize) -> Result<(), Error> {
+    let size = file
+        .seek(SeekFrom::End(0))
+        .map_err(|e| Error::new(EIO, "Failed to seek the SGX-protected file"))?;
+    get_range_end(size, len as u64)?;
+    let zeros = vec![0 as u8; std::cmp::min(len, 16 * 1024)];
+    let mut remaining_len = len;
+    while remaining_len > 0 {
+        let write_len = std::cmp::min(zeros.len(), remaining_len);
+        file.write_all(&zeros[..write_len])
+            .map_err(|e| Error::new(EIO, "Failed to write the SGX-protected file"))?;
+        remaining_len -= write_len;
+    }
+    Ok(())
+}
+
+fn random_u64() -> Result<u64, Error> {
+    let mut bytes = [0 as u8; 8];
+    let sgx_status = unsafe { sgx_read_rand(bytes.as_mut_ptr(), bytes.len()) };
+    if sgx_status != sgx_status_t::SGX_SUCCESS {
+        return errno!(EIO, "Failed to generate a random number");
+    }
+    Ok(u64::from_ne_bytes(bytes))
+}
+
+#[repr(C)]
+#[derive(Debug, Default)]
+struct HostStat {
+    dev: u64,
+    ino: u64,
+    mode: u32,
+    nlink: u32,
+    size: i64,
+    atime_sec: i64,
+    atime_nsec: i64,
+    mtime_sec: i64,
+    mtime_nsec: i64,
+    ctime_sec: i64,
+    ctime_nsec: i64,
+}
+
+/// The identity and the times of change of a host file, which tell whether the
+/// file is changed or replaced on the host
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HostFileVersion {
+    dev: u64,
+    ino: u64,
+    size: i64,
+    mtime: (i64, i64),
+    ctime: (i64, i64),
+}
+
+impl HostFileVersion {
+    pub fn is_same_file(&self, other: &HostFileVersion) -> bool {
+        self.dev == other.dev && self.ino == other.ino
+    }
+}
+
+pub fn get_host_file_version(host_path: &str) -> Result<HostFileVersion, Error> {
+    let host_stat = stat_host_file(host_path)?;
+    Ok(HostFileVersion {
+        dev: host_stat.dev,
+        ino: host_stat.ino,
+        size: host_stat.size,
+        mtime: (host_stat.mtime_sec, host_stat.mtime_nsec),
+        ctime: (host_stat.ctime_sec, host_stat.ctime_nsec),
+    })
+}
+
+fn stat_host_file(host_path: &str) -> Result<HostStat, Error> {
+    let c_path = to_host_cstring(host_path)?;
+    let mut host_stat: HostStat = Default::default();
+    let mut ret: c_int = 0;
+    let sgx_status = unsafe { ocall_stat_host_file(&mut ret, c_path.as_ptr(), &mut host_stat) };
+    check_host_retval(sgx_status, ret as i64)?;
+    Ok(host_stat)
+}
+
+/// Parse the entries returned by ocall_read_host_dir, whose format is a
+/// 64-bit inode number, an 8-bit d_type and a null-terminated name per entry
+fn parse_host_dir_entries(buf: &[u8]) -> Result<Vec<DirEntry>, Error> {
+    let mut entries = Vec::new();
+    let mut pos = 0;
+    while pos < buf.len() {
+        if pos + 9 > buf.len() {
+            return errno!(EIO, "Invalid directory entries from the host");
+        }
+        let mut ino_bytes = [0 as u8; 8];
+        ino_bytes.copy_from_slice(&buf[pos..pos + 8]);
+        let ino = u64::from_ne_bytes(ino_bytes);
+        let type_ = file_type_from_dtype(buf[pos + 8]);
+        let name_start = pos + 9;
+        let name_len = buf[name_start..]
+            .iter()
+            .position(|&b| b == 0)
+            .ok_or_else(|| Error::new(EIO, "Invalid directory entries from the host"))?;
+        let name = String::from_utf8_lossy(&buf[name_start..name_start + name_len]).into_owned();
+        entries.push(DirEntry { ino, type_, name });
+        pos = name_start + name_len + 1;
+    }
+    Ok(entries)
+}
+
+fn file_type_from_mode(mode: u32) -> FileType {
+    match mode & S_IFMT {
+        S_IFDIR => FileType::Dir,
+        S_IFLNK => FileType::SymLink,
+        S_IFCHR => FileType::CharDevice,
+        S_IFBLK => FileType::BlockDevice,
+        S_IFIFO => FileType::NamedPipe,
+        S_IFSOCK => FileType::Socket,
+        _ => FileType::File,
+    }
+}
+
+fn file_type_from_dtype(d_type: u8) -> FileType {
+    match d_type {
+        DT_DIR => FileType::Dir,
+        DT_LNK => FileType::SymLink,
+        DT_CHR => FileType::CharDevice,
+        DT_BLK => FileType::BlockDevice,
+        DT_FIFO => FileType::NamedPipe,
+        DT_SOCK => FileType::Socket,
+        _ => FileType::File,
+    }
+}
+
+fn create_host_file(host_path: &str, mode: u32) -> Result<(), Error> {
+    let c_path = to_host_cstring(host_path)?;
+    let mut ret: c_int = 0;
+    let sgx_status = unsafe { ocall_create_host_file(&mut ret, c_path.as_ptr(), mode) };
+    check_host_retval(sgx_status, ret as i64)?;
+    Ok(())
+}
+
+fn chmod_host_file(host_path: &str, mode: u32) -> Result<(), Error> {
+    let c_path = to_host_cstring(host_path)?;
+    let mut ret: c_int = 0;
+    let sgx_status = unsafe { ocall_chmod_host_file(&mut ret, c_path.as_ptr(), mode) };
+    check_host_retval(sgx_status, ret as i64)?;
+    Ok(())
+}
+
+fn mkdir_host_dir(host_path: &str, mode: u32) -> Result<(), Error> {
+    let c_path = to_host_cstring(host_path)?;
+    let mut ret: c_int = 0;
+    let sgx_status = unsafe { ocall_mkdir_host_dir(&mut ret, c_path.as_ptr(), mode) };
+    check_host_retval(sgx_status, ret as i64)?;
+    Ok(())
+}
+
+fn rmdir_host_dir(host_path: &str) -> Result<(), Error> {
+    let c_path = to_host_cstring(host_path)?;
+    let mut ret: c_int = 0;
+    let sgx_status = unsafe { ocall_rmdir_host_dir(&mut ret, c_path.as_ptr()) };
+    check_host_retval(sgx_status, ret as i64)?;
+    Ok(())
+}
+
+fn unlink_host_file(host_path: &str) -> Result<(), Error> {
+    let c_path = to_host_cstring(host_path)?;
+    let mut ret: c_int = 0;
+    let sgx_status = unsafe { ocall_unlink_host_file(&mut ret, c_path.as_ptr()) };
+    check_host_retval(sgx_status, ret as i64)?;
+    Ok(())
+}
+
+fn rename_host_file(old_host_path: &str, new_host_path: &str) -> Result<(), Error> {
+    let old_c_path = to_host_cstring(old_host_path)?;
+    let new_c_path = to_host_cstring(new_host_path)?;
+    let mut ret: c_int = 0;
+    let sgx_status =
+        unsafe { ocall_rename_host_file(&mut ret, old_c_path.as_ptr(), new_c_path.as_ptr()) };
+    check_host_retval(sgx_status, ret as i64)?;
+    Ok(())
+}
+
+fn link_host_file(old_host_path: &str, new_host_path: &str) -> Result<(), Error> {
+    let old_c_path = to_host_cstring(old_host_path)?;
+    let new_c_path = to_host_cstring(new_host_path)?;
+    let mut ret: c_int = 0;
+    let sgx_status =
+        unsafe { ocall_link_host_file(&mut ret, old_c_path.as_ptr(), new_c_path.as_ptr()) };
+    check_host_retval(sgx_status, ret as i64)?;
+    Ok(())
+}
+
+fn symlink_host_file(target: &str, host_path: &str) -> Result<(), Error> {
+    let c_target = CString::new(target).map_err(|_| Error::new(EINVAL, "Invalid target"))?;
+    let c_path = to_host_cstring(host_path)?;
+    let mut ret: c_int = 0;
+    let sgx_status =
+        unsafe { ocall_symlink_host_file(&mut ret, c_target.as_ptr(), c_path.as_ptr()) };
+    check_host_retval(sgx_status, ret as i64)?;
+    Ok(())
+}
+
+fn readlink_host_file(host_path: &str) -> Result<String, Error> {
+    let c_path = to_host_cstring(host_path)?;
+    let mut buf = vec![0 as u8; vfs::PATH_MAX];
+    let mut ret: int64_t = 0;
+    let sgx_status = unsafe {
+        ocall_readlink_host_file(
+            &mut ret,
+            c_path.as_ptr(),
+            buf.as_mut_ptr() as *mut c_char,
+            buf.len(),
+        )
+    };
+    let target_len = check_host_retval(sgx_status, ret)?;
+    if target_len >= buf.len() {
+        return errno!(ENAMETOOLONG, "The target of the symbolic link is too long");
+    }
+    String::from_utf8(buf[..target_len].to_vec())
+        .map_err(|_| Error::new(EINVAL, "Invalid target of the symbolic link"))
+}
+
+fn to_host_cstring(host_path: &str) -> Result<CString, Error> {
+    CString::new(host_path).map_err(|_| Error::new(EINVAL, "Invalid host path"))
+}
+
+extern "C" {
+    fn ocall_stat_host_file(
+        ret: *mut c_int,
+        path: *const c_char,
+        stat: *mut HostStat,
+    ) -> sgx_status_t;
+    fn ocall_create_host_file(ret: *mut c_int, path: *const c_char, mode: uint32_t)
+        -> sgx_status_t;
+    fn ocall_chmod_host_file(ret: *mut c_int, path: *const c_char, mode: uint32_t) -> sgx_status_t;
+    fn ocall_mkdir_host_dir(ret: *mut c_int, path: *const c_char, mode: uint32_t) -> sgx_status_t;
+    fn ocall_rmdir_host_dir(ret: *mut c_int, path: *const c_char) -> sgx_status_t;
+    fn ocall_unlink_host_file(ret: *mut c_int, path: *const c_char) -> sgx_status_t;
+    fn ocall_rename_host_file(
+        ret: *mut c_int,
+        old_path: *const c_char,
+        new_path: *const c_char,
+    ) -> sgx_status_t;
+    fn ocall_link_host_file(
+        ret: *mut c_int,
+        old_path: *const c_char,
+        new_path: *const c_char,
+    ) -> sgx_status_t;
+    fn ocall_symlink_host_file(
+        ret: *mut c_int,
+        target: *const c_char,
+        path: *const c_char,
+    ) -> sgx_status_t;
+    fn ocall_readlink_host_file(
+        ret: *mut int64_t,
+        path: *const c_char,
+        buf: *mut c_char,
+        buf_size: size_t,
+    ) -> sgx_status_t;
+    fn ocall_read_host_dir(
+        ret: *mut int64_t,
+        path: *const c_char,
+        buf: *mut c_void,
+        buf_size: size_t,
+    ) -> sgx_status_t;
+}
@@ -0,0 +1,147 @@
+use super::*;
+use config::ConfigKey;
+
+/// Where the key to encrypt protected files comes from
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SgxKeySource {
+    /// The all-zero key, which is what sgx_protect_file encrypts the
+    /// executables with. Anyone with the files can decrypt them.
+    Zero,
+    /// Derived from the sealing key of the enclave with the MRENCLAVE
+    /// policy, so only this very enclave can decrypt the files
+    MrEnclave,
+    /// Derived from the sealing key of the enclave with the MRSIGNER policy,
+    /// so any enclave of the same signer (and product) can decrypt the files
+    MrSigner,
+    /// Supplied by the host at boot, before any file is opened
+    Boot,
+}
+
+impl SgxKeySource {
+    pub fn get_key(&self) -> Result<sgx_key_128bit_t, Error> {
+        match *self {
+            SgxKeySource::Zero => Ok([0 as uint8_t; 16]),
+            SgxKeySource::MrEnclave => derive_sealing_key(SGX_KEYPOLICY_MRENCLAVE),
+            SgxKeySource::MrSigner => derive_sealing_key(SGX_KEYPOLICY_MRSIGNER),
+            SgxKeySource::Boot => match *BOOT_KEY.lock().unwrap() {
+                Some(key) => Ok(key),
+                None => errno!(EACCES, "No key for protected files is supplied at boot"),
+            },
+        }
+    }
+}
+
+/// The key sources of a SgxFS, which are selected by the longest matching
+/// prefix of the host path of a file
+#[derive(Debug)]
+pub struct SgxKeyTable {
+    default_source: SgxKeySource,
+    // Host path prefixes and their key sources
+    prefixes: Vec<(String, SgxKeySource)>,
+}
+
+impl SgxKeyTable {
+    pub fn new(default_source: SgxKeySource) -> SgxKeyTable {
+        SgxKeyTable {
+            default_source,
+            prefixes: Vec::new(),
+        }
+    }
+
+    /// Create the key table of the SgxFS mounted at `mount_point`, where the
+    /// path prefixes in the config are translated into those of the host
+    pub fn from_config(
+        mount_point: &str,
+        host_root: &str,
+        default_source: SgxKeySource,
+        config_keys: &[ConfigKey],
+    ) -> SgxKeyTable {
+        let mount_point = mount_point.trim_end_matches('/');
+        let host_root = host_root.trim_end_matches('/');
+        let mut key_table = SgxKeyTable::new(default_source);
+        for config_key in config_keys {
+            let path_prefix = config_key.path_prefix.trim_end_matches('/');
+            if !is_path_prefix(mount_point, path_prefix) {
+                continue;
+            }
+            let host_prefix = format!("{}{}", host_root, &path_prefix[mount_point.len()..]);
+            key_table.add_prefix(&host_prefix, config_key.source);
+        }
+        key_table
+    }
+
+    pub fn add_prefix(&mut self, host_prefix: &str, source: SgxKeySource) {
+        let host_prefix = host_prefix.trim_end_matches('/');
+        self.prefixes
+            .retain(|&(ref prefix, _)| prefix != host_prefix);
+        self.prefixes.push((host_prefix.to_owned(), source));
+    }
+
+    pub fn get_source(&self, host_path: &str) -> SgxKeySource {
+        self.prefixes
+            .iter()
+            .filter(|&&(ref prefix, _)| is_path_prefix(prefix, host_path))
+            .max_by_key(|&&(ref prefix, _)| prefix.len())
+            .map(|&(_, source)| source)
+            .unwrap_or(self.default_source)
+    }
+
+    pub fn get_key(&self, host_path: &str) -> Result<sgx_key_128bit_t, Error> {
+        self.get_source(host_path).get_key()
+    }
+
+    /// Whether a prefix is strictly inside the given directory, i.e., the
+    /// files in the directory may use different keys
+    pub fn has_prefix_inside(&self, host_dir_path: &str) -> bool {
+        let host_dir_path = host_dir_path.trim_end_matches('/');
+        self.prefixes.iter().any(|&(ref prefix, _)| {
+            prefix.len() > host_dir_path.len() && is_path_prefix(host_dir_path, prefix)
+        })
+    }
+}
+
+/// Whether `prefix` is `path` itself or one of its ancestor directories
+fn is_path_prefix(prefix: &str, path: &str) -> bool {
+    if !path.starts_with(prefix) {
+        return false;
+    }
+    prefix.is_empty() || path.len() == prefix.len() || path[prefix.len()..].starts_with('/')
+}
+
+/// Set the key of the Boot key source, which can only be done once
+pub fn set_sgxfs_boot_key(key: &sgx_key_128bit_t) -> Result<(), Error> {
+    let mut boot_key = BOOT_KEY.lock().unwrap();
+    if boot_key.is_some() {
+        return errno!(EEXIST, "The key for protected files is already supplied");
+    }
+    *boot_key = Some(*key);
+    Ok(())
+}
+
+lazy_static! {
+    static ref BOOT_KEY: SgxMutex<Option<sgx_key_128bit_t>> = SgxMutex::new(None);
+}
+
+// The key ID of the sealing key for protected files, so that the key differs
+// from the sealing keys derived for any other purpose
+const SGXFS_KEY_ID: &[u8] = b"Occlum LibOS SGX protected files";
+
+fn derive_sealing_key(key_policy: uint16_t) -> Result<sgx_key_128bit_t, Error> {
+    let report = unsafe { &*sgx_self_report() };
+    let mut key_request: sgx_key_request_t = Default::default();
+    key_request.key_name = SGX_KEYSELECT_SEAL;
+    key_request.key_policy = key_policy;
+    key_request.isv_svn = report.body.isv_svn;
+    key_request.cpu_svn = report.body.cpu_svn;
+    key_request.attribute_mask.flags = TSEAL_DEFAULT_FLAGSMASK;
+    key_request.attribute_mask.xfrm = 0;
+    key_request.misc_mask = TSEAL_DEFAULT_MISCMASK;
+    key_request.key_id.id[..SGXFS_KEY_ID.len()].copy_from_slice(SGXFS_KEY_ID);
+
+    let mut key: sgx_key_128bit_t = [0 as uint8_t; 16];
+    let sgx_status = unsafe { sgx_get_key(&key_request, &mut key) };
+    if sgx_status != sgx_status_t::SGX_SUCCESS {
+        return errno!(EIO, "Failed to derive the key for protected files");
+    }
+    Ok(key)
+}
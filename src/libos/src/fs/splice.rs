@@ -0,0 +1,204 @@
+use super::pipe::{PipeReader, PipeWriter};
+use super::*;
+
+// Flags of splice and tee
+pub const SPLICE_F_MOVE: u32 = 0x1;
+pub const SPLICE_F_NONBLOCK: u32 = 0x2;
+pub const SPLICE_F_MORE: u32 = 0x4;
+pub const SPLICE_F_GIFT: u32 = 0x8;
+const SPLICE_FLAGS: u32 = SPLICE_F_MOVE | SPLICE_F_NONBLOCK | SPLICE_F_MORE | SPLICE_F_GIFT;
+
+// The max length of the data read from a regular file at a time
+const COPY_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Copy data from a regular file to any file, where the data is read at the
+/// offset, which is then advanced, or from the file position if None
+pub fn do_sendfile(
+    out_fd: FileDesc,
+    in_fd: FileDesc,
+    offset: Option<&mut off_t>,
+    count: usize,
+) -> Result<usize, Error> {
+    check_offset(&offset)?;
+    let in_file = get_file(in_fd)?;
+    let out_file = get_file(out_fd)?;
+    check_regular_file(&in_file)?;
+    copy_from_file(&in_file, offset, count, |buf| out_file.write(buf))
+}
+
+/// Move data between a pipe and a regular file, where the offset of the
+/// regular file is used as in sendfile
+///
+/// The data moves through a buffer in the LibOS instead of the memory of the
+/// user. Splicing between two pipes is not supported.
+pub fn do_splice(
+    fd_in: FileDesc,
+    off_in: Option<&mut off_t>,
+    fd_out: FileDesc,
+    mut off_out: Option<&mut off_t>,
+    len: usize,
+    flags: u32,
+) -> Result<usize, Error> {
+    if flags & !SPLICE_FLAGS != 0 {
+        return errno!(EINVAL, "Invalid flags for splice");
+    }
+    check_offset(&off_in)?;
+    check_offset(&off_out)?;
+    let in_file = get_file(fd_in)?;
+    let out_file = get_file(fd_out)?;
+    if let Some(reader) = in_file.as_any().downcast_ref::<PipeReader>() {
+        if off_in.is_some() {
+            return errno!(ESPIPE, "Pipe does not support offsets");
+        }
+        check_regular_file(&out_file)?;
+        let is_nonblocking = is_nonblocking_with(&in_file, flags)?;
+        // Writing a regular file never sleeps, so the data is written while
+        // the pipe is locked, and only what is written is consumed
+        return reader.consume_with(len, is_nonblocking, |buf| {
+            write_file(&out_file, &mut off_out, buf)
+        });
+    }
+    if let Some(writer) = out_file.as_any().downcast_ref::<PipeWriter>() {
+        if off_out.is_some() {
+            return errno!(ESPIPE, "Pipe does not support offsets");
+        }
+        check_regular_file(&in_file)?;
+        let is_nonblocking = is_nonblocking_with(&out_file, flags)?;
+        return copy_from_file(&in_file, off_in, len, |buf| {
+            writer.write_bufs(&[buf], is_nonblocking)
+        });
+    }
+    errno!(EINVAL, "One of the files of splice must be a pipe")
+}
+
+/// Copy the data in a pipe to another pipe without consuming it
+pub fn do_tee(fd_in: FileDesc, fd_out: FileDesc, len: usize, flags: u32) -> Result<usize, Error> {
+    if flags & !SPLICE_FLAGS != 0 {
+        return errno!(EINVAL, "Invalid flags for tee");
+    }
+    let in_file = get_file(fd_in)?;
+    let out_file = get_file(fd_out)?;
+    let reader = in_file
+        .as_any()
+        .downcast_ref::<PipeReader>()
+        .ok_or_else(|| Error::new(EINVAL, "The input of tee is not a pipe"))?;
+    let writer = out_file
+        .as_any()
+        .downcast_ref::<PipeWriter>()
+        .ok_or_else(|| Error::new(EINVAL, "The output of tee is not a pipe"))?;
+    if reader.is_same_pipe(writer) {
+        return errno!(EINVAL, "The input and output of tee are the same pipe");
+    }
+
+    // The data is written after the input pipe is unlocked, as writing to
+    // the output pipe may sleep
+    let mut data = Vec::new();
+    reader.consume_with(len, is_nonblocking_with(&in_file, flags)?, |buf| {
+        data.extend_from_slice(buf);
+        Ok(0)
+    })?;
+    if data.is_empty() {
+        return Ok(0);
+    }
+    writer.write_bufs(&[&data], is_nonblocking_with(&out_file, flags)?)
+}
+
+/// Copy data between two regular files, where the offsets are used as in
+/// sendfile
+pub fn do_copy_file_range(
+    fd_in: FileDesc,
+    off_in: Option<&mut off_t>,
+    fd_out: FileDesc,
+    mut off_out: Option<&mut off_t>,
+    len: usize,
+    flags: u32,
+) -> Result<usize, Error> {
+    if flags != 0 {
+        return errno!(EINVAL, "Invalid flags for copy_file_range");
+    }
+    check_offset(&off_in)?;
+    check_offset(&off_out)?;
+    let in_file = get_file(fd_in)?;
+    let out_file = get_file(fd_out)?;
+    check_regular_file(&in_file)?;
+    check_regular_file(&out_file)?;
+    copy_from_file(&in_file, off_in, len, |buf| {
+        write_file(&out_file, &mut off_out, buf)
+    })
+}
+
+/// Copy at most `len` bytes from a regular file by `write_fn`, where the data
+/// is read as in sendfile. The data not taken by `write_fn` is left unread.
+fn copy_from_file<F>(
+    in_file: &FileRef,
+    mut offset: Option<&mut off_t>,
+    len: usize,
+    mut write_fn: F,
+) -> Result<usize, Error>
+where
+    F: FnMut(&[u8]) -> Result<usize, Error>,
+{
+    let mut buf = vec![0; len.min(COPY_CHUNK_SIZE)];
+    let mut copied_len = 0;
+    while copied_len < len {
+        let this_len = buf.len().min(len - copied_len);
+        let read_len = match offset {
+            Some(ref off) => in_file.read_at(**off as usize, &mut buf[..this_len])?,
+            None => in_file.read(&mut buf[..this_len])?,
+        };
+        if read_len == 0 {
+            break;
+        }
+        let result = write_fn(&buf[..read_len]);
+        let written_len = *result.as_ref().unwrap_or(&0);
+        match offset {
+            Some(ref mut off) => **off += written_len as off_t,
+            None if written_len < read_len => {
+                let unwritten_len = (read_len - written_len) as i64;
+                in_file.seek(SeekFrom::Current(-unwritten_len))?;
+            }
+            None => {}
+        }
+        copied_len += written_len;
+        match result {
+            Err(e) if copied_len == 0 => return Err(e),
+            Err(_) => break,
+            Ok(_) if written_len < read_len => break,
+            Ok(_) => {}
+        }
+    }
+    Ok(copied_len)
+}
+
+/// Write to a regular file at the offset, which is then advanced, or at the
+/// file position if None
+fn write_file(file: &FileRef, offset: &mut Option<&mut off_t>, buf: &[u8]) -> Result<usize, Error> {
+    match *offset {
+        Some(ref mut off) => {
+            let written_len = file.write_at(**off as usize, buf)?;
+            **off += written_len as off_t;
+            Ok(written_len)
+        }
+        None => file.write(buf),
+    }
+}
+
+fn check_offset(offset: &Option<&mut off_t>) -> Result<(), Error> {
+    match *offset {
+        Some(ref off) if **off < 0 => errno!(EINVAL, "The offset is negative"),
+        _ => Ok(()),
+    }
+}
+
+fn check_regular_file(file: &FileRef) -> Result<(), Error> {
+    if file.metadata()?.type_ != FileType::File {
+        return errno!(EINVAL, "Not a regular file");
+    }
+    Ok(())
+}
+
+/// Whether the I/O on the pipe is non-blocking, either by SPLICE_F_NONBLOCK
+/// or by the status flags of the pipe
+fn is_nonblocking_with(pipe: &FileRef, flags: u32) -> Result<bool, Error> {
+    Ok(flags & SPLICE_F_NONBLOCK != 0 || pipe.get_status_flags()? & O_NONBLOCK != 0)
+}
@@ -0,0 +1,704 @@
+use super::*;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Weak;
+use time::timespec_t;
+
+/// A file system that keeps all its files and directories in enclave memory
+///
+/// The data of a TmpFS is never written to the host, and is lost when the
+/// enclave is destroyed.
+#[derive(Debug)]
+pub struct TmpFS {
+    root: TmpINode,
+}
+
+impl TmpFS {
+    pub fn new(capacity: usize) -> TmpFS {
+        let fs_info = Arc::new(TmpFSInfo {
+            capacity,
+            used: AtomicUsize::new(0),
+            dir_lock: SgxMutex::new(()),
+        });
+        let root = TmpINode::new(
+            TmpINodeData::Dir(BTreeMap::new()),
+            0o777,
+            fs_info,
+            Weak::new(),
+        );
+        TmpFS { root }
+    }
+}
+
+impl FileSystem for TmpFS {
+    fn root_inode(&self) -> INodeRef {
+        Arc::new(self.root.clone())
+    }
+
+    fn sync(&self) -> Result<(), Error> {
+        // Nothing to write back
+        Ok(())
+    }
+}
+
+/// Keeps track of the total size of the files in a TmpFS
+#[derive(Debug)]
+struct TmpFSInfo {
+    capacity: usize,
+    used: AtomicUsize,
+    // Serializes the operations that change the directory tree, as they may
+    // lock more than one inode
+    dir_lock: SgxMutex<()>,
+}
+
+impl TmpFSInfo {
+    fn alloc(&self, size: usize) -> Result<(), Error> {
+        let mut used = self.used.load(Ordering::SeqCst);
+        loop {
+            if used + size > self.capacity {
+                return errno!(ENOSPC, "TmpFS is full");
+            }
+            let old_used = self
+                .used
+                .compare_and_swap(used, used + size, Ordering::SeqCst);
+            if old_used == used {
+                return Ok(());
+            }
+            used = old_used;
+        }
+    }
+
+    fn dealloc(&self, size: usize) {
+        self.used.fetch_sub(size, Ordering::SeqCst);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TmpINode {
+    inner: Arc<SgxRwLock<TmpINodeInner>>,
+    // The type never changes, so it can be read without locking the inode
+    type_: FileType,
+}
+
+struct TmpINodeInner {
+    data: TmpINodeData,
+    mode: u32,
+    nlinks: usize,
+    atime: timespec_t,
+    mtime: timespec_t,
+    ctime: timespec_t,
+    fs_info: Arc<TmpFSInfo>,
+    // The directory where the inode was created or last moved to, which is
+    // the only parent of a directory, as a directory cannot be hard linked
+    parent: Weak<SgxRwLock<TmpINodeInner>>,
+}
+
+impl TmpINodeInner {
+    /// Update the timestamps after the data is modified
+    fn touch(&mut self) {
+        let now = timespec_t::now();
+        self.mtime = now;
+        self.ctime = now;
+    }
+
+    fn get_entries(&self) -> Result<&BTreeMap<String, TmpINode>, Error> {
+        match self.data {
+            TmpINodeData::Dir(ref entries) => Ok(entries),
+            _ => errno!(ENOTDIR, "Not a directory"),
+        }
+    }
+
+    fn get_entries_mut(&mut self) -> Result<&mut BTreeMap<String, TmpINode>, Error> {
+        match self.data {
+            TmpINodeData::Dir(ref mut entries) => Ok(entries),
+            _ => errno!(ENOTDIR, "Not a directory"),
+        }
+    }
+}
+
+enum TmpINodeData {
+    File(Vec<u8>),
+    Dir(BTreeMap<String, TmpINode>),
+    SymLink(String),
+}
+
+impl TmpINode {
+    fn new(
+        data: TmpINodeData,
+        mode: u32,
+        fs_info: Arc<TmpFSInfo>,
+        parent: Weak<SgxRwLock<TmpINodeInner>>,
+    ) -> TmpINode {
+        let now = timespec_t::now();
+        let type_ = match data {
+            TmpINodeData::File(_) => FileType::File,
+            TmpINodeData::Dir(_) => FileType::Dir,
+            TmpINodeData::SymLink(_) => FileType::SymLink,
+        };
+        TmpINode {
+            inner: Arc::new(SgxRwLock::new(TmpINodeInner {
+                data,
+                mode,
+                nlinks: 1,
+                atime: now,
+                mtime: now,
+                ctime: now,
+                fs_info,
+                parent,
+            })),
+            type_,
+        }
+    }
+
+    fn is_dir(&self) -> bool {
+        self.type_ == FileType::Dir
+    }
+
+    fn get_ino(&self) -> u64 {
+        // The address of the inner object is unique while the inode is alive
+        &*self.inner as *const SgxRwLock<TmpINodeInner> as usize as u64
+    }
+
+    fn get_size(&self) -> usize {
+        match self.inner.read().unwrap().data {
+            TmpINodeData::File(ref content) => content.len(),
+            TmpINodeData::Dir(ref entries) => entries.len(),
+            TmpINodeData::SymLink(ref target) => target.len(),
+        }
+    }
+
+    /// Check whether this inode is `dir` or one of its descendants, by walking
+    /// up its ancestors
+    fn is_same_or_under(&self, dir: &TmpINode) -> bool {
+        let mut inner = self.inner.clone();
+        loop {
+            if Arc::ptr_eq(&inner, &dir.inner) {
+                return true;
+            }
+            let parent = inner.read().unwrap().parent.upgrade();
+            match parent {
+                Some(parent) => inner = parent,
+                None => return false,
+            }
+        }
+    }
+
+    fn get_fs_info(&self) -> Arc<TmpFSInfo> {
+        self.inner.read().unwrap().fs_info.clone()
+    }
+
+    fn read_at(&self, offset: usize, buf: &mut [u8]) -> Result<usize, Error> {
+        let inner = self.inner.read().unwrap();
+        let content = match inner.data {
+            TmpINodeData::File(ref content) => content,
+            TmpINodeData::Dir(_) => return errno!(EISDIR, "Cannot read a directory"),
+            TmpINodeData::SymLink(_) => return errno!(EINVAL, "Cannot read a symbolic link"),
+        };
+        if offset >= content.len() {
+            return Ok(0);
+        }
+        let read_len = std::cmp::min(buf.len(), content.len() - offset);
+        buf[..read_len].copy_from_slice(&content[offset..offset + read_len]);
+        Ok(read_len)
+    }
+
+    fn write_at(&self, offset: usize, buf: &[u8]) -> Result<usize, Error> {
+        let mut inner = self.inner.write().unwrap();
+        let fs_info = inner.fs_info.clone();
+        let content = match inner.data {
+            TmpINodeData::File(ref mut content) => content,
+            TmpINodeData::Dir(_) => return errno!(EISDIR, "Cannot write a directory"),
+            TmpINodeData::SymLink(_) => return errno!(EINVAL, "Cannot write a symbolic link"),
+        };
+        let end = offset + buf.len();
+        if end > content.len() {
+            fs_info.alloc(end - content.len())?;
+            content.resize(end, 0);
+        }
+        content[offset..end].copy_from_slice(buf);
+        inner.touch();
+        Ok(buf.len())
+    }
+
+    fn set_len(&self, len: usize) -> Result<(), Error> {
+        let mut inner = self.inner.write().unwrap();
+        let fs_info = inner.fs_info.clone();
+        match inner.data {
+            TmpINodeData::File(ref mut content) => {
+                if len > content.len() {
+                    fs_info.alloc(len - content.len())?;
+                } else {
+                    fs_info.dealloc(content.len() - len);
+                }
+                content.resize(len, 0);
+            }
+            TmpINodeData::Dir(_) => return errno!(EISDIR, "Cannot truncate a directory"),
+            TmpINodeData::SymLink(_) => return errno!(EINVAL, "Cannot truncate a symbolic link"),
+        }
+        inner.touch();
+        Ok(())
+    }
+
+    fn new_child(&self, name: &str, data: TmpINodeData, mode: u32) -> Result<INodeRef, Error> {
+        let fs_info = self.get_fs_info();
+        let _dir_guard = fs_info.dir_lock.lock().unwrap();
+        let mut inner = self.inner.write().unwrap();
+        {
+            let entries = inner.get_entries_mut()?;
+            if entries.contains_key(name) {
+                return errno!(EEXIST, "File exists");
+            }
+        }
+        let child = TmpINode::new(data, mode, fs_info.clone(), Arc::downgrade(&self.inner));
+        inner
+            .get_entries_mut()?
+            .insert(name.to_owned(), child.clone());
+        inner.touch();
+        Ok(Arc::new(child))
+    }
+
+    /// Remove a child, which must have been checked by the caller
+    fn remove_child(inner: &mut TmpINodeInner, name: &str) -> Result<(), Error> {
+        let child = inner
+            .get_entries_mut()?
+            .remove(name)
+            .ok_or_else(|| Error::new(ENOENT, "No such file or directory"))?;
+        child.drop_link();
+        inner.touch();
+        Ok(())
+    }
+
+    fn drop_link(&self) {
+        let mut inner = self.inner.write().unwrap();
+        inner.nlinks -= 1;
+        inner.ctime = timespec_t::now();
+    }
+
+    /// Check whether an entry can be replaced by another one in rename
+    fn check_replaceable(&self, replacement: &TmpINode) -> Result<(), Error> {
+        match (self.is_dir(), replacement.is_dir()) {
+            (true, true) if self.get_size() > 0 => errno!(ENOTEMPTY, "Directory not empty"),
+            (true, false) => errno!(EISDIR, "Cannot replace a directory with a non-directory"),
+            (false, true) => errno!(ENOTDIR, "Cannot replace a non-directory with a directory"),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl INode for TmpINode {
+    fn open(&self, flags: u32) -> Result<FileRef, Error> {
+        let is_readable = (flags & O_WRONLY) == 0;
+        let is_writable = (flags & O_WRONLY != 0) || (flags & O_RDWR != 0);
+        if is_writable && self.is_dir() {
+            return errno!(EISDIR, "Cannot open a directory for write");
+        }
+        if is_writable && (flags & O_TRUNC) != 0 {
+            self.set_len(0)?;
+        }
+
+        let file_ref: Arc<Box<File>> = Arc::new(Box::new(TmpFile {
+            inode: self.clone(),
+            inner: SgxMutex::new(TmpFileInner {
+                pos: 0,
+                is_readable,
+                is_writable,
+            }),
+            status_flags: StatusFlags::new(flags),
+        }));
+        Ok(file_ref)
+    }
+
+    fn find(&self, name: &str) -> Result<INodeRef, Error> {
+        let inner = self.inner.read().unwrap();
+        match inner.get_entries()?.get(name) {
+            Some(child) => Ok(Arc::new(child.clone())),
+            None => errno!(ENOENT, "No such file or directory"),
+        }
+    }
+
+    fn create(&self, name: &str, mode: u32) -> Result<INodeRef, Error> {
+        self.new_child(name, TmpINodeData::File(Vec::new()), mode)
+    }
+
+    fn mkdir(&self, name: &str, mode: u32) -> Result<INodeRef, Error> {
+        self.new_child(name, TmpINodeData::Dir(BTreeMap::new()), mode)
+    }
+
+    fn rmdir(&self, name: &str) -> Result<(), Error> {
+        let fs_info = self.get_fs_info();
+        let _dir_guard = fs_info.dir_lock.lock().unwrap();
+        let mut inner = self.inner.write().unwrap();
+        match inner.get_entries()?.get(name) {
+            Some(child) => {
+                if !child.is_dir() {
+                    return errno!(ENOTDIR, "Not a directory");
+                }
+                if child.get_size() > 0 {
+                    return errno!(ENOTEMPTY, "Directory not empty");
+                }
+            }
+            None => return errno!(ENOENT, "No such file or directory"),
+        }
+        TmpINode::remove_child(&mut inner, name)
+    }
+
+    fn list(&self) -> Result<Vec<DirEntry>, Error> {
+        let inner = self.inner.read().unwrap();
+        Ok(inner
+            .get_entries()?
+            .iter()
+            .map(|(name, child)| DirEntry {
+                ino: child.get_ino(),
+                type_: child.type_,
+                name: name.clone(),
+            })
+            .collect())
+    }
+
+    fn get_type(&self) -> Result<FileType, Error> {
+        Ok(self.type_)
+    }
+
+    fn metadata(&self) -> Result<Metadata, Error> {
+        let inner = self.inner.read().unwrap();
+        let (size, nlinks) = match inner.data {
+            TmpINodeData::File(ref content) => (content.len(), inner.nlinks),
+            TmpINodeData::Dir(ref entries) => (0, 2),
+            TmpINodeData::SymLink(ref target) => (target.len(), inner.nlinks),
+        };
+        Ok(Metadata {
+            dev: 0,
+            ino: self.get_ino(),
+            size,
+            type_: self.type_,
+            mode: inner.mode,
+            nlinks,
+            atime: inner.atime,
+            mtime: inner.mtime,
+            ctime: inner.ctime,
+        })
+    }
+
+    fn unlink(&self, name: &str) -> Result<(), Error> {
+        let fs_info = self.get_fs_info();
+        let _dir_guard = fs_info.dir_lock.lock().unwrap();
+        let mut inner = self.inner.write().unwrap();
+        match inner.get_entries()?.get(name) {
+            Some(child) if child.is_dir() => return errno!(EISDIR, "Is a directory"),
+            Some(_) => {}
+            None => return errno!(ENOENT, "No such file or directory"),
+        }
+        TmpINode::remove_child(&mut inner, name)
+    }
+
+    fn link(&self, name: &str, target: &INodeRef) -> Result<(), Error> {
+        let target = target
+            .as_any()
+            .downcast_ref::<TmpINode>()
+            .ok_or_else(|| Error::new(EXDEV, "Not in the same file system"))?;
+        if target.is_dir() {
+            return errno!(EPERM, "Cannot create a hard link to a directory");
+        }
+        let fs_info = self.get_fs_info();
+        if !Arc::ptr_eq(&fs_info, &target.get_fs_info()) {
+            return errno!(EXDEV, "Not in the same file system");
+        }
+
+        let _dir_guard = fs_info.dir_lock.lock().unwrap();
+        let mut inner = self.inner.write().unwrap();
+        if inner.get_entries()?.contains_key(name) {
+            return errno!(EEXIST, "File exists");
+        }
+        {
+            let mut target_inner = target.inner.write().unwrap();
+            target_inner.nlinks += 1;
+            target_inner.ctime = timespec_t::now();
+        }
+        inner
+            .get_entries_mut()?
+            .insert(name.to_owned(), target.clone());
+        inner.touch();
+        Ok(())
+    }
+
+    fn rename(&self, old_name: &str, target_dir: &INodeRef, new_name: &str) -> Result<(), Error> {
+        let target_dir = target_dir
+            .as_any()
+            .downcast_ref::<TmpINode>()
+            .ok_or_else(|| Error::new(EXDEV, "Not in the same file system"))?;
+        let fs_info = self.get_fs_info();
+        if !Arc::ptr_eq(&fs_info, &target_dir.get_fs_info()) {
+            return errno!(EXDEV, "Not in the same file system");
+        }
+
+        // Holding the directory lock of the file system, no other thread can
+        // lock more than one inode at a time, so the two directories can be
+        // locked in any order
+        let _dir_guard = fs_info.dir_lock.lock().unwrap();
+        let child = match self.inner.read().unwrap().get_entries()?.get(old_name) {
+            Some(child) => child.clone(),
+            None => return errno!(ENOENT, "No such file or directory"),
+        };
+        // The ancestors are walked before any inode is locked, as the source
+        // directory may be one of them
+        if child.is_dir() && target_dir.is_same_or_under(&child) {
+            return errno!(EINVAL, "Cannot move a directory into itself");
+        }
+        let mut src_inner = self.inner.write().unwrap();
+
+        if Arc::ptr_eq(&self.inner, &target_dir.inner) {
+            if old_name == new_name {
+                return Ok(());
+            }
+            if let Some(old_child) = src_inner.get_entries()?.get(new_name) {
+                if Arc::ptr_eq(&old_child.inner, &child.inner) {
+                    // Both names are hard links to the same file
+                    return Ok(());
+                }
+                old_child.check_replaceable(&child)?;
+            }
+            if src_inner.get_entries()?.contains_key(new_name) {
+                TmpINode::remove_child(&mut src_inner, new_name)?;
+            }
+            let entries = src_inner.get_entries_mut()?;
+            entries.remove(old_name);
+            entries.insert(new_name.to_owned(), child);
+            src_inner.touch();
+            return Ok(());
+        }
+
+        let mut dst_inner = target_dir.inner.write().unwrap();
+        if let Some(old_child) = dst_inner.get_entries()?.get(new_name) {
+            if Arc::ptr_eq(&old_child.inner, &child.inner) {
+                // Both names are hard links to the same file
+                return Ok(());
+            }
+            old_child.check_replaceable(&child)?;
+        }
+        if dst_inner.get_entries()?.contains_key(new_name) {
+            TmpINode::remove_child(&mut dst_inner, new_name)?;
+        }
+        src_inner.get_entries_mut()?.remove(old_name);
+        child.inner.write().unwrap().parent = Arc::downgrade(&target_dir.inner);
+        dst_inner
+            .get_entries_mut()?
+            .insert(new_name.to_owned(), child);
+        src_inner.touch();
+        dst_inner.touch();
+        Ok(())
+    }
+
+    fn symlink(&self, name: &str, target: &str) -> Result<INodeRef, Error> {
+        self.new_child(name, TmpINodeData::SymLink(target.to_owned()), 0o777)
+    }
+
+    fn read_link(&self) -> Result<String, Error> {
+        match self.inner.read().unwrap().data {
+            TmpINodeData::SymLink(ref target) => Ok(target.clone()),
+            _ => errno!(EINVAL, "Not a symbolic link"),
+        }
+    }
+
+    fn as_any(&self) -> &Any {
+        self
+    }
+}
+
+impl Drop for TmpINodeInner {
+    fn drop(&mut self) {
+        if let TmpINodeData::File(ref content) = self.data {
+            self.fs_info.dealloc(content.len());
+        }
+    }
+}
+
+impl Debug for TmpINodeInner {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let type_str = match self.data {
+            TmpINodeData::File(_) => "file",
+            TmpINodeData::Dir(_) => "dir",
+            TmpINodeData::SymLink(_) => "symlink",
+        };
+        write!(
+            f,
+            "TmpINodeInner {{ type: {}, mode: {:o} }}",
+            type_str, self.mode
+        )
+    }
+}
+
+/// An opened file of TmpFS
+#[derive(Debug)]
+pub struct TmpFile {
+    inode: TmpINode,
+    inner: SgxMutex<TmpFileInner>,
+    status_flags: StatusFlags,
+}
+
+#[derive(Debug)]
+struct TmpFileInner {
+    pos: usize,
+    is_readable: bool,
+    is_writable: bool,
+}
+
+impl TmpFile {
+    fn do_read(&self, inner: &mut TmpFileInner, buf: &mut [u8]) -> Result<usize, Error> {
+        if !inner.is_readable {
+            return errno!(EBADF, "File not readable");
+        }
+        let read_len = self.inode.read_at(inner.pos, buf)?;
+        inner.pos += read_len;
+        Ok(read_len)
+    }
+
+    fn do_write(&self, inner: &mut TmpFileInner, buf: &[u8]) -> Result<usize, Error> {
+        if !inner.is_writable {
+            return errno!(EBADF, "File not writable");
+        }
+        if self.status_flags.is_append() {
+            inner.pos = self.inode.get_size();
+        }
+        let write_len = self.inode.write_at(inner.pos, buf)?;
+        inner.pos += write_len;
+        Ok(write_len)
+    }
+}
+
+impl File for TmpFile {
+    fn read(&self, buf: &mut [u8]) -> Result<usize, Error> {
+        let mut inner = self.inner.lock().unwrap();
+        self.do_read(&mut inner, buf)
+    }
+
+    fn write(&self, buf: &[u8]) -> Result<usize, Error> {
+        let mut inner = self.inner.lock().unwrap();
+        self.do_write(&mut inner, buf)
+    }
+
+    fn readv<'a, 'b>(&self, bufs: &'a mut [&'b mut [u8]]) -> Result<usize, Error> {
+        let mut inner = self.inner.lock().unwrap();
+        let mut total_bytes = 0;
+        for buf in bufs {
+            match self.do_read(&mut inner, buf) {
+                Ok(this_len) => {
+                    total_bytes += this_len;
+                    if this_len < buf.len() {
+                        break;
+                    }
+                }
+                Err(e) => {
+                    match total_bytes {
+                        // a complete failure
+                        0 => return Err(e),
+                        // a partially failure
+                        _ => break,
+                    }
+                }
+            }
+        }
+        Ok(total_bytes)
+    }
+
+    fn writev<'a, 'b>(&self, bufs: &'a [&'b [u8]]) -> Result<usize, Error> {
+        let mut inner = self.inner.lock().unwrap();
+        let mut total_bytes = 0;
+        for buf in bufs {
+            match self.do_write(&mut inner, buf) {
+                Ok(this_len) => {
+                    total_bytes += this_len;
+                    if this_len < buf.len() {
+                        break;
+                    }
+                }
+                Err(e) => {
+                    match total_bytes {
+                        // a complete failure
+                        0 => return Err(e),
+                        // a partially failure
+                        _ => break,
+                    }
+                }
+            }
+        }
+        Ok(total_bytes)
+    }
+
+    fn read_at(&self, offset: usize, buf: &mut [u8]) -> Result<usize, Error> {
+        if !self.inner.lock().unwrap().is_readable {
+            return errno!(EBADF, "File not readable");
+        }
+        self.inode.read_at(offset, buf)
+    }
+
+    fn write_at(&self, offset: usize, buf: &[u8]) -> Result<usize, Error> {
+        if !self.inner.lock().unwrap().is_writable {
+            return errno!(EBADF, "File not writable");
+        }
+        self.inode.write_at(offset, buf)
+    }
+
+    fn seek(&self, pos: SeekFrom) -> Result<off_t, Error> {
+        let mut inner = self.inner.lock().unwrap();
+        let new_pos = match pos {
+            SeekFrom::Start(offset) => offset as i64,
+            SeekFrom::End(offset) => self.inode.get_size() as i64 + offset,
+            SeekFrom::Current(offset) => inner.pos as i64 + offset,
+        };
+        if new_pos < 0 {
+            return errno!(EINVAL, "Invalid seek position");
+        }
+        inner.pos = new_pos as usize;
+        Ok(new_pos as off_t)
+    }
+
+    fn set_len(&self, len: u64) -> Result<(), Error> {
+        let inner = self.inner.lock().unwrap();
+        if !inner.is_writable {
+            return errno!(EINVAL, "File not writable");
+        }
+        self.inode.set_len(len as usize)
+    }
+
+    fn allocate(&self, offset: u64, len: u64) -> Result<(), Error> {
+        let inner = self.inner.lock().unwrap();
+        if !inner.is_writable {
+            return errno!(EBADF, "File not writable");
+        }
+        let end = get_range_end(offset, len)?;
+        if end > self.inode.get_size() {
+            self.inode.set_len(end)?;
+        }
+        Ok(())
+    }
+
+    fn metadata(&self) -> Result<Metadata, Error> {
+        self.inode.metadata()
+    }
+
+    fn get_status_flags(&self) -> Result<u32, Error> {
+        Ok(self.status_flags.get())
+    }
+
+    fn set_status_flags(&self, flags: u32) -> Result<(), Error> {
+        self.status_flags.set(flags);
+        Ok(())
+    }
+
+    fn poll(&self) -> Result<u32, Error> {
+        Ok(POLL_ALWAYS_READY)
+    }
+
+    fn poll_queue(&self) -> Option<&IoWaitQueue> {
+        None
+    }
+
+    fn ioctl(&self, cmd: &mut IoctlCmd) -> Result<(), Error> {
+        errno!(ENOTTY, "Regular file does not support ioctl")
+    }
+
+    fn as_any(&self) -> &Any {
+        self
+    }
+}
@@ -0,0 +1,427 @@
+use super::*;
+use std::ffi::CStr;
+use time::timespec_t;
+
+/// An index node, i.e., a file, a directory or any other object that can be
+/// found in the file system namespace
+pub trait INode: Debug + Sync + Send {
+    /// Open the inode and return a new open file description
+    fn open(&self, flags: u32) -> Result<FileRef, Error>;
+    /// Find the child with the given name in a directory
+    fn find(&self, name: &str) -> Result<INodeRef, Error>;
+    /// Create a regular file with the given name in a directory
+    fn create(&self, name: &str, mode: u32) -> Result<INodeRef, Error>;
+    /// Create a sub-directory with the given name in a directory
+    fn mkdir(&self, name: &str, mode: u32) -> Result<INodeRef, Error>;
+    /// Remove the empty sub-directory with the given name in a directory
+    fn rmdir(&self, name: &str) -> Result<(), Error>;
+    /// List the entries of a directory, excluding "." and ".."
+    fn list(&self) -> Result<Vec<DirEntry>, Error>;
+    /// Get the type of the inode
+    fn get_type(&self) -> Result<FileType, Error>;
+    /// Get the metadata of the inode
+    fn metadata(&self) -> Result<Metadata, Error>;
+    /// Remove the non-directory entry with the given name in a directory
+    fn unlink(&self, name: &str) -> Result<(), Error>;
+    /// Create a hard link with the given name to a file in a directory
+    fn link(&self, name: &str, target: &INodeRef) -> Result<(), Error>;
+    /// Move the entry with the given name into the target directory, with a
+    /// new name, replacing whatever is there atomically
+    fn rename(&self, old_name: &str, target_dir: &INodeRef, new_name: &str) -> Result<(), Error>;
+    /// Create a symbolic link with the given name to the target path
+    fn symlink(&self, name: &str, target: &str) -> Result<INodeRef, Error>;
+    /// Read the target path of a symbolic link
+    fn read_link(&self) -> Result<String, Error>;
+    fn as_any(&self) -> &Any;
+}
+
+pub type INodeRef = Arc<INode>;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FileType {
+    File,
+    Dir,
+    SymLink,
+    CharDevice,
+    BlockDevice,
+    NamedPipe,
+    Socket,
+    /// A file not in any file system, e.g., an epoll file, whose mode has no
+    /// file type bits
+    Anonymous,
+}
+
+/// The metadata of a file, i.e., what stat returns
+#[derive(Clone, Debug)]
+pub struct Metadata {
+    pub dev: u64,
+    pub ino: u64,
+    pub size: usize,
+    pub type_: FileType,
+    /// The permission bits
+    pub mode: u32,
+    pub nlinks: usize,
+    pub atime: timespec_t,
+    pub mtime: timespec_t,
+    pub ctime: timespec_t,
+}
+
+/// An entry of a directory
+#[derive(Clone, Debug)]
+pub struct DirEntry {
+    pub ino: u64,
+    pub type_: FileType,
+    pub name: String,
+}
+
+pub trait FileSystem: Debug + Sync + Send {
+    fn root_inode(&self) -> INodeRef;
+    fn sync(&self) -> Result<(), Error>;
+}
+
+pub type FileSystemRef = Arc<FileSystem>;
+
+/// The table of all mounted file systems
+///
+/// A path is resolved by the file system mounted at its longest prefix.
+#[derive(Debug)]
+pub struct MountTable {
+    mounts: Vec<MountPoint>,
+}
+
+#[derive(Debug, Clone)]
+struct MountPoint {
+    path: Vec<String>,
+    fs: FileSystemRef,
+}
+
+impl MountTable {
+    pub fn new(root_fs: FileSystemRef) -> MountTable {
+        MountTable {
+            mounts: vec![MountPoint {
+                path: Vec::new(),
+                fs: root_fs,
+            }],
+        }
+    }
+
+    pub fn mount(&mut self, path: &str, fs: FileSystemRef) -> Result<(), Error> {
+        let path = split_path(path)?;
+        if self.mounts.iter().any(|mount| mount.path == path) {
+            return errno!(EBUSY, "A file system is already mounted there");
+        }
+        self.mounts.push(MountPoint { path, fs });
+        // Keep the longer mount paths first so that they take precedence
+        self.mounts.sort_by(|a, b| b.path.len().cmp(&a.path.len()));
+        Ok(())
+    }
+
+    pub fn umount(&mut self, path: &str) -> Result<FileSystemRef, Error> {
+        let path = split_path(path)?;
+        if path.is_empty() {
+            return errno!(EBUSY, "Cannot umount the root file system");
+        }
+        let mount_i = self
+            .mounts
+            .iter()
+            .position(|mount| mount.path == path)
+            .ok_or_else(|| Error::new(EINVAL, "Not a mount point"))?;
+        Ok(self.mounts.remove(mount_i).fs)
+    }
+
+    /// Find the file system that an absolute path belongs to, and return the
+    /// file system together with the rest of the path inside it
+    fn resolve<'a>(&self, path: &'a [String]) -> (FileSystemRef, &'a [String]) {
+        for mount in &self.mounts {
+            if path.starts_with(&mount.path) {
+                return (mount.fs.clone(), &path[mount.path.len()..]);
+            }
+        }
+        panic!("THIS SHOULD NEVER HAPPEN!");
+    }
+
+    /// Get the file system mounted exactly at the path
+    fn get(&self, path: &[String]) -> Option<FileSystemRef> {
+        self.mounts
+            .iter()
+            .find(|mount| mount.path == path)
+            .map(|mount| mount.fs.clone())
+    }
+
+    fn get_root(&self) -> FileSystemRef {
+        self.get(&[]).unwrap()
+    }
+
+    pub fn get_all(&self) -> Vec<FileSystemRef> {
+        self.mounts.iter().map(|mount| mount.fs.clone()).collect()
+    }
+}
+
+lazy_static! {
+    static ref MOUNT_TABLE: SgxRwLock<MountTable> = {
+        // The executables are encrypted with the zero key by sgx_protect_file
+        let keys =
+            SgxKeyTable::from_config("/", "/", SgxKeySource::Zero, &config::LIBOS_CONFIG.keys);
+        let root_fs = Arc::new(SgxFS::new("/", keys));
+        SgxRwLock::new(MountTable::new(root_fs))
+    };
+}
+
+pub fn mount(path: &str, fs: FileSystemRef) -> Result<(), Error> {
+    MOUNT_TABLE.write().unwrap().mount(path, fs)
+}
+
+pub fn umount(path: &str) -> Result<FileSystemRef, Error> {
+    MOUNT_TABLE.write().unwrap().umount(path)
+}
+
+pub fn sync_all() -> Result<(), Error> {
+    let all_fs = MOUNT_TABLE.read().unwrap().get_all();
+    for fs in all_fs {
+        fs.sync()?;
+    }
+    Ok(())
+}
+
+/// Look up the inode of an absolute path, following all symlinks
+pub fn lookup(path: &str) -> Result<INodeRef, Error> {
+    let (_, inode, _) = walk_path(path, true)?;
+    Ok(inode)
+}
+
+/// Look up the inode of an absolute path, following the symlinks in the path
+/// except the last one
+pub fn lookup_no_follow(path: &str) -> Result<INodeRef, Error> {
+    let (_, inode, _) = walk_path(path, false)?;
+    Ok(inode)
+}
+
+/// Look up the inode of an absolute path, and return it together with the
+/// path where all symlinks are resolved
+pub fn lookup_real_path(path: &str) -> Result<(INodeRef, String), Error> {
+    let (_, inode, real_path) = walk_path(path, true)?;
+    Ok((inode, format!("/{}", real_path.join("/"))))
+}
+
+/// Look up the inode of the parent directory of an absolute path, and return
+/// it together with the last component of the path
+pub fn lookup_parent(path: &str) -> Result<(INodeRef, String), Error> {
+    let (_, dir_inode, file_name) = walk_parent(path)?;
+    Ok((dir_inode, file_name))
+}
+
+/// Look up where to create a file at an absolute path as lookup_parent does,
+/// except that a symlink at the last component is followed, so that the file
+/// is created at the target of a dangling symlink
+pub fn lookup_parent_to_create(path: &str) -> Result<(INodeRef, String), Error> {
+    let mut path = path.to_owned();
+    for _ in 0..MAX_SYMLINKS {
+        let (_, dir_inode, file_name) = walk_parent(&path)?;
+        let child = match dir_inode.find(&file_name) {
+            Ok(child) => child,
+            Err(ref e) if e.errno == ENOENT => return Ok((dir_inode, file_name)),
+            Err(e) => return Err(e),
+        };
+        if child.get_type()? != FileType::SymLink {
+            return Ok((dir_inode, file_name));
+        }
+        let target = child.read_link()?;
+        path = if target.starts_with("/") {
+            target
+        } else {
+            let mut dir_path = split_abs_path(&path)?;
+            dir_path.pop();
+            format!("/{}/{}", dir_path.join("/"), target)
+        };
+    }
+    errno!(ELOOP, "Too many symbolic links encountered")
+}
+
+/// Rename a file or directory, where both paths are absolute
+pub fn rename(old_path: &str, new_path: &str) -> Result<(), Error> {
+    let (old_fs, old_dir, old_name) = walk_parent(old_path)?;
+    let (new_fs, new_dir, new_name) = walk_parent(new_path)?;
+    if !is_same_fs(&old_fs, &new_fs) {
+        return errno!(EXDEV, "Cannot rename across file systems");
+    }
+    if new_path.starts_with(old_path) && new_path[old_path.len()..].starts_with("/") {
+        return errno!(EINVAL, "Cannot move a directory into itself");
+    }
+    old_dir.rename(&old_name, &new_dir, &new_name)
+}
+
+/// Create a hard link at new_path to the file at old_path, where both paths
+/// are absolute
+pub fn link(old_path: &str, new_path: &str) -> Result<(), Error> {
+    let (old_fs, old_dir, old_name) = walk_parent(old_path)?;
+    let (new_fs, new_dir, new_name) = walk_parent(new_path)?;
+    if !is_same_fs(&old_fs, &new_fs) {
+        return errno!(EXDEV, "Cannot link across file systems");
+    }
+    let target = old_dir.find(&old_name)?;
+    if target.get_type()? == FileType::Dir {
+        return errno!(EPERM, "Cannot link to a directory");
+    }
+    new_dir.link(&new_name, &target)
+}
+
+fn is_same_fs(fs: &FileSystemRef, other_fs: &FileSystemRef) -> bool {
+    &**fs as *const FileSystem as *const u8 == &**other_fs as *const FileSystem as *const u8
+}
+
+fn walk_parent(path: &str) -> Result<(FileSystemRef, INodeRef, String), Error> {
+    let mut path = split_abs_path(path)?;
+    let file_name = path
+        .pop()
+        .ok_or_else(|| Error::new(EBUSY, "The root directory cannot be changed"))?;
+    let (fs, dir_inode, _) = walk_path(&format!("/{}", path.join("/")), true)?;
+    Ok((fs, dir_inode, file_name))
+}
+
+/// The max number of symlinks to follow in the lookup of a path
+const MAX_SYMLINKS: usize = 40;
+
+/// Walk through the components of an absolute path, switching to the root of
+/// the file system mounted at each directory and following symlinks
+///
+/// Return the inode found, the file system it belongs to and its real path.
+fn walk_path(
+    path: &str,
+    follow_last: bool,
+) -> Result<(FileSystemRef, INodeRef, Vec<String>), Error> {
+    // The components to walk through, in the reversed order
+    let mut pending: Vec<String> = split_abs_path(path)?.into_iter().rev().collect();
+    let mut real_path: Vec<String> = Vec::new();
+    let mut fs = MOUNT_TABLE.read().unwrap().get_root();
+    let mut inode = fs.root_inode();
+    let mut symlink_count = 0;
+
+    while let Some(name) = pending.pop() {
+        match name.as_str() {
+            "" | "." => continue,
+            ".." => {
+                real_path.pop();
+                // The real path has no symlink, so it is simple to look up
+                let (parent_fs, parent_inode) = walk_real_path(&real_path)?;
+                fs = parent_fs;
+                inode = parent_inode;
+                continue;
+            }
+            _ => {}
+        }
+
+        // A mount point need not exist in the parent file system
+        real_path.push(name);
+        if let Some(mounted_fs) = MOUNT_TABLE.read().unwrap().get(&real_path) {
+            inode = mounted_fs.root_inode();
+            fs = mounted_fs;
+            continue;
+        }
+        let name = real_path.pop().unwrap();
+
+        let child = inode.find(&name)?;
+        let is_last = pending.is_empty();
+        if child.get_type()? == FileType::SymLink && (follow_last || !is_last) {
+            symlink_count += 1;
+            if symlink_count > MAX_SYMLINKS {
+                return errno!(ELOOP, "Too many symbolic links encountered");
+            }
+            let target = child.read_link()?;
+            if target.starts_with("/") {
+                real_path.clear();
+                fs = MOUNT_TABLE.read().unwrap().get_root();
+                inode = fs.root_inode();
+            }
+            pending.extend(target.split('/').rev().map(|name| name.to_owned()));
+            continue;
+        }
+
+        real_path.push(name);
+        inode = child;
+    }
+    Ok((fs, inode, real_path))
+}
+
+fn walk_real_path(path: &[String]) -> Result<(FileSystemRef, INodeRef), Error> {
+    let (fs, path_in_fs) = MOUNT_TABLE.read().unwrap().resolve(path);
+    let mut inode = fs.root_inode();
+    for name in path_in_fs {
+        inode = inode.find(name)?;
+    }
+    Ok((fs, inode))
+}
+
+/// Convert a path into a normalized absolute path, resolving relative paths
+/// against the given working directory
+pub fn to_abs_path(cwd: &str, path: &str) -> Result<String, Error> {
+    let components = if path.starts_with("/") {
+        split_path(path)?
+    } else {
+        if path.is_empty() {
+            return errno!(ENOENT, "Empty path");
+        }
+        split_path(&format!("{}/{}", cwd, path))?
+    };
+    Ok(format!("/{}", components.join("/")))
+}
+
+fn split_abs_path(path: &str) -> Result<Vec<String>, Error> {
+    if !path.starts_with("/") {
+        return errno!(EINVAL, "Not an absolute path");
+    }
+    split_path(path)
+}
+
+/// Split an absolute path into its components, with "." and ".." resolved
+fn split_path(path: &str) -> Result<Vec<String>, Error> {
+    if path.is_empty() {
+        return errno!(ENOENT, "Empty path");
+    }
+    if path.len() > PATH_MAX {
+        return errno!(ENAMETOOLONG, "Path too long");
+    }
+
+    let mut components = Vec::new();
+    for name in path.split('/') {
+        match name {
+            "" | "." => {}
+            ".." => {
+                components.pop();
+            }
+            _ => {
+                if name.len() > NAME_MAX {
+                    return errno!(ENAMETOOLONG, "File name too long");
+                }
+                components.push(name.to_owned());
+            }
+        }
+    }
+    Ok(components)
+}
+
+pub const PATH_MAX: usize = 4096;
+pub const NAME_MAX: usize = 255;
+
+/// Get the working directory of the host, which is where the first process
+/// starts in
+pub fn get_host_cwd() -> &'static str {
+    &HOST_CWD
+}
+
+lazy_static! {
+    static ref HOST_CWD: String = {
+        let mut buf = vec![0 as u8; PATH_MAX];
+        let mut ret: c_int = 0;
+        let sgx_status =
+            unsafe { ocall_getcwd(&mut ret, buf.as_mut_ptr() as *mut c_char, buf.len()) };
+        if sgx_status != sgx_status_t::SGX_SUCCESS || ret != 0 {
+            panic!("Failed to get the working directory of the host");
+        }
+        let cwd = unsafe { CStr::from_ptr(buf.as_ptr() as *const c_char) };
+        cwd.to_string_lossy().into_owned()
+    };
+}
+
+extern "C" {
+    fn ocall_getcwd(ret: *mut c_int, buf: *mut c_char, buf_size: size_t) -> sgx_status_t;
+}
@@ -26,9 +26,11 @@ use std::panic;
 
 #[macro_use]
 mod prelude;
+mod config;
 mod entry;
 mod errno;
 mod fs;
+mod net;
 mod process;
 mod syscall;
 mod time;
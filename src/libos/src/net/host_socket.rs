@@ -0,0 +1,467 @@
+use super::*;
+
+// The max length of an address, i.e., the size of struct sockaddr_storage
+const MAX_ADDR_LEN: usize = 128;
+// The buffer of an OCall is copied onto the untrusted stack, so at most one
+// chunk is sent or received at a time
+const MAX_HOST_IO_SIZE: usize = 64 * 1024;
+
+/// An AF_INET or AF_INET6 socket, which forwards all operations to a socket
+/// of the host
+///
+/// The host socket is blocking unless the socket is non-blocking, so blocking
+/// operations block in the host. The data and addresses are visible to the
+/// host, as no encryption is done by the LibOS.
+#[derive(Debug)]
+pub struct HostSocket {
+    host_fd: c_int,
+    type_: u32,
+    status_flags: StatusFlags,
+    io_changes: IoChangeCounter,
+}
+
+impl HostSocket {
+    pub fn new(domain: u32, type_: u32, protocol: u32, flags: u32) -> Result<HostSocket, Error> {
+        let host_type = type_ | (flags & SOCK_NONBLOCK);
+        let mut ret: c_int = 0;
+        let sgx_status = unsafe {
+            ocall_create_host_socket(
+                &mut ret,
+                domain as c_int,
+                host_type as c_int,
+                protocol as c_int,
+            )
+        };
+        let host_fd = check_host_retval(sgx_status, ret as i64)? as c_int;
+        Ok(HostSocket::from_host_fd(host_fd, type_, flags))
+    }
+
+    fn from_host_fd(host_fd: c_int, type_: u32, flags: u32) -> HostSocket {
+        HostSocket {
+            host_fd: host_fd,
+            type_: type_,
+            status_flags: StatusFlags::new(O_RDWR | (flags & SOCK_NONBLOCK)),
+            io_changes: IoChangeCounter::new(),
+        }
+    }
+
+    fn send(&self, data: &[u8], flags: u32, addr: Option<&[u8]>) -> Result<usize, Error> {
+        let result = self.send_in_chunks(data, flags, addr);
+        self.io_changes.count_io(&result, data.len());
+        result
+    }
+
+    /// Send the data, which is split into chunks for a stream socket
+    fn send_in_chunks(&self, data: &[u8], flags: u32, addr: Option<&[u8]>) -> Result<usize, Error> {
+        if self.type_ != SOCK_STREAM {
+            if data.len() > MAX_HOST_IO_SIZE {
+                return errno!(EMSGSIZE, "The message is too long");
+            }
+            return self.send_to_host(data, flags, addr);
+        }
+        let mut sent_len = 0;
+        loop {
+            let chunk = &data[sent_len..data.len().min(sent_len + MAX_HOST_IO_SIZE)];
+            match self.send_to_host(chunk, flags, addr) {
+                Ok(this_len) => {
+                    sent_len += this_len;
+                    if sent_len == data.len() || this_len < chunk.len() {
+                        return Ok(sent_len);
+                    }
+                }
+                // The data sent so far is reported instead of the error
+                Err(_) if sent_len > 0 => return Ok(sent_len),
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    fn send_to_host(&self, buf: &[u8], flags: u32, addr: Option<&[u8]>) -> Result<usize, Error> {
+        let (addr_ptr, addr_len) = match addr {
+            Some(addr) => {
+                check_addr_len(addr)?;
+                (addr.as_ptr(), addr.len())
+            }
+            None => (std::ptr::null(), 0),
+        };
+        let mut ret: int64_t = 0;
+        let sgx_status = unsafe {
+            ocall_send_host_socket(
+                &mut ret,
+                self.host_fd,
+                buf.as_ptr() as *const c_void,
+                buf.len(),
+                flags as c_int,
+                addr_ptr as *const c_void,
+                addr_len as uint32_t,
+            )
+        };
+        check_host_len(sgx_status, ret, buf.len())
+    }
+
+    /// Receive at most one chunk of data into the buffers, and return its
+    /// length together with the address of the sender
+    fn recv(&self, bufs: &mut [&mut [u8]], flags: u32) -> Result<(usize, Vec<u8>), Error> {
+        let total_len: usize = bufs.iter().map(|buf| buf.len()).sum();
+        let mut data = vec![0; total_len.min(MAX_HOST_IO_SIZE)];
+        let mut addr = vec![0; MAX_ADDR_LEN];
+        let mut addr_len: uint32_t = 0;
+        let mut ret: int64_t = 0;
+        let sgx_status = unsafe {
+            ocall_recv_host_socket(
+                &mut ret,
+                self.host_fd,
+                data.as_mut_ptr() as *mut c_void,
+                data.len(),
+                flags as c_int,
+                addr.as_mut_ptr() as *mut c_void,
+                addr.len() as uint32_t,
+                &mut addr_len,
+            )
+        };
+        let result = check_host_len(sgx_status, ret, data.len());
+        self.io_changes.count_io(&result, data.len());
+        let recv_len = result?;
+        addr.truncate((addr_len as usize).min(MAX_ADDR_LEN));
+
+        let mut copied_len = 0;
+        for buf in bufs.iter_mut() {
+            if copied_len == recv_len {
+                break;
+            }
+            let this_len = buf.len().min(recv_len - copied_len);
+            buf[..this_len].copy_from_slice(&data[copied_len..copied_len + this_len]);
+            copied_len += this_len;
+        }
+        Ok((recv_len, addr))
+    }
+
+    fn get_name(&self, is_peer: bool) -> Result<Vec<u8>, Error> {
+        let mut addr = vec![0; MAX_ADDR_LEN];
+        let mut addr_len: uint32_t = 0;
+        let mut ret: c_int = 0;
+        let sgx_status = unsafe {
+            ocall_get_host_socket_name(
+                &mut ret,
+                self.host_fd,
+                is_peer as c_int,
+                addr.as_mut_ptr() as *mut c_void,
+                addr.len() as uint32_t,
+                &mut addr_len,
+            )
+        };
+        check_host_retval(sgx_status, ret as i64)?;
+        addr.truncate((addr_len as usize).min(MAX_ADDR_LEN));
+        Ok(addr)
+    }
+}
+
+impl Socket for HostSocket {
+    fn bind(&self, addr: &[u8]) -> Result<(), Error> {
+        check_addr_len(addr)?;
+        let mut ret: c_int = 0;
+        let sgx_status = unsafe {
+            ocall_bind_host_socket(
+                &mut ret,
+                self.host_fd,
+                addr.as_ptr() as *const c_void,
+                addr.len() as uint32_t,
+            )
+        };
+        check_host_retval(sgx_status, ret as i64)?;
+        Ok(())
+    }
+
+    fn listen(&self, backlog: usize) -> Result<(), Error> {
+        let mut ret: c_int = 0;
+        let sgx_status =
+            unsafe { ocall_listen_host_socket(&mut ret, self.host_fd, backlog as c_int) };
+        check_host_retval(sgx_status, ret as i64)?;
+        Ok(())
+    }
+
+    fn accept(&self, flags: u32) -> Result<(FileRef, Vec<u8>), Error> {
+        let mut addr = vec![0; MAX_ADDR_LEN];
+        let mut addr_len: uint32_t = 0;
+        let mut ret: c_int = 0;
+        let sgx_status = unsafe {
+            ocall_accept_host_socket(
+                &mut ret,
+                self.host_fd,
+                addr.as_mut_ptr() as *mut c_void,
+                addr.len() as uint32_t,
+                &mut addr_len,
+                (flags & SOCK_NONBLOCK) as c_int,
+            )
+        };
+        let result = check_host_retval(sgx_status, ret as i64);
+        // A pending connection is either accepted or not, i.e., never short
+        self.io_changes.count_io(&result, 0);
+        let new_host_fd = result? as c_int;
+        addr.truncate((addr_len as usize).min(MAX_ADDR_LEN));
+        let new_socket = HostSocket::from_host_fd(new_host_fd, self.type_, flags);
+        Ok((Arc::new(Box::new(new_socket)), addr))
+    }
+
+    /// Connect to the address, which fails with EINPROGRESS if the socket is
+    /// non-blocking, and the result is then known from SO_ERROR once the
+    /// socket becomes writable
+    fn connect(&self, addr: &[u8]) -> Result<(), Error> {
+        check_addr_len(addr)?;
+        let mut ret: c_int = 0;
+        let sgx_status = unsafe {
+            ocall_connect_host_socket(
+                &mut ret,
+                self.host_fd,
+                addr.as_ptr() as *const c_void,
+                addr.len() as uint32_t,
+            )
+        };
+        check_host_retval(sgx_status, ret as i64)?;
+        Ok(())
+    }
+
+    fn get_sock_name(&self) -> Result<Vec<u8>, Error> {
+        self.get_name(false)
+    }
+
+    fn get_peer_name(&self) -> Result<Vec<u8>, Error> {
+        self.get_name(true)
+    }
+
+    fn send_to(&self, bufs: &[&[u8]], flags: u32, addr: Option<&[u8]>) -> Result<usize, Error> {
+        self.send(&bufs.concat(), flags, addr)
+    }
+
+    fn recv_from(&self, bufs: &mut [&mut [u8]], flags: u32) -> Result<(usize, Vec<u8>), Error> {
+        self.recv(bufs, flags)
+    }
+
+    fn shutdown(&self, how: u32) -> Result<(), Error> {
+        let mut ret: c_int = 0;
+        let sgx_status =
+            unsafe { ocall_shutdown_host_socket(&mut ret, self.host_fd, how as c_int) };
+        check_host_retval(sgx_status, ret as i64)?;
+        Ok(())
+    }
+
+    fn get_sock_opt(&self, level: u32, name: u32, max_len: usize) -> Result<Vec<u8>, Error> {
+        let mut value = vec![0; max_len];
+        let mut value_len: uint32_t = 0;
+        let mut ret: c_int = 0;
+        let sgx_status = unsafe {
+            ocall_get_host_socket_opt(
+                &mut ret,
+                self.host_fd,
+                level as c_int,
+                name as c_int,
+                value.as_mut_ptr() as *mut c_void,
+                value.len() as uint32_t,
+                &mut value_len,
+            )
+        };
+        check_host_retval(sgx_status, ret as i64)?;
+        value.truncate((value_len as usize).min(max_len));
+        Ok(value)
+    }
+
+    fn set_sock_opt(&self, level: u32, name: u32, value: &[u8]) -> Result<(), Error> {
+        let mut ret: c_int = 0;
+        let sgx_status = unsafe {
+            ocall_set_host_socket_opt(
+                &mut ret,
+                self.host_fd,
+                level as c_int,
+                name as c_int,
+                value.as_ptr() as *const c_void,
+                value.len() as uint32_t,
+            )
+        };
+        check_host_retval(sgx_status, ret as i64)?;
+        Ok(())
+    }
+}
+
+impl File for HostSocket {
+    fn read(&self, buf: &mut [u8]) -> Result<usize, Error> {
+        self.readv(&mut [buf])
+    }
+
+    fn write(&self, buf: &[u8]) -> Result<usize, Error> {
+        self.send(buf, 0, None)
+    }
+
+    fn readv<'a, 'b>(&self, bufs: &'a mut [&'b mut [u8]]) -> Result<usize, Error> {
+        let (read_len, _) = self.recv(bufs, 0)?;
+        Ok(read_len)
+    }
+
+    fn writev<'a, 'b>(&self, bufs: &'a [&'b [u8]]) -> Result<usize, Error> {
+        self.send(&bufs.concat(), 0, None)
+    }
+
+    fn read_at(&self, offset: usize, buf: &mut [u8]) -> Result<usize, Error> {
+        errno!(ESPIPE, "Socket does not support read_at")
+    }
+
+    fn write_at(&self, offset: usize, buf: &[u8]) -> Result<usize, Error> {
+        errno!(ESPIPE, "Socket does not support write_at")
+    }
+
+    fn seek(&self, pos: SeekFrom) -> Result<off_t, Error> {
+        errno!(ESPIPE, "Socket does not support seek")
+    }
+
+    fn set_len(&self, len: u64) -> Result<(), Error> {
+        errno!(EINVAL, "Socket does not support truncate")
+    }
+
+    fn allocate(&self, offset: u64, len: u64) -> Result<(), Error> {
+        errno!(ESPIPE, "Socket does not support allocate")
+    }
+
+    fn metadata(&self) -> Result<Metadata, Error> {
+        Ok(socket_metadata())
+    }
+
+    fn get_status_flags(&self) -> Result<u32, Error> {
+        Ok(self.status_flags.get())
+    }
+
+    fn set_status_flags(&self, flags: u32) -> Result<(), Error> {
+        let is_nonblocking = flags & O_NONBLOCK != 0;
+        if is_nonblocking != self.status_flags.is_nonblocking() {
+            let mut ret: c_int = 0;
+            let sgx_status = unsafe {
+                ocall_set_host_socket_nonblocking(&mut ret, self.host_fd, is_nonblocking as c_int)
+            };
+            check_host_retval(sgx_status, ret as i64)?;
+        }
+        self.status_flags.set(flags);
+        Ok(())
+    }
+
+    fn poll(&self) -> Result<u32, Error> {
+        let mut ret: c_int = 0;
+        let sgx_status = unsafe { ocall_poll_host_socket(&mut ret, self.host_fd) };
+        let events = check_host_retval(sgx_status, ret as i64)? as u32;
+        Ok(events & (POLLIN | POLLPRI | POLLOUT | POLLERR | POLLHUP | POLLRDNORM | POLLWRNORM))
+    }
+
+    fn poll_queue(&self) -> Option<&IoWaitQueue> {
+        // The host does not notify when the socket becomes ready
+        None
+    }
+
+    fn get_num_changes(&self) -> Option<usize> {
+        Some(self.io_changes.get_num_changes())
+    }
+
+    fn ioctl(&self, cmd: &mut IoctlCmd) -> Result<(), Error> {
+        match *cmd {
+            IoctlCmd::FionRead(ref mut len) => ioctl_host_fd(self.host_fd, FIONREAD, &mut **len),
+            _ => errno!(ENOTTY, "Socket does not support the ioctl command"),
+        }
+    }
+
+    fn as_any(&self) -> &Any {
+        self
+    }
+}
+
+impl Drop for HostSocket {
+    fn drop(&mut self) {
+        unsafe {
+            ocall_close_host_socket(self.host_fd);
+        }
+    }
+}
+
+fn check_addr_len(addr: &[u8]) -> Result<(), Error> {
+    if addr.len() > MAX_ADDR_LEN {
+        return errno!(EINVAL, "Invalid length of the address");
+    }
+    Ok(())
+}
+
+extern "C" {
+    fn ocall_create_host_socket(
+        ret: *mut c_int,
+        domain: c_int,
+        type_: c_int,
+        protocol: c_int,
+    ) -> sgx_status_t;
+    fn ocall_bind_host_socket(
+        ret: *mut c_int,
+        fd: c_int,
+        addr: *const c_void,
+        addr_len: uint32_t,
+    ) -> sgx_status_t;
+    fn ocall_listen_host_socket(ret: *mut c_int, fd: c_int, backlog: c_int) -> sgx_status_t;
+    fn ocall_accept_host_socket(
+        ret: *mut c_int,
+        fd: c_int,
+        addr: *mut c_void,
+        addr_buf_len: uint32_t,
+        addr_len: *mut uint32_t,
+        flags: c_int,
+    ) -> sgx_status_t;
+    fn ocall_connect_host_socket(
+        ret: *mut c_int,
+        fd: c_int,
+        addr: *const c_void,
+        addr_len: uint32_t,
+    ) -> sgx_status_t;
+    fn ocall_get_host_socket_name(
+        ret: *mut c_int,
+        fd: c_int,
+        is_peer: c_int,
+        addr: *mut c_void,
+        addr_buf_len: uint32_t,
+        addr_len: *mut uint32_t,
+    ) -> sgx_status_t;
+    fn ocall_send_host_socket(
+        ret: *mut int64_t,
+        fd: c_int,
+        buf: *const c_void,
+        buf_size: size_t,
+        flags: c_int,
+        addr: *const c_void,
+        addr_len: uint32_t,
+    ) -> sgx_status_t;
+    fn ocall_recv_host_socket(
+        ret: *mut int64_t,
+        fd: c_int,
+        buf: *mut c_void,
+        buf_size: size_t,
+        flags: c_int,
+        addr: *mut c_void,
+        addr_buf_len: uint32_t,
+        addr_len: *mut uint32_t,
+    ) -> sgx_status_t;
+    fn ocall_shutdown_host_socket(ret: *mut c_int, fd: c_int, how: c_int) -> sgx_status_t;
+    fn ocall_get_host_socket_opt(
+        ret: *mut c_int,
+        fd: c_int,
+        level: c_int,
+        name: c_int,
+        value: *mut c_void,
+        value_buf_len: uint32_t,
+        value_len: *mut uint32_t,
+    ) -> sgx_status_t;
+    fn ocall_set_host_socket_opt(
+        ret: *mut c_int,
+        fd: c_int,
+        level: c_int,
+        name: c_int,
+        value: *const c_void,
+        value_len: uint32_t,
+    ) -> sgx_status_t;
+    fn ocall_set_host_socket_nonblocking(
+        ret: *mut c_int,
+        fd: c_int,
+        is_nonblocking: c_int,
+    ) -> sgx_status_t;
+    fn ocall_poll_host_socket(ret: *mut c_int, fd: c_int) -> sgx_status_t;
+    fn ocall_close_host_socket(fd: c_int) -> sgx_status_t;
+}
@@ -0,0 +1,308 @@
+use super::*;
+use fs::{
+    get_file, ioctl_host_fd, File, FileDesc, FileRef, FileType, IoChangeCounter, IoWaitQueue,
+    IoctlCmd, Metadata, StatusFlags, FIONREAD, O_CLOEXEC, O_NONBLOCK, O_RDWR, POLLERR, POLLHUP,
+    POLLIN, POLLOUT, POLLPRI, POLLRDNORM, POLLWRNORM,
+};
+use prelude::*;
+use {fs, process, std};
+
+mod host_socket;
+mod socket;
+mod unix_addr;
+mod unix_dgram;
+mod unix_stream;
+
+pub use self::host_socket::HostSocket;
+pub use self::socket::{as_socket, Socket};
+pub use self::unix_addr::UnixAddr;
+pub use self::unix_dgram::UnixDatagram;
+pub use self::unix_stream::UnixStream;
+
+// Address families
+pub const AF_UNSPEC: u32 = 0;
+pub const AF_UNIX: u32 = 1;
+pub const AF_INET: u32 = 2;
+pub const AF_INET6: u32 = 10;
+
+// Socket types, which may be or-ed with SOCK_NONBLOCK and SOCK_CLOEXEC
+pub const SOCK_STREAM: u32 = 1;
+pub const SOCK_DGRAM: u32 = 2;
+pub const SOCK_TYPE_MASK: u32 = 0xf;
+pub const SOCK_NONBLOCK: u32 = O_NONBLOCK;
+pub const SOCK_CLOEXEC: u32 = O_CLOEXEC;
+
+// Flags of send and recv
+pub const MSG_DONTWAIT: u32 = 0x40;
+// No signal is raised on a broken connection, as signals are not supported
+pub const MSG_NOSIGNAL: u32 = 0x4000;
+const SUPPORTED_MSG_FLAGS: u32 = MSG_DONTWAIT | MSG_NOSIGNAL;
+
+// Ways of shutdown
+pub const SHUT_RD: u32 = 0;
+pub const SHUT_WR: u32 = 1;
+pub const SHUT_RDWR: u32 = 2;
+
+// The max length of the queue of pending connections
+pub const SOMAXCONN: u32 = 4096;
+
+// Levels and names of the socket options at the socket level. The options of
+// the other levels are only supported by the sockets of the host.
+pub const SOL_SOCKET: u32 = 1;
+pub const SO_REUSEADDR: u32 = 2;
+pub const SO_TYPE: u32 = 3;
+pub const SO_ERROR: u32 = 4;
+pub const SO_SNDBUF: u32 = 7;
+pub const SO_RCVBUF: u32 = 8;
+pub const SO_KEEPALIVE: u32 = 9;
+pub const SO_DOMAIN: u32 = 39;
+
+// The max length of the value of a socket option
+pub const MAX_SOCK_OPT_LEN: usize = 1024;
+
+pub fn do_socket(domain: u32, type_: u32, protocol: u32) -> Result<FileDesc, Error> {
+    let flags = type_ & !SOCK_TYPE_MASK;
+    if flags & !(SOCK_NONBLOCK | SOCK_CLOEXEC) != 0 {
+        return errno!(EINVAL, "Invalid flags for socket");
+    }
+    let socket = new_socket(domain, type_ & SOCK_TYPE_MASK, protocol, flags)?;
+    let current_ref = process::get_current();
+    let mut current = current_ref.lock().unwrap();
+    Ok(current
+        .get_files_mut()
+        .put(socket, flags & SOCK_CLOEXEC != 0))
+}
+
+fn new_socket(domain: u32, type_: u32, protocol: u32, flags: u32) -> Result<FileRef, Error> {
+    let socket: FileRef = match domain {
+        AF_UNIX => {
+            check_unix_protocol(protocol)?;
+            match type_ {
+                SOCK_STREAM => Arc::new(Box::new(UnixStream::new(flags))),
+                SOCK_DGRAM => Arc::new(Box::new(UnixDatagram::new(flags))),
+                _ => return errno!(ESOCKTNOSUPPORT, "Socket type not supported"),
+            }
+        }
+        AF_INET | AF_INET6 => {
+            if type_ != SOCK_STREAM && type_ != SOCK_DGRAM {
+                return errno!(ESOCKTNOSUPPORT, "Socket type not supported");
+            }
+            // The protocol is checked by the host
+            Arc::new(Box::new(HostSocket::new(domain, type_, protocol, flags)?))
+        }
+        _ => return errno!(EAFNOSUPPORT, "Address family not supported"),
+    };
+    Ok(socket)
+}
+
+pub fn do_socketpair(domain: u32, type_: u32, protocol: u32) -> Result<[FileDesc; 2], Error> {
+    let flags = type_ & !SOCK_TYPE_MASK;
+    if flags & !(SOCK_NONBLOCK | SOCK_CLOEXEC) != 0 {
+        return errno!(EINVAL, "Invalid flags for socketpair");
+    }
+    if domain != AF_UNIX {
+        return errno!(EOPNOTSUPP, "socketpair is only supported by AF_UNIX");
+    }
+    check_unix_protocol(protocol)?;
+    let (socket0, socket1): (FileRef, FileRef) = match type_ & SOCK_TYPE_MASK {
+        SOCK_STREAM => {
+            let (socket0, socket1) = UnixStream::new_pair(flags);
+            (Arc::new(Box::new(socket0)), Arc::new(Box::new(socket1)))
+        }
+        SOCK_DGRAM => {
+            let (socket0, socket1) = UnixDatagram::new_pair(flags);
+            (Arc::new(Box::new(socket0)), Arc::new(Box::new(socket1)))
+        }
+        _ => return errno!(ESOCKTNOSUPPORT, "Socket type not supported"),
+    };
+
+    let current_ref = process::get_current();
+    let mut current = current_ref.lock().unwrap();
+    let close_on_spawn = flags & SOCK_CLOEXEC != 0;
+    let fd0 = current.get_files_mut().put(socket0, close_on_spawn);
+    let fd1 = current.get_files_mut().put(socket1, close_on_spawn);
+    Ok([fd0, fd1])
+}
+
+fn check_unix_protocol(protocol: u32) -> Result<(), Error> {
+    // The protocol may also be PF_UNIX, which equals AF_UNIX
+    if protocol != 0 && protocol != AF_UNIX {
+        return errno!(EPROTONOSUPPORT, "Protocol not supported");
+    }
+    Ok(())
+}
+
+pub fn do_bind(fd: FileDesc, addr: &[u8]) -> Result<(), Error> {
+    let file_ref = get_file(fd)?;
+    as_socket(&file_ref)?.bind(addr)
+}
+
+pub fn do_listen(fd: FileDesc, backlog: i32) -> Result<(), Error> {
+    let backlog = if backlog < 0 {
+        0
+    } else {
+        (backlog as u32).min(SOMAXCONN)
+    };
+    let file_ref = get_file(fd)?;
+    as_socket(&file_ref)?.listen(backlog as usize)
+}
+
+/// Accept a pending connection, and return the fd of the new socket together
+/// with the address of the peer
+pub fn do_accept4(fd: FileDesc, flags: u32) -> Result<(FileDesc, Vec<u8>), Error> {
+    if flags & !(SOCK_NONBLOCK | SOCK_CLOEXEC) != 0 {
+        return errno!(EINVAL, "Invalid flags for accept4");
+    }
+    let file_ref = get_file(fd)?;
+    let (new_socket, peer_addr) = as_socket(&file_ref)?.accept(flags & SOCK_NONBLOCK)?;
+
+    let current_ref = process::get_current();
+    let mut current = current_ref.lock().unwrap();
+    let new_fd = current
+        .get_files_mut()
+        .put(new_socket, flags & SOCK_CLOEXEC != 0);
+    Ok((new_fd, peer_addr))
+}
+
+pub fn do_connect(fd: FileDesc, addr: &[u8]) -> Result<(), Error> {
+    let file_ref = get_file(fd)?;
+    as_socket(&file_ref)?.connect(addr)
+}
+
+pub fn do_getsockname(fd: FileDesc) -> Result<Vec<u8>, Error> {
+    let file_ref = get_file(fd)?;
+    as_socket(&file_ref)?.get_sock_name()
+}
+
+pub fn do_getpeername(fd: FileDesc) -> Result<Vec<u8>, Error> {
+    let file_ref = get_file(fd)?;
+    as_socket(&file_ref)?.get_peer_name()
+}
+
+pub fn do_sendto(
+    fd: FileDesc,
+    buf: &[u8],
+    flags: u32,
+    addr: Option<&[u8]>,
+) -> Result<usize, Error> {
+    do_sendmsg(fd, &[buf], flags, addr)
+}
+
+/// Receive a message, and return its length together with the address of
+/// the sender, which is empty for a connection-mode socket
+pub fn do_recvfrom(fd: FileDesc, buf: &mut [u8], flags: u32) -> Result<(usize, Vec<u8>), Error> {
+    do_recvmsg(fd, &mut [buf], flags)
+}
+
+/// Send the data in all the buffers as one message
+pub fn do_sendmsg(
+    fd: FileDesc,
+    bufs: &[&[u8]],
+    flags: u32,
+    addr: Option<&[u8]>,
+) -> Result<usize, Error> {
+    check_msg_flags(flags)?;
+    let file_ref = get_file(fd)?;
+    as_socket(&file_ref)?.send_to(bufs, flags, addr)
+}
+
+/// Receive a message into the buffers, and return its length together with
+/// the address of the sender
+pub fn do_recvmsg(
+    fd: FileDesc,
+    bufs: &mut [&mut [u8]],
+    flags: u32,
+) -> Result<(usize, Vec<u8>), Error> {
+    check_msg_flags(flags)?;
+    let file_ref = get_file(fd)?;
+    as_socket(&file_ref)?.recv_from(bufs, flags)
+}
+
+fn check_msg_flags(flags: u32) -> Result<(), Error> {
+    if flags & !SUPPORTED_MSG_FLAGS != 0 {
+        return errno!(EOPNOTSUPP, "Flags of send or recv not supported");
+    }
+    Ok(())
+}
+
+pub fn do_shutdown(fd: FileDesc, how: u32) -> Result<(), Error> {
+    if how != SHUT_RD && how != SHUT_WR && how != SHUT_RDWR {
+        return errno!(EINVAL, "Invalid way of shutdown");
+    }
+    let file_ref = get_file(fd)?;
+    as_socket(&file_ref)?.shutdown(how)
+}
+
+/// Get the value of a socket option, which is at most `max_len` bytes
+pub fn do_getsockopt(
+    fd: FileDesc,
+    level: u32,
+    name: u32,
+    max_len: usize,
+) -> Result<Vec<u8>, Error> {
+    let file_ref = get_file(fd)?;
+    as_socket(&file_ref)?.get_sock_opt(level, name, max_len.min(MAX_SOCK_OPT_LEN))
+}
+
+pub fn do_setsockopt(fd: FileDesc, level: u32, name: u32, value: &[u8]) -> Result<(), Error> {
+    if value.len() > MAX_SOCK_OPT_LEN {
+        return errno!(EINVAL, "The value of the socket option is too long");
+    }
+    let file_ref = get_file(fd)?;
+    as_socket(&file_ref)?.set_sock_opt(level, name, value)
+}
+
+/// Get an option of an AF_UNIX socket, where only a few options of the socket
+/// level are supported
+fn get_unix_sock_opt(
+    type_: u32,
+    level: u32,
+    name: u32,
+    buf_size: usize,
+    max_len: usize,
+) -> Result<Vec<u8>, Error> {
+    if level != SOL_SOCKET {
+        return errno!(ENOPROTOOPT, "Socket option not supported");
+    }
+    let value = match name {
+        SO_TYPE => type_ as i32,
+        SO_DOMAIN => AF_UNIX as i32,
+        // Errors are reported by the operations directly
+        SO_ERROR => 0,
+        SO_SNDBUF | SO_RCVBUF => buf_size as i32,
+        _ => return errno!(ENOPROTOOPT, "Socket option not supported"),
+    };
+    let mut value = value.to_ne_bytes().to_vec();
+    value.truncate(max_len);
+    Ok(value)
+}
+
+/// Set an option of an AF_UNIX socket, where the supported options are
+/// accepted but have no effect
+fn set_unix_sock_opt(level: u32, name: u32, value: &[u8]) -> Result<(), Error> {
+    if level != SOL_SOCKET {
+        return errno!(ENOPROTOOPT, "Socket option not supported");
+    }
+    match name {
+        SO_REUSEADDR | SO_SNDBUF | SO_RCVBUF | SO_KEEPALIVE => {}
+        _ => return errno!(ENOPROTOOPT, "Socket option not supported"),
+    }
+    if value.len() < std::mem::size_of::<i32>() {
+        return errno!(EINVAL, "The value of the socket option is too short");
+    }
+    Ok(())
+}
+
+fn socket_metadata() -> Metadata {
+    Metadata {
+        dev: 0,
+        ino: 0,
+        size: 0,
+        type_: FileType::Socket,
+        mode: 0o777,
+        nlinks: 1,
+        atime: Default::default(),
+        mtime: Default::default(),
+        ctime: Default::default(),
+    }
+}
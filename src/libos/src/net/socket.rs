@@ -0,0 +1,42 @@
+use super::*;
+
+/// The operations of a socket besides those of a file, where the addresses
+/// are the raw bytes of struct sockaddr of the address family
+pub trait Socket: File {
+    fn bind(&self, addr: &[u8]) -> Result<(), Error>;
+    /// Start to accept connections, where `backlog` is the max number of
+    /// pending connections
+    fn listen(&self, backlog: usize) -> Result<(), Error>;
+    /// Accept a pending connection, and return the new socket with the
+    /// status flags together with the address of the peer
+    fn accept(&self, flags: u32) -> Result<(FileRef, Vec<u8>), Error>;
+    fn connect(&self, addr: &[u8]) -> Result<(), Error>;
+    fn get_sock_name(&self) -> Result<Vec<u8>, Error>;
+    fn get_peer_name(&self) -> Result<Vec<u8>, Error>;
+    /// Send the data in all the buffers as one message to the given address,
+    /// or the connected peer if None
+    fn send_to(&self, bufs: &[&[u8]], flags: u32, addr: Option<&[u8]>) -> Result<usize, Error>;
+    /// Receive a message into the buffers, and return its length together
+    /// with the address of the sender, which is empty for a connection-mode
+    /// socket
+    fn recv_from(&self, bufs: &mut [&mut [u8]], flags: u32) -> Result<(usize, Vec<u8>), Error>;
+    fn shutdown(&self, how: u32) -> Result<(), Error>;
+    /// Get the value of an option, which is at most `max_len` bytes
+    fn get_sock_opt(&self, level: u32, name: u32, max_len: usize) -> Result<Vec<u8>, Error>;
+    fn set_sock_opt(&self, level: u32, name: u32, value: &[u8]) -> Result<(), Error>;
+}
+
+/// Get the socket operations of an open file, or fail with ENOTSOCK
+pub fn as_socket(file_ref: &FileRef) -> Result<&Socket, Error> {
+    let any = file_ref.as_any();
+    if let Some(socket) = any.downcast_ref::<UnixStream>() {
+        return Ok(socket);
+    }
+    if let Some(socket) = any.downcast_ref::<UnixDatagram>() {
+        return Ok(socket);
+    }
+    if let Some(socket) = any.downcast_ref::<HostSocket>() {
+        return Ok(socket);
+    }
+    errno!(ENOTSOCK, "Not a socket")
+}
@@ -0,0 +1,196 @@
+use super::unix_dgram::Inbox;
+use super::unix_stream::Backlog;
+use super::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Weak;
+
+// The size of sa_family in struct sockaddr
+const SA_FAMILY_SIZE: usize = 2;
+// The size of sun_path in struct sockaddr_un
+const UNIX_PATH_MAX: usize = 108;
+
+/// The address of an AF_UNIX socket
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum UnixAddr {
+    /// Not bound to any name
+    Unnamed,
+    /// A name in the file system
+    Path(String),
+    /// A name in the abstract namespace, where sun_path starts with a null
+    /// byte and the name is the rest of the address
+    Abstract(Vec<u8>),
+}
+
+impl UnixAddr {
+    /// Parse an address from the raw bytes of struct sockaddr_un
+    pub fn from_raw(raw: &[u8]) -> Result<UnixAddr, Error> {
+        if raw.len() < SA_FAMILY_SIZE || raw.len() > SA_FAMILY_SIZE + UNIX_PATH_MAX {
+            return errno!(EINVAL, "Invalid length of the address");
+        }
+        let family = u16::from_ne_bytes([raw[0], raw[1]]) as u32;
+        if family != AF_UNIX {
+            return errno!(EINVAL, "Not an address of AF_UNIX");
+        }
+        let sun_path = &raw[SA_FAMILY_SIZE..];
+        if sun_path.is_empty() {
+            return Ok(UnixAddr::Unnamed);
+        }
+        if sun_path[0] == 0 {
+            return Ok(UnixAddr::Abstract(sun_path[1..].to_vec()));
+        }
+        // The path may be terminated by a null byte or the end of the address
+        let path_len = sun_path
+            .iter()
+            .position(|&byte| byte == 0)
+            .unwrap_or(sun_path.len());
+        let path = String::from_utf8(sun_path[..path_len].to_vec())
+            .map_err(|_| Error::new(EINVAL, "The path is not valid UTF-8"))?;
+        Ok(UnixAddr::Path(path))
+    }
+
+    /// Convert the address into the raw bytes of struct sockaddr_un, whose
+    /// length is that of the address instead of the whole struct
+    pub fn to_raw(&self) -> Vec<u8> {
+        let mut raw = (AF_UNIX as u16).to_ne_bytes().to_vec();
+        match *self {
+            UnixAddr::Unnamed => {}
+            UnixAddr::Path(ref path) => {
+                raw.extend_from_slice(path.as_bytes());
+                raw.push(0);
+            }
+            UnixAddr::Abstract(ref name) => {
+                raw.push(0);
+                raw.extend_from_slice(name);
+            }
+        }
+        raw
+    }
+
+    /// Get the key of the address in the namespace, where a relative path is
+    /// resolved against the working directory of the current process
+    fn to_key(&self) -> Result<UnixAddr, Error> {
+        match *self {
+            UnixAddr::Unnamed => errno!(EINVAL, "The address is unnamed"),
+            UnixAddr::Path(ref path) => {
+                let current_ref = process::get_current();
+                let current = current_ref.lock().unwrap();
+                let abs_path = fs::to_abs_path(current.get_cwd(), path)?;
+                Ok(UnixAddr::Path(abs_path))
+            }
+            UnixAddr::Abstract(_) => Ok(self.clone()),
+        }
+    }
+}
+
+/// What a name is bound to, i.e., the pending connections of a stream
+/// socket, or the received messages of a datagram socket
+#[derive(Debug, Clone)]
+pub enum UnixEndpoint {
+    Stream(Arc<Backlog>),
+    Dgram(Arc<Inbox>),
+}
+
+#[derive(Debug)]
+enum WeakEndpoint {
+    Stream(Weak<Backlog>),
+    Dgram(Weak<Inbox>),
+}
+
+impl UnixEndpoint {
+    fn downgrade(&self) -> WeakEndpoint {
+        match *self {
+            UnixEndpoint::Stream(ref backlog) => WeakEndpoint::Stream(Arc::downgrade(backlog)),
+            UnixEndpoint::Dgram(ref inbox) => WeakEndpoint::Dgram(Arc::downgrade(inbox)),
+        }
+    }
+}
+
+impl WeakEndpoint {
+    fn upgrade(&self) -> Option<UnixEndpoint> {
+        match *self {
+            WeakEndpoint::Stream(ref backlog) => backlog.upgrade().map(UnixEndpoint::Stream),
+            WeakEndpoint::Dgram(ref inbox) => inbox.upgrade().map(UnixEndpoint::Dgram),
+        }
+    }
+}
+
+lazy_static! {
+    /// The names bound by the sockets in all processes, keyed by the absolute
+    /// paths or the abstract names. The file systems do not support special
+    /// files, so no socket file is created for a path, and a name is released
+    /// once its socket is closed.
+    static ref UNIX_NAMESPACE: SgxMutex<HashMap<UnixAddr, WeakEndpoint>> =
+        { SgxMutex::new(HashMap::new()) };
+}
+
+/// A name bound by a socket, which is released when dropped
+#[derive(Debug)]
+pub struct UnixName {
+    addr: UnixAddr,
+    key: UnixAddr,
+}
+
+impl UnixName {
+    /// Bind the address to the endpoint, or fail with EADDRINUSE if the
+    /// address is already bound
+    pub fn bind(addr: &UnixAddr, endpoint: &UnixEndpoint) -> Result<UnixName, Error> {
+        let key = addr.to_key()?;
+        let mut namespace = UNIX_NAMESPACE.lock().unwrap();
+        let is_bound = match namespace.get(&key) {
+            Some(weak_endpoint) => weak_endpoint.upgrade().is_some(),
+            None => false,
+        };
+        if is_bound {
+            return errno!(EADDRINUSE, "The address is already in use");
+        }
+        namespace.insert(key.clone(), endpoint.downgrade());
+        Ok(UnixName {
+            addr: addr.clone(),
+            key: key,
+        })
+    }
+
+    /// Bind a unique name in the abstract namespace, which consists of five
+    /// hex digits as on Linux
+    pub fn autobind(endpoint: &UnixEndpoint) -> Result<UnixName, Error> {
+        for _ in 0..MAX_AUTOBIND_NAMES {
+            let id = NEXT_AUTOBIND_ID.fetch_add(1, Ordering::SeqCst) % MAX_AUTOBIND_NAMES;
+            let addr = UnixAddr::Abstract(format!("{:05x}", id).into_bytes());
+            match UnixName::bind(&addr, endpoint) {
+                Err(ref e) if e.errno == EADDRINUSE => continue,
+                result => return result,
+            }
+        }
+        errno!(EADDRINUSE, "No name is available for autobind")
+    }
+
+    pub fn get_addr(&self) -> &UnixAddr {
+        &self.addr
+    }
+}
+
+impl Drop for UnixName {
+    fn drop(&mut self) {
+        UNIX_NAMESPACE.lock().unwrap().remove(&self.key);
+    }
+}
+
+const MAX_AUTOBIND_NAMES: usize = 0x100000;
+static NEXT_AUTOBIND_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// Look up the endpoint that the address is bound to
+pub fn lookup_name(addr: &UnixAddr) -> Result<UnixEndpoint, Error> {
+    let key = addr.to_key()?;
+    let endpoint = UNIX_NAMESPACE
+        .lock()
+        .unwrap()
+        .get(&key)
+        .and_then(|weak_endpoint| weak_endpoint.upgrade());
+    match endpoint {
+        Some(endpoint) => Ok(endpoint),
+        None => match *addr {
+            UnixAddr::Path(_) => errno!(ENOENT, "No socket is bound to the path"),
+            _ => errno!(ECONNREFUSED, "No socket is bound to the name"),
+        },
+    }
+}
@@ -0,0 +1,386 @@
+use super::unix_addr::{lookup_name, UnixEndpoint, UnixName};
+use super::*;
+use std::sync::Weak;
+use util::ring_buf::{RingBuf, RingBufReader};
+
+// The capacity of the buffer of the received messages
+const DGRAM_BUF_SIZE: usize = 256 * 1024;
+// Each message in the buffer starts with the length of the data and that of
+// the address of the sender, followed by the address and then the data
+const MSG_HEADER_SIZE: usize = 8;
+
+/// An AF_UNIX datagram socket
+#[derive(Debug)]
+pub struct UnixDatagram {
+    inner: SgxMutex<DgramInner>,
+    inbox: Arc<Inbox>,
+    status_flags: StatusFlags,
+}
+
+#[derive(Debug)]
+struct DgramInner {
+    name: Option<UnixName>,
+    // The default destination of the messages, set by connect
+    peer: Option<DgramPeer>,
+    is_read_shutdown: bool,
+    is_write_shutdown: bool,
+}
+
+#[derive(Debug)]
+struct DgramPeer {
+    addr: UnixAddr,
+    inbox: Weak<Inbox>,
+}
+
+/// The received messages of a datagram socket, to which its name is bound
+#[derive(Debug)]
+pub struct Inbox {
+    buf: SgxMutex<RingBuf>,
+    // The wait queue of the receiving socket, on which the sending threads
+    // also sleep while the buffer is full
+    wait_queue: IoWaitQueue,
+}
+
+impl Inbox {
+    fn new() -> Inbox {
+        Inbox {
+            buf: SgxMutex::new(RingBuf::new(DGRAM_BUF_SIZE)),
+            wait_queue: IoWaitQueue::new(),
+        }
+    }
+
+    /// Put a message into the buffer as a whole, sleeping until there is
+    /// enough space
+    fn send(&self, data: &[u8], sender_addr: &[u8], is_nonblocking: bool) -> Result<usize, Error> {
+        let msg_len = MSG_HEADER_SIZE + sender_addr.len() + data.len();
+        // One byte of the ring buffer is always kept free
+        if msg_len >= DGRAM_BUF_SIZE {
+            return errno!(EMSGSIZE, "The message is too long");
+        }
+        self.wait_queue.wait_until(is_nonblocking, || {
+            let buf = self.buf.lock().unwrap();
+            if buf.writer.is_peer_closed() {
+                return errno!(ECONNREFUSED, "The receiving socket has been closed");
+            }
+            if buf.writer.bytes_to_write() < msg_len {
+                return Ok(None);
+            }
+            buf.writer.write(&(data.len() as u32).to_ne_bytes())?;
+            buf.writer
+                .write(&(sender_addr.len() as u32).to_ne_bytes())?;
+            buf.writer.write(sender_addr)?;
+            buf.writer.write(data)?;
+            Ok(Some(()))
+        })?;
+        self.wait_queue.wake_all();
+        Ok(data.len())
+    }
+
+    /// Take a message out of the buffer, and return the length of the data
+    /// read into the buffers together with the address of the sender. The
+    /// data that does not fit is discarded.
+    fn read_msg(reader: &RingBufReader, bufs: &mut [&mut [u8]]) -> Result<(usize, Vec<u8>), Error> {
+        let mut header = [0; MSG_HEADER_SIZE];
+        reader.read(&mut header)?;
+        let mut data_len_bytes = [0; 4];
+        data_len_bytes.copy_from_slice(&header[..4]);
+        let mut addr_len_bytes = [0; 4];
+        addr_len_bytes.copy_from_slice(&header[4..]);
+        let mut remaining_len = u32::from_ne_bytes(data_len_bytes) as usize;
+        let mut sender_addr = vec![0; u32::from_ne_bytes(addr_len_bytes) as usize];
+        reader.read(&mut sender_addr)?;
+
+        let mut read_len = 0;
+        for buf in bufs {
+            let this_len = buf.len().min(remaining_len);
+            reader.read(&mut buf[..this_len])?;
+            read_len += this_len;
+            remaining_len -= this_len;
+        }
+        let mut discarded = [0; 256];
+        while remaining_len > 0 {
+            let this_len = discarded.len().min(remaining_len);
+            reader.read(&mut discarded[..this_len])?;
+            remaining_len -= this_len;
+        }
+        Ok((read_len, sender_addr))
+    }
+
+    /// Refuse the messages sent afterwards
+    fn close(&self) {
+        self.buf.lock().unwrap().reader.close();
+        self.wait_queue.wake_all();
+    }
+}
+
+unsafe impl Send for Inbox {}
+unsafe impl Sync for Inbox {}
+
+impl UnixDatagram {
+    pub fn new(flags: u32) -> UnixDatagram {
+        UnixDatagram {
+            inner: SgxMutex::new(DgramInner {
+                name: None,
+                peer: None,
+                is_read_shutdown: false,
+                is_write_shutdown: false,
+            }),
+            inbox: Arc::new(Inbox::new()),
+            status_flags: StatusFlags::new(O_RDWR | (flags & O_NONBLOCK)),
+        }
+    }
+
+    /// Create a pair of sockets connected to each other
+    pub fn new_pair(flags: u32) -> (UnixDatagram, UnixDatagram) {
+        let socket = UnixDatagram::new(flags);
+        let peer_socket = UnixDatagram::new(flags);
+        socket.inner.lock().unwrap().peer = Some(DgramPeer {
+            addr: UnixAddr::Unnamed,
+            inbox: Arc::downgrade(&peer_socket.inbox),
+        });
+        peer_socket.inner.lock().unwrap().peer = Some(DgramPeer {
+            addr: UnixAddr::Unnamed,
+            inbox: Arc::downgrade(&socket.inbox),
+        });
+        (socket, peer_socket)
+    }
+
+    fn get_endpoint(&self) -> UnixEndpoint {
+        UnixEndpoint::Dgram(self.inbox.clone())
+    }
+
+    fn is_nonblocking_with(&self, flags: u32) -> bool {
+        self.status_flags.is_nonblocking() || (flags & MSG_DONTWAIT) != 0
+    }
+
+    /// Send a message to the given address, or the connected peer if None
+    fn send(&self, data: &[u8], addr: Option<&[u8]>, is_nonblocking: bool) -> Result<usize, Error> {
+        let peer_inbox = match addr {
+            Some(addr) => lookup_inbox(&UnixAddr::from_raw(addr)?)?,
+            None => {
+                let inner = self.inner.lock().unwrap();
+                match inner.peer {
+                    Some(ref peer) => peer.inbox.upgrade().ok_or_else(|| {
+                        Error::new(ECONNREFUSED, "The connected socket has been closed")
+                    })?,
+                    None => return errno!(ENOTCONN, "The socket is not connected"),
+                }
+            }
+        };
+        // An unnamed sender has an empty address
+        let sender_addr = {
+            let inner = self.inner.lock().unwrap();
+            if inner.is_write_shutdown {
+                return errno!(EPIPE, "The socket is shut down for write");
+            }
+            match inner.name {
+                Some(ref name) => name.get_addr().to_raw(),
+                None => Vec::new(),
+            }
+        };
+        peer_inbox.send(data, &sender_addr, is_nonblocking)
+    }
+
+    /// Receive a message, sleeping until there is one
+    fn recv(
+        &self,
+        bufs: &mut [&mut [u8]],
+        is_nonblocking: bool,
+    ) -> Result<(usize, Vec<u8>), Error> {
+        let result = self.inbox.wait_queue.wait_until(is_nonblocking, || {
+            let buf = self.inbox.buf.lock().unwrap();
+            if buf.reader.bytes_to_read() == 0 {
+                if self.inner.lock().unwrap().is_read_shutdown {
+                    return Ok(Some((0, Vec::new())));
+                }
+                return Ok(None);
+            }
+            Inbox::read_msg(&buf.reader, bufs).map(Some)
+        })?;
+        // So that the threads sending to the full buffer can continue
+        self.inbox.wait_queue.wake_all();
+        Ok(result)
+    }
+}
+
+/// Look up the received messages of the datagram socket bound to the address
+fn lookup_inbox(addr: &UnixAddr) -> Result<Arc<Inbox>, Error> {
+    match lookup_name(addr)? {
+        UnixEndpoint::Dgram(inbox) => Ok(inbox),
+        UnixEndpoint::Stream(_) => errno!(EPROTOTYPE, "The address is bound by a stream socket"),
+    }
+}
+
+impl Socket for UnixDatagram {
+    fn bind(&self, addr: &[u8]) -> Result<(), Error> {
+        let addr = UnixAddr::from_raw(addr)?;
+        let mut inner = self.inner.lock().unwrap();
+        if inner.name.is_some() {
+            return errno!(EINVAL, "The socket is already bound");
+        }
+        let name = match addr {
+            UnixAddr::Unnamed => UnixName::autobind(&self.get_endpoint())?,
+            _ => UnixName::bind(&addr, &self.get_endpoint())?,
+        };
+        inner.name = Some(name);
+        Ok(())
+    }
+
+    fn listen(&self, backlog: usize) -> Result<(), Error> {
+        errno!(EOPNOTSUPP, "Datagram socket does not support listen")
+    }
+
+    fn accept(&self, flags: u32) -> Result<(FileRef, Vec<u8>), Error> {
+        errno!(EOPNOTSUPP, "Datagram socket does not support accept")
+    }
+
+    /// Set the default destination of the messages, or clear it if the
+    /// address family is AF_UNSPEC
+    fn connect(&self, addr: &[u8]) -> Result<(), Error> {
+        if addr.len() >= 2 && u16::from_ne_bytes([addr[0], addr[1]]) as u32 == AF_UNSPEC {
+            self.inner.lock().unwrap().peer = None;
+            return Ok(());
+        }
+        let addr = UnixAddr::from_raw(addr)?;
+        let peer_inbox = lookup_inbox(&addr)?;
+        self.inner.lock().unwrap().peer = Some(DgramPeer {
+            addr: addr,
+            inbox: Arc::downgrade(&peer_inbox),
+        });
+        Ok(())
+    }
+
+    fn get_sock_name(&self) -> Result<Vec<u8>, Error> {
+        let inner = self.inner.lock().unwrap();
+        let addr = match inner.name {
+            Some(ref name) => name.get_addr().to_raw(),
+            None => UnixAddr::Unnamed.to_raw(),
+        };
+        Ok(addr)
+    }
+
+    fn get_peer_name(&self) -> Result<Vec<u8>, Error> {
+        let inner = self.inner.lock().unwrap();
+        match inner.peer {
+            Some(ref peer) => Ok(peer.addr.to_raw()),
+            None => errno!(ENOTCONN, "The socket is not connected"),
+        }
+    }
+
+    fn send_to(&self, bufs: &[&[u8]], flags: u32, addr: Option<&[u8]>) -> Result<usize, Error> {
+        self.send(&bufs.concat(), addr, self.is_nonblocking_with(flags))
+    }
+
+    fn recv_from(&self, bufs: &mut [&mut [u8]], flags: u32) -> Result<(usize, Vec<u8>), Error> {
+        self.recv(bufs, self.is_nonblocking_with(flags))
+    }
+
+    fn shutdown(&self, how: u32) -> Result<(), Error> {
+        {
+            let mut inner = self.inner.lock().unwrap();
+            if how != SHUT_WR {
+                inner.is_read_shutdown = true;
+            }
+            if how != SHUT_RD {
+                inner.is_write_shutdown = true;
+            }
+        }
+        // So that the threads sleeping in receiving return
+        self.inbox.wait_queue.wake_all();
+        Ok(())
+    }
+
+    fn get_sock_opt(&self, level: u32, name: u32, max_len: usize) -> Result<Vec<u8>, Error> {
+        get_unix_sock_opt(SOCK_DGRAM, level, name, DGRAM_BUF_SIZE, max_len)
+    }
+
+    fn set_sock_opt(&self, level: u32, name: u32, value: &[u8]) -> Result<(), Error> {
+        set_unix_sock_opt(level, name, value)
+    }
+}
+
+impl File for UnixDatagram {
+    fn read(&self, buf: &mut [u8]) -> Result<usize, Error> {
+        self.readv(&mut [buf])
+    }
+
+    fn write(&self, buf: &[u8]) -> Result<usize, Error> {
+        self.send(buf, None, self.status_flags.is_nonblocking())
+    }
+
+    fn readv<'a, 'b>(&self, bufs: &'a mut [&'b mut [u8]]) -> Result<usize, Error> {
+        let (read_len, _) = self.recv(bufs, self.status_flags.is_nonblocking())?;
+        Ok(read_len)
+    }
+
+    /// Send the data in all the buffers as one message
+    fn writev<'a, 'b>(&self, bufs: &'a [&'b [u8]]) -> Result<usize, Error> {
+        self.send(&bufs.concat(), None, self.status_flags.is_nonblocking())
+    }
+
+    fn read_at(&self, offset: usize, buf: &mut [u8]) -> Result<usize, Error> {
+        errno!(ESPIPE, "Socket does not support read_at")
+    }
+
+    fn write_at(&self, offset: usize, buf: &[u8]) -> Result<usize, Error> {
+        errno!(ESPIPE, "Socket does not support write_at")
+    }
+
+    fn seek(&self, pos: SeekFrom) -> Result<off_t, Error> {
+        errno!(ESPIPE, "Socket does not support seek")
+    }
+
+    fn set_len(&self, len: u64) -> Result<(), Error> {
+        errno!(EINVAL, "Socket does not support truncate")
+    }
+
+    fn allocate(&self, offset: u64, len: u64) -> Result<(), Error> {
+        errno!(ESPIPE, "Socket does not support allocate")
+    }
+
+    fn metadata(&self) -> Result<Metadata, Error> {
+        Ok(socket_metadata())
+    }
+
+    fn get_status_flags(&self) -> Result<u32, Error> {
+        Ok(self.status_flags.get())
+    }
+
+    fn set_status_flags(&self, flags: u32) -> Result<(), Error> {
+        self.status_flags.set(flags);
+        Ok(())
+    }
+
+    fn poll(&self) -> Result<u32, Error> {
+        // Always reported writable, as whether sending blocks depends on the
+        // receiving socket
+        let mut events = POLLOUT | POLLWRNORM;
+        let has_msg = self.inbox.buf.lock().unwrap().reader.bytes_to_read() > 0;
+        if has_msg || self.inner.lock().unwrap().is_read_shutdown {
+            events |= POLLIN | POLLRDNORM;
+        }
+        Ok(events)
+    }
+
+    fn poll_queue(&self) -> Option<&IoWaitQueue> {
+        Some(&self.inbox.wait_queue)
+    }
+
+    fn ioctl(&self, cmd: &mut IoctlCmd) -> Result<(), Error> {
+        errno!(ENOTTY, "Datagram socket does not support ioctl")
+    }
+
+    fn as_any(&self) -> &Any {
+        self
+    }
+}
+
+impl Drop for UnixDatagram {
+    fn drop(&mut self) {
+        // So that the threads sending to this socket get ECONNREFUSED
+        self.inbox.close();
+    }
+}
+
+unsafe impl Send for UnixDatagram {}
+unsafe impl Sync for UnixDatagram {}
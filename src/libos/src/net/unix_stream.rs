@@ -0,0 +1,572 @@
+use super::unix_addr::{lookup_name, UnixEndpoint, UnixName};
+use super::*;
+use util::ring_buf::{RingBuf, RingBufReader, RingBufWriter};
+
+// The capacity of the buffer of each direction of a connection
+const STREAM_BUF_SIZE: usize = 256 * 1024;
+
+/// An AF_UNIX stream socket
+#[derive(Debug)]
+pub struct UnixStream {
+    inner: SgxMutex<StreamInner>,
+    backlog: Arc<Backlog>,
+    wait_queue: Arc<IoWaitQueue>,
+    status_flags: StatusFlags,
+}
+
+#[derive(Debug)]
+struct StreamInner {
+    name: Option<UnixName>,
+    state: StreamState,
+}
+
+#[derive(Debug)]
+enum StreamState {
+    Unconnected,
+    Listening,
+    Connected(Connection),
+}
+
+/// One end of a connection, which reads from and writes to the two ring
+/// buffers shared with the other end
+#[derive(Debug)]
+struct Connection {
+    reader: RingBufReader,
+    writer: RingBufWriter,
+    addr: UnixAddr,
+    peer_addr: UnixAddr,
+    // Woken whenever the events of the peer may have changed by this end
+    peer_wait_queue: Arc<IoWaitQueue>,
+    is_read_shutdown: bool,
+    is_write_shutdown: bool,
+}
+
+impl Connection {
+    fn new_pair(
+        addr: UnixAddr,
+        wait_queue: &Arc<IoWaitQueue>,
+        peer_addr: UnixAddr,
+        peer_wait_queue: &Arc<IoWaitQueue>,
+    ) -> (Connection, Connection) {
+        let ring_buf = RingBuf::new(STREAM_BUF_SIZE);
+        let peer_ring_buf = RingBuf::new(STREAM_BUF_SIZE);
+        let conn = Connection {
+            reader: peer_ring_buf.reader,
+            writer: ring_buf.writer,
+            addr: addr.clone(),
+            peer_addr: peer_addr.clone(),
+            peer_wait_queue: peer_wait_queue.clone(),
+            is_read_shutdown: false,
+            is_write_shutdown: false,
+        };
+        let peer_conn = Connection {
+            reader: ring_buf.reader,
+            writer: peer_ring_buf.writer,
+            addr: peer_addr,
+            peer_addr: addr,
+            peer_wait_queue: wait_queue.clone(),
+            is_read_shutdown: false,
+            is_write_shutdown: false,
+        };
+        (conn, peer_conn)
+    }
+
+    /// Whether no more data can be read, after which a read returns zero
+    fn is_read_done(&self) -> bool {
+        self.is_read_shutdown || self.reader.is_peer_closed()
+    }
+
+    /// Whether no more data can be written, after which a write fails
+    fn is_write_done(&self) -> bool {
+        self.is_write_shutdown || self.writer.is_peer_closed()
+    }
+}
+
+/// The pending connections of a stream socket, to which its name is bound
+#[derive(Debug)]
+pub struct Backlog {
+    inner: SgxMutex<BacklogInner>,
+    // The wait queue of the listening socket, on which the connecting threads
+    // also sleep while the backlog is full
+    wait_queue: Arc<IoWaitQueue>,
+}
+
+#[derive(Debug)]
+struct BacklogInner {
+    is_listening: bool,
+    addr: UnixAddr,
+    max_len: usize,
+    pending: VecDeque<UnixStream>,
+}
+
+impl Backlog {
+    fn new(wait_queue: Arc<IoWaitQueue>) -> Backlog {
+        Backlog {
+            inner: SgxMutex::new(BacklogInner {
+                is_listening: false,
+                addr: UnixAddr::Unnamed,
+                max_len: 0,
+                pending: VecDeque::new(),
+            }),
+            wait_queue: wait_queue,
+        }
+    }
+
+    fn listen(&self, addr: UnixAddr, max_len: usize) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.is_listening = true;
+        inner.addr = addr;
+        inner.max_len = max_len;
+    }
+
+    /// Stop listening, and return the pending connections to be refused
+    fn close(&self) -> VecDeque<UnixStream> {
+        let mut inner = self.inner.lock().unwrap();
+        inner.is_listening = false;
+        std::mem::replace(&mut inner.pending, VecDeque::new())
+    }
+
+    fn has_pending(&self) -> bool {
+        !self.inner.lock().unwrap().pending.is_empty()
+    }
+}
+
+impl BacklogInner {
+    fn is_full(&self) -> bool {
+        // As on Linux, one more connection than the backlog can be pending
+        self.pending.len() > self.max_len
+    }
+}
+
+unsafe impl Send for Backlog {}
+unsafe impl Sync for Backlog {}
+
+impl UnixStream {
+    pub fn new(flags: u32) -> UnixStream {
+        UnixStream::new_with_state(
+            StreamState::Unconnected,
+            Arc::new(IoWaitQueue::new()),
+            flags,
+        )
+    }
+
+    /// Create a pair of connected sockets
+    pub fn new_pair(flags: u32) -> (UnixStream, UnixStream) {
+        let wait_queue = Arc::new(IoWaitQueue::new());
+        let peer_wait_queue = Arc::new(IoWaitQueue::new());
+        let (conn, peer_conn) = Connection::new_pair(
+            UnixAddr::Unnamed,
+            &wait_queue,
+            UnixAddr::Unnamed,
+            &peer_wait_queue,
+        );
+        (
+            UnixStream::new_with_state(StreamState::Connected(conn), wait_queue, flags),
+            UnixStream::new_with_state(StreamState::Connected(peer_conn), peer_wait_queue, flags),
+        )
+    }
+
+    fn new_with_state(state: StreamState, wait_queue: Arc<IoWaitQueue>, flags: u32) -> UnixStream {
+        UnixStream {
+            inner: SgxMutex::new(StreamInner {
+                name: None,
+                state: state,
+            }),
+            backlog: Arc::new(Backlog::new(wait_queue.clone())),
+            wait_queue: wait_queue,
+            status_flags: StatusFlags::new(O_RDWR | (flags & O_NONBLOCK)),
+        }
+    }
+
+    fn get_endpoint(&self) -> UnixEndpoint {
+        UnixEndpoint::Stream(self.backlog.clone())
+    }
+
+    fn get_peer_wait_queue(&self) -> Result<Arc<IoWaitQueue>, Error> {
+        let inner = self.inner.lock().unwrap();
+        Ok(inner.get_connection()?.peer_wait_queue.clone())
+    }
+
+    fn is_nonblocking_with(&self, flags: u32) -> bool {
+        self.status_flags.is_nonblocking() || (flags & MSG_DONTWAIT) != 0
+    }
+
+    /// Read the available data, or sleep until some data is written. Only
+    /// when the peer has been closed or shut down for write is an empty read
+    /// the end of file.
+    fn recv(&self, bufs: &mut [&mut [u8]], is_nonblocking: bool) -> Result<usize, Error> {
+        let peer_wait_queue = self.get_peer_wait_queue()?;
+        if bufs.iter().all(|buf| buf.len() == 0) {
+            return Ok(0);
+        }
+        let read_len = self.wait_queue.wait_until(is_nonblocking, || {
+            let inner = self.inner.lock().unwrap();
+            let conn = inner.get_connection()?;
+            if conn.is_read_shutdown {
+                return Ok(Some(0));
+            }
+            // Checked before reading, so the data written right before the
+            // peer is closed is not missed
+            let is_peer_closed = conn.reader.is_peer_closed();
+            let read_len = conn.reader.readv(bufs)?;
+            if read_len > 0 || is_peer_closed {
+                Ok(Some(read_len))
+            } else {
+                Ok(None)
+            }
+        })?;
+        if read_len > 0 {
+            peer_wait_queue.wake_all();
+        }
+        Ok(read_len)
+    }
+
+    /// Write all the data, sleeping whenever the buffer is full. If the
+    /// connection is broken, the data written so far is returned, or EPIPE if
+    /// none. In non-blocking mode, only the data that fits is written.
+    fn send(&self, bufs: &[&[u8]], is_nonblocking: bool) -> Result<usize, Error> {
+        let peer_wait_queue = self.get_peer_wait_queue()?;
+        let total_len: usize = bufs.iter().map(|buf| buf.len()).sum();
+        if total_len == 0 {
+            return Ok(0);
+        }
+        let mut written_len = 0;
+        loop {
+            // The peer is woken after each write instead of inside the try,
+            // as the wait queue of this end is locked there
+            let result = self.wait_queue.wait_until(is_nonblocking, || {
+                let inner = self.inner.lock().unwrap();
+                let conn = inner.get_connection()?;
+                if conn.is_write_done() {
+                    return errno!(EPIPE, "The connection is shut down for write");
+                }
+                match conn.writer.writev(bufs, written_len)? {
+                    0 => Ok(None),
+                    this_len => Ok(Some(this_len)),
+                }
+            });
+            match result {
+                Ok(this_len) => {
+                    written_len += this_len;
+                    peer_wait_queue.wake_all();
+                }
+                Err(_) if written_len > 0 => return Ok(written_len),
+                Err(e) => return Err(e),
+            }
+            if written_len == total_len || is_nonblocking {
+                return Ok(written_len);
+            }
+        }
+    }
+}
+
+impl StreamInner {
+    fn get_addr(&self) -> UnixAddr {
+        if let Some(ref name) = self.name {
+            return name.get_addr().clone();
+        }
+        match self.state {
+            StreamState::Connected(ref conn) => conn.addr.clone(),
+            _ => UnixAddr::Unnamed,
+        }
+    }
+
+    fn is_listening(&self) -> bool {
+        match self.state {
+            StreamState::Listening => true,
+            _ => false,
+        }
+    }
+
+    fn get_connection(&self) -> Result<&Connection, Error> {
+        match self.state {
+            StreamState::Connected(ref conn) => Ok(conn),
+            _ => errno!(ENOTCONN, "The socket is not connected"),
+        }
+    }
+
+    fn get_connection_mut(&mut self) -> Result<&mut Connection, Error> {
+        match self.state {
+            StreamState::Connected(ref mut conn) => Ok(conn),
+            _ => errno!(ENOTCONN, "The socket is not connected"),
+        }
+    }
+}
+
+impl Socket for UnixStream {
+    fn bind(&self, addr: &[u8]) -> Result<(), Error> {
+        let addr = UnixAddr::from_raw(addr)?;
+        let mut inner = self.inner.lock().unwrap();
+        if inner.name.is_some() {
+            return errno!(EINVAL, "The socket is already bound");
+        }
+        let name = match addr {
+            UnixAddr::Unnamed => UnixName::autobind(&self.get_endpoint())?,
+            _ => UnixName::bind(&addr, &self.get_endpoint())?,
+        };
+        inner.name = Some(name);
+        Ok(())
+    }
+
+    fn listen(&self, backlog: usize) -> Result<(), Error> {
+        let mut inner = self.inner.lock().unwrap();
+        if let StreamState::Connected(_) = inner.state {
+            return errno!(EINVAL, "The socket is already connected");
+        }
+        if inner.name.is_none() {
+            inner.name = Some(UnixName::autobind(&self.get_endpoint())?);
+        }
+        self.backlog.listen(inner.get_addr(), backlog);
+        inner.state = StreamState::Listening;
+        Ok(())
+    }
+
+    fn accept(&self, flags: u32) -> Result<(FileRef, Vec<u8>), Error> {
+        if !self.inner.lock().unwrap().is_listening() {
+            return errno!(EINVAL, "The socket is not listening");
+        }
+        let is_nonblocking = self.status_flags.is_nonblocking();
+        let new_socket = self.wait_queue.wait_until(is_nonblocking, || {
+            Ok(self.backlog.inner.lock().unwrap().pending.pop_front())
+        })?;
+        // So that the threads connecting to the full backlog can continue
+        self.wait_queue.wake_all();
+
+        new_socket.status_flags.set(flags);
+        let peer_addr = new_socket.get_peer_name()?;
+        let new_socket: FileRef = Arc::new(Box::new(new_socket));
+        Ok((new_socket, peer_addr))
+    }
+
+    fn connect(&self, addr: &[u8]) -> Result<(), Error> {
+        let addr = UnixAddr::from_raw(addr)?;
+        let self_addr = {
+            let inner = self.inner.lock().unwrap();
+            match inner.state {
+                StreamState::Unconnected => {}
+                StreamState::Listening => return errno!(EINVAL, "The socket is listening"),
+                StreamState::Connected(_) => {
+                    return errno!(EISCONN, "The socket is already connected")
+                }
+            }
+            inner.get_addr()
+        };
+        let backlog = match lookup_name(&addr)? {
+            UnixEndpoint::Stream(backlog) => backlog,
+            UnixEndpoint::Dgram(_) => {
+                return errno!(EPROTOTYPE, "The address is bound by a datagram socket")
+            }
+        };
+
+        // The socket accepted by the listening socket
+        let listener_addr = backlog.inner.lock().unwrap().addr.clone();
+        let peer_wait_queue = Arc::new(IoWaitQueue::new());
+        let (conn, peer_conn) =
+            Connection::new_pair(self_addr, &self.wait_queue, listener_addr, &peer_wait_queue);
+        let mut peer_socket = Some(UnixStream::new_with_state(
+            StreamState::Connected(peer_conn),
+            peer_wait_queue,
+            0,
+        ));
+
+        let is_nonblocking = self.status_flags.is_nonblocking();
+        backlog.wait_queue.wait_until(is_nonblocking, || {
+            let mut backlog_inner = backlog.inner.lock().unwrap();
+            if !backlog_inner.is_listening {
+                return errno!(ECONNREFUSED, "The socket is not listening");
+            }
+            if backlog_inner.is_full() {
+                return Ok(None);
+            }
+            backlog_inner.pending.push_back(peer_socket.take().unwrap());
+            Ok(Some(()))
+        })?;
+        backlog.wait_queue.wake_all();
+
+        {
+            let mut inner = self.inner.lock().unwrap();
+            if inner.get_connection().is_ok() {
+                return errno!(EISCONN, "The socket is connected by others");
+            }
+            inner.state = StreamState::Connected(conn);
+        }
+        self.wait_queue.wake_all();
+        Ok(())
+    }
+
+    fn get_sock_name(&self) -> Result<Vec<u8>, Error> {
+        Ok(self.inner.lock().unwrap().get_addr().to_raw())
+    }
+
+    fn get_peer_name(&self) -> Result<Vec<u8>, Error> {
+        let inner = self.inner.lock().unwrap();
+        Ok(inner.get_connection()?.peer_addr.to_raw())
+    }
+
+    fn send_to(&self, bufs: &[&[u8]], flags: u32, addr: Option<&[u8]>) -> Result<usize, Error> {
+        if addr.is_some() {
+            let is_connected = self.inner.lock().unwrap().get_connection().is_ok();
+            if is_connected {
+                return errno!(EISCONN, "The socket is already connected");
+            } else {
+                return errno!(EOPNOTSUPP, "Cannot send to an address by a stream socket");
+            }
+        }
+        self.send(bufs, self.is_nonblocking_with(flags))
+    }
+
+    fn recv_from(&self, bufs: &mut [&mut [u8]], flags: u32) -> Result<(usize, Vec<u8>), Error> {
+        let read_len = self.recv(bufs, self.is_nonblocking_with(flags))?;
+        Ok((read_len, Vec::new()))
+    }
+
+    fn shutdown(&self, how: u32) -> Result<(), Error> {
+        let peer_wait_queue = {
+            let mut inner = self.inner.lock().unwrap();
+            let conn = inner.get_connection_mut()?;
+            if how != SHUT_WR {
+                conn.is_read_shutdown = true;
+                conn.reader.close();
+            }
+            if how != SHUT_RD {
+                conn.is_write_shutdown = true;
+                conn.writer.close();
+            }
+            conn.peer_wait_queue.clone()
+        };
+        peer_wait_queue.wake_all();
+        self.wait_queue.wake_all();
+        Ok(())
+    }
+
+    fn get_sock_opt(&self, level: u32, name: u32, max_len: usize) -> Result<Vec<u8>, Error> {
+        get_unix_sock_opt(SOCK_STREAM, level, name, STREAM_BUF_SIZE, max_len)
+    }
+
+    fn set_sock_opt(&self, level: u32, name: u32, value: &[u8]) -> Result<(), Error> {
+        set_unix_sock_opt(level, name, value)
+    }
+}
+
+impl File for UnixStream {
+    fn read(&self, buf: &mut [u8]) -> Result<usize, Error> {
+        self.readv(&mut [buf])
+    }
+
+    fn write(&self, buf: &[u8]) -> Result<usize, Error> {
+        self.writev(&[buf])
+    }
+
+    fn readv<'a, 'b>(&self, bufs: &'a mut [&'b mut [u8]]) -> Result<usize, Error> {
+        self.recv(bufs, self.status_flags.is_nonblocking())
+    }
+
+    fn writev<'a, 'b>(&self, bufs: &'a [&'b [u8]]) -> Result<usize, Error> {
+        self.send(bufs, self.status_flags.is_nonblocking())
+    }
+
+    fn read_at(&self, offset: usize, buf: &mut [u8]) -> Result<usize, Error> {
+        errno!(ESPIPE, "Socket does not support read_at")
+    }
+
+    fn write_at(&self, offset: usize, buf: &[u8]) -> Result<usize, Error> {
+        errno!(ESPIPE, "Socket does not support write_at")
+    }
+
+    fn seek(&self, pos: SeekFrom) -> Result<off_t, Error> {
+        errno!(ESPIPE, "Socket does not support seek")
+    }
+
+    fn set_len(&self, len: u64) -> Result<(), Error> {
+        errno!(EINVAL, "Socket does not support truncate")
+    }
+
+    fn allocate(&self, offset: u64, len: u64) -> Result<(), Error> {
+        errno!(ESPIPE, "Socket does not support allocate")
+    }
+
+    fn metadata(&self) -> Result<Metadata, Error> {
+        Ok(socket_metadata())
+    }
+
+    fn get_status_flags(&self) -> Result<u32, Error> {
+        Ok(self.status_flags.get())
+    }
+
+    fn set_status_flags(&self, flags: u32) -> Result<(), Error> {
+        self.status_flags.set(flags);
+        Ok(())
+    }
+
+    fn poll(&self) -> Result<u32, Error> {
+        let inner = self.inner.lock().unwrap();
+        let events = match inner.state {
+            // As on Linux, an unconnected socket is writable but hung up
+            StreamState::Unconnected => POLLOUT | POLLWRNORM | POLLHUP,
+            StreamState::Listening => {
+                if self.backlog.has_pending() {
+                    POLLIN | POLLRDNORM
+                } else {
+                    0
+                }
+            }
+            StreamState::Connected(ref conn) => {
+                let mut events = 0;
+                if conn.reader.bytes_to_read() > 0 || conn.is_read_done() {
+                    events |= POLLIN | POLLRDNORM;
+                }
+                if conn.writer.bytes_to_write() > 0 || conn.is_write_done() {
+                    events |= POLLOUT | POLLWRNORM;
+                }
+                if conn.is_read_done() && conn.is_write_done() {
+                    events |= POLLHUP;
+                }
+                events
+            }
+        };
+        Ok(events)
+    }
+
+    fn poll_queue(&self) -> Option<&IoWaitQueue> {
+        Some(&self.wait_queue)
+    }
+
+    fn ioctl(&self, cmd: &mut IoctlCmd) -> Result<(), Error> {
+        match *cmd {
+            IoctlCmd::FionRead(ref mut len) => {
+                let inner = self.inner.lock().unwrap();
+                **len = inner.get_connection()?.reader.bytes_to_read() as i32;
+                Ok(())
+            }
+            _ => errno!(ENOTTY, "Socket does not support the ioctl command"),
+        }
+    }
+
+    fn as_any(&self) -> &Any {
+        self
+    }
+}
+
+impl Drop for UnixStream {
+    fn drop(&mut self) {
+        let is_listening = {
+            let inner = self.inner.lock().unwrap();
+            if let StreamState::Connected(ref conn) = inner.state {
+                // Closed before waking the peer, which then gets EOF or EPIPE
+                conn.reader.close();
+                conn.writer.close();
+                conn.peer_wait_queue.wake_all();
+            }
+            inner.is_listening()
+        };
+        if is_listening {
+            // Closing the pending connections wakes the connected sockets
+            drop(self.backlog.close());
+            // So that the connecting threads get ECONNREFUSED
+            self.wait_queue.wake_all();
+        }
+    }
+}
+
+unsafe impl Send for UnixStream {}
+unsafe impl Sync for UnixStream {}
@@ -11,6 +11,7 @@ pub use std::sync::{
     Arc, SgxMutex, SgxMutexGuard, SgxRwLock, SgxRwLockReadGuard, SgxRwLockWriteGuard,
 };
 //pub use std::borrow::BorrowMut;
+pub use std::any::Any;
 pub use std::borrow::ToOwned;
 pub use std::boxed::Box;
 pub use std::cmp::{Ordering, PartialOrd};
@@ -54,3 +55,26 @@ pub fn align_up(addr: usize, align: usize) -> usize {
 pub fn align_down(addr: usize, align: usize) -> usize {
     addr & !(align - 1)
 }
+
+/// Check the return value of an OCall that returns the negated errno of the
+/// host on failure
+pub fn check_host_retval(sgx_status: sgx_status_t, ret: i64) -> Result<usize, Error> {
+    if sgx_status != sgx_status_t::SGX_SUCCESS {
+        return errno!(EIO, "OCall failed");
+    }
+    if ret < 0 {
+        let errno = Errno::from_errno(-ret as i32);
+        return Err(Error::new(errno, "Host operation failed"));
+    }
+    Ok(ret as usize)
+}
+
+/// Check the return value of an OCall that returns the length of the data in
+/// a buffer of `max_len` bytes, which an untrusted host may exceed
+pub fn check_host_len(sgx_status: sgx_status_t, ret: i64, max_len: usize) -> Result<usize, Error> {
+    let len = check_host_retval(sgx_status, ret)?;
+    if len > max_len {
+        return errno!(EIO, "Host returned a length beyond the buffer");
+    }
+    Ok(len)
+}
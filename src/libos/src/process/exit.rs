@@ -17,6 +17,15 @@ pub fn do_exit(exit_status: i32) {
     // Update current
     current.exit_status = exit_status;
     current.status = Status::ZOMBIE;
+    // Write back the shared file mappings, which are not unmapped until the
+    // zombie is reaped
+    current.get_vm_mut().msync_all().ok();
+    // Close all files, so that peers of pipes, etc. see them closed at exit
+    // instead of when the zombie is reaped. The files are closed before any
+    // other process is locked, so that the peers blocked on them are woken
+    // up first.
+    let files = std::mem::replace(current.get_files_mut(), FileTable::new());
+    drop(files);
 
     // Update children
     for child_weak in &current.children {
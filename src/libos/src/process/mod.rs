@@ -19,6 +19,7 @@ pub struct Process {
     tgid: pid_t,
     exit_status: i32,
     exec_path: String,
+    cwd: String,
     parent: Option<ProcessRef>,
     children: Vec<ProcessWeakRef>,
     waiting_children: Option<WaitQueue<ChildProcessFilter, pid_t>>,
@@ -14,6 +14,8 @@ lazy_static! {
             tgid: 0,
             exit_status: 0,
             exec_path: "".to_owned(),
+            // The first process starts in the working directory of the host
+            cwd: fs::get_host_cwd().to_owned(),
             parent: None,
             children: Vec::new(),
             waiting_children: Default::default(),
@@ -26,6 +28,7 @@ lazy_static! {
 impl Process {
     pub fn new(
         exec_path: &str,
+        cwd: &str,
         task: Task,
         vm: ProcessVM,
         file_table: FileTable,
@@ -38,6 +41,7 @@ impl Process {
             pgid: new_pid,
             tgid: new_pid,
             exec_path: exec_path.to_owned(),
+            cwd: cwd.to_owned(),
             exit_status: 0,
             parent: None,
             children: Vec::new(),
@@ -72,6 +76,12 @@ impl Process {
     pub fn get_exec_path(&self) -> &str {
         &self.exec_path
     }
+    pub fn get_cwd(&self) -> &str {
+        &self.cwd
+    }
+    pub fn set_cwd(&mut self, cwd: String) {
+        self.cwd = cwd;
+    }
     pub fn get_vm(&self) -> &ProcessVM {
         &self.vm
     }
@@ -1,10 +1,9 @@
 use self::init_stack::{AuxKey, AuxTable};
 use super::task::Task;
 use super::*;
-use fs::{File, FileDesc, FileTable, StdinFile, StdoutFile /*, StderrFile*/};
+use fs::{File, FileDesc, FileRef, FileTable, StderrFile, StdinFile, StdoutFile, O_RDONLY};
 use std::ffi::{CStr, CString};
 use std::path::Path;
-use std::sgxfs::SgxFile;
 use vm::{ProcessVM, VMRangeTrait};
 use xmas_elf::symbol_table::Entry;
 use xmas_elf::{header, program, sections, ElfFile};
@@ -29,14 +28,17 @@ pub fn do_spawn<P: AsRef<Path>>(
     file_actions: &[FileAction],
     parent_ref: &ProcessRef,
 ) -> Result<u32, Error> {
+    // The child inherits the working directory of the parent, against which
+    // a relative path of the executable is resolved
+    let cwd = parent_ref.lock().unwrap().get_cwd().to_owned();
     let mut elf_buf = {
-        let key: sgx_key_128bit_t = [0 as uint8_t; 16];
-        let mut sgx_file = SgxFile::open_ex(elf_path, &key)
-            .map_err(|e| (Errno::ENOENT, "Failed to open the SGX-protected file"))?;
-
-        let mut elf_buf = Vec::<u8>::new();
-        sgx_file.read_to_end(&mut elf_buf);
-        elf_buf
+        let path = elf_path
+            .as_ref()
+            .to_str()
+            .ok_or_else(|| Error::new(Errno::EINVAL, "Invalid path"))?;
+        let abs_path = fs::to_abs_path(&cwd, path)?;
+        let file = fs::open_file(&abs_path, O_RDONLY, 0)?;
+        read_to_end(&file)?
     };
 
     let elf_file = {
@@ -67,7 +69,7 @@ pub fn do_spawn<P: AsRef<Path>>(
         };
         let files = init_files(parent_ref, file_actions)?;
         let exec_path = elf_path.as_ref().to_str().unwrap();
-        Process::new(exec_path, task, vm, files)?
+        Process::new(exec_path, &cwd, task, vm, files)?
     };
     parent_adopts_new_child(&parent_ref, &new_process_ref);
     process_table::put(new_pid, new_process_ref.clone());
@@ -75,6 +77,23 @@ pub fn do_spawn<P: AsRef<Path>>(
     Ok(new_pid)
 }
 
+fn read_to_end(file: &FileRef) -> Result<Vec<u8>, Error> {
+    let mut buf = Vec::<u8>::new();
+    let mut read_len = 0;
+    loop {
+        if buf.len() == read_len {
+            buf.resize(read_len + 4096, 0);
+        }
+        let this_len = file.read(&mut buf[read_len..])?;
+        if this_len == 0 {
+            break;
+        }
+        read_len += this_len;
+    }
+    buf.truncate(read_len);
+    Ok(buf)
+}
+
 fn init_files(parent_ref: &ProcessRef, file_actions: &[FileAction]) -> Result<FileTable, Error> {
     // Usually, we just inherit the file table from the parent
     let parent = parent_ref.lock().unwrap();
@@ -103,8 +122,7 @@ fn init_files(parent_ref: &ProcessRef, file_actions: &[FileAction]) -> Result<Fi
     let mut file_table = FileTable::new();
     let stdin: Arc<Box<File>> = Arc::new(Box::new(StdinFile::new()));
     let stdout: Arc<Box<File>> = Arc::new(Box::new(StdoutFile::new()));
-    // TODO: implement and use a real stderr
-    let stderr = stdout.clone();
+    let stderr: Arc<Box<File>> = Arc::new(Box::new(StderrFile::new()));
     file_table.put(stdin, false);
     file_table.put(stdout, false);
     file_table.put(stderr, false);
@@ -57,6 +57,36 @@ where
 
         waiter.inner.lock().unwrap().result.unwrap()
     }
+
+    /// Sleep until woken or the timeout (in microseconds) elapses, in which
+    /// case None is returned. Without a timeout, it sleeps until woken. The
+    /// waiter is borrowed, so that it can be deleted afterwards from the wait
+    /// queues that have not woken it.
+    pub fn sleep_until_woken_with_timeout(
+        waiter: &Waiter<D, R>,
+        timeout_us: Option<u64>,
+    ) -> Option<R> {
+        let deadline_us =
+            timeout_us.map(|timeout_us| time::do_gettimeofday().as_usec() + timeout_us as i64);
+        loop {
+            {
+                let waiter_inner = waiter.inner.lock().unwrap();
+                if waiter_inner.is_woken {
+                    return waiter_inner.result;
+                }
+            }
+            match deadline_us {
+                Some(deadline_us) => {
+                    let remaining_us = deadline_us - time::do_gettimeofday().as_usec();
+                    if remaining_us <= 0 {
+                        return None;
+                    }
+                    wait_event_timeout(waiter.thread, remaining_us);
+                }
+                None => wait_event(waiter.thread),
+            }
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -86,6 +116,11 @@ where
         });
     }
 
+    pub fn del_waiter(&mut self, waiter: &Waiter<D, R>) {
+        self.waiters
+            .retain(|queued_waiter| !Arc::ptr_eq(&queued_waiter.inner, &waiter.inner));
+    }
+
     pub fn del_and_wake_one_waiter<F>(&mut self, cond: F) -> usize
     where
         F: Fn(&D) -> Option<R>,
@@ -111,26 +146,61 @@ where
         set_event(del_waiter.thread);
         1
     }
+
+    pub fn del_and_wake_all_waiters<F>(&mut self, cond: F) -> usize
+    where
+        F: Fn(&D) -> Option<R>,
+    {
+        let mut woken_waiters = Vec::new();
+        let mut waiter_i = 0;
+        while waiter_i < self.waiters.len() {
+            let is_woken = {
+                let mut waiter_inner = self.waiters[waiter_i].inner.lock().unwrap();
+                if let Some(waiter_result) = cond(&waiter_inner.data) {
+                    waiter_inner.is_woken = true;
+                    waiter_inner.result = Some(waiter_result);
+                    true
+                } else {
+                    false
+                }
+            };
+            if is_woken {
+                woken_waiters.push(self.waiters.swap_remove(waiter_i));
+            } else {
+                waiter_i += 1;
+            }
+        }
+        for waiter in &woken_waiters {
+            set_event(waiter.thread);
+        }
+        woken_waiters.len()
+    }
 }
 
+// The threads sleep on the events provided by the PAL instead of the untrusted
+// events of the SGX SDK, which cannot be waited with a timeout
+
 fn wait_event(thread: *const c_void) {
     let mut ret: c_int = 0;
-    let mut sgx_ret: c_int = 0;
-    unsafe {
-        sgx_ret = sgx_thread_wait_untrusted_event_ocall(&mut ret as *mut c_int, thread);
+    let sgx_status = unsafe { ocall_wait_thread_event(&mut ret, thread, -1) };
+    if sgx_status != sgx_status_t::SGX_SUCCESS || ret != 0 {
+        panic!("ERROR: OCall failed!");
     }
-    if ret != 0 || sgx_ret != 0 {
+}
+
+fn wait_event_timeout(thread: *const c_void, timeout_us: i64) {
+    let mut ret: c_int = 0;
+    let sgx_status = unsafe { ocall_wait_thread_event(&mut ret, thread, timeout_us) };
+    if sgx_status != sgx_status_t::SGX_SUCCESS || (ret != 0 && ret != Errno::ETIMEDOUT.as_retval())
+    {
         panic!("ERROR: OCall failed!");
     }
 }
 
 fn set_event(thread: *const c_void) {
     let mut ret: c_int = 0;
-    let mut sgx_ret: c_int = 0;
-    unsafe {
-        sgx_ret = sgx_thread_set_untrusted_event_ocall(&mut ret as *mut c_int, thread);
-    }
-    if ret != 0 || sgx_ret != 0 {
+    let sgx_status = unsafe { ocall_set_thread_event(&mut ret, thread) };
+    if sgx_status != sgx_status_t::SGX_SUCCESS || ret != 0 {
         panic!("ERROR: OCall failed!");
     }
 }
@@ -138,24 +208,13 @@ fn set_event(thread: *const c_void) {
 extern "C" {
     fn sgx_thread_get_self() -> *const c_void;
 
-    /* Go outside and wait on my untrusted event */
-    fn sgx_thread_wait_untrusted_event_ocall(ret: *mut c_int, self_thread: *const c_void) -> c_int;
-
-    /* Wake a thread waiting on its untrusted event */
-    fn sgx_thread_set_untrusted_event_ocall(ret: *mut c_int, waiter_thread: *const c_void)
-        -> c_int;
-
-    /* Wake a thread waiting on its untrusted event, and wait on my untrusted event */
-    fn sgx_thread_setwait_untrusted_events_ocall(
+    /* Go outside and wait on my event, or until the timeout elapses */
+    fn ocall_wait_thread_event(
         ret: *mut c_int,
-        waiter_thread: *const c_void,
         self_thread: *const c_void,
-    ) -> c_int;
+        timeout_us: int64_t,
+    ) -> sgx_status_t;
 
-    /* Wake multiple threads waiting on their untrusted events */
-    fn sgx_thread_set_multiple_untrusted_events_ocall(
-        ret: *mut c_int,
-        waiter_threads: *const *const c_void,
-        total: size_t,
-    ) -> c_int;
+    /* Wake a thread waiting on its event */
+    fn ocall_set_thread_event(ret: *mut c_int, thread: *const c_void) -> sgx_status_t;
 }
@@ -1,13 +1,16 @@
 use super::*;
-use fs::{off_t, FileDesc};
+use fs::{
+    off_t, termios_t, winsize_t, EpollCtlCmd, EpollEvent, FcntlCmd, FdSet, FileDesc, IoctlCmd,
+    PollFd, Stat,
+};
 use prelude::*;
 use process::{pid_t, ChildProcessFilter, FileAction};
 use std::ffi::{CStr, CString};
 use std::ptr;
-use time::timeval_t;
+use time::{timespec_t, timeval_t};
 use util::mem_util::from_user::*;
 use vm::{VMAreaFlags, VMResizeOptions};
-use {fs, process, std, vm};
+use {fs, net, process, std, vm};
 // Use the internal syscall wrappers from sgx_tstd
 //use std::libc_fs as fs;
 //use std::libc_io as io;
@@ -18,6 +21,21 @@ pub struct iovec_t {
     len: size_t,
 }
 
+/// The struct msghdr of sendmsg and recvmsg, laid out as in musl
+#[allow(non_camel_case_types)]
+#[repr(C)]
+pub struct msghdr_t {
+    name: *mut c_void,
+    name_len: c_uint,
+    iov: *mut iovec_t,
+    iov_len: c_int,
+    __pad1: c_int,
+    control: *mut c_void,
+    control_len: c_uint,
+    __pad2: c_uint,
+    flags: c_int,
+}
+
 /*
  * This Rust-version of fdop correspond to the C-version one in Occlum.
  * See <path_to_musl_libc>/src/process/fdop.h.
@@ -110,33 +128,60 @@ fn do_write(fd: c_int, buf: *const c_void, size: size_t) -> Result<size_t, Error
 
 fn do_writev(fd: c_int, iov: *const iovec_t, count: c_int) -> Result<size_t, Error> {
     let fd = fd as FileDesc;
+    let bufs_vec = clone_iovecs(iov, count)?;
+    let bufs = &bufs_vec[..];
 
-    let count = {
-        if count < 0 {
-            return Err(Error::new(Errno::EINVAL, "Invalid count of iovec"));
-        }
-        count as usize
+    fs::do_writev(fd, bufs)
+}
+
+fn do_readv(fd: c_int, iov: *mut iovec_t, count: c_int) -> Result<size_t, Error> {
+    let fd = fd as FileDesc;
+    let mut bufs_vec = clone_iovecs_mut(iov, count)?;
+    let bufs = &mut bufs_vec[..];
+
+    fs::do_readv(fd, bufs)
+}
+
+fn do_pread(fd: c_int, buf: *mut c_void, size: size_t, offset: off_t) -> Result<size_t, Error> {
+    let fd = fd as FileDesc;
+    let safe_buf = {
+        check_mut_array(buf as *mut u8, size)?;
+        unsafe { std::slice::from_raw_parts_mut(buf as *mut u8, size) }
     };
+    fs::do_pread(fd, safe_buf, offset)
+}
 
-    check_array(iov, count);
-    let bufs_vec = {
-        let mut bufs_vec = Vec::with_capacity(count);
-        for iov_i in 0..count {
-            let iov_ptr = unsafe { iov.offset(iov_i as isize) };
-            let iov = unsafe { &*iov_ptr };
-            let buf = unsafe { std::slice::from_raw_parts(iov.base as *const u8, iov.len) };
-            bufs_vec.push(buf);
-        }
-        bufs_vec
+fn do_pwrite(fd: c_int, buf: *const c_void, size: size_t, offset: off_t) -> Result<size_t, Error> {
+    let fd = fd as FileDesc;
+    let safe_buf = {
+        check_array(buf as *const u8, size)?;
+        unsafe { std::slice::from_raw_parts(buf as *const u8, size) }
     };
-    let bufs = &bufs_vec[..];
+    fs::do_pwrite(fd, safe_buf, offset)
+}
 
-    fs::do_writev(fd, bufs)
+fn do_preadv(fd: c_int, iov: *mut iovec_t, count: c_int, offset: off_t) -> Result<size_t, Error> {
+    let fd = fd as FileDesc;
+    let mut bufs_vec = clone_iovecs_mut(iov, count)?;
+    let bufs = &mut bufs_vec[..];
+
+    fs::do_preadv(fd, bufs, offset)
 }
 
-fn do_readv(fd: c_int, iov: *mut iovec_t, count: c_int) -> Result<size_t, Error> {
+fn do_pwritev(
+    fd: c_int,
+    iov: *const iovec_t,
+    count: c_int,
+    offset: off_t,
+) -> Result<size_t, Error> {
     let fd = fd as FileDesc;
+    let bufs_vec = clone_iovecs(iov, count)?;
+    let bufs = &bufs_vec[..];
+
+    fs::do_pwritev(fd, bufs, offset)
+}
 
+fn clone_iovecs<'a>(iov: *const iovec_t, count: c_int) -> Result<Vec<&'a [u8]>, Error> {
     let count = {
         if count < 0 {
             return Err(Error::new(Errno::EINVAL, "Invalid count of iovec"));
@@ -144,20 +189,34 @@ fn do_readv(fd: c_int, iov: *mut iovec_t, count: c_int) -> Result<size_t, Error>
         count as usize
     };
 
-    check_array(iov, count);
-    let mut bufs_vec = {
-        let mut bufs_vec = Vec::with_capacity(count);
-        for iov_i in 0..count {
-            let iov_ptr = unsafe { iov.offset(iov_i as isize) };
-            let iov = unsafe { &*iov_ptr };
-            let buf = unsafe { std::slice::from_raw_parts_mut(iov.base as *mut u8, iov.len) };
-            bufs_vec.push(buf);
+    check_array(iov, count)?;
+    let mut bufs_vec = Vec::with_capacity(count);
+    for iov_i in 0..count {
+        let iov_ptr = unsafe { iov.offset(iov_i as isize) };
+        let iov = unsafe { &*iov_ptr };
+        let buf = unsafe { std::slice::from_raw_parts(iov.base as *const u8, iov.len) };
+        bufs_vec.push(buf);
+    }
+    Ok(bufs_vec)
+}
+
+fn clone_iovecs_mut<'a>(iov: *mut iovec_t, count: c_int) -> Result<Vec<&'a mut [u8]>, Error> {
+    let count = {
+        if count < 0 {
+            return Err(Error::new(Errno::EINVAL, "Invalid count of iovec"));
         }
-        bufs_vec
+        count as usize
     };
-    let bufs = &mut bufs_vec[..];
 
-    fs::do_readv(fd, bufs)
+    check_array(iov, count)?;
+    let mut bufs_vec = Vec::with_capacity(count);
+    for iov_i in 0..count {
+        let iov_ptr = unsafe { iov.offset(iov_i as isize) };
+        let iov = unsafe { &*iov_ptr };
+        let buf = unsafe { std::slice::from_raw_parts_mut(iov.base as *mut u8, iov.len) };
+        bufs_vec.push(buf);
+    }
+    Ok(bufs_vec)
 }
 
 pub fn do_lseek(fd: c_int, offset: off_t, whence: c_int) -> Result<off_t, Error> {
@@ -197,8 +256,10 @@ fn do_mmap(
 ) -> Result<*const c_void, Error> {
     let addr = addr as usize;
     let size = size as usize;
-    let flags = VMAreaFlags(prot as u32);
-    vm::do_mmap(addr, size, flags).map(|ret_addr| ret_addr as *const c_void)
+    let prot = VMAreaFlags(prot as u32);
+    let flags = flags as u32;
+    vm::do_mmap(addr, size, prot, flags, fd as FileDesc, offset)
+        .map(|ret_addr| ret_addr as *const c_void)
 }
 
 fn do_munmap(addr: *const c_void, size: size_t) -> Result<(), Error> {
@@ -207,6 +268,12 @@ fn do_munmap(addr: *const c_void, size: size_t) -> Result<(), Error> {
     vm::do_munmap(addr, size)
 }
 
+fn do_msync(addr: *const c_void, size: size_t, flags: c_int) -> Result<(), Error> {
+    let addr = addr as usize;
+    let size = size as usize;
+    vm::do_msync(addr, size, flags as u32)
+}
+
 fn do_mremap(
     old_addr: *const c_void,
     old_size: size_t,
@@ -259,7 +326,6 @@ fn do_wait4(pid: c_int, _exit_status: *mut c_int) -> Result<pid_t, Error> {
 
 fn do_pipe2(fds_u: *mut c_int, flags: c_int) -> Result<(), Error> {
     check_mut_array(fds_u, 2)?;
-    // TODO: how to deal with open flags???
     let fds = fs::do_pipe2(flags as u32)?;
     unsafe {
         *fds_u.offset(0) = fds[0] as c_int;
@@ -268,6 +334,583 @@ fn do_pipe2(fds_u: *mut c_int, flags: c_int) -> Result<(), Error> {
     Ok(())
 }
 
+fn do_mkdir(path: *const c_char, mode: c_int) -> Result<(), Error> {
+    let path = clone_cstring_safely(path)?.to_string_lossy().into_owned();
+    fs::do_mkdir(&path, mode as u32)
+}
+
+fn do_rmdir(path: *const c_char) -> Result<(), Error> {
+    let path = clone_cstring_safely(path)?.to_string_lossy().into_owned();
+    fs::do_rmdir(&path)
+}
+
+fn do_getdents64(fd: c_int, buf: *mut u8, buf_size: c_uint) -> Result<usize, Error> {
+    let fd = fd as FileDesc;
+    let safe_buf = {
+        let buf_size = buf_size as usize;
+        check_mut_array(buf, buf_size)?;
+        unsafe { std::slice::from_raw_parts_mut(buf, buf_size) }
+    };
+    fs::do_getdents64(fd, safe_buf)
+}
+
+fn do_chdir(path: *const c_char) -> Result<(), Error> {
+    let path = clone_cstring_safely(path)?.to_string_lossy().into_owned();
+    fs::do_chdir(&path)
+}
+
+fn do_getcwd(buf: *mut c_char, buf_size: size_t) -> Result<usize, Error> {
+    check_mut_array(buf, buf_size)?;
+    let cwd = fs::do_getcwd();
+    // The length of the cwd, including the terminating null byte
+    let cwd_len = cwd.len() + 1;
+    if cwd_len > buf_size {
+        return errno!(ERANGE, "The buffer is too small for the cwd");
+    }
+    let safe_buf = unsafe { std::slice::from_raw_parts_mut(buf as *mut u8, cwd_len) };
+    safe_buf[..cwd.len()].copy_from_slice(cwd.as_bytes());
+    safe_buf[cwd.len()] = 0;
+    Ok(cwd_len)
+}
+
+fn do_stat(path: *const c_char, stat_buf: *mut Stat) -> Result<(), Error> {
+    let path = clone_cstring_safely(path)?.to_string_lossy().into_owned();
+    check_mut_ptr(stat_buf)?;
+    let stat = fs::do_stat(&path)?;
+    unsafe {
+        stat_buf.write(stat);
+    }
+    Ok(())
+}
+
+fn do_fstat(fd: c_int, stat_buf: *mut Stat) -> Result<(), Error> {
+    check_mut_ptr(stat_buf)?;
+    let stat = fs::do_fstat(fd as FileDesc)?;
+    unsafe {
+        stat_buf.write(stat);
+    }
+    Ok(())
+}
+
+fn do_lstat(path: *const c_char, stat_buf: *mut Stat) -> Result<(), Error> {
+    let path = clone_cstring_safely(path)?.to_string_lossy().into_owned();
+    check_mut_ptr(stat_buf)?;
+    let stat = fs::do_lstat(&path)?;
+    unsafe {
+        stat_buf.write(stat);
+    }
+    Ok(())
+}
+
+fn do_fstatat(
+    dirfd: c_int,
+    path: *const c_char,
+    stat_buf: *mut Stat,
+    flags: c_int,
+) -> Result<(), Error> {
+    let path = clone_cstring_safely(path)?.to_string_lossy().into_owned();
+    check_mut_ptr(stat_buf)?;
+    let stat = fs::do_fstatat(dirfd, &path, flags as u32)?;
+    unsafe {
+        stat_buf.write(stat);
+    }
+    Ok(())
+}
+
+fn do_fcntl(fd: c_int, cmd: c_int, arg: u64) -> Result<isize, Error> {
+    let cmd = FcntlCmd::from_raw(cmd as u32, arg)?;
+    fs::do_fcntl(fd as FileDesc, &cmd)
+}
+
+fn do_ioctl(fd: c_int, cmd: c_uint, argp: *mut c_void) -> Result<(), Error> {
+    let mut cmd = match cmd {
+        fs::TCGETS => IoctlCmd::TcGets(clone_ioctl_arg_mut(argp as *mut termios_t)?),
+        fs::TIOCGWINSZ => IoctlCmd::TiocGWinSz(clone_ioctl_arg_mut(argp as *mut winsize_t)?),
+        fs::FIONREAD => IoctlCmd::FionRead(clone_ioctl_arg_mut(argp as *mut i32)?),
+        fs::FIONBIO => IoctlCmd::FionBio(*clone_ioctl_arg_mut(argp as *mut i32)?),
+        _ => return errno!(ENOTTY, "Unsupported ioctl command"),
+    };
+    fs::do_ioctl(fd as FileDesc, &mut cmd)
+}
+
+fn clone_ioctl_arg_mut<'a, T>(argp: *mut T) -> Result<&'a mut T, Error> {
+    if argp.is_null() {
+        return errno!(EFAULT, "The argument of ioctl is null");
+    }
+    check_mut_ptr(argp)?;
+    Ok(unsafe { &mut *argp })
+}
+
+fn do_sendfile(
+    out_fd: c_int,
+    in_fd: c_int,
+    offset: *mut off_t,
+    count: size_t,
+) -> Result<size_t, Error> {
+    let offset = clone_offset_mut(offset)?;
+    fs::do_sendfile(out_fd as FileDesc, in_fd as FileDesc, offset, count)
+}
+
+fn do_splice(
+    fd_in: c_int,
+    off_in: *mut off_t,
+    fd_out: c_int,
+    off_out: *mut off_t,
+    len: size_t,
+    flags: c_uint,
+) -> Result<size_t, Error> {
+    let off_in = clone_offset_mut(off_in)?;
+    let off_out = clone_offset_mut(off_out)?;
+    fs::do_splice(
+        fd_in as FileDesc,
+        off_in,
+        fd_out as FileDesc,
+        off_out,
+        len,
+        flags,
+    )
+}
+
+fn do_copy_file_range(
+    fd_in: c_int,
+    off_in: *mut off_t,
+    fd_out: c_int,
+    off_out: *mut off_t,
+    len: size_t,
+    flags: c_uint,
+) -> Result<size_t, Error> {
+    let off_in = clone_offset_mut(off_in)?;
+    let off_out = clone_offset_mut(off_out)?;
+    fs::do_copy_file_range(
+        fd_in as FileDesc,
+        off_in,
+        fd_out as FileDesc,
+        off_out,
+        len,
+        flags,
+    )
+}
+
+/// The offset is optional, which is used and updated in place of the file
+/// position if given
+fn clone_offset_mut<'a>(offset: *mut off_t) -> Result<Option<&'a mut off_t>, Error> {
+    if offset.is_null() {
+        return Ok(None);
+    }
+    check_mut_ptr(offset)?;
+    let offset = unsafe { &mut *offset };
+    if *offset < 0 {
+        return errno!(EINVAL, "Invalid offset");
+    }
+    Ok(Some(offset))
+}
+
+fn do_poll(fds: *mut PollFd, nfds: u64, timeout_ms: c_int) -> Result<usize, Error> {
+    let timeout_us = if timeout_ms < 0 {
+        None
+    } else {
+        Some(timeout_ms as u64 * 1000)
+    };
+    do_poll_with_timeout(fds, nfds, timeout_us)
+}
+
+fn do_ppoll(
+    fds: *mut PollFd,
+    nfds: u64,
+    timeout: *const timespec_t,
+    sigmask: *const c_void,
+) -> Result<usize, Error> {
+    // TODO: handle sigmask after signals are supported
+    let timeout_us = if timeout.is_null() {
+        None
+    } else {
+        check_ptr(timeout)?;
+        let timeout = unsafe { &*timeout };
+        if !timeout.is_valid() {
+            return errno!(EINVAL, "Invalid timeout");
+        }
+        Some(timeout.as_usec() as u64)
+    };
+    do_poll_with_timeout(fds, nfds, timeout_us)
+}
+
+fn do_poll_with_timeout(
+    fds: *mut PollFd,
+    nfds: u64,
+    timeout_us: Option<u64>,
+) -> Result<usize, Error> {
+    let nfds = nfds as usize;
+    let safe_fds = if nfds > 0 {
+        check_mut_array(fds, nfds)?;
+        unsafe { std::slice::from_raw_parts_mut(fds, nfds) }
+    } else {
+        &mut []
+    };
+    fs::do_poll(safe_fds, timeout_us)
+}
+
+fn do_select(
+    nfds: c_int,
+    readfds: *mut FdSet,
+    writefds: *mut FdSet,
+    exceptfds: *mut FdSet,
+    timeout: *const timeval_t,
+) -> Result<usize, Error> {
+    if nfds < 0 {
+        return errno!(EINVAL, "nfds is negative");
+    }
+    let clone_fds = |fds: *mut FdSet| -> Result<Option<&mut FdSet>, Error> {
+        if fds.is_null() {
+            return Ok(None);
+        }
+        check_mut_ptr(fds)?;
+        Ok(Some(unsafe { &mut *fds }))
+    };
+    let readfds = clone_fds(readfds)?;
+    let writefds = clone_fds(writefds)?;
+    let exceptfds = clone_fds(exceptfds)?;
+    let timeout_us = if timeout.is_null() {
+        None
+    } else {
+        check_ptr(timeout)?;
+        let timeout = unsafe { &*timeout };
+        if !timeout.is_valid() {
+            return errno!(EINVAL, "Invalid timeout");
+        }
+        Some(timeout.as_usec() as u64)
+    };
+    fs::do_select(nfds as usize, readfds, writefds, exceptfds, timeout_us)
+}
+
+fn do_eventfd(init_val: c_uint) -> Result<FileDesc, Error> {
+    fs::do_eventfd2(init_val, 0)
+}
+
+fn do_eventfd2(init_val: c_uint, flags: c_int) -> Result<FileDesc, Error> {
+    fs::do_eventfd2(init_val, flags as u32)
+}
+
+fn do_epoll_create(size: c_int) -> Result<FileDesc, Error> {
+    if size <= 0 {
+        return errno!(EINVAL, "size must be greater than zero");
+    }
+    fs::do_epoll_create1(0)
+}
+
+fn do_epoll_create1(flags: c_int) -> Result<FileDesc, Error> {
+    fs::do_epoll_create1(flags as u32)
+}
+
+fn do_epoll_ctl(epfd: c_int, op: c_int, fd: c_int, event: *const EpollEvent) -> Result<(), Error> {
+    let event = if event.is_null() {
+        None
+    } else {
+        check_ptr(event)?;
+        Some(unsafe { *event })
+    };
+    let cmd = EpollCtlCmd::from_raw(op, fd as FileDesc, event)?;
+    fs::do_epoll_ctl(epfd as FileDesc, &cmd)
+}
+
+fn do_epoll_pwait(
+    epfd: c_int,
+    events: *mut EpollEvent,
+    maxevents: c_int,
+    timeout_ms: c_int,
+    sigmask: *const c_void,
+) -> Result<usize, Error> {
+    // TODO: handle sigmask after signals are supported
+    if maxevents <= 0 {
+        return errno!(EINVAL, "maxevents must be greater than zero");
+    }
+    let maxevents = maxevents as usize;
+    check_mut_array(events, maxevents)?;
+    let safe_events = unsafe { std::slice::from_raw_parts_mut(events, maxevents) };
+    let timeout_us = if timeout_ms < 0 {
+        None
+    } else {
+        Some(timeout_ms as u64 * 1000)
+    };
+    fs::do_epoll_wait(epfd as FileDesc, safe_events, timeout_us)
+}
+
+fn do_socket(domain: c_int, type_: c_int, protocol: c_int) -> Result<FileDesc, Error> {
+    net::do_socket(domain as u32, type_ as u32, protocol as u32)
+}
+
+fn do_socketpair(
+    domain: c_int,
+    type_: c_int,
+    protocol: c_int,
+    sv: *mut c_int,
+) -> Result<(), Error> {
+    check_mut_array(sv, 2)?;
+    let fds = net::do_socketpair(domain as u32, type_ as u32, protocol as u32)?;
+    unsafe {
+        *sv.offset(0) = fds[0] as c_int;
+        *sv.offset(1) = fds[1] as c_int;
+    }
+    Ok(())
+}
+
+/// Clone the raw bytes of a struct sockaddr from the user process
+fn clone_sockaddr_safely(addr: *const c_void, addr_len: c_uint) -> Result<Vec<u8>, Error> {
+    if addr.is_null() {
+        return errno!(EFAULT, "The address is null");
+    }
+    let addr = addr as *const u8;
+    let addr_len = addr_len as usize;
+    check_array(addr, addr_len)?;
+    Ok(unsafe { std::slice::from_raw_parts(addr, addr_len) }.to_vec())
+}
+
+/// Copy the raw bytes of a struct sockaddr to the user process, which is
+/// truncated if the buffer is too small, and set the length to the actual
+/// one. Nothing is copied if the address buffer is null.
+fn copy_sockaddr_to_user(
+    raw_addr: &[u8],
+    addr: *mut c_void,
+    addr_len: *mut c_uint,
+) -> Result<(), Error> {
+    if addr.is_null() {
+        return Ok(());
+    }
+    check_mut_ptr(addr_len)?;
+    let buf_len = unsafe { *addr_len } as usize;
+    let copy_len = buf_len.min(raw_addr.len());
+    let addr = addr as *mut u8;
+    check_mut_array(addr, copy_len)?;
+    let safe_addr = unsafe { std::slice::from_raw_parts_mut(addr, copy_len) };
+    safe_addr.copy_from_slice(&raw_addr[..copy_len]);
+    unsafe {
+        *addr_len = raw_addr.len() as c_uint;
+    }
+    Ok(())
+}
+
+fn do_bind(fd: c_int, addr: *const c_void, addr_len: c_uint) -> Result<(), Error> {
+    let addr = clone_sockaddr_safely(addr, addr_len)?;
+    net::do_bind(fd as FileDesc, &addr)
+}
+
+fn do_listen(fd: c_int, backlog: c_int) -> Result<(), Error> {
+    net::do_listen(fd as FileDesc, backlog)
+}
+
+fn do_accept4(
+    fd: c_int,
+    addr: *mut c_void,
+    addr_len: *mut c_uint,
+    flags: c_int,
+) -> Result<FileDesc, Error> {
+    let (new_fd, peer_addr) = net::do_accept4(fd as FileDesc, flags as u32)?;
+    copy_sockaddr_to_user(&peer_addr, addr, addr_len)?;
+    Ok(new_fd)
+}
+
+fn do_connect(fd: c_int, addr: *const c_void, addr_len: c_uint) -> Result<(), Error> {
+    let addr = clone_sockaddr_safely(addr, addr_len)?;
+    net::do_connect(fd as FileDesc, &addr)
+}
+
+fn do_getsockname(fd: c_int, addr: *mut c_void, addr_len: *mut c_uint) -> Result<(), Error> {
+    let sock_addr = net::do_getsockname(fd as FileDesc)?;
+    copy_sockaddr_to_user(&sock_addr, addr, addr_len)
+}
+
+fn do_getpeername(fd: c_int, addr: *mut c_void, addr_len: *mut c_uint) -> Result<(), Error> {
+    let peer_addr = net::do_getpeername(fd as FileDesc)?;
+    copy_sockaddr_to_user(&peer_addr, addr, addr_len)
+}
+
+fn do_sendto(
+    fd: c_int,
+    buf: *const c_void,
+    len: size_t,
+    flags: c_int,
+    addr: *const c_void,
+    addr_len: c_uint,
+) -> Result<usize, Error> {
+    let safe_buf = {
+        let buf = buf as *const u8;
+        check_array(buf, len)?;
+        unsafe { std::slice::from_raw_parts(buf, len) }
+    };
+    let addr = if addr.is_null() {
+        None
+    } else {
+        Some(clone_sockaddr_safely(addr, addr_len)?)
+    };
+    net::do_sendto(
+        fd as FileDesc,
+        safe_buf,
+        flags as u32,
+        addr.as_ref().map(|addr| addr.as_slice()),
+    )
+}
+
+fn do_recvfrom(
+    fd: c_int,
+    buf: *mut c_void,
+    len: size_t,
+    flags: c_int,
+    addr: *mut c_void,
+    addr_len: *mut c_uint,
+) -> Result<usize, Error> {
+    let safe_buf = {
+        let buf = buf as *mut u8;
+        check_mut_array(buf, len)?;
+        unsafe { std::slice::from_raw_parts_mut(buf, len) }
+    };
+    let (recv_len, src_addr) = net::do_recvfrom(fd as FileDesc, safe_buf, flags as u32)?;
+    copy_sockaddr_to_user(&src_addr, addr, addr_len)?;
+    Ok(recv_len)
+}
+
+fn do_sendmsg(fd: c_int, msg: *const msghdr_t, flags: c_int) -> Result<usize, Error> {
+    check_ptr(msg)?;
+    let msg = unsafe { &*msg };
+    if msg.control_len != 0 {
+        return errno!(EOPNOTSUPP, "Control messages are not supported");
+    }
+    let bufs_vec = clone_iovecs(msg.iov, msg.iov_len)?;
+    let addr = if msg.name.is_null() {
+        None
+    } else {
+        Some(clone_sockaddr_safely(msg.name, msg.name_len)?)
+    };
+    net::do_sendmsg(
+        fd as FileDesc,
+        &bufs_vec[..],
+        flags as u32,
+        addr.as_ref().map(|addr| addr.as_slice()),
+    )
+}
+
+fn do_recvmsg(fd: c_int, msg: *mut msghdr_t, flags: c_int) -> Result<usize, Error> {
+    check_mut_ptr(msg)?;
+    let msg = unsafe { &mut *msg };
+    let mut bufs_vec = clone_iovecs_mut(msg.iov, msg.iov_len)?;
+    let (recv_len, src_addr) = net::do_recvmsg(fd as FileDesc, &mut bufs_vec[..], flags as u32)?;
+    copy_sockaddr_to_user(&src_addr, msg.name, &mut msg.name_len)?;
+    // No control messages are received, and no truncation is reported
+    msg.control_len = 0;
+    msg.flags = 0;
+    Ok(recv_len)
+}
+
+fn do_getsockopt(
+    fd: c_int,
+    level: c_int,
+    name: c_int,
+    value: *mut c_void,
+    value_len: *mut c_uint,
+) -> Result<(), Error> {
+    check_mut_ptr(value_len)?;
+    let max_len = unsafe { *value_len } as usize;
+    let safe_value = {
+        let value = value as *mut u8;
+        check_mut_array(value, max_len)?;
+        unsafe { std::slice::from_raw_parts_mut(value, max_len) }
+    };
+    let opt_value = net::do_getsockopt(fd as FileDesc, level as u32, name as u32, max_len)?;
+    safe_value[..opt_value.len()].copy_from_slice(&opt_value);
+    unsafe {
+        *value_len = opt_value.len() as c_uint;
+    }
+    Ok(())
+}
+
+fn do_setsockopt(
+    fd: c_int,
+    level: c_int,
+    name: c_int,
+    value: *const c_void,
+    value_len: c_uint,
+) -> Result<(), Error> {
+    let safe_value = {
+        let value = value as *const u8;
+        let value_len = value_len as usize;
+        check_array(value, value_len)?;
+        unsafe { std::slice::from_raw_parts(value, value_len) }
+    };
+    net::do_setsockopt(fd as FileDesc, level as u32, name as u32, safe_value)
+}
+
+fn do_shutdown(fd: c_int, how: c_int) -> Result<(), Error> {
+    net::do_shutdown(fd as FileDesc, how as u32)
+}
+
+fn do_truncate(path: *const c_char, len: off_t) -> Result<(), Error> {
+    let path = clone_cstring_safely(path)?.to_string_lossy().into_owned();
+    fs::do_truncate(&path, len)
+}
+
+fn do_unlink(path: *const c_char) -> Result<(), Error> {
+    let path = clone_cstring_safely(path)?.to_string_lossy().into_owned();
+    fs::do_unlink(&path)
+}
+
+fn do_unlinkat(dirfd: c_int, path: *const c_char, flags: c_int) -> Result<(), Error> {
+    let path = clone_cstring_safely(path)?.to_string_lossy().into_owned();
+    fs::do_unlinkat(dirfd, &path, flags as u32)
+}
+
+fn do_rename(old_path: *const c_char, new_path: *const c_char) -> Result<(), Error> {
+    let old_path = clone_cstring_safely(old_path)?
+        .to_string_lossy()
+        .into_owned();
+    let new_path = clone_cstring_safely(new_path)?
+        .to_string_lossy()
+        .into_owned();
+    fs::do_rename(&old_path, &new_path)
+}
+
+fn do_renameat(
+    old_dirfd: c_int,
+    old_path: *const c_char,
+    new_dirfd: c_int,
+    new_path: *const c_char,
+) -> Result<(), Error> {
+    let old_path = clone_cstring_safely(old_path)?
+        .to_string_lossy()
+        .into_owned();
+    let new_path = clone_cstring_safely(new_path)?
+        .to_string_lossy()
+        .into_owned();
+    fs::do_renameat(old_dirfd, &old_path, new_dirfd, &new_path)
+}
+
+fn do_link(old_path: *const c_char, new_path: *const c_char) -> Result<(), Error> {
+    let old_path = clone_cstring_safely(old_path)?
+        .to_string_lossy()
+        .into_owned();
+    let new_path = clone_cstring_safely(new_path)?
+        .to_string_lossy()
+        .into_owned();
+    fs::do_link(&old_path, &new_path)
+}
+
+fn do_symlink(target: *const c_char, link_path: *const c_char) -> Result<(), Error> {
+    let target = clone_cstring_safely(target)?.to_string_lossy().into_owned();
+    let link_path = clone_cstring_safely(link_path)?
+        .to_string_lossy()
+        .into_owned();
+    fs::do_symlink(&target, &link_path)
+}
+
+fn do_readlink(path: *const c_char, buf: *mut c_char, buf_size: size_t) -> Result<usize, Error> {
+    let path = clone_cstring_safely(path)?.to_string_lossy().into_owned();
+    check_mut_array(buf, buf_size)?;
+    let target = fs::do_readlink(&path)?;
+    // The target is truncated if the buffer is too small, and not terminated
+    // by a null byte
+    let copy_len = std::cmp::min(target.len(), buf_size);
+    let safe_buf = unsafe { std::slice::from_raw_parts_mut(buf as *mut u8, copy_len) };
+    safe_buf.copy_from_slice(&target.as_bytes()[..copy_len]);
+    Ok(copy_len)
+}
+
 fn do_gettimeofday(tv_u: *mut timeval_t) -> Result<(), Error> {
     check_mut_ptr(tv_u)?;
     let tv = time::do_gettimeofday();
@@ -290,7 +933,7 @@ pub extern "C" fn occlum_mmap(
 ) -> *const c_void {
     match do_mmap(addr, length, prot, flags, fd, offset) {
         Ok(ret_addr) => ret_addr,
-        Err(e) => MAP_FAILED,
+        Err(e) => e.errno.as_retval() as isize as *const c_void,
     }
 }
 
@@ -302,6 +945,14 @@ pub extern "C" fn occlum_munmap(addr: *const c_void, length: size_t) -> c_int {
     }
 }
 
+#[no_mangle]
+pub extern "C" fn occlum_msync(addr: *const c_void, length: size_t, flags: c_int) -> c_int {
+    match do_msync(addr, length, flags) {
+        Ok(()) => 0,
+        Err(e) => e.errno.as_retval(),
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn occlum_mremap(
     old_addr: *const c_void,
@@ -391,14 +1042,66 @@ pub extern "C" fn occlum_writev(fd: c_int, iov: *const iovec_t, count: c_int) ->
 }
 
 #[no_mangle]
-pub extern "C" fn occlum_lseek(fd: c_int, offset: off_t, whence: c_int) -> off_t {
-    match do_lseek(fd, offset, whence) {
-        Ok(ret) => ret,
-        Err(e) => {
-            -1 as off_t // this special value indicates error
-        }
-    }
-}
+pub extern "C" fn occlum_pread(
+    fd: c_int,
+    buf: *mut c_void,
+    size: size_t,
+    offset: off_t,
+) -> ssize_t {
+    match do_pread(fd, buf, size, offset) {
+        Ok(read_len) => read_len as ssize_t,
+        Err(e) => e.errno.as_retval() as ssize_t,
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn occlum_pwrite(
+    fd: c_int,
+    buf: *const c_void,
+    size: size_t,
+    offset: off_t,
+) -> ssize_t {
+    match do_pwrite(fd, buf, size, offset) {
+        Ok(write_len) => write_len as ssize_t,
+        Err(e) => e.errno.as_retval() as ssize_t,
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn occlum_preadv(
+    fd: c_int,
+    iov: *mut iovec_t,
+    count: c_int,
+    offset: off_t,
+) -> ssize_t {
+    match do_preadv(fd, iov, count, offset) {
+        Ok(read_len) => read_len as ssize_t,
+        Err(e) => e.errno.as_retval() as ssize_t,
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn occlum_pwritev(
+    fd: c_int,
+    iov: *const iovec_t,
+    count: c_int,
+    offset: off_t,
+) -> ssize_t {
+    match do_pwritev(fd, iov, count, offset) {
+        Ok(write_len) => write_len as ssize_t,
+        Err(e) => e.errno.as_retval() as ssize_t,
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn occlum_lseek(fd: c_int, offset: off_t, whence: c_int) -> off_t {
+    match do_lseek(fd, offset, whence) {
+        Ok(ret) => ret,
+        Err(e) => {
+            -1 as off_t // this special value indicates error
+        }
+    }
+}
 
 #[no_mangle]
 pub extern "C" fn occlum_getpid() -> c_uint {
@@ -478,6 +1181,23 @@ pub extern "C" fn occlum_dup3(old_fd: c_int, new_fd: c_int, flags: c_int) -> c_i
     }
 }
 
+#[no_mangle]
+pub extern "C" fn occlum_fsync(fd: c_int) -> c_int {
+    match fs::do_fsync(fd as FileDesc) {
+        Ok(()) => 0,
+        Err(e) => e.errno.as_retval(),
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn occlum_fdatasync(fd: c_int) -> c_int {
+    // The data and the metadata of a protected file are written back together
+    match fs::do_fsync(fd as FileDesc) {
+        Ok(()) => 0,
+        Err(e) => e.errno.as_retval(),
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn occlum_sync() -> c_int {
     match fs::do_sync() {
@@ -486,6 +1206,497 @@ pub extern "C" fn occlum_sync() -> c_int {
     }
 }
 
+#[no_mangle]
+pub extern "C" fn occlum_mkdir(path: *const c_char, mode: c_int) -> c_int {
+    match do_mkdir(path, mode) {
+        Ok(()) => 0,
+        Err(e) => e.errno.as_retval(),
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn occlum_rmdir(path: *const c_char) -> c_int {
+    match do_rmdir(path) {
+        Ok(()) => 0,
+        Err(e) => e.errno.as_retval(),
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn occlum_getdents64(fd: c_int, buf: *mut c_void, buf_size: c_uint) -> c_int {
+    match do_getdents64(fd, buf as *mut u8, buf_size) {
+        Ok(read_len) => read_len as c_int,
+        Err(e) => e.errno.as_retval(),
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn occlum_chdir(path: *const c_char) -> c_int {
+    match do_chdir(path) {
+        Ok(()) => 0,
+        Err(e) => e.errno.as_retval(),
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn occlum_fchdir(fd: c_int) -> c_int {
+    match fs::do_fchdir(fd as FileDesc) {
+        Ok(()) => 0,
+        Err(e) => e.errno.as_retval(),
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn occlum_getcwd(buf: *mut c_char, buf_size: size_t) -> c_int {
+    match do_getcwd(buf, buf_size) {
+        Ok(cwd_len) => cwd_len as c_int,
+        Err(e) => e.errno.as_retval(),
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn occlum_stat(path: *const c_char, stat_buf: *mut Stat) -> c_int {
+    match do_stat(path, stat_buf) {
+        Ok(()) => 0,
+        Err(e) => e.errno.as_retval(),
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn occlum_fstat(fd: c_int, stat_buf: *mut Stat) -> c_int {
+    match do_fstat(fd, stat_buf) {
+        Ok(()) => 0,
+        Err(e) => e.errno.as_retval(),
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn occlum_lstat(path: *const c_char, stat_buf: *mut Stat) -> c_int {
+    match do_lstat(path, stat_buf) {
+        Ok(()) => 0,
+        Err(e) => e.errno.as_retval(),
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn occlum_fstatat(
+    dirfd: c_int,
+    path: *const c_char,
+    stat_buf: *mut Stat,
+    flags: c_int,
+) -> c_int {
+    match do_fstatat(dirfd, path, stat_buf, flags) {
+        Ok(()) => 0,
+        Err(e) => e.errno.as_retval(),
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn occlum_fcntl(fd: c_int, cmd: c_int, arg: u64) -> c_int {
+    match do_fcntl(fd, cmd, arg) {
+        Ok(ret) => ret as c_int,
+        Err(e) => e.errno.as_retval(),
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn occlum_ioctl(fd: c_int, cmd: c_uint, argp: *mut c_void) -> c_int {
+    match do_ioctl(fd, cmd, argp) {
+        Ok(()) => 0,
+        Err(e) => e.errno.as_retval(),
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn occlum_sendfile(
+    out_fd: c_int,
+    in_fd: c_int,
+    offset: *mut off_t,
+    count: size_t,
+) -> ssize_t {
+    match do_sendfile(out_fd, in_fd, offset, count) {
+        Ok(len) => len as ssize_t,
+        Err(e) => e.errno.as_retval() as ssize_t,
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn occlum_splice(
+    fd_in: c_int,
+    off_in: *mut off_t,
+    fd_out: c_int,
+    off_out: *mut off_t,
+    len: size_t,
+    flags: c_uint,
+) -> ssize_t {
+    match do_splice(fd_in, off_in, fd_out, off_out, len, flags) {
+        Ok(len) => len as ssize_t,
+        Err(e) => e.errno.as_retval() as ssize_t,
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn occlum_tee(fd_in: c_int, fd_out: c_int, len: size_t, flags: c_uint) -> ssize_t {
+    match fs::do_tee(fd_in as FileDesc, fd_out as FileDesc, len, flags) {
+        Ok(len) => len as ssize_t,
+        Err(e) => e.errno.as_retval() as ssize_t,
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn occlum_copy_file_range(
+    fd_in: c_int,
+    off_in: *mut off_t,
+    fd_out: c_int,
+    off_out: *mut off_t,
+    len: size_t,
+    flags: c_uint,
+) -> ssize_t {
+    match do_copy_file_range(fd_in, off_in, fd_out, off_out, len, flags) {
+        Ok(len) => len as ssize_t,
+        Err(e) => e.errno.as_retval() as ssize_t,
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn occlum_poll(fds: *mut PollFd, nfds: u64, timeout_ms: c_int) -> c_int {
+    match do_poll(fds, nfds, timeout_ms) {
+        Ok(num_ready_fds) => num_ready_fds as c_int,
+        Err(e) => e.errno.as_retval(),
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn occlum_ppoll(
+    fds: *mut PollFd,
+    nfds: u64,
+    timeout: *const timespec_t,
+    sigmask: *const c_void,
+) -> c_int {
+    match do_ppoll(fds, nfds, timeout, sigmask) {
+        Ok(num_ready_fds) => num_ready_fds as c_int,
+        Err(e) => e.errno.as_retval(),
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn occlum_select(
+    nfds: c_int,
+    readfds: *mut FdSet,
+    writefds: *mut FdSet,
+    exceptfds: *mut FdSet,
+    timeout: *const timeval_t,
+) -> c_int {
+    match do_select(nfds, readfds, writefds, exceptfds, timeout) {
+        Ok(num_ready_fds) => num_ready_fds as c_int,
+        Err(e) => e.errno.as_retval(),
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn occlum_eventfd(init_val: c_uint) -> c_int {
+    match do_eventfd(init_val) {
+        Ok(fd) => fd as c_int,
+        Err(e) => e.errno.as_retval(),
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn occlum_eventfd2(init_val: c_uint, flags: c_int) -> c_int {
+    match do_eventfd2(init_val, flags) {
+        Ok(fd) => fd as c_int,
+        Err(e) => e.errno.as_retval(),
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn occlum_epoll_create(size: c_int) -> c_int {
+    match do_epoll_create(size) {
+        Ok(epfd) => epfd as c_int,
+        Err(e) => e.errno.as_retval(),
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn occlum_epoll_create1(flags: c_int) -> c_int {
+    match do_epoll_create1(flags) {
+        Ok(epfd) => epfd as c_int,
+        Err(e) => e.errno.as_retval(),
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn occlum_epoll_ctl(
+    epfd: c_int,
+    op: c_int,
+    fd: c_int,
+    event: *const EpollEvent,
+) -> c_int {
+    match do_epoll_ctl(epfd, op, fd, event) {
+        Ok(()) => 0,
+        Err(e) => e.errno.as_retval(),
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn occlum_epoll_pwait(
+    epfd: c_int,
+    events: *mut EpollEvent,
+    maxevents: c_int,
+    timeout: c_int,
+    sigmask: *const c_void,
+) -> c_int {
+    match do_epoll_pwait(epfd, events, maxevents, timeout, sigmask) {
+        Ok(num_events) => num_events as c_int,
+        Err(e) => e.errno.as_retval(),
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn occlum_socket(domain: c_int, type_: c_int, protocol: c_int) -> c_int {
+    match do_socket(domain, type_, protocol) {
+        Ok(fd) => fd as c_int,
+        Err(e) => e.errno.as_retval(),
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn occlum_socketpair(
+    domain: c_int,
+    type_: c_int,
+    protocol: c_int,
+    sv: *mut c_int,
+) -> c_int {
+    match do_socketpair(domain, type_, protocol, sv) {
+        Ok(()) => 0,
+        Err(e) => e.errno.as_retval(),
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn occlum_bind(fd: c_int, addr: *const c_void, addr_len: c_uint) -> c_int {
+    match do_bind(fd, addr, addr_len) {
+        Ok(()) => 0,
+        Err(e) => e.errno.as_retval(),
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn occlum_listen(fd: c_int, backlog: c_int) -> c_int {
+    match do_listen(fd, backlog) {
+        Ok(()) => 0,
+        Err(e) => e.errno.as_retval(),
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn occlum_accept4(
+    fd: c_int,
+    addr: *mut c_void,
+    addr_len: *mut c_uint,
+    flags: c_int,
+) -> c_int {
+    match do_accept4(fd, addr, addr_len, flags) {
+        Ok(new_fd) => new_fd as c_int,
+        Err(e) => e.errno.as_retval(),
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn occlum_connect(fd: c_int, addr: *const c_void, addr_len: c_uint) -> c_int {
+    match do_connect(fd, addr, addr_len) {
+        Ok(()) => 0,
+        Err(e) => e.errno.as_retval(),
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn occlum_getsockname(fd: c_int, addr: *mut c_void, addr_len: *mut c_uint) -> c_int {
+    match do_getsockname(fd, addr, addr_len) {
+        Ok(()) => 0,
+        Err(e) => e.errno.as_retval(),
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn occlum_getpeername(fd: c_int, addr: *mut c_void, addr_len: *mut c_uint) -> c_int {
+    match do_getpeername(fd, addr, addr_len) {
+        Ok(()) => 0,
+        Err(e) => e.errno.as_retval(),
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn occlum_sendto(
+    fd: c_int,
+    buf: *const c_void,
+    len: size_t,
+    flags: c_int,
+    addr: *const c_void,
+    addr_len: c_uint,
+) -> ssize_t {
+    match do_sendto(fd, buf, len, flags, addr, addr_len) {
+        Ok(send_len) => send_len as ssize_t,
+        Err(e) => e.errno.as_retval() as ssize_t,
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn occlum_recvfrom(
+    fd: c_int,
+    buf: *mut c_void,
+    len: size_t,
+    flags: c_int,
+    addr: *mut c_void,
+    addr_len: *mut c_uint,
+) -> ssize_t {
+    match do_recvfrom(fd, buf, len, flags, addr, addr_len) {
+        Ok(recv_len) => recv_len as ssize_t,
+        Err(e) => e.errno.as_retval() as ssize_t,
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn occlum_sendmsg(fd: c_int, msg: *const msghdr_t, flags: c_int) -> ssize_t {
+    match do_sendmsg(fd, msg, flags) {
+        Ok(sent_len) => sent_len as ssize_t,
+        Err(e) => e.errno.as_retval() as ssize_t,
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn occlum_recvmsg(fd: c_int, msg: *mut msghdr_t, flags: c_int) -> ssize_t {
+    match do_recvmsg(fd, msg, flags) {
+        Ok(recv_len) => recv_len as ssize_t,
+        Err(e) => e.errno.as_retval() as ssize_t,
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn occlum_getsockopt(
+    fd: c_int,
+    level: c_int,
+    name: c_int,
+    value: *mut c_void,
+    value_len: *mut c_uint,
+) -> c_int {
+    match do_getsockopt(fd, level, name, value, value_len) {
+        Ok(()) => 0,
+        Err(e) => e.errno.as_retval(),
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn occlum_setsockopt(
+    fd: c_int,
+    level: c_int,
+    name: c_int,
+    value: *const c_void,
+    value_len: c_uint,
+) -> c_int {
+    match do_setsockopt(fd, level, name, value, value_len) {
+        Ok(()) => 0,
+        Err(e) => e.errno.as_retval(),
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn occlum_shutdown(fd: c_int, how: c_int) -> c_int {
+    match do_shutdown(fd, how) {
+        Ok(()) => 0,
+        Err(e) => e.errno.as_retval(),
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn occlum_truncate(path: *const c_char, len: off_t) -> c_int {
+    match do_truncate(path, len) {
+        Ok(()) => 0,
+        Err(e) => e.errno.as_retval(),
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn occlum_ftruncate(fd: c_int, len: off_t) -> c_int {
+    match fs::do_ftruncate(fd as FileDesc, len) {
+        Ok(()) => 0,
+        Err(e) => e.errno.as_retval(),
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn occlum_fallocate(fd: c_int, mode: c_int, offset: off_t, len: off_t) -> c_int {
+    match fs::do_fallocate(fd as FileDesc, mode as u32, offset, len) {
+        Ok(()) => 0,
+        Err(e) => e.errno.as_retval(),
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn occlum_unlink(path: *const c_char) -> c_int {
+    match do_unlink(path) {
+        Ok(()) => 0,
+        Err(e) => e.errno.as_retval(),
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn occlum_unlinkat(dirfd: c_int, path: *const c_char, flags: c_int) -> c_int {
+    match do_unlinkat(dirfd, path, flags) {
+        Ok(()) => 0,
+        Err(e) => e.errno.as_retval(),
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn occlum_rename(old_path: *const c_char, new_path: *const c_char) -> c_int {
+    match do_rename(old_path, new_path) {
+        Ok(()) => 0,
+        Err(e) => e.errno.as_retval(),
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn occlum_renameat(
+    old_dirfd: c_int,
+    old_path: *const c_char,
+    new_dirfd: c_int,
+    new_path: *const c_char,
+) -> c_int {
+    match do_renameat(old_dirfd, old_path, new_dirfd, new_path) {
+        Ok(()) => 0,
+        Err(e) => e.errno.as_retval(),
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn occlum_link(old_path: *const c_char, new_path: *const c_char) -> c_int {
+    match do_link(old_path, new_path) {
+        Ok(()) => 0,
+        Err(e) => e.errno.as_retval(),
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn occlum_symlink(target: *const c_char, link_path: *const c_char) -> c_int {
+    match do_symlink(target, link_path) {
+        Ok(()) => 0,
+        Err(e) => e.errno.as_retval(),
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn occlum_readlink(
+    path: *const c_char,
+    buf: *mut c_char,
+    buf_size: size_t,
+) -> ssize_t {
+    match do_readlink(path, buf, buf_size) {
+        Ok(len) => len as ssize_t,
+        Err(e) => e.errno.as_retval() as ssize_t,
+    }
+}
+
 // TODO: handle tz: timezone_t
 #[no_mangle]
 pub extern "C" fn occlum_gettimeofday(tv: *mut timeval_t) -> c_int {
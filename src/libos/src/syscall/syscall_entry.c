@@ -1,3 +1,4 @@
+#include <stddef.h>
 #include "syscall.h"
 #include "task.h"
 
@@ -53,6 +54,74 @@ long dispatch_syscall(int num, long arg0, long arg1, long arg2, long arg3, long
         ret = occlum_readv(fd, iov, count);
         break;
     }
+    case SYS_pread64: {
+        DECL_SYSCALL_ARG(int, fd, arg0);
+        DECL_SYSCALL_ARG(void*, buf, arg1);
+        DECL_SYSCALL_ARG(size_t, buf_size, arg2);
+        DECL_SYSCALL_ARG(off_t, offset, arg3);
+        ret = occlum_pread(fd, buf, buf_size, offset);
+        break;
+    }
+    case SYS_pwrite64: {
+        DECL_SYSCALL_ARG(int, fd, arg0);
+        DECL_SYSCALL_ARG(const void*, buf, arg1);
+        DECL_SYSCALL_ARG(size_t, buf_size, arg2);
+        DECL_SYSCALL_ARG(off_t, offset, arg3);
+        ret = occlum_pwrite(fd, buf, buf_size, offset);
+        break;
+    }
+    case SYS_preadv: {
+        DECL_SYSCALL_ARG(int, fd, arg0);
+        DECL_SYSCALL_ARG(struct iovec*, iov, arg1);
+        DECL_SYSCALL_ARG(int, count, arg2);
+        DECL_SYSCALL_ARG(off_t, offset, arg3);
+        ret = occlum_preadv(fd, iov, count, offset);
+        break;
+    }
+    case SYS_pwritev: {
+        DECL_SYSCALL_ARG(int, fd, arg0);
+        DECL_SYSCALL_ARG(const struct iovec*, iov, arg1);
+        DECL_SYSCALL_ARG(int, count, arg2);
+        DECL_SYSCALL_ARG(off_t, offset, arg3);
+        ret = occlum_pwritev(fd, iov, count, offset);
+        break;
+    }
+    case SYS_sendfile: {
+        DECL_SYSCALL_ARG(int, out_fd, arg0);
+        DECL_SYSCALL_ARG(int, in_fd, arg1);
+        DECL_SYSCALL_ARG(off_t*, offset, arg2);
+        DECL_SYSCALL_ARG(size_t, count, arg3);
+        ret = occlum_sendfile(out_fd, in_fd, offset, count);
+        break;
+    }
+    case SYS_splice: {
+        DECL_SYSCALL_ARG(int, fd_in, arg0);
+        DECL_SYSCALL_ARG(off_t*, off_in, arg1);
+        DECL_SYSCALL_ARG(int, fd_out, arg2);
+        DECL_SYSCALL_ARG(off_t*, off_out, arg3);
+        DECL_SYSCALL_ARG(size_t, len, arg4);
+        DECL_SYSCALL_ARG(unsigned int, flags, arg5);
+        ret = occlum_splice(fd_in, off_in, fd_out, off_out, len, flags);
+        break;
+    }
+    case SYS_tee: {
+        DECL_SYSCALL_ARG(int, fd_in, arg0);
+        DECL_SYSCALL_ARG(int, fd_out, arg1);
+        DECL_SYSCALL_ARG(size_t, len, arg2);
+        DECL_SYSCALL_ARG(unsigned int, flags, arg3);
+        ret = occlum_tee(fd_in, fd_out, len, flags);
+        break;
+    }
+    case SYS_copy_file_range: {
+        DECL_SYSCALL_ARG(int, fd_in, arg0);
+        DECL_SYSCALL_ARG(off_t*, off_in, arg1);
+        DECL_SYSCALL_ARG(int, fd_out, arg2);
+        DECL_SYSCALL_ARG(off_t*, off_out, arg3);
+        DECL_SYSCALL_ARG(size_t, len, arg4);
+        DECL_SYSCALL_ARG(unsigned int, flags, arg5);
+        ret = occlum_copy_file_range(fd_in, off_in, fd_out, off_out, len, flags);
+        break;
+    }
     case SYS_lseek: {
         DECL_SYSCALL_ARG(int, fd, arg0);
         DECL_SYSCALL_ARG(off_t, offset, arg1);
@@ -60,6 +129,337 @@ long dispatch_syscall(int num, long arg0, long arg1, long arg2, long arg3, long
         ret = occlum_lseek(fd, offset, whence);
         break;
     }
+    case SYS_mkdir: {
+        DECL_SYSCALL_ARG(const char*, path, arg0);
+        DECL_SYSCALL_ARG(int, mode, arg1);
+        ret = occlum_mkdir(path, mode);
+        break;
+    }
+    case SYS_rmdir: {
+        DECL_SYSCALL_ARG(const char*, path, arg0);
+        ret = occlum_rmdir(path);
+        break;
+    }
+    case SYS_getdents64: {
+        DECL_SYSCALL_ARG(int, fd, arg0);
+        DECL_SYSCALL_ARG(void*, buf, arg1);
+        DECL_SYSCALL_ARG(unsigned int, buf_size, arg2);
+        ret = occlum_getdents64(fd, buf, buf_size);
+        break;
+    }
+    case SYS_chdir: {
+        DECL_SYSCALL_ARG(const char*, path, arg0);
+        ret = occlum_chdir(path);
+        break;
+    }
+    case SYS_fchdir: {
+        DECL_SYSCALL_ARG(int, fd, arg0);
+        ret = occlum_fchdir(fd);
+        break;
+    }
+    case SYS_getcwd: {
+        DECL_SYSCALL_ARG(char*, buf, arg0);
+        DECL_SYSCALL_ARG(size_t, buf_size, arg1);
+        ret = occlum_getcwd(buf, buf_size);
+        break;
+    }
+    case SYS_stat: {
+        DECL_SYSCALL_ARG(const char*, path, arg0);
+        DECL_SYSCALL_ARG(struct stat*, buf, arg1);
+        ret = occlum_stat(path, buf);
+        break;
+    }
+    case SYS_fstat: {
+        DECL_SYSCALL_ARG(int, fd, arg0);
+        DECL_SYSCALL_ARG(struct stat*, buf, arg1);
+        ret = occlum_fstat(fd, buf);
+        break;
+    }
+    case SYS_lstat: {
+        DECL_SYSCALL_ARG(const char*, path, arg0);
+        DECL_SYSCALL_ARG(struct stat*, buf, arg1);
+        ret = occlum_lstat(path, buf);
+        break;
+    }
+    case SYS_newfstatat: {
+        DECL_SYSCALL_ARG(int, dirfd, arg0);
+        DECL_SYSCALL_ARG(const char*, path, arg1);
+        DECL_SYSCALL_ARG(struct stat*, buf, arg2);
+        DECL_SYSCALL_ARG(int, flags, arg3);
+        ret = occlum_fstatat(dirfd, path, buf, flags);
+        break;
+    }
+    case SYS_fcntl: {
+        DECL_SYSCALL_ARG(int, fd, arg0);
+        DECL_SYSCALL_ARG(int, cmd, arg1);
+        DECL_SYSCALL_ARG(unsigned long, arg, arg2);
+        ret = occlum_fcntl(fd, cmd, arg);
+        break;
+    }
+    case SYS_ioctl: {
+        DECL_SYSCALL_ARG(int, fd, arg0);
+        DECL_SYSCALL_ARG(unsigned int, cmd, arg1);
+        DECL_SYSCALL_ARG(void*, argp, arg2);
+        ret = occlum_ioctl(fd, cmd, argp);
+        break;
+    }
+    case SYS_poll: {
+        DECL_SYSCALL_ARG(struct pollfd*, fds, arg0);
+        DECL_SYSCALL_ARG(unsigned long, nfds, arg1);
+        DECL_SYSCALL_ARG(int, timeout, arg2);
+        ret = occlum_poll(fds, nfds, timeout);
+        break;
+    }
+    case SYS_ppoll: {
+        DECL_SYSCALL_ARG(struct pollfd*, fds, arg0);
+        DECL_SYSCALL_ARG(unsigned long, nfds, arg1);
+        DECL_SYSCALL_ARG(const struct timespec*, timeout, arg2);
+        DECL_SYSCALL_ARG(const void*, sigmask, arg3);
+        ret = occlum_ppoll(fds, nfds, timeout, sigmask);
+        break;
+    }
+    case SYS_select: {
+        DECL_SYSCALL_ARG(int, nfds, arg0);
+        DECL_SYSCALL_ARG(void*, readfds, arg1);
+        DECL_SYSCALL_ARG(void*, writefds, arg2);
+        DECL_SYSCALL_ARG(void*, exceptfds, arg3);
+        DECL_SYSCALL_ARG(const struct timeval*, timeout, arg4);
+        ret = occlum_select(nfds, readfds, writefds, exceptfds, timeout);
+        break;
+    }
+    case SYS_eventfd: {
+        DECL_SYSCALL_ARG(unsigned int, init_val, arg0);
+        ret = occlum_eventfd(init_val);
+        break;
+    }
+    case SYS_eventfd2: {
+        DECL_SYSCALL_ARG(unsigned int, init_val, arg0);
+        DECL_SYSCALL_ARG(int, flags, arg1);
+        ret = occlum_eventfd2(init_val, flags);
+        break;
+    }
+    case SYS_socket: {
+        DECL_SYSCALL_ARG(int, domain, arg0);
+        DECL_SYSCALL_ARG(int, type, arg1);
+        DECL_SYSCALL_ARG(int, protocol, arg2);
+        ret = occlum_socket(domain, type, protocol);
+        break;
+    }
+    case SYS_socketpair: {
+        DECL_SYSCALL_ARG(int, domain, arg0);
+        DECL_SYSCALL_ARG(int, type, arg1);
+        DECL_SYSCALL_ARG(int, protocol, arg2);
+        DECL_SYSCALL_ARG(int*, sv, arg3);
+        ret = occlum_socketpair(domain, type, protocol, sv);
+        break;
+    }
+    case SYS_bind: {
+        DECL_SYSCALL_ARG(int, fd, arg0);
+        DECL_SYSCALL_ARG(const void*, addr, arg1);
+        DECL_SYSCALL_ARG(unsigned int, addr_len, arg2);
+        ret = occlum_bind(fd, addr, addr_len);
+        break;
+    }
+    case SYS_listen: {
+        DECL_SYSCALL_ARG(int, fd, arg0);
+        DECL_SYSCALL_ARG(int, backlog, arg1);
+        ret = occlum_listen(fd, backlog);
+        break;
+    }
+    case SYS_accept: {
+        DECL_SYSCALL_ARG(int, fd, arg0);
+        DECL_SYSCALL_ARG(void*, addr, arg1);
+        DECL_SYSCALL_ARG(unsigned int*, addr_len, arg2);
+        ret = occlum_accept4(fd, addr, addr_len, 0);
+        break;
+    }
+    case SYS_accept4: {
+        DECL_SYSCALL_ARG(int, fd, arg0);
+        DECL_SYSCALL_ARG(void*, addr, arg1);
+        DECL_SYSCALL_ARG(unsigned int*, addr_len, arg2);
+        DECL_SYSCALL_ARG(int, flags, arg3);
+        ret = occlum_accept4(fd, addr, addr_len, flags);
+        break;
+    }
+    case SYS_connect: {
+        DECL_SYSCALL_ARG(int, fd, arg0);
+        DECL_SYSCALL_ARG(const void*, addr, arg1);
+        DECL_SYSCALL_ARG(unsigned int, addr_len, arg2);
+        ret = occlum_connect(fd, addr, addr_len);
+        break;
+    }
+    case SYS_getsockname: {
+        DECL_SYSCALL_ARG(int, fd, arg0);
+        DECL_SYSCALL_ARG(void*, addr, arg1);
+        DECL_SYSCALL_ARG(unsigned int*, addr_len, arg2);
+        ret = occlum_getsockname(fd, addr, addr_len);
+        break;
+    }
+    case SYS_getpeername: {
+        DECL_SYSCALL_ARG(int, fd, arg0);
+        DECL_SYSCALL_ARG(void*, addr, arg1);
+        DECL_SYSCALL_ARG(unsigned int*, addr_len, arg2);
+        ret = occlum_getpeername(fd, addr, addr_len);
+        break;
+    }
+    case SYS_sendto: {
+        DECL_SYSCALL_ARG(int, fd, arg0);
+        DECL_SYSCALL_ARG(const void*, buf, arg1);
+        DECL_SYSCALL_ARG(size_t, len, arg2);
+        DECL_SYSCALL_ARG(int, flags, arg3);
+        DECL_SYSCALL_ARG(const void*, addr, arg4);
+        DECL_SYSCALL_ARG(unsigned int, addr_len, arg5);
+        ret = occlum_sendto(fd, buf, len, flags, addr, addr_len);
+        break;
+    }
+    case SYS_recvfrom: {
+        DECL_SYSCALL_ARG(int, fd, arg0);
+        DECL_SYSCALL_ARG(void*, buf, arg1);
+        DECL_SYSCALL_ARG(size_t, len, arg2);
+        DECL_SYSCALL_ARG(int, flags, arg3);
+        DECL_SYSCALL_ARG(void*, addr, arg4);
+        DECL_SYSCALL_ARG(unsigned int*, addr_len, arg5);
+        ret = occlum_recvfrom(fd, buf, len, flags, addr, addr_len);
+        break;
+    }
+    case SYS_sendmsg: {
+        DECL_SYSCALL_ARG(int, fd, arg0);
+        DECL_SYSCALL_ARG(const void*, msg, arg1);
+        DECL_SYSCALL_ARG(int, flags, arg2);
+        ret = occlum_sendmsg(fd, msg, flags);
+        break;
+    }
+    case SYS_recvmsg: {
+        DECL_SYSCALL_ARG(int, fd, arg0);
+        DECL_SYSCALL_ARG(void*, msg, arg1);
+        DECL_SYSCALL_ARG(int, flags, arg2);
+        ret = occlum_recvmsg(fd, msg, flags);
+        break;
+    }
+    case SYS_getsockopt: {
+        DECL_SYSCALL_ARG(int, fd, arg0);
+        DECL_SYSCALL_ARG(int, level, arg1);
+        DECL_SYSCALL_ARG(int, name, arg2);
+        DECL_SYSCALL_ARG(void*, value, arg3);
+        DECL_SYSCALL_ARG(unsigned int*, value_len, arg4);
+        ret = occlum_getsockopt(fd, level, name, value, value_len);
+        break;
+    }
+    case SYS_setsockopt: {
+        DECL_SYSCALL_ARG(int, fd, arg0);
+        DECL_SYSCALL_ARG(int, level, arg1);
+        DECL_SYSCALL_ARG(int, name, arg2);
+        DECL_SYSCALL_ARG(const void*, value, arg3);
+        DECL_SYSCALL_ARG(unsigned int, value_len, arg4);
+        ret = occlum_setsockopt(fd, level, name, value, value_len);
+        break;
+    }
+    case SYS_shutdown: {
+        DECL_SYSCALL_ARG(int, fd, arg0);
+        DECL_SYSCALL_ARG(int, how, arg1);
+        ret = occlum_shutdown(fd, how);
+        break;
+    }
+    case SYS_epoll_create: {
+        DECL_SYSCALL_ARG(int, size, arg0);
+        ret = occlum_epoll_create(size);
+        break;
+    }
+    case SYS_epoll_create1: {
+        DECL_SYSCALL_ARG(int, flags, arg0);
+        ret = occlum_epoll_create1(flags);
+        break;
+    }
+    case SYS_epoll_ctl: {
+        DECL_SYSCALL_ARG(int, epfd, arg0);
+        DECL_SYSCALL_ARG(int, op, arg1);
+        DECL_SYSCALL_ARG(int, fd, arg2);
+        DECL_SYSCALL_ARG(const struct epoll_event*, event, arg3);
+        ret = occlum_epoll_ctl(epfd, op, fd, event);
+        break;
+    }
+    case SYS_epoll_wait: {
+        DECL_SYSCALL_ARG(int, epfd, arg0);
+        DECL_SYSCALL_ARG(struct epoll_event*, events, arg1);
+        DECL_SYSCALL_ARG(int, maxevents, arg2);
+        DECL_SYSCALL_ARG(int, timeout, arg3);
+        ret = occlum_epoll_pwait(epfd, events, maxevents, timeout, NULL);
+        break;
+    }
+    case SYS_epoll_pwait: {
+        DECL_SYSCALL_ARG(int, epfd, arg0);
+        DECL_SYSCALL_ARG(struct epoll_event*, events, arg1);
+        DECL_SYSCALL_ARG(int, maxevents, arg2);
+        DECL_SYSCALL_ARG(int, timeout, arg3);
+        DECL_SYSCALL_ARG(const void*, sigmask, arg4);
+        ret = occlum_epoll_pwait(epfd, events, maxevents, timeout, sigmask);
+        break;
+    }
+    case SYS_truncate: {
+        DECL_SYSCALL_ARG(const char*, path, arg0);
+        DECL_SYSCALL_ARG(off_t, len, arg1);
+        ret = occlum_truncate(path, len);
+        break;
+    }
+    case SYS_ftruncate: {
+        DECL_SYSCALL_ARG(int, fd, arg0);
+        DECL_SYSCALL_ARG(off_t, len, arg1);
+        ret = occlum_ftruncate(fd, len);
+        break;
+    }
+    case SYS_fallocate: {
+        DECL_SYSCALL_ARG(int, fd, arg0);
+        DECL_SYSCALL_ARG(int, mode, arg1);
+        DECL_SYSCALL_ARG(off_t, offset, arg2);
+        DECL_SYSCALL_ARG(off_t, len, arg3);
+        ret = occlum_fallocate(fd, mode, offset, len);
+        break;
+    }
+    case SYS_unlink: {
+        DECL_SYSCALL_ARG(const char*, path, arg0);
+        ret = occlum_unlink(path);
+        break;
+    }
+    case SYS_unlinkat: {
+        DECL_SYSCALL_ARG(int, dirfd, arg0);
+        DECL_SYSCALL_ARG(const char*, path, arg1);
+        DECL_SYSCALL_ARG(int, flags, arg2);
+        ret = occlum_unlinkat(dirfd, path, flags);
+        break;
+    }
+    case SYS_rename: {
+        DECL_SYSCALL_ARG(const char*, old_path, arg0);
+        DECL_SYSCALL_ARG(const char*, new_path, arg1);
+        ret = occlum_rename(old_path, new_path);
+        break;
+    }
+    case SYS_renameat: {
+        DECL_SYSCALL_ARG(int, old_dirfd, arg0);
+        DECL_SYSCALL_ARG(const char*, old_path, arg1);
+        DECL_SYSCALL_ARG(int, new_dirfd, arg2);
+        DECL_SYSCALL_ARG(const char*, new_path, arg3);
+        ret = occlum_renameat(old_dirfd, old_path, new_dirfd, new_path);
+        break;
+    }
+    case SYS_link: {
+        DECL_SYSCALL_ARG(const char*, old_path, arg0);
+        DECL_SYSCALL_ARG(const char*, new_path, arg1);
+        ret = occlum_link(old_path, new_path);
+        break;
+    }
+    case SYS_symlink: {
+        DECL_SYSCALL_ARG(const char*, target, arg0);
+        DECL_SYSCALL_ARG(const char*, link_path, arg1);
+        ret = occlum_symlink(target, link_path);
+        break;
+    }
+    case SYS_readlink: {
+        DECL_SYSCALL_ARG(const char*, path, arg0);
+        DECL_SYSCALL_ARG(char*, buf, arg1);
+        DECL_SYSCALL_ARG(size_t, buf_size, arg2);
+        ret = occlum_readlink(path, buf, buf_size);
+        break;
+    }
     case SYS_spawn: {
         DECL_SYSCALL_ARG(int*, child_pid, arg0);
         DECL_SYSCALL_ARG(const char*, path, arg1);
@@ -85,6 +485,16 @@ long dispatch_syscall(int num, long arg0, long arg1, long arg2, long arg3, long
         ret = occlum_getppid();
         break;
     }
+    case SYS_fsync: {
+        DECL_SYSCALL_ARG(int, fd, arg0);
+        ret = occlum_fsync(fd);
+        break;
+    }
+    case SYS_fdatasync: {
+        DECL_SYSCALL_ARG(int, fd, arg0);
+        ret = occlum_fdatasync(fd);
+        break;
+    }
     case SYS_sync: {
         ret = occlum_sync();
         break;
@@ -105,6 +515,13 @@ long dispatch_syscall(int num, long arg0, long arg1, long arg2, long arg3, long
         ret = occlum_munmap(addr, length);
         break;
     }
+    case SYS_msync: {
+        DECL_SYSCALL_ARG(void*, addr, arg0);
+        DECL_SYSCALL_ARG(size_t, length, arg1);
+        DECL_SYSCALL_ARG(int, flags, arg2);
+        ret = occlum_msync(addr, length, flags);
+        break;
+    }
     case SYS_mremap: {
         DECL_SYSCALL_ARG(void*, old_addr, arg0);
         DECL_SYSCALL_ARG(size_t, old_size, arg1);
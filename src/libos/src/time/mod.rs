@@ -13,6 +13,48 @@ pub struct timeval_t {
     usec: suseconds_t,
 }
 
+impl timeval_t {
+    pub fn as_timespec(&self) -> timespec_t {
+        timespec_t {
+            sec: self.sec,
+            nsec: self.usec * 1000,
+        }
+    }
+
+    pub fn as_usec(&self) -> i64 {
+        self.sec * 1000_000 + self.usec
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.sec >= 0 && self.usec >= 0 && self.usec < 1000_000
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct timespec_t {
+    sec: time_t,
+    nsec: i64,
+}
+
+impl timespec_t {
+    pub fn new(sec: time_t, nsec: i64) -> timespec_t {
+        timespec_t { sec, nsec }
+    }
+
+    pub fn now() -> timespec_t {
+        do_gettimeofday().as_timespec()
+    }
+
+    pub fn as_usec(&self) -> i64 {
+        self.sec * 1000_000 + self.nsec / 1000
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.sec >= 0 && self.nsec >= 0 && self.nsec < 1000_000_000
+    }
+}
+
 pub fn do_gettimeofday() -> timeval_t {
     let mut tv: timeval_t = Default::default();
     unsafe {
@@ -42,6 +42,7 @@ struct RingBufInner {
     head: AtomicUsize,  // write to head
     tail: AtomicUsize,  // read from tail
     closed: AtomicBool, // if reader has been dropped
+    writer_closed: AtomicBool, // if writer has been dropped
 }
 
 const RING_BUF_ALIGN : usize = 16;
@@ -60,6 +61,7 @@ impl RingBufInner {
             head: AtomicUsize::new(0),
             tail: AtomicUsize::new(0),
             closed: AtomicBool::new(false),
+            writer_closed: AtomicBool::new(false),
         }
     }
 
@@ -75,6 +77,10 @@ impl RingBufInner {
         self.tail.load(Ordering::SeqCst)
     }
 
+    fn get_len(&self) -> usize {
+        (self.get_head().wrapping_sub(self.get_tail())) & self.get_mask()
+    }
+
     fn set_head(&self, new_head: usize) {
         self.head.store(new_head, Ordering::SeqCst)
     }
@@ -91,6 +97,14 @@ impl RingBufInner {
         self.closed.store(true, Ordering::SeqCst);
     }
 
+    fn is_writer_closed(&self) -> bool {
+        self.writer_closed.load(Ordering::SeqCst)
+    }
+
+    fn close_writer(&self) {
+        self.writer_closed.store(true, Ordering::SeqCst);
+    }
+
     unsafe fn read_at(&self, pos: usize, dst_buf: &mut [u8]) {
         let dst_ptr = dst_buf.as_mut_ptr();
         let dst_len = dst_buf.len();
@@ -153,12 +167,71 @@ impl RingBufReader {
         }
         Ok(buf_pos)
     }
+
+    /// Read into the buffers in turn, until the ring buffer is empty
+    pub fn readv(&self, bufs: &mut [&mut [u8]]) -> Result<usize, Error> {
+        let mut total_bytes = 0;
+        for buf in bufs {
+            match self.read(buf) {
+                Ok(this_len) => {
+                    total_bytes += this_len;
+                    if this_len < buf.len() {
+                        break;
+                    }
+                }
+                Err(e) => {
+                    match total_bytes {
+                        // a complete failure
+                        0 => return Err(e),
+                        // a partially failure
+                        _ => break,
+                    }
+                }
+            }
+        }
+        Ok(total_bytes)
+    }
+
+    /// Copy the data at the start of the ring buffer without consuming it
+    pub fn peek(&self, buf: &mut [u8]) -> usize {
+        let tail = self.inner.get_tail();
+        let peek_len = min(buf.len(), self.inner.get_len());
+        // The data may wrap around the end of the buffer
+        let first_len = min(peek_len, self.inner.capacity - tail);
+        unsafe {
+            self.inner.read_at(tail, &mut buf[..first_len]);
+            self.inner.read_at(0, &mut buf[first_len..peek_len]);
+        }
+        peek_len
+    }
+
+    /// Discard at most `len` bytes at the start of the ring buffer
+    pub fn skip(&self, len: usize) -> usize {
+        let skipped_len = min(len, self.inner.get_len());
+        let tail = (self.inner.get_tail() + skipped_len) & self.inner.get_mask();
+        self.inner.set_tail(tail);
+        skipped_len
+    }
+
+    /// The number of bytes that can be read now
+    pub fn bytes_to_read(&self) -> usize {
+        self.inner.get_len()
+    }
+
+    /// Whether the writer has been closed, after which no more data arrives
+    pub fn is_peer_closed(&self) -> bool {
+        self.inner.is_writer_closed()
+    }
+
+    pub fn close(&self) {
+        self.inner.close();
+    }
 }
 
 impl Drop for RingBufReader {
     fn drop(&mut self) {
         // So the writer knows when a reader is finished
-        self.inner.close();
+        self.close();
     }
 }
 
@@ -176,7 +249,10 @@ impl RingBufWriter {
 
             let write_nbytes = {
                 let may_write_nbytes = if tail <= head {
-                    self.inner.capacity - head
+                    // Keep one byte free if wrapping around, as head == tail
+                    // means the buffer is empty
+                    let reserved_nbytes = if tail == 0 { 1 } else { 0 };
+                    self.inner.capacity - head - reserved_nbytes
                 } else {
                     tail - head - 1
                 };
@@ -200,4 +276,58 @@ impl RingBufWriter {
         }
         Ok(buf_pos)
     }
+
+    /// Write the data in the buffers after the first `skipped_len` bytes,
+    /// until the ring buffer is full
+    pub fn writev(&self, bufs: &[&[u8]], skipped_len: usize) -> Result<usize, Error> {
+        let mut skipped_len = skipped_len;
+        let mut total_bytes = 0;
+        for buf in bufs {
+            if skipped_len >= buf.len() {
+                skipped_len -= buf.len();
+                continue;
+            }
+            let buf = &buf[skipped_len..];
+            skipped_len = 0;
+            match self.write(buf) {
+                Ok(this_len) => {
+                    total_bytes += this_len;
+                    if this_len < buf.len() {
+                        break;
+                    }
+                }
+                Err(e) => {
+                    match total_bytes {
+                        // a complete failure
+                        0 => return Err(e),
+                        // a partially failure
+                        _ => break,
+                    }
+                }
+            }
+        }
+        Ok(total_bytes)
+    }
+
+    /// The number of bytes that can be written now
+    pub fn bytes_to_write(&self) -> usize {
+        // One byte is always kept free, as head == tail means empty
+        self.inner.capacity - 1 - self.inner.get_len()
+    }
+
+    /// Whether the reader has been closed, after which writes fail
+    pub fn is_peer_closed(&self) -> bool {
+        self.inner.is_closed()
+    }
+
+    pub fn close(&self) {
+        self.inner.close_writer();
+    }
+}
+
+impl Drop for RingBufWriter {
+    fn drop(&mut self) {
+        // So the reader knows when no more data will be written
+        self.close();
+    }
 }
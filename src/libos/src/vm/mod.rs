@@ -1,4 +1,4 @@
-use fs::{off_t, FileDesc};
+use fs::{off_t, FileDesc, FileRef, FileType, O_ACCMODE, O_RDWR, O_WRONLY};
 use prelude::*;
 use process::{get_current, Process, ProcessRef};
 use std::fmt;
@@ -15,13 +15,44 @@ mod vm_space;
 pub use self::process_vm::ProcessVM;
 pub use self::vm_range::{VMRange, VMRangeTrait};
 
-// TODO: separate proc and flags
-// TODO: accept fd and offset
-pub fn do_mmap(addr: usize, size: usize, flags: VMAreaFlags) -> Result<usize, Error> {
+// Flags of mmap
+pub const MAP_SHARED: u32 = 0x01;
+pub const MAP_PRIVATE: u32 = 0x02;
+pub const MAP_TYPE: u32 = 0x0f;
+pub const MAP_FIXED: u32 = 0x10;
+pub const MAP_ANONYMOUS: u32 = 0x20;
+
+// Flags of msync
+pub const MS_ASYNC: u32 = 0x1;
+pub const MS_INVALIDATE: u32 = 0x2;
+pub const MS_SYNC: u32 = 0x4;
+
+pub fn do_mmap(
+    addr: usize,
+    size: usize,
+    prot: VMAreaFlags,
+    flags: u32,
+    fd: FileDesc,
+    offset: off_t,
+) -> Result<usize, Error> {
+    let is_shared = match flags & MAP_TYPE {
+        MAP_SHARED => true,
+        MAP_PRIVATE => false,
+        _ => return errno!(EINVAL, "Invalid type of mapping"),
+    };
+    if size == 0 {
+        return errno!(EINVAL, "Size is zero");
+    }
     let current_ref = get_current();
     let mut current_process = current_ref.lock().unwrap();
+    let file_backing = if flags & MAP_ANONYMOUS == 0 {
+        let file = current_process.get_files().get(fd)?;
+        Some(VMFileBacking::new(file, offset, is_shared, prot)?)
+    } else {
+        None
+    };
     let current_vm = current_process.get_vm_mut();
-    current_vm.mmap(addr, size, flags)
+    current_vm.mmap(addr, size, prot, file_backing)
 }
 
 pub fn do_munmap(addr: usize, size: usize) -> Result<(), Error> {
@@ -31,6 +62,23 @@ pub fn do_munmap(addr: usize, size: usize) -> Result<(), Error> {
     current_vm.munmap(addr, size)
 }
 
+/// Write the shared file mappings in the range back to the files, which is
+/// done at once even for MS_ASYNC
+pub fn do_msync(addr: usize, size: usize, flags: u32) -> Result<(), Error> {
+    if addr % PAGE_SIZE != 0 {
+        return errno!(EINVAL, "Address is not page-aligned");
+    }
+    if flags & !(MS_ASYNC | MS_INVALIDATE | MS_SYNC) != 0
+        || (flags & MS_ASYNC != 0 && flags & MS_SYNC != 0)
+    {
+        return errno!(EINVAL, "Invalid flags for msync");
+    }
+    let current_ref = get_current();
+    let mut current_process = current_ref.lock().unwrap();
+    let current_vm = current_process.get_vm_mut();
+    current_vm.msync(addr, size)
+}
+
 // TODO: accept flags
 pub fn do_mremap(
     old_addr: usize,
@@ -67,6 +115,19 @@ pub struct VMDomain {
 pub struct VMArea {
     range: VMRange,
     flags: VMAreaFlags,
+    file_backing: Option<VMFileBacking>,
+}
+
+/// The file whose contents at the offset are mapped into a VMArea
+#[derive(Clone, Debug)]
+pub struct VMFileBacking {
+    file: FileRef,
+    offset: usize,
+    is_shared: bool,
+    // A copy of the memory as loaded from or last written to the file if the
+    // mapping is shared, so that only the pages changed through the mapping
+    // are written back
+    synced_data: Vec<u8>,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -144,7 +144,14 @@ impl ProcessVM {
     }
 
     // TODO: support overriding the mmaping of already mmaped range
-    pub fn mmap(&mut self, addr: usize, size: usize, flags: VMAreaFlags) -> Result<usize, Error> {
+    pub fn mmap(
+        &mut self,
+        addr: usize,
+        size: usize,
+        flags: VMAreaFlags,
+        file_backing: Option<VMFileBacking>,
+    ) -> Result<usize, Error> {
+        let size = align_up(size, PAGE_SIZE);
         let alloc_options = {
             let mmap_start_addr = self.get_mmap_start();
 
@@ -162,7 +169,13 @@ impl ProcessVM {
             alloc_options
         };
         // TODO: when failed, try to resize data_domain
-        let new_mmap_vma = self.data_domain.alloc_area(&alloc_options, flags)?;
+        let mut new_mmap_vma = self.data_domain.alloc_area(&alloc_options, flags)?;
+        if let Some(file_backing) = file_backing {
+            if let Err(e) = new_mmap_vma.load_from_file(file_backing) {
+                self.data_domain.dealloc_area(&mut new_mmap_vma);
+                return Err(e);
+            }
+        }
         let addr = new_mmap_vma.get_start();
         self.mmap_vmas.push(Box::new(new_mmap_vma));
         Ok(addr)
@@ -172,6 +185,7 @@ impl ProcessVM {
         // TODO: handle the case when the given range [addr, addr + size)
         // does not match exactly with any vma. For example, when this range
         // cover multiple ranges or cover some range partially.
+        let size = align_up(size, PAGE_SIZE);
 
         let mmap_vma_i = {
             let mmap_vma_i = self
@@ -185,10 +199,50 @@ impl ProcessVM {
         };
 
         let mut removed_mmap_vma = self.mmap_vmas.swap_remove(mmap_vma_i);
+        // As on Linux, munmap does not fail when the write-back fails
+        removed_mmap_vma.sync_all_to_file().ok();
         self.data_domain.dealloc_area(&mut removed_mmap_vma);
         Ok(())
     }
 
+    /// Write the shared file mappings in [addr, addr + size) back to the
+    /// files, which fails with ENOMEM if some pages in the range are not
+    /// mapped by mmap
+    pub fn msync(&mut self, addr: usize, size: usize) -> Result<(), Error> {
+        // The address is page-aligned, so the end is the same as addr +
+        // align_up(size, PAGE_SIZE), but is checked for overflow
+        let end = addr
+            .checked_add(size)
+            .and_then(|end| end.checked_add(PAGE_SIZE - 1))
+            .map(|end| align_down(end, PAGE_SIZE))
+            .ok_or_else(|| Error::new(Errno::ENOMEM, "Range is not fully mapped"))?;
+        let mapped_size: usize = self
+            .get_mmap_vmas()
+            .iter()
+            .map(|vma| {
+                end.min(vma.get_end())
+                    .saturating_sub(addr.max(vma.get_start()))
+            })
+            .sum();
+        if mapped_size < end - addr {
+            return errno!(ENOMEM, "Range is not fully mapped");
+        }
+        for vma in &mut self.mmap_vmas {
+            if vma.get_start() < end && addr < vma.get_end() {
+                vma.sync_to_file(addr, end)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Write all the shared file mappings back to the files
+    pub fn msync_all(&mut self) -> Result<(), Error> {
+        for vma in &mut self.mmap_vmas {
+            vma.sync_all_to_file()?;
+        }
+        Ok(())
+    }
+
     pub fn mremap(
         &mut self,
         old_addr: usize,
@@ -8,4 +8,107 @@ impl super::VMArea {
     pub fn get_flags_mut(&mut self) -> &mut VMAreaFlags {
         &mut self.flags
     }
+
+    pub fn get_file_backing(&self) -> Option<&VMFileBacking> {
+        self.file_backing.as_ref()
+    }
+
+    /// Fill the memory with the contents of the file, where the part beyond
+    /// the end of the file is left zeroed
+    pub fn load_from_file(&mut self, mut file_backing: VMFileBacking) -> Result<(), Error> {
+        let mem =
+            unsafe { std::slice::from_raw_parts_mut(self.get_start() as *mut u8, self.get_size()) };
+        let mut loaded_len = 0;
+        while loaded_len < mem.len() {
+            let file_offset = file_backing.offset + loaded_len;
+            let read_len = file_backing
+                .file
+                .read_at(file_offset, &mut mem[loaded_len..])?;
+            if read_len == 0 {
+                break;
+            }
+            loaded_len += read_len;
+        }
+        if file_backing.is_shared {
+            file_backing.synced_data = mem.to_vec();
+        }
+        self.file_backing = Some(file_backing);
+        Ok(())
+    }
+
+    /// Write the pages in [start, end) that are changed through the mapping
+    /// back to the file if this is a shared file mapping. The pages are
+    /// compared with the ones loaded from or last written to the file, so
+    /// that the data written to the file through other means are not
+    /// overwritten by the unchanged pages. As on Linux, the pages beyond the
+    /// end of the file are not written back.
+    pub fn sync_to_file(&mut self, start: usize, end: usize) -> Result<(), Error> {
+        let vma_start = self.get_start();
+        let start = align_down(start.max(vma_start), PAGE_SIZE);
+        let end = end.min(self.get_end());
+        let can_write = self.flags.can_write();
+        let file_backing = match self.file_backing {
+            Some(ref mut file_backing) if file_backing.is_shared => file_backing,
+            _ => return Ok(()),
+        };
+        if !can_write {
+            return Ok(());
+        }
+        let file = &file_backing.file;
+        let file_end = {
+            let file_size = file.metadata()?.size;
+            vma_start + file_size.saturating_sub(file_backing.offset)
+        };
+
+        let mut addr = start;
+        while addr < end && addr < file_end {
+            let mem_page = unsafe { std::slice::from_raw_parts(addr as *const u8, PAGE_SIZE) };
+            let page_offset = addr - vma_start;
+            let synced_page = &mut file_backing.synced_data[page_offset..page_offset + PAGE_SIZE];
+            if mem_page != &synced_page[..] {
+                let file_offset = file_backing.offset + page_offset;
+                let len = PAGE_SIZE.min(file_end - addr);
+                file.write_at(file_offset, &mem_page[..len])?;
+                synced_page.copy_from_slice(mem_page);
+            }
+            addr += PAGE_SIZE;
+        }
+        Ok(())
+    }
+
+    pub fn sync_all_to_file(&mut self) -> Result<(), Error> {
+        self.sync_to_file(self.get_start(), self.get_end())
+    }
+}
+
+impl VMFileBacking {
+    /// Check whether the file can be mapped with the protection, which
+    /// requires a regular file open for reading, and also for writing if the
+    /// mapping is shared and writable
+    pub fn new(
+        file: FileRef,
+        offset: off_t,
+        is_shared: bool,
+        prot: VMAreaFlags,
+    ) -> Result<VMFileBacking, Error> {
+        if offset < 0 || offset as usize % PAGE_SIZE != 0 {
+            return errno!(EINVAL, "Offset is not page-aligned");
+        }
+        if file.metadata()?.type_ != FileType::File {
+            return errno!(ENODEV, "Only regular files can be mapped");
+        }
+        let access_mode = file.get_status_flags()? & O_ACCMODE;
+        if access_mode == O_WRONLY {
+            return errno!(EACCES, "File is not open for reading");
+        }
+        if is_shared && prot.can_write() && access_mode != O_RDWR {
+            return errno!(EACCES, "File is not open for writing");
+        }
+        Ok(VMFileBacking {
+            file,
+            offset: offset as usize,
+            is_shared,
+            synced_data: Vec::new(),
+        })
+    }
 }
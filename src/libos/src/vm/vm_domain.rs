@@ -18,6 +18,7 @@ impl VMDomain {
         Ok(VMArea {
             range: new_range,
             flags: flags,
+            file_backing: None,
         })
     }
 
@@ -27,6 +27,10 @@ int futex_wait(volatile int* uaddr, int val) {
     return futex(uaddr, FUTEX_WAIT, val, NULL, NULL, 0);
 }
 
+int futex_wait_timeout(volatile int* uaddr, int val, struct timespec* timeout) {
+    return futex(uaddr, FUTEX_WAIT, val, timeout, NULL, 0);
+}
+
 int futex_wakeup(volatile int* uaddr) {
     return futex(uaddr, FUTEX_WAKE, INT_MAX, NULL, NULL, 0);
 }
@@ -4,6 +4,8 @@
 #include <sys/time.h>
 
 int futex_wait(volatile int* uaddr, int val);
+// Return -ETIMEDOUT if the timeout elapses
+int futex_wait_timeout(volatile int* uaddr, int val, struct timespec* timeout);
 int futex_wakeup(volatile int* uaddr);
 
 #endif /* __ATOMIC_H_ */
@@ -0,0 +1,20 @@
+#include <fcntl.h>
+#include <stdint.h>
+#include <unistd.h>
+
+/*
+ * OCalls that access plain files on the host for HostFS. The LibOS verifies
+ * whatever is read through these OCalls, so no checks are needed here.
+ */
+
+int ocall_open_host_file(const char* path) {
+    return open(path, O_RDONLY | O_CLOEXEC);
+}
+
+int64_t ocall_pread_host_file(int fd, void* buf, size_t buf_size, int64_t offset) {
+    return pread(fd, buf, buf_size, offset);
+}
+
+void ocall_close_host_file(int fd) {
+    close(fd);
+}
@@ -0,0 +1,145 @@
+#define _GNU_SOURCE
+#include <errno.h>
+#include <fcntl.h>
+#include <poll.h>
+#include <stdint.h>
+#include <sys/socket.h>
+#include <unistd.h>
+
+/*
+ * OCalls that operate on the sockets of the host for the AF_INET and AF_INET6
+ * sockets of the LibOS. The sockets are close-on-exec, while O_NONBLOCK
+ * follows the status flags of the sockets in the LibOS, so blocking calls
+ * block in the host. The data sent through these OCalls is not protected.
+ *
+ * On failure, these OCalls return the negated errno of the host.
+ */
+
+int ocall_create_host_socket(int domain, int type, int protocol) {
+    int fd = socket(domain, type | SOCK_CLOEXEC, protocol);
+    if (fd < 0) {
+        return -errno;
+    }
+    return fd;
+}
+
+int ocall_bind_host_socket(int fd, const void* addr, uint32_t addr_len) {
+    if (bind(fd, (const struct sockaddr*)addr, addr_len) < 0) {
+        return -errno;
+    }
+    return 0;
+}
+
+int ocall_listen_host_socket(int fd, int backlog) {
+    if (listen(fd, backlog) < 0) {
+        return -errno;
+    }
+    return 0;
+}
+
+/* The length of the address may be larger than the buffer if truncated */
+int ocall_accept_host_socket(int fd, void* addr, uint32_t addr_buf_len,
+                             uint32_t* addr_len, int flags) {
+    socklen_t len = addr_buf_len;
+    int new_fd = accept4(fd, (struct sockaddr*)addr, &len, flags | SOCK_CLOEXEC);
+    if (new_fd < 0) {
+        return -errno;
+    }
+    *addr_len = len;
+    return new_fd;
+}
+
+int ocall_connect_host_socket(int fd, const void* addr, uint32_t addr_len) {
+    if (connect(fd, (const struct sockaddr*)addr, addr_len) < 0) {
+        return -errno;
+    }
+    return 0;
+}
+
+/* Get the address of the socket, or that of the peer if is_peer is set */
+int ocall_get_host_socket_name(int fd, int is_peer, void* addr,
+                               uint32_t addr_buf_len, uint32_t* addr_len) {
+    socklen_t len = addr_buf_len;
+    int ret = is_peer ? getpeername(fd, (struct sockaddr*)addr, &len)
+                      : getsockname(fd, (struct sockaddr*)addr, &len);
+    if (ret < 0) {
+        return -errno;
+    }
+    *addr_len = len;
+    return 0;
+}
+
+/*
+ * Send to the connected peer if addr is NULL. SIGPIPE is never raised, which
+ * would kill the whole host process.
+ */
+int64_t ocall_send_host_socket(int fd, const void* buf, size_t buf_size, int flags,
+                               const void* addr, uint32_t addr_len) {
+    ssize_t ret = sendto(fd, buf, buf_size, flags | MSG_NOSIGNAL, (const struct sockaddr*)addr,
+                         addr == NULL ? 0 : addr_len);
+    if (ret < 0) {
+        return -errno;
+    }
+    return ret;
+}
+
+int64_t ocall_recv_host_socket(int fd, void* buf, size_t buf_size, int flags,
+                               void* addr, uint32_t addr_buf_len, uint32_t* addr_len) {
+    socklen_t len = addr_buf_len;
+    ssize_t ret = recvfrom(fd, buf, buf_size, flags, (struct sockaddr*)addr, &len);
+    if (ret < 0) {
+        return -errno;
+    }
+    *addr_len = len;
+    return ret;
+}
+
+int ocall_shutdown_host_socket(int fd, int how) {
+    if (shutdown(fd, how) < 0) {
+        return -errno;
+    }
+    return 0;
+}
+
+int ocall_get_host_socket_opt(int fd, int level, int name, void* value,
+                              uint32_t value_buf_len, uint32_t* value_len) {
+    socklen_t len = value_buf_len;
+    if (getsockopt(fd, level, name, value, &len) < 0) {
+        return -errno;
+    }
+    *value_len = len;
+    return 0;
+}
+
+int ocall_set_host_socket_opt(int fd, int level, int name, const void* value,
+                              uint32_t value_len) {
+    if (setsockopt(fd, level, name, value, value_len) < 0) {
+        return -errno;
+    }
+    return 0;
+}
+
+int ocall_set_host_socket_nonblocking(int fd, int is_nonblocking) {
+    int flags = fcntl(fd, F_GETFL);
+    if (flags < 0) {
+        return -errno;
+    }
+    flags = is_nonblocking ? (flags | O_NONBLOCK) : (flags & ~O_NONBLOCK);
+    if (fcntl(fd, F_SETFL, flags) < 0) {
+        return -errno;
+    }
+    return 0;
+}
+
+/* Get the events ready on the socket, i.e., the revents of poll */
+int ocall_poll_host_socket(int fd) {
+    struct pollfd poll_fd = { .fd = fd, .events = POLLIN | POLLPRI | POLLOUT };
+    if (poll(&poll_fd, 1, 0) < 0) {
+        return -errno;
+    }
+    return poll_fd.revents;
+}
+
+void ocall_close_host_socket(int fd) {
+    close(fd);
+}
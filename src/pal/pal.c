@@ -1,10 +1,14 @@
 #include <stdio.h>
+#include <stdlib.h>
+#include <errno.h>
 #include <string.h>
 #include <assert.h>
 
 #include <unistd.h>
 #include <pwd.h>
+#include <sys/ioctl.h>
 #include <sys/time.h>
+#include <poll.h>
 
 #define MAX_PATH FILENAME_MAX
 
@@ -193,6 +197,55 @@ void ocall_print_string(const char* msg) {
     printf("%s", msg);
 }
 
+// Stderr is unbuffered, so the data is written to the host right away
+int64_t ocall_write_stderr(const void* buf, size_t buf_size) {
+    ssize_t ret = write(STDERR_FILENO, buf, buf_size);
+    if (ret < 0) {
+        return -errno;
+    }
+    return ret;
+}
+
+// Stdin is not buffered in the enclave, so whether it is readable can be
+// told by polling it on the host
+int64_t ocall_read_stdin(void* buf, size_t buf_size, int is_nonblocking) {
+    if (is_nonblocking) {
+        struct pollfd poll_fd = { .fd = STDIN_FILENO, .events = POLLIN };
+        int num_ready = poll(&poll_fd, 1, 0);
+        if (num_ready < 0) {
+            return -errno;
+        }
+        if (num_ready == 0) {
+            return -EAGAIN;
+        }
+    }
+    ssize_t ret = read(STDIN_FILENO, buf, buf_size);
+    if (ret < 0) {
+        return -errno;
+    }
+    return ret;
+}
+
+// Get the events ready on stdin, i.e., the revents of poll
+int ocall_poll_stdin(void) {
+    struct pollfd poll_fd = { .fd = STDIN_FILENO, .events = POLLIN };
+    if (poll(&poll_fd, 1, 0) < 0) {
+        return -errno;
+    }
+    return poll_fd.revents;
+}
+
+/*
+ * Do an ioctl command on a host fd, e.g., to query the terminal of stdin,
+ * whose argument is only written by the host
+ */
+int ocall_ioctl_host_fd(int fd, uint32_t cmd, void* arg, size_t arg_size) {
+    if (ioctl(fd, cmd, arg) < 0) {
+        return -errno;
+    }
+    return 0;
+}
+
 int ocall_run_new_task(void) {
     int ret = run_new_task(global_eid);
     return ret;
@@ -209,6 +262,50 @@ void ocall_sync(void) {
     sync();
 }
 
+int ocall_getcwd(char* buf, size_t buf_size) {
+    if (getcwd(buf, buf_size) == NULL) {
+        return -1;
+    }
+    return 0;
+}
+
+
+// Parse a 128-bit key in 32 hex digits
+static int parse_sgxfs_key(const char* key_hex, uint8_t* key) {
+    if (strlen(key_hex) != 32) {
+        return -1;
+    }
+    for (int i = 0; i < 16; i++) {
+        unsigned int byte;
+        if (sscanf(key_hex + 2 * i, "%2x", &byte) != 1) {
+            return -1;
+        }
+        key[i] = (uint8_t)byte;
+    }
+    return 0;
+}
+
+// Supply the key of protected files, if any, from the environment variable
+static int set_sgxfs_key(void) {
+    const char* key_hex = getenv("OCCLUM_SGXFS_KEY");
+    if (key_hex == NULL) {
+        return 0;
+    }
+
+    uint8_t key[16];
+    if (parse_sgxfs_key(key_hex, key) < 0) {
+        printf("ERROR: OCCLUM_SGXFS_KEY must be a 128-bit key in 32 hex digits\n");
+        return -1;
+    }
+    int status = 0;
+    sgx_status_t sgx_ret = libos_set_sgxfs_key(global_eid, &status, key);
+    memset(key, 0, sizeof(key));
+    if (sgx_ret != SGX_SUCCESS) {
+        print_error_message(sgx_ret);
+        return -1;
+    }
+    return status;
+}
 
 /* Application entry */
 int SGX_CDECL main(int argc, const char *argv[])
@@ -233,11 +330,20 @@ int SGX_CDECL main(int argc, const char *argv[])
         return -1;
     }
 
+    if (set_sgxfs_key() < 0) {
+        printf("ERROR: failed to supply the key of protected files\n");
+        return -1;
+    }
+
     sgx_ret = libos_boot(global_eid, &status, executable_path, &argv[2]);
     if(sgx_ret != SGX_SUCCESS) {
         print_error_message(sgx_ret);
         return status;
     }
+    if (status != 0) {
+        printf("ERROR: failed to boot the LibOS\n");
+        return status;
+    }
 
     status = wait_all_tasks();
 
@@ -0,0 +1,140 @@
+#include <dirent.h>
+#include <stdio.h>
+#include <errno.h>
+#include <fcntl.h>
+#include <stdint.h>
+#include <string.h>
+#include <sys/stat.h>
+#include <unistd.h>
+#include "Enclave_u.h"
+
+/*
+ * OCalls that manage the directories on the host for SgxFS. The contents of
+ * files are protected by the LibOS, but the directory structure is not.
+ *
+ * On failure, these OCalls return the negated errno of the host.
+ */
+
+/* Symbolic links are not followed, as they are resolved by the LibOS */
+int ocall_stat_host_file(const char* path, struct host_stat_t* stat_buf) {
+    struct stat st;
+    if (lstat(path, &st) < 0) {
+        return -errno;
+    }
+    stat_buf->dev = st.st_dev;
+    stat_buf->ino = st.st_ino;
+    stat_buf->mode = st.st_mode;
+    stat_buf->nlink = st.st_nlink;
+    stat_buf->size = st.st_size;
+    stat_buf->atime_sec = st.st_atim.tv_sec;
+    stat_buf->atime_nsec = st.st_atim.tv_nsec;
+    stat_buf->mtime_sec = st.st_mtim.tv_sec;
+    stat_buf->mtime_nsec = st.st_mtim.tv_nsec;
+    stat_buf->ctime_sec = st.st_ctim.tv_sec;
+    stat_buf->ctime_nsec = st.st_ctim.tv_nsec;
+    return 0;
+}
+
+/* Create an empty file, which must not exist */
+int ocall_create_host_file(const char* path, uint32_t mode) {
+    int fd = open(path, O_WRONLY | O_CREAT | O_EXCL, mode);
+    if (fd < 0) {
+        return -errno;
+    }
+    close(fd);
+    return 0;
+}
+
+int ocall_chmod_host_file(const char* path, uint32_t mode) {
+    if (chmod(path, mode) < 0) {
+        return -errno;
+    }
+    return 0;
+}
+
+int ocall_mkdir_host_dir(const char* path, uint32_t mode) {
+    if (mkdir(path, mode) < 0) {
+        return -errno;
+    }
+    return 0;
+}
+
+int ocall_rmdir_host_dir(const char* path) {
+    if (rmdir(path) < 0) {
+        return -errno;
+    }
+    return 0;
+}
+
+int ocall_unlink_host_file(const char* path) {
+    if (unlink(path) < 0) {
+        return -errno;
+    }
+    return 0;
+}
+
+int ocall_rename_host_file(const char* old_path, const char* new_path) {
+    if (rename(old_path, new_path) < 0) {
+        return -errno;
+    }
+    return 0;
+}
+
+int ocall_link_host_file(const char* old_path, const char* new_path) {
+    if (link(old_path, new_path) < 0) {
+        return -errno;
+    }
+    return 0;
+}
+
+int ocall_symlink_host_file(const char* target, const char* path) {
+    if (symlink(target, path) < 0) {
+        return -errno;
+    }
+    return 0;
+}
+
+int64_t ocall_readlink_host_file(const char* path, char* buf, size_t buf_size) {
+    ssize_t len = readlink(path, buf, buf_size);
+    if (len < 0) {
+        return -errno;
+    }
+    return len;
+}
+
+/*
+ * Read all the entries (except "." and "..") of a directory into the buffer.
+ * Each entry is encoded as a 64-bit inode number, an 8-bit d_type, followed
+ * by a null-terminated name.
+ *
+ * Return the number of bytes needed to hold all the entries, which may be
+ * greater than buf_size; in that case, the buffer holds only some entries.
+ */
+int64_t ocall_read_host_dir(const char* path, void* buf, size_t buf_size) {
+    DIR* dir = opendir(path);
+    if (dir == NULL) {
+        return -errno;
+    }
+
+    int64_t total_len = 0;
+    struct dirent* entry;
+    while ((entry = readdir(dir)) != NULL) {
+        if (strcmp(entry->d_name, ".") == 0 || strcmp(entry->d_name, "..") == 0) {
+            continue;
+        }
+
+        size_t name_len = strlen(entry->d_name) + 1;
+        size_t entry_len = sizeof(uint64_t) + sizeof(uint8_t) + name_len;
+        if (total_len + entry_len <= buf_size) {
+            char* entry_buf = (char*)buf + total_len;
+            uint64_t ino = entry->d_ino;
+            uint8_t type = entry->d_type;
+            memcpy(entry_buf, &ino, sizeof(ino));
+            memcpy(entry_buf + sizeof(ino), &type, sizeof(type));
+            memcpy(entry_buf + sizeof(ino) + sizeof(type), entry->d_name, name_len);
+        }
+        total_len += entry_len;
+    }
+    closedir(dir);
+    return total_len;
+}
@@ -0,0 +1,73 @@
+#include <stdlib.h>
+#include <stdint.h>
+#include <errno.h>
+#include <pthread.h>
+#include "futex.h"
+
+// The event on which a thread of the enclave sleeps until another thread
+// wakes it up. Unlike the untrusted events of the SGX SDK, an event can be
+// waited with a timeout.
+struct thread_event {
+    const void* thread;
+    volatile int is_set;
+    struct thread_event* next;
+};
+
+static struct thread_event* thread_events = NULL;
+static pthread_mutex_t thread_events_lock = PTHREAD_MUTEX_INITIALIZER;
+
+static struct thread_event* get_thread_event(const void* thread) {
+    pthread_mutex_lock(&thread_events_lock);
+    struct thread_event* event = thread_events;
+    while (event != NULL && event->thread != thread) {
+        event = event->next;
+    }
+    if (event == NULL) {
+        event = calloc(1, sizeof(*event));
+        if (event != NULL) {
+            event->thread = thread;
+            event->next = thread_events;
+            thread_events = event;
+        }
+    }
+    pthread_mutex_unlock(&thread_events_lock);
+    return event;
+}
+
+// Wait until the event is set, or the timeout elapses if it is not negative.
+// The event may be set before the thread starts waiting, in which case the
+// thread returns immediately.
+int ocall_wait_thread_event(const void* self_thread, int64_t timeout_us) {
+    struct thread_event* event = get_thread_event(self_thread);
+    if (event == NULL) {
+        return -ENOMEM;
+    }
+
+    struct timespec timeout = {
+        .tv_sec = timeout_us / 1000000,
+        .tv_nsec = (timeout_us % 1000000) * 1000,
+    };
+    struct timespec* timeout_ptr = timeout_us < 0 ? NULL : &timeout;
+    while (!__sync_bool_compare_and_swap(&event->is_set, 1, 0)) {
+        int ret = futex_wait_timeout(&event->is_set, 0, timeout_ptr);
+        if (ret == -ETIMEDOUT) {
+            // The event may be set right after the timeout
+            if (__sync_bool_compare_and_swap(&event->is_set, 1, 0)) {
+                break;
+            }
+            return -ETIMEDOUT;
+        }
+    }
+    return 0;
+}
+
+int ocall_set_thread_event(const void* thread) {
+    struct thread_event* event = get_thread_event(thread);
+    if (event == NULL) {
+        return -ENOMEM;
+    }
+
+    __sync_lock_test_and_set(&event->is_set, 1);
+    futex_wakeup(&event->is_set);
+    return 0;
+}
@@ -0,0 +1,117 @@
+#include <sys/types.h>
+#include <sys/stat.h>
+#include <fcntl.h>
+#include <unistd.h>
+#include <string.h>
+#include <errno.h>
+#include <stdio.h>
+
+#define BUF_SIZE    64
+
+static int is_all_zeros(const char* buf, size_t len) {
+    for (size_t i = 0; i < len; i++) {
+        if (buf[i] != 0) {
+            return 0;
+        }
+    }
+    return 1;
+}
+
+static int test_dev_null(void) {
+    char buf[BUF_SIZE];
+    int fd;
+
+    if ((fd = open("/dev/null", O_RDWR)) < 0) {
+        printf("ERROR: failed to open /dev/null\n");
+        return -1;
+    }
+    if (write(fd, buf, sizeof(buf)) != sizeof(buf)) {
+        printf("ERROR: failed to write to /dev/null\n");
+        return -1;
+    }
+    if (read(fd, buf, sizeof(buf)) != 0) {
+        printf("ERROR: read from /dev/null does not return EOF\n");
+        return -1;
+    }
+    close(fd);
+    return 0;
+}
+
+static int test_dev_zero(void) {
+    char buf[BUF_SIZE];
+    int fd;
+
+    memset(buf, 0xff, sizeof(buf));
+    if ((fd = open("/dev/zero", O_RDONLY)) < 0) {
+        printf("ERROR: failed to open /dev/zero\n");
+        return -1;
+    }
+    if (read(fd, buf, sizeof(buf)) != sizeof(buf) || !is_all_zeros(buf, sizeof(buf))) {
+        printf("ERROR: read from /dev/zero does not return zeros\n");
+        return -1;
+    }
+    close(fd);
+    return 0;
+}
+
+static int test_dev_full(void) {
+    char buf[BUF_SIZE] = {0};
+    int fd;
+
+    if ((fd = open("/dev/full", O_WRONLY)) < 0) {
+        printf("ERROR: failed to open /dev/full\n");
+        return -1;
+    }
+    if (write(fd, buf, sizeof(buf)) >= 0 || errno != ENOSPC) {
+        printf("ERROR: write to /dev/full does not fail with ENOSPC\n");
+        return -1;
+    }
+    close(fd);
+    return 0;
+}
+
+static int test_dev_random(const char* dev_path) {
+    char buf0[BUF_SIZE] = {0}, buf1[BUF_SIZE] = {0};
+    int fd;
+
+    if ((fd = open(dev_path, O_RDONLY)) < 0) {
+        printf("ERROR: failed to open %s\n", dev_path);
+        return -1;
+    }
+    if (read(fd, buf0, sizeof(buf0)) != sizeof(buf0) ||
+        read(fd, buf1, sizeof(buf1)) != sizeof(buf1)) {
+        printf("ERROR: failed to read from %s\n", dev_path);
+        return -1;
+    }
+    if (is_all_zeros(buf0, sizeof(buf0)) || memcmp(buf0, buf1, sizeof(buf0)) == 0) {
+        printf("ERROR: the bytes read from %s are not random\n", dev_path);
+        return -1;
+    }
+    close(fd);
+    return 0;
+}
+
+int main(int argc, const char* argv[]) {
+    struct stat stat_buf;
+
+    if (test_dev_null() < 0 || test_dev_zero() < 0 || test_dev_full() < 0 ||
+        test_dev_random("/dev/random") < 0 || test_dev_random("/dev/urandom") < 0) {
+        return -1;
+    }
+
+    if (stat("/dev/null", &stat_buf) < 0 || !S_ISCHR(stat_buf.st_mode)) {
+        printf("ERROR: /dev/null is not a character device\n");
+        return -1;
+    }
+    if (open("/dev/non_existing", O_RDONLY) >= 0) {
+        printf("ERROR: opened a non-existing device\n");
+        return -1;
+    }
+    if (open("/dev/new_file", O_WRONLY | O_CREAT, 0666) >= 0) {
+        printf("ERROR: created a file in devfs\n");
+        return -1;
+    }
+
+    printf("Device files work successfully\n");
+    return 0;
+}
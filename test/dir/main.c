@@ -0,0 +1,103 @@
+#include <sys/types.h>
+#include <sys/stat.h>
+#include <dirent.h>
+#include <fcntl.h>
+#include <unistd.h>
+#include <string.h>
+#include <stdio.h>
+
+static int dir_contains(const char* dir_path, const char* name) {
+    DIR* dir = opendir(dir_path);
+    if (dir == NULL) {
+        return -1;
+    }
+    int found = 0;
+    struct dirent* entry;
+    while ((entry = readdir(dir)) != NULL) {
+        if (strcmp(entry->d_name, name) == 0) {
+            found = 1;
+        }
+    }
+    closedir(dir);
+    return found;
+}
+
+static int test_dir(const char* parent_path) {
+    char dir_path[256], sub_dir_path[256];
+    snprintf(dir_path, sizeof(dir_path), "%s/dir_test", parent_path);
+    snprintf(sub_dir_path, sizeof(sub_dir_path), "%s/sub_dir", dir_path);
+
+    if (mkdir(dir_path, 0777) < 0 || mkdir(sub_dir_path, 0777) < 0) {
+        printf("ERROR: failed to create directories in %s\n", parent_path);
+        return -1;
+    }
+    if (mkdir(dir_path, 0777) == 0) {
+        printf("ERROR: created an existing directory\n");
+        return -1;
+    }
+    if (dir_contains(dir_path, "sub_dir") != 1 || dir_contains(dir_path, ".") != 1) {
+        printf("ERROR: failed to list the directory %s\n", dir_path);
+        return -1;
+    }
+    if (rmdir(dir_path) == 0) {
+        printf("ERROR: removed a non-empty directory\n");
+        return -1;
+    }
+    if (rmdir(sub_dir_path) < 0 || rmdir(dir_path) < 0) {
+        printf("ERROR: failed to remove directories in %s\n", parent_path);
+        return -1;
+    }
+    if (dir_contains(parent_path, "dir_test") != 0) {
+        printf("ERROR: the removed directory is still listed\n");
+        return -1;
+    }
+    return 0;
+}
+
+static int test_cwd(void) {
+    char cwd[256];
+    int fd, dir_fd;
+
+    if ((dir_fd = open(".", O_RDONLY | O_DIRECTORY)) < 0) {
+        printf("ERROR: failed to open the cwd\n");
+        return -1;
+    }
+    if (chdir("/tmp") < 0 || getcwd(cwd, sizeof(cwd)) == NULL || strcmp(cwd, "/tmp") != 0) {
+        printf("ERROR: failed to change the cwd to /tmp\n");
+        return -1;
+    }
+    // Relative paths are resolved against the new cwd
+    if ((fd = open("cwd_test.txt", O_WRONLY | O_CREAT, 0666)) < 0) {
+        printf("ERROR: failed to create a file with a relative path\n");
+        return -1;
+    }
+    close(fd);
+    if ((fd = open("/tmp/cwd_test.txt", O_RDONLY)) < 0) {
+        printf("ERROR: the file is not created in the cwd\n");
+        return -1;
+    }
+    close(fd);
+
+    if (fchdir(dir_fd) < 0 || getcwd(cwd, sizeof(cwd)) == NULL || strcmp(cwd, "/tmp") == 0) {
+        printf("ERROR: failed to change the cwd back by fchdir\n");
+        return -1;
+    }
+    close(dir_fd);
+    return 0;
+}
+
+int main(int argc, const char* argv[]) {
+    // A directory in tmpfs
+    if (test_dir("/tmp") < 0) {
+        return -1;
+    }
+    // A directory in the protected file system, relative to the cwd
+    if (test_dir(".") < 0) {
+        return -1;
+    }
+    if (test_cwd() < 0) {
+        return -1;
+    }
+    printf("Directory operations succeed\n");
+    return 0;
+}
@@ -0,0 +1,242 @@
+#include <sys/epoll.h>
+#include <sys/wait.h>
+#include <fcntl.h>
+#include <spawn.h>
+#include <unistd.h>
+#include <string.h>
+#include <errno.h>
+#include <stdio.h>
+
+static const char* write_msg = "Hello World\n";
+
+static int add_pipe(int epfd, int pipe_fds[2], unsigned int events) {
+    if (pipe(pipe_fds) < 0) {
+        printf("ERROR: failed to create a pipe\n");
+        return -1;
+    }
+    struct epoll_event event = { .events = events, .data.fd = pipe_fds[0] };
+    if (epoll_ctl(epfd, EPOLL_CTL_ADD, pipe_fds[0], &event) < 0) {
+        printf("ERROR: failed to add fd %d to the epoll file\n", pipe_fds[0]);
+        return -1;
+    }
+    return 0;
+}
+
+static int expect_events(int epfd, int timeout, int expected_fd) {
+    struct epoll_event events[4];
+    int num_events = epoll_wait(epfd, events, 4, timeout);
+    if (expected_fd < 0) {
+        if (num_events != 0) {
+            printf("ERROR: epoll_wait reports %d unexpected events\n", num_events);
+            return -1;
+        }
+        return 0;
+    }
+    if (num_events != 1 || events[0].data.fd != expected_fd ||
+        (events[0].events & EPOLLIN) == 0) {
+        printf("ERROR: epoll_wait does not report fd %d is readable\n", expected_fd);
+        return -1;
+    }
+    return 0;
+}
+
+static int test_level_triggered(int epfd) {
+    int pipe_fds[2];
+    if (add_pipe(epfd, pipe_fds, EPOLLIN) < 0 ||
+        expect_events(epfd, 0, -1) < 0) {
+        return -1;
+    }
+    write(pipe_fds[1], write_msg, strlen(write_msg));
+    // Reported as long as the pipe is readable
+    if (expect_events(epfd, -1, pipe_fds[0]) < 0 ||
+        expect_events(epfd, 0, pipe_fds[0]) < 0) {
+        return -1;
+    }
+    char buf[32];
+    read(pipe_fds[0], buf, sizeof(buf));
+    if (expect_events(epfd, 10, -1) < 0) {
+        return -1;
+    }
+    if (epoll_ctl(epfd, EPOLL_CTL_DEL, pipe_fds[0], NULL) < 0) {
+        printf("ERROR: failed to delete fd %d from the epoll file\n", pipe_fds[0]);
+        return -1;
+    }
+    close(pipe_fds[0]);
+    close(pipe_fds[1]);
+    return 0;
+}
+
+static int test_edge_triggered(int epfd) {
+    int pipe_fds[2];
+    if (add_pipe(epfd, pipe_fds, EPOLLIN | EPOLLET) < 0) {
+        return -1;
+    }
+    write(pipe_fds[1], write_msg, strlen(write_msg));
+    // Reported only once until more data is written
+    if (expect_events(epfd, -1, pipe_fds[0]) < 0 ||
+        expect_events(epfd, 0, -1) < 0) {
+        return -1;
+    }
+    write(pipe_fds[1], write_msg, strlen(write_msg));
+    if (expect_events(epfd, -1, pipe_fds[0]) < 0) {
+        return -1;
+    }
+    epoll_ctl(epfd, EPOLL_CTL_DEL, pipe_fds[0], NULL);
+    close(pipe_fds[0]);
+    close(pipe_fds[1]);
+    return 0;
+}
+
+static int test_oneshot(int epfd) {
+    int pipe_fds[2];
+    if (add_pipe(epfd, pipe_fds, EPOLLIN | EPOLLONESHOT) < 0) {
+        return -1;
+    }
+    write(pipe_fds[1], write_msg, strlen(write_msg));
+    // Disabled after reported once until modified
+    if (expect_events(epfd, -1, pipe_fds[0]) < 0 ||
+        expect_events(epfd, 0, -1) < 0) {
+        return -1;
+    }
+    struct epoll_event event = { .events = EPOLLIN | EPOLLONESHOT, .data.fd = pipe_fds[0] };
+    if (epoll_ctl(epfd, EPOLL_CTL_MOD, pipe_fds[0], &event) < 0) {
+        printf("ERROR: failed to modify fd %d in the epoll file\n", pipe_fds[0]);
+        return -1;
+    }
+    if (expect_events(epfd, -1, pipe_fds[0]) < 0) {
+        return -1;
+    }
+    epoll_ctl(epfd, EPOLL_CTL_DEL, pipe_fds[0], NULL);
+    close(pipe_fds[0]);
+    close(pipe_fds[1]);
+    return 0;
+}
+
+static int test_wakeup(int epfd) {
+    int pipe_fds[2];
+    if (add_pipe(epfd, pipe_fds, EPOLLIN) < 0) {
+        return -1;
+    }
+
+    // The child writes to the pipe while the parent sleeps in epoll_wait
+    posix_spawn_file_actions_t file_actions;
+    posix_spawn_file_actions_init(&file_actions);
+    posix_spawn_file_actions_adddup2(&file_actions, pipe_fds[1], STDOUT_FILENO);
+    posix_spawn_file_actions_addclose(&file_actions, pipe_fds[0]);
+    const char* child_prog = "hello_world/bin.encrypted";
+    const char* child_argv[2] = { child_prog, NULL };
+    int child_pid;
+    if (posix_spawn(&child_pid, child_prog, &file_actions,
+            NULL, (char* const*)child_argv, NULL) < 0) {
+        printf("ERROR: failed to spawn a child process\n");
+        return -1;
+    }
+    close(pipe_fds[1]);
+
+    if (expect_events(epfd, -1, pipe_fds[0]) < 0) {
+        return -1;
+    }
+    int status;
+    if (wait4(child_pid, &status, 0, NULL) < 0) {
+        printf("ERROR: failed to wait4 the child process\n");
+        return -1;
+    }
+    // A closed file is removed from the interest list
+    close(pipe_fds[0]);
+    if (expect_events(epfd, 0, -1) < 0) {
+        return -1;
+    }
+    return 0;
+}
+
+static int test_ctl_errors(int epfd) {
+    int pipe_fds[2];
+    if (add_pipe(epfd, pipe_fds, EPOLLIN) < 0) {
+        return -1;
+    }
+    struct epoll_event event = { .events = EPOLLIN };
+    struct epoll_event events[1];
+    if (epoll_ctl(epfd, EPOLL_CTL_ADD, pipe_fds[0], &event) >= 0 || errno != EEXIST ||
+        epoll_ctl(epfd, EPOLL_CTL_DEL, pipe_fds[1], NULL) >= 0 || errno != ENOENT ||
+        epoll_ctl(epfd, EPOLL_CTL_ADD, epfd, &event) >= 0 || errno != EINVAL ||
+        epoll_ctl(pipe_fds[1], EPOLL_CTL_ADD, pipe_fds[0], &event) >= 0 || errno != EINVAL ||
+        epoll_wait(epfd, events, 0, 0) >= 0 || errno != EINVAL) {
+        printf("ERROR: epoll_ctl or epoll_wait does not fail as expected\n");
+        return -1;
+    }
+    epoll_ctl(epfd, EPOLL_CTL_DEL, pipe_fds[0], NULL);
+    close(pipe_fds[0]);
+    close(pipe_fds[1]);
+    return 0;
+}
+
+// An epoll file cannot watch itself through another fd, nor can epoll files
+// watch each other
+static int test_nesting(int epfd) {
+    struct epoll_event event = { .events = EPOLLIN };
+    int dup_epfd = dup(epfd);
+    if (dup_epfd < 0) {
+        printf("ERROR: failed to dup the epoll file\n");
+        return -1;
+    }
+    if (epoll_ctl(epfd, EPOLL_CTL_ADD, dup_epfd, &event) >= 0 || errno != EINVAL ||
+        epoll_ctl(dup_epfd, EPOLL_CTL_ADD, epfd, &event) >= 0 || errno != EINVAL) {
+        printf("ERROR: adding a dup of the epoll file to itself does not fail with EINVAL\n");
+        return -1;
+    }
+    close(dup_epfd);
+
+    int other_epfd = epoll_create1(0);
+    if (other_epfd < 0) {
+        printf("ERROR: failed to create an epoll file\n");
+        return -1;
+    }
+    if (epoll_ctl(epfd, EPOLL_CTL_ADD, other_epfd, &event) < 0) {
+        printf("ERROR: failed to add an epoll file to another\n");
+        return -1;
+    }
+    if (epoll_ctl(other_epfd, EPOLL_CTL_ADD, epfd, &event) >= 0 || errno != ELOOP) {
+        printf("ERROR: adding epoll files to each other does not fail with ELOOP\n");
+        return -1;
+    }
+    // The nested epoll file is readable once the file it watches is
+    int pipe_fds[2];
+    if (add_pipe(other_epfd, pipe_fds, EPOLLIN) < 0 ||
+        write(pipe_fds[1], write_msg, strlen(write_msg)) != strlen(write_msg)) {
+        return -1;
+    }
+    struct epoll_event events[1];
+    if (epoll_wait(epfd, events, 1, 1000) != 1) {
+        printf("ERROR: the nested epoll file is not readable\n");
+        return -1;
+    }
+    epoll_ctl(epfd, EPOLL_CTL_DEL, other_epfd, NULL);
+    close(other_epfd);
+    close(pipe_fds[0]);
+    close(pipe_fds[1]);
+    return 0;
+}
+
+int main(int argc, const char* argv[]) {
+    int epfd = epoll_create1(EPOLL_CLOEXEC);
+    if (epfd < 0) {
+        printf("ERROR: failed to create an epoll file\n");
+        return -1;
+    }
+    if ((fcntl(epfd, F_GETFD) & FD_CLOEXEC) == 0) {
+        printf("ERROR: EPOLL_CLOEXEC is not set\n");
+        return -1;
+    }
+
+    if (test_level_triggered(epfd) < 0 ||
+        test_edge_triggered(epfd) < 0 ||
+        test_oneshot(epfd) < 0 ||
+        test_wakeup(epfd) < 0 ||
+        test_ctl_errors(epfd) < 0 ||
+        test_nesting(epfd) < 0) {
+        return -1;
+    }
+    close(epfd);
+    printf("Epoll works successfully\n");
+    return 0;
+}
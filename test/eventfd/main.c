@@ -0,0 +1,135 @@
+#include <sys/eventfd.h>
+#include <sys/epoll.h>
+#include <fcntl.h>
+#include <poll.h>
+#include <unistd.h>
+#include <stdint.h>
+#include <errno.h>
+#include <stdio.h>
+
+static int write_counter(int fd, uint64_t val) {
+    if (write(fd, &val, sizeof(val)) != sizeof(val)) {
+        printf("ERROR: failed to write %lu to the eventfd\n", val);
+        return -1;
+    }
+    return 0;
+}
+
+static int expect_counter(int fd, uint64_t expected_val) {
+    uint64_t val = 0;
+    if (read(fd, &val, sizeof(val)) != sizeof(val) || val != expected_val) {
+        printf("ERROR: read %lu from the eventfd, expected %lu\n", val, expected_val);
+        return -1;
+    }
+    return 0;
+}
+
+static int test_counter(void) {
+    int fd = eventfd(0, 0);
+    if (fd < 0) {
+        printf("ERROR: failed to create an eventfd\n");
+        return -1;
+    }
+    if (write_counter(fd, 3) < 0 ||
+        write_counter(fd, 4) < 0 ||
+        expect_counter(fd, 7) < 0) {
+        return -1;
+    }
+    close(fd);
+    return 0;
+}
+
+static int test_semaphore(void) {
+    int fd = eventfd(2, EFD_SEMAPHORE | EFD_NONBLOCK);
+    if (fd < 0) {
+        printf("ERROR: failed to create an eventfd\n");
+        return -1;
+    }
+    if (expect_counter(fd, 1) < 0 ||
+        expect_counter(fd, 1) < 0) {
+        return -1;
+    }
+    uint64_t val;
+    if (read(fd, &val, sizeof(val)) >= 0 || errno != EAGAIN) {
+        printf("ERROR: reading an empty non-blocking eventfd does not fail with EAGAIN\n");
+        return -1;
+    }
+    close(fd);
+    return 0;
+}
+
+static int test_cloexec(void) {
+    int fd = eventfd(0, EFD_CLOEXEC);
+    if (fd < 0 || (fcntl(fd, F_GETFD) & FD_CLOEXEC) == 0) {
+        printf("ERROR: EFD_CLOEXEC is not set\n");
+        return -1;
+    }
+    close(fd);
+    return 0;
+}
+
+static int test_invalid_args(void) {
+    int fd = eventfd(0, 0);
+    if (fd < 0) {
+        printf("ERROR: failed to create an eventfd\n");
+        return -1;
+    }
+    uint32_t small_buf = 1;
+    uint64_t too_large = UINT64_MAX;
+    if (read(fd, &small_buf, sizeof(small_buf)) >= 0 || errno != EINVAL ||
+        write(fd, &small_buf, sizeof(small_buf)) >= 0 || errno != EINVAL ||
+        write(fd, &too_large, sizeof(too_large)) >= 0 || errno != EINVAL ||
+        eventfd(0, O_APPEND) >= 0 || errno != EINVAL) {
+        printf("ERROR: eventfd does not fail with EINVAL as expected\n");
+        return -1;
+    }
+    close(fd);
+    return 0;
+}
+
+static int test_poll(void) {
+    int fd = eventfd(0, 0);
+    if (fd < 0) {
+        printf("ERROR: failed to create an eventfd\n");
+        return -1;
+    }
+    struct pollfd poll_fd = { .fd = fd, .events = POLLIN };
+    if (poll(&poll_fd, 1, 0) != 0) {
+        printf("ERROR: poll reports an empty eventfd is readable\n");
+        return -1;
+    }
+
+    int epfd = epoll_create1(0);
+    struct epoll_event event = { .events = EPOLLIN, .data.fd = fd };
+    if (epfd < 0 || epoll_ctl(epfd, EPOLL_CTL_ADD, fd, &event) < 0) {
+        printf("ERROR: failed to add the eventfd to an epoll file\n");
+        return -1;
+    }
+    if (write_counter(fd, 1) < 0) {
+        return -1;
+    }
+    if (poll(&poll_fd, 1, -1) != 1 || (poll_fd.revents & POLLIN) == 0) {
+        printf("ERROR: poll does not report the eventfd is readable\n");
+        return -1;
+    }
+    struct epoll_event events[1];
+    if (epoll_wait(epfd, events, 1, -1) != 1 || events[0].data.fd != fd) {
+        printf("ERROR: epoll_wait does not report the eventfd is readable\n");
+        return -1;
+    }
+    close(epfd);
+    close(fd);
+    return 0;
+}
+
+int main(int argc, const char* argv[]) {
+    if (test_counter() < 0 ||
+        test_semaphore() < 0 ||
+        test_cloexec() < 0 ||
+        test_invalid_args() < 0 ||
+        test_poll() < 0) {
+        return -1;
+    }
+    printf("Eventfd works successfully\n");
+    return 0;
+}
@@ -0,0 +1,126 @@
+#include <sys/types.h>
+#include <sys/stat.h>
+#include <fcntl.h>
+#include <unistd.h>
+#include <string.h>
+#include <stdio.h>
+
+static int test_fd_flags(int fd) {
+    int new_fd;
+
+    if (fcntl(fd, F_GETFD) != 0) {
+        printf("ERROR: FD_CLOEXEC is set unexpectedly\n");
+        return -1;
+    }
+    if (fcntl(fd, F_SETFD, FD_CLOEXEC) < 0 || fcntl(fd, F_GETFD) != FD_CLOEXEC) {
+        printf("ERROR: failed to set FD_CLOEXEC\n");
+        return -1;
+    }
+    if (fcntl(fd, F_SETFD, 0) < 0 || fcntl(fd, F_GETFD) != 0) {
+        printf("ERROR: failed to clear FD_CLOEXEC\n");
+        return -1;
+    }
+
+    if ((new_fd = fcntl(fd, F_DUPFD, 10)) < 10) {
+        printf("ERROR: F_DUPFD returns an fd less than the arg\n");
+        return -1;
+    }
+    if (fcntl(new_fd, F_GETFD) != 0) {
+        printf("ERROR: F_DUPFD sets FD_CLOEXEC on the new fd\n");
+        return -1;
+    }
+    close(new_fd);
+
+    if ((new_fd = fcntl(fd, F_DUPFD_CLOEXEC, 10)) < 10 || fcntl(new_fd, F_GETFD) != FD_CLOEXEC) {
+        printf("ERROR: F_DUPFD_CLOEXEC does not set FD_CLOEXEC on the new fd\n");
+        return -1;
+    }
+    // The fd flags are not shared between duplicated fds
+    if (fcntl(fd, F_GETFD) != 0) {
+        printf("ERROR: F_DUPFD_CLOEXEC changes the flags of the old fd\n");
+        return -1;
+    }
+    close(new_fd);
+    return 0;
+}
+
+static int test_status_flags(int fd) {
+    int dup_fd, flags;
+
+    if ((flags = fcntl(fd, F_GETFL)) < 0 || (flags & O_ACCMODE) != O_RDWR ||
+        (flags & O_APPEND) != 0) {
+        printf("ERROR: F_GETFL returns wrong flags\n");
+        return -1;
+    }
+    if (fcntl(fd, F_SETFL, flags | O_APPEND | O_NONBLOCK) < 0) {
+        printf("ERROR: failed to set the status flags\n");
+        return -1;
+    }
+    // The status flags are shared between duplicated fds
+    if ((dup_fd = dup(fd)) < 0) {
+        printf("ERROR: failed to dup the fd\n");
+        return -1;
+    }
+    flags = fcntl(dup_fd, F_GETFL);
+    if ((flags & O_APPEND) == 0 || (flags & O_NONBLOCK) == 0 || (flags & O_ACCMODE) != O_RDWR) {
+        printf("ERROR: the status flags are not shared between duplicated fds\n");
+        return -1;
+    }
+    close(dup_fd);
+    return 0;
+}
+
+static int test_append(const char* file_name, int fd) {
+    char read_buf[128] = {0};
+
+    if (write(fd, "Hello", 5) != 5 || lseek(fd, 0, SEEK_SET) != 0 ||
+        write(fd, "World", 5) != 5) {
+        printf("ERROR: failed to write to %s\n", file_name);
+        return -1;
+    }
+    if (pread(fd, read_buf, sizeof(read_buf) - 1, 0) != 10 ||
+        strcmp(read_buf, "HelloWorld") != 0) {
+        printf("ERROR: O_APPEND set by fcntl does not take effect\n");
+        return -1;
+    }
+    return 0;
+}
+
+static int test_fcntl(const char* file_name) {
+    int fd;
+    if ((fd = open(file_name, O_RDWR | O_CREAT | O_TRUNC, 0666)) < 0) {
+        printf("ERROR: failed to create %s\n", file_name);
+        return -1;
+    }
+    if (test_fd_flags(fd) < 0 || test_status_flags(fd) < 0 || test_append(file_name, fd) < 0) {
+        return -1;
+    }
+    close(fd);
+    return 0;
+}
+
+int main(int argc, const char* argv[]) {
+    int pipe_fds[2];
+
+    // A file in tmpfs and a protected file
+    if (test_fcntl("/tmp/fcntl_test.txt") < 0 || test_fcntl("fcntl_test.txt") < 0) {
+        return -1;
+    }
+
+    if (pipe(pipe_fds) < 0) {
+        printf("ERROR: failed to create a pipe\n");
+        return -1;
+    }
+    if ((fcntl(pipe_fds[0], F_GETFL) & O_ACCMODE) != O_RDONLY ||
+        (fcntl(pipe_fds[1], F_GETFL) & O_ACCMODE) != O_WRONLY) {
+        printf("ERROR: F_GETFL returns wrong access modes for a pipe\n");
+        return -1;
+    }
+    if (fcntl(pipe_fds[0], 0xdead) >= 0) {
+        printf("ERROR: an unknown fcntl command succeeds\n");
+        return -1;
+    }
+
+    printf("fcntl works successfully\n");
+    return 0;
+}
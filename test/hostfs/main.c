@@ -0,0 +1,98 @@
+#include <sys/stat.h>
+#include <fcntl.h>
+#include <unistd.h>
+#include <string.h>
+#include <errno.h>
+#include <stdio.h>
+
+#define BLOCK_SIZE 4096
+
+// The data of blocks.txt and tampered.txt, i.e., a to z repeated
+static char expected_data(int pos) {
+    return 'a' + pos % 26;
+}
+
+static int test_read_file(void) {
+    const char* hello_msg = "Hello from HostFS\n";
+    char buf[64] = {0};
+    int fd = open("/host/hello.txt", O_RDONLY);
+    if (fd < 0) {
+        printf("ERROR: failed to open a file in the manifest\n");
+        return -1;
+    }
+    if (read(fd, buf, sizeof(buf)) != strlen(hello_msg) || strcmp(buf, hello_msg) != 0) {
+        printf("ERROR: the content of the file is wrong\n");
+        return -1;
+    }
+    close(fd);
+    return 0;
+}
+
+static int test_read_blocks(void) {
+    const int file_size = 10000;
+    char buf[BLOCK_SIZE * 3];
+    struct stat stat_buf;
+    int fd = open("/host/dir/blocks.txt", O_RDONLY);
+    if (fd < 0 || fstat(fd, &stat_buf) < 0 || stat_buf.st_size != file_size) {
+        printf("ERROR: failed to open the file of multiple blocks\n");
+        return -1;
+    }
+    // Read across the boundaries of blocks, up to the end of the file
+    int offset = BLOCK_SIZE - 100;
+    int len = pread(fd, buf, sizeof(buf), offset);
+    if (len != file_size - offset) {
+        printf("ERROR: failed to read the file of multiple blocks\n");
+        return -1;
+    }
+    for (int i = 0; i < len; i++) {
+        if (buf[i] != expected_data(offset + i)) {
+            printf("ERROR: the content at offset %d is wrong\n", offset + i);
+            return -1;
+        }
+    }
+    close(fd);
+    return 0;
+}
+
+static int test_not_in_manifest(void) {
+    if (open("/host/not_in_manifest.txt", O_RDONLY) >= 0 || errno != ENOENT) {
+        printf("ERROR: a file not in the manifest is opened\n");
+        return -1;
+    }
+    if (open("/host/new.txt", O_WRONLY | O_CREAT, 0666) >= 0 || errno != EROFS) {
+        printf("ERROR: a file is created in HostFS\n");
+        return -1;
+    }
+    return 0;
+}
+
+static int test_tampered_block(void) {
+    char buf[BLOCK_SIZE];
+    int fd = open("/host/tampered.txt", O_RDONLY);
+    if (fd < 0) {
+        printf("ERROR: failed to open the tampered file\n");
+        return -1;
+    }
+    // Only the second block is tampered with
+    if (read(fd, buf, sizeof(buf)) != sizeof(buf) || buf[100] != expected_data(100)) {
+        printf("ERROR: failed to read the block that is not tampered with\n");
+        return -1;
+    }
+    if (read(fd, buf, sizeof(buf)) >= 0 || errno != EIO) {
+        printf("ERROR: the tampered block is read without EIO\n");
+        return -1;
+    }
+    close(fd);
+    return 0;
+}
+
+int main(int argc, const char* argv[]) {
+    if (test_read_file() < 0 ||
+        test_read_blocks() < 0 ||
+        test_not_in_manifest() < 0 ||
+        test_tampered_block() < 0) {
+        return -1;
+    }
+    printf("HostFS works successfully\n");
+    return 0;
+}
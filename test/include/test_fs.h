@@ -0,0 +1,81 @@
+// The helpers of the tests of file systems, which print an error and return
+// -1 on failure
+#ifndef __TEST_FS_H__
+#define __TEST_FS_H__
+
+#include <sys/types.h>
+#include <sys/stat.h>
+#include <fcntl.h>
+#include <unistd.h>
+#include <stdlib.h>
+#include <string.h>
+#include <stdio.h>
+
+// Create a file with the data, which replaces the content if the file exists,
+// and return an fd of it open for reading and writing at offset 0
+static inline int create_file(const char* path, const void* data, size_t len) {
+    int fd = open(path, O_RDWR | O_CREAT | O_TRUNC, 0666);
+    if (fd < 0) {
+        printf("ERROR: failed to create %s\n", path);
+        return -1;
+    }
+    if (pwrite(fd, data, len, 0) != len) {
+        printf("ERROR: failed to write to %s\n", path);
+        close(fd);
+        return -1;
+    }
+    return fd;
+}
+
+// The same as create_file, but the file is closed
+static inline int write_file(const char* path, const void* data, size_t len) {
+    int fd = create_file(path, data, len);
+    if (fd < 0) {
+        return -1;
+    }
+    close(fd);
+    return 0;
+}
+
+static inline int check_fd_size(int fd, off_t expected_size) {
+    struct stat stat_buf;
+    if (fstat(fd, &stat_buf) < 0 || stat_buf.st_size != expected_size) {
+        printf("ERROR: the size of fd %d is not %ld\n", fd, (long)expected_size);
+        return -1;
+    }
+    return 0;
+}
+
+// Check the data at the offset of the file
+static inline int check_fd_content(int fd, off_t offset, const void* expected, size_t len) {
+    char* buf = malloc(len + 1);
+    if (buf == NULL) {
+        printf("ERROR: failed to allocate a buffer\n");
+        return -1;
+    }
+    int ret = 0;
+    if (pread(fd, buf, len, offset) != len || memcmp(buf, expected, len) != 0) {
+        printf("ERROR: the content of fd %d at offset %ld is wrong\n", fd, (long)offset);
+        ret = -1;
+    }
+    free(buf);
+    return ret;
+}
+
+// Check that the file has exactly the data
+static inline int check_file(const char* path, const void* expected, size_t len) {
+    int fd = open(path, O_RDONLY);
+    if (fd < 0) {
+        printf("ERROR: failed to open %s\n", path);
+        return -1;
+    }
+    int ret = 0;
+    if (check_fd_size(fd, len) < 0 || check_fd_content(fd, 0, expected, len) < 0) {
+        printf("ERROR: the content of %s is wrong\n", path);
+        ret = -1;
+    }
+    close(fd);
+    return ret;
+}
+
+#endif /* __TEST_FS_H__ */
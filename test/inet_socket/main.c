@@ -0,0 +1,272 @@
+#define _GNU_SOURCE
+#include <sys/socket.h>
+#include <sys/epoll.h>
+#include <sys/uio.h>
+#include <netinet/in.h>
+#include <arpa/inet.h>
+#include <fcntl.h>
+#include <poll.h>
+#include <unistd.h>
+#include <string.h>
+#include <errno.h>
+#include <stdio.h>
+
+static const char* write_msg = "Hello World\n";
+
+static int check_transfer(int sender_fd, int receiver_fd) {
+    char buf[32] = { 0 };
+    if (send(sender_fd, write_msg, strlen(write_msg), 0) != strlen(write_msg) ||
+        recv(receiver_fd, buf, sizeof(buf), 0) != strlen(write_msg) ||
+        strcmp(buf, write_msg) != 0) {
+        printf("ERROR: the message received is not the one sent\n");
+        return -1;
+    }
+    return 0;
+}
+
+// Bind the socket to a free port on the loopback address
+static int bind_loopback(int fd, struct sockaddr_in* addr) {
+    memset(addr, 0, sizeof(*addr));
+    addr->sin_family = AF_INET;
+    addr->sin_addr.s_addr = htonl(INADDR_LOOPBACK);
+    addr->sin_port = 0;
+    socklen_t addr_len = sizeof(*addr);
+    if (bind(fd, (struct sockaddr*)addr, sizeof(*addr)) < 0 ||
+        getsockname(fd, (struct sockaddr*)addr, &addr_len) < 0) {
+        printf("ERROR: failed to bind to the loopback address\n");
+        return -1;
+    }
+    if (addr_len != sizeof(*addr) || addr->sin_port == 0) {
+        printf("ERROR: the address from getsockname is wrong\n");
+        return -1;
+    }
+    return 0;
+}
+
+static int is_same_addr(const struct sockaddr_in* addr0, const struct sockaddr_in* addr1) {
+    return addr0->sin_family == addr1->sin_family &&
+           addr0->sin_port == addr1->sin_port &&
+           addr0->sin_addr.s_addr == addr1->sin_addr.s_addr;
+}
+
+static int test_tcp(void) {
+    int listen_fd = socket(AF_INET, SOCK_STREAM, 0);
+    if (listen_fd < 0) {
+        printf("ERROR: failed to create a TCP socket\n");
+        return -1;
+    }
+    int value = 1, type = 0;
+    socklen_t value_len = sizeof(type);
+    if (setsockopt(listen_fd, SOL_SOCKET, SO_REUSEADDR, &value, sizeof(value)) < 0 ||
+        getsockopt(listen_fd, SOL_SOCKET, SO_TYPE, &type, &value_len) < 0 ||
+        type != SOCK_STREAM || value_len != sizeof(type)) {
+        printf("ERROR: failed to set or get the socket options\n");
+        return -1;
+    }
+    struct sockaddr_in listen_addr;
+    if (bind_loopback(listen_fd, &listen_addr) < 0) {
+        return -1;
+    }
+    if (listen(listen_fd, 5) < 0) {
+        printf("ERROR: failed to listen\n");
+        return -1;
+    }
+
+    // No connection is pending yet
+    if (fcntl(listen_fd, F_SETFL, O_NONBLOCK) < 0 ||
+        accept(listen_fd, NULL, NULL) >= 0 || errno != EAGAIN ||
+        fcntl(listen_fd, F_SETFL, 0) < 0) {
+        printf("ERROR: accept does not fail with EAGAIN\n");
+        return -1;
+    }
+
+    int client_fd = socket(AF_INET, SOCK_STREAM, 0);
+    if (client_fd < 0 ||
+        connect(client_fd, (struct sockaddr*)&listen_addr, sizeof(listen_addr)) < 0) {
+        printf("ERROR: failed to connect\n");
+        return -1;
+    }
+    struct pollfd poll_fd = { .fd = listen_fd, .events = POLLIN };
+    if (poll(&poll_fd, 1, 1000) != 1 || !(poll_fd.revents & POLLIN)) {
+        printf("ERROR: the listening socket is not readable\n");
+        return -1;
+    }
+    struct sockaddr_in peer_addr, client_addr;
+    socklen_t peer_addr_len = sizeof(peer_addr), client_addr_len = sizeof(client_addr);
+    int server_fd = accept4(listen_fd, (struct sockaddr*)&peer_addr, &peer_addr_len,
+                            SOCK_CLOEXEC);
+    if (server_fd < 0) {
+        printf("ERROR: failed to accept\n");
+        return -1;
+    }
+    if (getsockname(client_fd, (struct sockaddr*)&client_addr, &client_addr_len) < 0 ||
+        !is_same_addr(&peer_addr, &client_addr)) {
+        printf("ERROR: the address of the peer is wrong\n");
+        return -1;
+    }
+    if (check_transfer(client_fd, server_fd) < 0 ||
+        check_transfer(server_fd, client_fd) < 0) {
+        return -1;
+    }
+
+    // The data in multiple buffers is received as a whole
+    char buf0[6] = { 0 }, buf1[32] = { 0 };
+    struct iovec send_iov[2] = {
+        { .iov_base = (void*)write_msg, .iov_len = 6 },
+        { .iov_base = (void*)(write_msg + 6), .iov_len = strlen(write_msg) - 6 },
+    };
+    struct iovec recv_iov[2] = {
+        { .iov_base = buf0, .iov_len = sizeof(buf0) },
+        { .iov_base = buf1, .iov_len = sizeof(buf1) },
+    };
+    struct msghdr send_msg = { .msg_iov = send_iov, .msg_iovlen = 2 };
+    struct msghdr recv_msg = { .msg_iov = recv_iov, .msg_iovlen = 2 };
+    if (sendmsg(client_fd, &send_msg, 0) != strlen(write_msg) ||
+        recvmsg(server_fd, &recv_msg, 0) != strlen(write_msg) ||
+        memcmp(buf0, write_msg, 6) != 0 || strcmp(buf1, write_msg + 6) != 0) {
+        printf("ERROR: the message of sendmsg is not received by recvmsg\n");
+        return -1;
+    }
+
+    char buf[32];
+    shutdown(client_fd, SHUT_WR);
+    if (read(server_fd, buf, sizeof(buf)) != 0) {
+        printf("ERROR: read does not return EOF after shutdown\n");
+        return -1;
+    }
+    close(client_fd);
+    close(server_fd);
+    close(listen_fd);
+    return 0;
+}
+
+static int test_udp(void) {
+    int fd0 = socket(AF_INET, SOCK_DGRAM, 0);
+    int fd1 = socket(AF_INET, SOCK_DGRAM | SOCK_NONBLOCK, 0);
+    if (fd0 < 0 || fd1 < 0) {
+        printf("ERROR: failed to create UDP sockets\n");
+        return -1;
+    }
+    struct sockaddr_in addr0, addr1;
+    if (bind_loopback(fd0, &addr0) < 0 || bind_loopback(fd1, &addr1) < 0) {
+        return -1;
+    }
+
+    char buf[32] = { 0 };
+    if (recv(fd1, buf, sizeof(buf), 0) >= 0 || errno != EAGAIN) {
+        printf("ERROR: recv does not fail with EAGAIN\n");
+        return -1;
+    }
+
+    // The boundaries of the messages are kept
+    if (sendto(fd0, write_msg, 5, 0, (struct sockaddr*)&addr1, sizeof(addr1)) != 5 ||
+        sendto(fd0, write_msg, strlen(write_msg), 0,
+               (struct sockaddr*)&addr1, sizeof(addr1)) != strlen(write_msg)) {
+        printf("ERROR: failed to sendto\n");
+        return -1;
+    }
+    struct pollfd poll_fd = { .fd = fd1, .events = POLLIN };
+    if (poll(&poll_fd, 1, 1000) != 1) {
+        printf("ERROR: the UDP socket is not readable\n");
+        return -1;
+    }
+    struct sockaddr_in src_addr;
+    socklen_t src_addr_len = sizeof(src_addr);
+    if (recvfrom(fd1, buf, sizeof(buf), 0, (struct sockaddr*)&src_addr, &src_addr_len) != 5 ||
+        memcmp(buf, write_msg, 5) != 0) {
+        printf("ERROR: the first message is not received\n");
+        return -1;
+    }
+    if (src_addr_len != sizeof(src_addr) || !is_same_addr(&src_addr, &addr0)) {
+        printf("ERROR: the address of the sender is wrong\n");
+        return -1;
+    }
+    memset(buf, 0, sizeof(buf));
+    if (recvfrom(fd1, buf, sizeof(buf), 0, NULL, NULL) != strlen(write_msg) ||
+        strcmp(buf, write_msg) != 0) {
+        printf("ERROR: the second message is not received\n");
+        return -1;
+    }
+
+    // A connected UDP socket sends to its peer by default
+    if (connect(fd1, (struct sockaddr*)&addr0, sizeof(addr0)) < 0 ||
+        check_transfer(fd1, fd0) < 0) {
+        return -1;
+    }
+    close(fd0);
+    close(fd1);
+    return 0;
+}
+
+// An edge-triggered epoll reports a socket again once new data arrives after
+// all the data have been read
+static int test_epoll_edge_triggered(void) {
+    int listen_fd = socket(AF_INET, SOCK_STREAM, 0);
+    int client_fd = socket(AF_INET, SOCK_STREAM, 0);
+    if (listen_fd < 0 || client_fd < 0) {
+        printf("ERROR: failed to create TCP sockets\n");
+        return -1;
+    }
+    struct sockaddr_in listen_addr;
+    if (bind_loopback(listen_fd, &listen_addr) < 0) {
+        return -1;
+    }
+    if (listen(listen_fd, 5) < 0 ||
+        connect(client_fd, (struct sockaddr*)&listen_addr, sizeof(listen_addr)) < 0) {
+        printf("ERROR: failed to connect\n");
+        return -1;
+    }
+    int server_fd = accept4(listen_fd, NULL, NULL, SOCK_NONBLOCK);
+    if (server_fd < 0) {
+        printf("ERROR: failed to accept\n");
+        return -1;
+    }
+
+    int epfd = epoll_create1(0);
+    struct epoll_event event = { .events = EPOLLIN | EPOLLET, .data.fd = server_fd };
+    if (epfd < 0 || epoll_ctl(epfd, EPOLL_CTL_ADD, server_fd, &event) < 0) {
+        printf("ERROR: failed to add the socket to epoll\n");
+        return -1;
+    }
+
+    char buf[32];
+    for (int round = 0; round < 3; round++) {
+        if (send(client_fd, write_msg, strlen(write_msg), 0) != strlen(write_msg)) {
+            printf("ERROR: failed to send\n");
+            return -1;
+        }
+        struct epoll_event ready_event;
+        if (epoll_wait(epfd, &ready_event, 1, 1000) != 1 ||
+            ready_event.data.fd != server_fd || !(ready_event.events & EPOLLIN)) {
+            printf("ERROR: the new data are not reported in round %d\n", round);
+            return -1;
+        }
+        if (epoll_wait(epfd, &ready_event, 1, 0) != 0) {
+            printf("ERROR: the same data are reported twice in round %d\n", round);
+            return -1;
+        }
+        ssize_t len;
+        while ((len = recv(server_fd, buf, sizeof(buf), 0)) > 0) {
+            ;
+        }
+        if (len == 0 || errno != EAGAIN) {
+            printf("ERROR: recv does not fail with EAGAIN\n");
+            return -1;
+        }
+    }
+    close(epfd);
+    close(client_fd);
+    close(server_fd);
+    close(listen_fd);
+    return 0;
+}
+
+int main(int argc, const char* argv[]) {
+    if (test_tcp() < 0 ||
+        test_udp() < 0 ||
+        test_epoll_edge_triggered() < 0) {
+        return -1;
+    }
+    printf("Inet socket works successfully\n");
+    return 0;
+}
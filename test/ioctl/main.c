@@ -0,0 +1,112 @@
+#include <sys/ioctl.h>
+#include <sys/socket.h>
+#include <fcntl.h>
+#include <termios.h>
+#include <unistd.h>
+#include <string.h>
+#include <errno.h>
+#include <stdio.h>
+
+static const char* write_msg = "Hello World\n";
+
+static int test_tty(void) {
+    // The host decides whether stdout is a terminal, so only the consistency
+    // of the results is checked
+    struct winsize winsize;
+    struct termios termios;
+    int is_tty = isatty(STDOUT_FILENO);
+    int ret = ioctl(STDOUT_FILENO, TIOCGWINSZ, &winsize);
+    if ((is_tty && ret < 0) || (!is_tty && (ret >= 0 || errno != ENOTTY))) {
+        printf("ERROR: TIOCGWINSZ does not agree with isatty\n");
+        return -1;
+    }
+    ret = tcgetattr(STDOUT_FILENO, &termios);
+    if ((is_tty && ret < 0) || (!is_tty && (ret >= 0 || errno != ENOTTY))) {
+        printf("ERROR: tcgetattr does not agree with isatty\n");
+        return -1;
+    }
+
+    int fd = open("/tmp/ioctl_test.txt", O_RDWR | O_CREAT | O_TRUNC, 0666);
+    if (fd < 0) {
+        printf("ERROR: failed to create a file\n");
+        return -1;
+    }
+    if (isatty(fd) || errno != ENOTTY) {
+        printf("ERROR: a regular file is treated as a terminal\n");
+        return -1;
+    }
+    close(fd);
+    return 0;
+}
+
+static int test_fionread(void) {
+    int pipe_fds[2];
+    if (pipe(pipe_fds) < 0) {
+        printf("ERROR: failed to create a pipe\n");
+        return -1;
+    }
+    int len = -1;
+    if (ioctl(pipe_fds[0], FIONREAD, &len) < 0 || len != 0) {
+        printf("ERROR: FIONREAD of an empty pipe is wrong\n");
+        return -1;
+    }
+    write(pipe_fds[1], write_msg, strlen(write_msg));
+    if (ioctl(pipe_fds[0], FIONREAD, &len) < 0 || len != strlen(write_msg)) {
+        printf("ERROR: FIONREAD of the read end of a pipe is wrong\n");
+        return -1;
+    }
+    if (ioctl(pipe_fds[1], FIONREAD, &len) < 0 || len != strlen(write_msg)) {
+        printf("ERROR: FIONREAD of the write end of a pipe is wrong\n");
+        return -1;
+    }
+    close(pipe_fds[0]);
+    close(pipe_fds[1]);
+
+    int sv[2];
+    if (socketpair(AF_UNIX, SOCK_STREAM, 0, sv) < 0) {
+        printf("ERROR: failed to create a pair of sockets\n");
+        return -1;
+    }
+    write(sv[0], write_msg, strlen(write_msg));
+    if (ioctl(sv[1], FIONREAD, &len) < 0 || len != strlen(write_msg)) {
+        printf("ERROR: FIONREAD of a socket is wrong\n");
+        return -1;
+    }
+    close(sv[0]);
+    close(sv[1]);
+    return 0;
+}
+
+static int test_fionbio(void) {
+    int pipe_fds[2];
+    if (pipe(pipe_fds) < 0) {
+        printf("ERROR: failed to create a pipe\n");
+        return -1;
+    }
+    int on = 1, off = 0;
+    char buf[32];
+    if (ioctl(pipe_fds[0], FIONBIO, &on) < 0 ||
+        !(fcntl(pipe_fds[0], F_GETFL) & O_NONBLOCK) ||
+        read(pipe_fds[0], buf, sizeof(buf)) >= 0 || errno != EAGAIN) {
+        printf("ERROR: FIONBIO does not set O_NONBLOCK\n");
+        return -1;
+    }
+    if (ioctl(pipe_fds[0], FIONBIO, &off) < 0 ||
+        (fcntl(pipe_fds[0], F_GETFL) & O_NONBLOCK)) {
+        printf("ERROR: FIONBIO does not clear O_NONBLOCK\n");
+        return -1;
+    }
+    close(pipe_fds[0]);
+    close(pipe_fds[1]);
+    return 0;
+}
+
+int main(int argc, const char* argv[]) {
+    if (test_tty() < 0 ||
+        test_fionread() < 0 ||
+        test_fionbio() < 0) {
+        return -1;
+    }
+    printf("ioctl works successfully\n");
+    return 0;
+}
@@ -0,0 +1,223 @@
+#include <sys/types.h>
+#include <sys/stat.h>
+#include <fcntl.h>
+#include <unistd.h>
+#include <string.h>
+#include <errno.h>
+#include <stdio.h>
+#include "test_fs.h"
+
+static const char* write_msg = "Hello World\n";
+
+static int create_msg_file(const char* file_name) {
+    return write_file(file_name, write_msg, strlen(write_msg));
+}
+
+static int check_msg_file(const char* file_name) {
+    return check_file(file_name, write_msg, strlen(write_msg));
+}
+
+static int test_rename_and_unlink(const char* old_name, const char* new_name) {
+    struct stat stat_buf;
+
+    if (create_msg_file(old_name) < 0) {
+        return -1;
+    }
+    if (rename(old_name, new_name) < 0) {
+        printf("ERROR: failed to rename %s to %s\n", old_name, new_name);
+        return -1;
+    }
+    if (stat(old_name, &stat_buf) == 0) {
+        printf("ERROR: %s still exists after rename\n", old_name);
+        return -1;
+    }
+    if (check_msg_file(new_name) < 0) {
+        return -1;
+    }
+
+    if (unlink(new_name) < 0) {
+        printf("ERROR: failed to unlink %s\n", new_name);
+        return -1;
+    }
+    if (stat(new_name, &stat_buf) == 0) {
+        printf("ERROR: %s still exists after unlink\n", new_name);
+        return -1;
+    }
+    return 0;
+}
+
+// Rotate a log, i.e., rename it while it is open, where the data written
+// after the rename goes to the renamed file
+static int test_rename_open_file(const char* old_name, const char* new_name) {
+    const char* rotated_msg = "Hello Again\n";
+    char read_buf[128] = {0};
+    int fd;
+    if ((fd = open(old_name, O_WRONLY | O_CREAT | O_TRUNC, 0666)) < 0 ||
+        write(fd, write_msg, strlen(write_msg)) != strlen(write_msg)) {
+        printf("ERROR: failed to write to %s\n", old_name);
+        return -1;
+    }
+    if (rename(old_name, new_name) < 0) {
+        printf("ERROR: failed to rename the open file %s\n", old_name);
+        return -1;
+    }
+    if (write(fd, rotated_msg, strlen(rotated_msg)) != strlen(rotated_msg) ||
+        fsync(fd) < 0) {
+        printf("ERROR: failed to write to %s after rename\n", old_name);
+        return -1;
+    }
+    close(fd);
+
+    if ((fd = open(new_name, O_RDONLY)) < 0) {
+        printf("ERROR: failed to open %s\n", new_name);
+        return -1;
+    }
+    int len = read(fd, read_buf, sizeof(read_buf) - 1);
+    if (len != strlen(write_msg) + strlen(rotated_msg) ||
+        strncmp(read_buf, write_msg, strlen(write_msg)) != 0 ||
+        strcmp(read_buf + strlen(write_msg), rotated_msg) != 0) {
+        printf("ERROR: the data written after rename is lost\n");
+        return -1;
+    }
+    close(fd);
+    unlink(new_name);
+    return 0;
+}
+
+static int test_symlink(const char* file_name, const char* link_name) {
+    char read_buf[128] = {0};
+    struct stat stat_buf;
+
+    if (create_msg_file(file_name) < 0) {
+        return -1;
+    }
+    if (symlink(file_name, link_name) < 0) {
+        printf("ERROR: failed to create symlink %s\n", link_name);
+        return -1;
+    }
+    if (readlink(link_name, read_buf, sizeof(read_buf)) != strlen(file_name) ||
+        strcmp(read_buf, file_name) != 0) {
+        printf("ERROR: readlink returns a wrong target for %s\n", link_name);
+        return -1;
+    }
+    if (lstat(link_name, &stat_buf) < 0 || !S_ISLNK(stat_buf.st_mode)) {
+        printf("ERROR: lstat does not return a symlink for %s\n", link_name);
+        return -1;
+    }
+    if (stat(link_name, &stat_buf) < 0 || !S_ISREG(stat_buf.st_mode)) {
+        printf("ERROR: stat does not follow symlink %s\n", link_name);
+        return -1;
+    }
+    if (check_msg_file(link_name) < 0) {
+        return -1;
+    }
+    if (unlink(link_name) < 0 || check_msg_file(file_name) < 0) {
+        printf("ERROR: failed to unlink symlink %s\n", link_name);
+        return -1;
+    }
+    unlink(file_name);
+    return 0;
+}
+
+static int test_hard_link(void) {
+    const char* file_name = "/tmp/link_test.txt";
+    const char* link_name = "/tmp/link_test_hard.txt";
+    struct stat stat_buf;
+
+    if (create_msg_file(file_name) < 0) {
+        return -1;
+    }
+    if (link(file_name, link_name) < 0) {
+        printf("ERROR: failed to create hard link %s\n", link_name);
+        return -1;
+    }
+    if (stat(file_name, &stat_buf) < 0 || stat_buf.st_nlink != 2) {
+        printf("ERROR: wrong link count after link\n");
+        return -1;
+    }
+    if (unlink(file_name) < 0 || check_msg_file(link_name) < 0) {
+        printf("ERROR: the file is lost after unlinking one of its links\n");
+        return -1;
+    }
+    if (stat(link_name, &stat_buf) < 0 || stat_buf.st_nlink != 1) {
+        printf("ERROR: wrong link count after unlink\n");
+        return -1;
+    }
+    unlink(link_name);
+    return 0;
+}
+
+static int test_rename_dir(void) {
+    struct stat stat_buf;
+
+    if (mkdir("/tmp/link_test_dir", 0755) < 0 ||
+        create_msg_file("/tmp/link_test_dir/file.txt") < 0) {
+        printf("ERROR: failed to prepare a directory\n");
+        return -1;
+    }
+    if (rename("/tmp/link_test_dir", "/tmp/link_test_dir/sub") == 0) {
+        printf("ERROR: moved a directory into itself\n");
+        return -1;
+    }
+    // The same, but through a symlink that the path alone does not reveal
+    if (mkdir("/tmp/link_test_dir/sub", 0755) < 0 ||
+        symlink("/tmp/link_test_dir", "/tmp/link_test_dir_sym") < 0) {
+        printf("ERROR: failed to prepare a symlink to the directory\n");
+        return -1;
+    }
+    if (rename("/tmp/link_test_dir", "/tmp/link_test_dir_sym/moved") == 0 || errno != EINVAL ||
+        rename("/tmp/link_test_dir", "/tmp/link_test_dir_sym/sub/moved") == 0 || errno != EINVAL) {
+        printf("ERROR: moved a directory into itself through a symlink\n");
+        return -1;
+    }
+    if (unlink("/tmp/link_test_dir_sym") < 0 ||
+        unlinkat(AT_FDCWD, "/tmp/link_test_dir/sub", AT_REMOVEDIR) < 0) {
+        printf("ERROR: failed to remove the symlink and the subdirectory\n");
+        return -1;
+    }
+    if (rename("/tmp/link_test_dir", "/tmp/link_test_dir2") < 0 ||
+        check_msg_file("/tmp/link_test_dir2/file.txt") < 0) {
+        printf("ERROR: failed to rename a directory\n");
+        return -1;
+    }
+    if (unlinkat(AT_FDCWD, "/tmp/link_test_dir2", 0) == 0) {
+        printf("ERROR: unlinked a directory without AT_REMOVEDIR\n");
+        return -1;
+    }
+    if (unlink("/tmp/link_test_dir2/file.txt") < 0 ||
+        unlinkat(AT_FDCWD, "/tmp/link_test_dir2", AT_REMOVEDIR) < 0) {
+        printf("ERROR: failed to remove the directory\n");
+        return -1;
+    }
+    if (stat("/tmp/link_test_dir2", &stat_buf) == 0) {
+        printf("ERROR: the directory still exists after removed\n");
+        return -1;
+    }
+    return 0;
+}
+
+int main(int argc, const char* argv[]) {
+    // A file in tmpfs and a protected file
+    if (test_rename_and_unlink("/tmp/link_test.txt", "/tmp/link_test_new.txt") < 0 ||
+        test_rename_and_unlink("link_test.txt", "link_test_new.txt") < 0) {
+        return -1;
+    }
+    if (test_rename_open_file("/tmp/link_test.log", "/tmp/link_test.log.1") < 0 ||
+        test_rename_open_file("link_test.log", "link_test.log.1") < 0) {
+        return -1;
+    }
+    if (test_symlink("/tmp/link_test.txt", "/tmp/link_test_sym") < 0 ||
+        test_symlink("link_test.txt", "link_test_sym") < 0) {
+        return -1;
+    }
+    if (test_hard_link() < 0 || test_rename_dir() < 0) {
+        return -1;
+    }
+    if (rename("link_test_non_existing.txt", "/tmp/link_test.txt") == 0) {
+        printf("ERROR: renamed a non-existing file\n");
+        return -1;
+    }
+
+    printf("Rename, unlink and links work successfully\n");
+    return 0;
+}
@@ -0,0 +1,210 @@
+#include <sys/mman.h>
+#include <fcntl.h>
+#include <unistd.h>
+#include <string.h>
+#include <errno.h>
+#include <stdio.h>
+#include "test_fs.h"
+
+#define PAGE_SIZE 4096
+
+static const char* write_msg = "Hello World\n";
+
+// Create a file of two pages, where the second page starts with the message
+static int create_test_file(const char* file_path) {
+    int fd = create_file(file_path, write_msg, strlen(write_msg));
+    if (fd < 0) {
+        return -1;
+    }
+    if (pwrite(fd, write_msg, strlen(write_msg), PAGE_SIZE) != strlen(write_msg)) {
+        printf("ERROR: failed to write the second page of %s\n", file_path);
+        close(fd);
+        return -1;
+    }
+    return fd;
+}
+
+static int check_content(int fd, off_t offset, const char* content) {
+    return check_fd_content(fd, offset, content, strlen(content));
+}
+
+static int test_private_mapping(const char* file_path) {
+    int fd = create_test_file(file_path);
+    if (fd < 0) {
+        return -1;
+    }
+    char* addr = mmap(NULL, PAGE_SIZE, PROT_READ | PROT_WRITE, MAP_PRIVATE, fd, PAGE_SIZE);
+    if (addr == MAP_FAILED) {
+        printf("ERROR: failed to mmap a file privately\n");
+        return -1;
+    }
+    // The part beyond the end of the file is zeroed
+    if (memcmp(addr, write_msg, strlen(write_msg)) != 0 ||
+        addr[strlen(write_msg)] != 0 || addr[PAGE_SIZE - 1] != 0) {
+        printf("ERROR: the content of the mapping is wrong\n");
+        return -1;
+    }
+    // The changes are not written back
+    addr[0] = 'h';
+    if (munmap(addr, PAGE_SIZE) < 0 || check_content(fd, PAGE_SIZE, write_msg) < 0) {
+        printf("ERROR: the private mapping is written back\n");
+        return -1;
+    }
+    close(fd);
+    return 0;
+}
+
+static int test_shared_mapping(const char* file_path) {
+    int fd = create_test_file(file_path);
+    if (fd < 0) {
+        return -1;
+    }
+    char* addr = mmap(NULL, 2 * PAGE_SIZE, PROT_READ | PROT_WRITE, MAP_SHARED, fd, 0);
+    if (addr == MAP_FAILED) {
+        printf("ERROR: failed to mmap a file shared\n");
+        return -1;
+    }
+    // The mapping can outlive the fd
+    close(fd);
+    fd = open(file_path, O_RDONLY);
+    if (fd < 0) {
+        printf("ERROR: failed to reopen the file\n");
+        return -1;
+    }
+
+    addr[0] = 'h';
+    if (msync(addr, 2 * PAGE_SIZE, MS_SYNC) < 0 || check_content(fd, 0, "hello World\n") < 0) {
+        printf("ERROR: the shared mapping is not written back by msync\n");
+        return -1;
+    }
+    addr[PAGE_SIZE] = 'h';
+    if (munmap(addr, 2 * PAGE_SIZE) < 0 || check_content(fd, PAGE_SIZE, "hello World\n") < 0) {
+        printf("ERROR: the shared mapping is not written back by munmap\n");
+        return -1;
+    }
+    // The file is not extended by the mapping
+    if (lseek(fd, 0, SEEK_END) != PAGE_SIZE + strlen(write_msg)) {
+        printf("ERROR: the size of the file is changed\n");
+        return -1;
+    }
+    close(fd);
+    return 0;
+}
+
+// The pages not changed through the mapping are not written back, so they do
+// not overwrite the data written to the file by write
+static int test_shared_mapping_and_write(const char* file_path) {
+    int fd = create_test_file(file_path);
+    if (fd < 0) {
+        return -1;
+    }
+    char* addr = mmap(NULL, 2 * PAGE_SIZE, PROT_READ | PROT_WRITE, MAP_SHARED, fd, 0);
+    if (addr == MAP_FAILED) {
+        printf("ERROR: failed to mmap a file shared\n");
+        return -1;
+    }
+    if (pwrite(fd, "HELLO", 5, PAGE_SIZE) != 5) {
+        printf("ERROR: failed to write the file\n");
+        return -1;
+    }
+    addr[0] = 'h';
+    if (msync(addr, 2 * PAGE_SIZE, MS_SYNC) < 0 ||
+        check_content(fd, 0, "hello World\n") < 0 ||
+        check_content(fd, PAGE_SIZE, "HELLO World\n") < 0) {
+        printf("ERROR: the data written to the file are overwritten by msync\n");
+        return -1;
+    }
+
+    // A page written back is compared with the data written back, instead of
+    // the data loaded
+    if (pwrite(fd, "HELLO", 5, 0) != 5) {
+        printf("ERROR: failed to write the file\n");
+        return -1;
+    }
+    if (munmap(addr, 2 * PAGE_SIZE) < 0 ||
+        check_content(fd, 0, "HELLO World\n") < 0 ||
+        check_content(fd, PAGE_SIZE, "HELLO World\n") < 0) {
+        printf("ERROR: the data written to the file are overwritten by munmap\n");
+        return -1;
+    }
+    close(fd);
+    return 0;
+}
+
+static int test_invalid_mappings(const char* file_path) {
+    int fd = create_test_file(file_path);
+    if (fd < 0) {
+        return -1;
+    }
+    if (mmap(NULL, PAGE_SIZE, PROT_READ, MAP_PRIVATE, fd, 1) != MAP_FAILED ||
+        errno != EINVAL) {
+        printf("ERROR: mmap with an unaligned offset does not fail with EINVAL\n");
+        return -1;
+    }
+    close(fd);
+
+    fd = open(file_path, O_RDONLY);
+    if (mmap(NULL, PAGE_SIZE, PROT_READ | PROT_WRITE, MAP_SHARED, fd, 0) != MAP_FAILED ||
+        errno != EACCES) {
+        printf("ERROR: writable shared mmap of a read-only file does not fail with EACCES\n");
+        return -1;
+    }
+    close(fd);
+
+    fd = open(file_path, O_WRONLY);
+    if (mmap(NULL, PAGE_SIZE, PROT_READ, MAP_PRIVATE, fd, 0) != MAP_FAILED ||
+        errno != EACCES) {
+        printf("ERROR: mmap of a write-only file does not fail with EACCES\n");
+        return -1;
+    }
+    close(fd);
+
+    int pipe_fds[2];
+    if (pipe(pipe_fds) < 0) {
+        printf("ERROR: failed to create a pipe\n");
+        return -1;
+    }
+    if (mmap(NULL, PAGE_SIZE, PROT_READ, MAP_PRIVATE, pipe_fds[0], 0) != MAP_FAILED ||
+        errno != ENODEV) {
+        printf("ERROR: mmap of a pipe does not fail with ENODEV\n");
+        return -1;
+    }
+    close(pipe_fds[0]);
+    close(pipe_fds[1]);
+
+    char* addr = mmap(NULL, PAGE_SIZE, PROT_READ | PROT_WRITE,
+                      MAP_PRIVATE | MAP_ANONYMOUS, -1, 0);
+    if (addr == MAP_FAILED) {
+        printf("ERROR: failed to mmap anonymously\n");
+        return -1;
+    }
+    if (msync(addr + 1, PAGE_SIZE, MS_SYNC) == 0 || errno != EINVAL) {
+        printf("ERROR: msync with an unaligned address does not fail with EINVAL\n");
+        return -1;
+    }
+    if (msync(addr, (size_t)-1 - 2 * PAGE_SIZE, MS_SYNC) == 0 || errno != ENOMEM) {
+        printf("ERROR: msync with an overflowing size does not fail with ENOMEM\n");
+        return -1;
+    }
+    munmap(addr, PAGE_SIZE);
+    return 0;
+}
+
+int main(int argc, const char* argv[]) {
+    // A file in tmpfs and a protected file
+    const char* file_paths[] = { "/tmp/mmap_test.txt", "mmap_test.txt" };
+    for (int i = 0; i < 2; i++) {
+        if (test_private_mapping(file_paths[i]) < 0 ||
+            test_shared_mapping(file_paths[i]) < 0 ||
+            test_shared_mapping_and_write(file_paths[i]) < 0) {
+            printf("ERROR: failed to mmap %s\n", file_paths[i]);
+            return -1;
+        }
+    }
+    if (test_invalid_mappings(file_paths[0]) < 0) {
+        return -1;
+    }
+    unlink(file_paths[1]);
+    printf("mmap works successfully\n");
+    return 0;
+}
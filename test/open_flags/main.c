@@ -0,0 +1,130 @@
+#include <sys/types.h>
+#include <sys/stat.h>
+#include <fcntl.h>
+#include <unistd.h>
+#include <string.h>
+#include <errno.h>
+#include <stdio.h>
+
+#define PATH_LEN    128
+
+static const char* write_msg = "Hello World\n";
+
+static int expect_open_error(const char* path, int flags, int expected_errno) {
+    int fd = open(path, flags, 0666);
+    if (fd >= 0) {
+        printf("ERROR: open %s with flags 0x%x does not fail\n", path, flags);
+        close(fd);
+        return -1;
+    }
+    if (errno != expected_errno) {
+        printf("ERROR: open %s with flags 0x%x fails with errno %d instead of %d\n",
+               path, flags, errno, expected_errno);
+        return -1;
+    }
+    return 0;
+}
+
+static int test_open_flags(const char* dir_path) {
+    char file_path[PATH_LEN], link_path[PATH_LEN], read_buf[128] = {0};
+    struct stat stat_buf;
+    int fd;
+
+    snprintf(file_path, sizeof(file_path), "%s/open_flags_test.txt", dir_path);
+    snprintf(link_path, sizeof(link_path), "%s/open_flags_test_sym", dir_path);
+    unlink(file_path);
+
+    // Create a new file with the given permissions
+    if ((fd = open(file_path, O_WRONLY | O_CREAT | O_EXCL, 0640)) < 0) {
+        printf("ERROR: failed to create %s exclusively\n", file_path);
+        return -1;
+    }
+    if (write(fd, write_msg, strlen(write_msg)) != strlen(write_msg)) {
+        printf("ERROR: failed to write to %s\n", file_path);
+        return -1;
+    }
+    close(fd);
+    if (stat(file_path, &stat_buf) < 0 || (stat_buf.st_mode & 0777) != 0640) {
+        printf("ERROR: the permissions of %s are not 0640\n", file_path);
+        return -1;
+    }
+
+    // Fail to create the file exclusively again
+    if (expect_open_error(file_path, O_WRONLY | O_CREAT | O_EXCL, EEXIST) < 0) {
+        return -1;
+    }
+    // Open the existing file with O_CREAT, which keeps its content
+    if ((fd = open(file_path, O_RDWR | O_CREAT, 0666)) < 0) {
+        printf("ERROR: failed to open the existing %s with O_CREAT\n", file_path);
+        return -1;
+    }
+    if (read(fd, read_buf, sizeof(read_buf)) != strlen(write_msg) ||
+        strcmp(read_buf, write_msg) != 0) {
+        printf("ERROR: the content of %s is lost after open with O_CREAT\n", file_path);
+        return -1;
+    }
+    // The file is both readable and writable with O_RDWR
+    if (write(fd, write_msg, strlen(write_msg)) != strlen(write_msg)) {
+        printf("ERROR: failed to write to %s opened with O_RDWR\n", file_path);
+        return -1;
+    }
+    close(fd);
+
+    if (expect_open_error(file_path, O_RDONLY | O_DIRECTORY, ENOTDIR) < 0 ||
+        expect_open_error(dir_path, O_WRONLY, EISDIR) < 0 ||
+        expect_open_error(dir_path, O_RDONLY | O_CREAT, EISDIR) < 0 ||
+        expect_open_error("/tmp/open_flags_non_existing.txt", O_RDONLY, ENOENT) < 0 ||
+        expect_open_error(file_path, O_ACCMODE, EINVAL) < 0) {
+        return -1;
+    }
+
+    // A symbolic link is not followed with O_NOFOLLOW
+    unlink(link_path);
+    if (symlink(file_path, link_path) < 0) {
+        printf("ERROR: failed to create the symbolic link %s\n", link_path);
+        return -1;
+    }
+    if (expect_open_error(link_path, O_RDONLY | O_NOFOLLOW, ELOOP) < 0 ||
+        expect_open_error(link_path, O_WRONLY | O_CREAT | O_EXCL, EEXIST) < 0) {
+        return -1;
+    }
+    if ((fd = open(link_path, O_RDONLY)) < 0) {
+        printf("ERROR: failed to open %s through the symbolic link\n", file_path);
+        return -1;
+    }
+    close(fd);
+
+    // O_CREAT through a dangling symbolic link creates its target, unless
+    // with O_EXCL
+    if (unlink(file_path) < 0) {
+        printf("ERROR: failed to remove %s\n", file_path);
+        return -1;
+    }
+    if (expect_open_error(link_path, O_WRONLY | O_CREAT | O_EXCL, EEXIST) < 0) {
+        return -1;
+    }
+    if ((fd = open(link_path, O_WRONLY | O_CREAT, 0666)) < 0) {
+        printf("ERROR: failed to create %s through the dangling symbolic link\n", file_path);
+        return -1;
+    }
+    close(fd);
+    if (lstat(file_path, &stat_buf) < 0 || !S_ISREG(stat_buf.st_mode)) {
+        printf("ERROR: %s is not created through the dangling symbolic link\n", file_path);
+        return -1;
+    }
+
+    if (unlink(link_path) < 0 || unlink(file_path) < 0) {
+        printf("ERROR: failed to remove %s\n", file_path);
+        return -1;
+    }
+    return 0;
+}
+
+int main(int argc, const char* argv[]) {
+    // A directory in tmpfs and one of protected files
+    if (test_open_flags("/tmp") < 0 || test_open_flags(".") < 0) {
+        return -1;
+    }
+    printf("Open flags work successfully\n");
+    return 0;
+}
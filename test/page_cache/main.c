@@ -0,0 +1,181 @@
+#include <sys/stat.h>
+#include <fcntl.h>
+#include <unistd.h>
+#include <string.h>
+#include <stdio.h>
+#include "test_fs.h"
+
+#define PAGE_SIZE 4096
+// Larger than the page cache set in libos.conf, so that pages are evicted
+#define FILE_SIZE (64 * PAGE_SIZE)
+
+static const char* file_name = "page_cache_test.dat";
+static const char* other_file_name = "page_cache_test_other.dat";
+static char write_buf[FILE_SIZE];
+static char read_buf[FILE_SIZE];
+
+static int test_read_write(void) {
+    for (int i = 0; i < FILE_SIZE; i++) {
+        write_buf[i] = (char)(i * 7 + i / PAGE_SIZE);
+    }
+    int write_fd = open(file_name, O_WRONLY | O_CREAT | O_TRUNC, 0666);
+    int read_fd = open(file_name, O_RDONLY);
+    if (write_fd < 0 || read_fd < 0) {
+        printf("ERROR: failed to open the file\n");
+        return -1;
+    }
+    if (write(write_fd, write_buf, FILE_SIZE) != FILE_SIZE) {
+        printf("ERROR: failed to write the file\n");
+        return -1;
+    }
+    // The data not yet written back is seen by the other open file
+    if (check_fd_size(read_fd, FILE_SIZE) < 0 ||
+        check_fd_content(read_fd, 0, write_buf, FILE_SIZE) < 0) {
+        return -1;
+    }
+
+    // Overwrite across the boundaries of pages
+    const char* msg = "Hello World\n";
+    off_t offset = 3 * PAGE_SIZE - 5;
+    memcpy(write_buf + offset, msg, strlen(msg));
+    if (pwrite(write_fd, msg, strlen(msg), offset) != strlen(msg) ||
+        check_fd_content(read_fd, offset - PAGE_SIZE, write_buf + offset - PAGE_SIZE,
+                         2 * PAGE_SIZE) < 0) {
+        printf("ERROR: the overwritten data is wrong\n");
+        return -1;
+    }
+
+    if (fsync(write_fd) < 0 || fdatasync(read_fd) < 0 || (sync(), 0)) {
+        printf("ERROR: failed to sync the file\n");
+        return -1;
+    }
+    close(write_fd);
+    close(read_fd);
+
+    // The data is kept after the file is closed
+    int fd = open(file_name, O_RDONLY);
+    if (fd < 0 || check_fd_size(fd, FILE_SIZE) < 0 ||
+        check_fd_content(fd, 0, write_buf, FILE_SIZE) < 0) {
+        return -1;
+    }
+    close(fd);
+    return 0;
+}
+
+static int test_sparse_write(void) {
+    int fd = open(file_name, O_RDWR | O_CREAT | O_TRUNC, 0666);
+    if (fd < 0) {
+        printf("ERROR: failed to open the file\n");
+        return -1;
+    }
+    // The gap before the data reads as zeros
+    const char* msg = "Hello World\n";
+    off_t offset = 5 * PAGE_SIZE + 100;
+    if (lseek(fd, offset, SEEK_SET) != offset ||
+        write(fd, msg, strlen(msg)) != strlen(msg) ||
+        check_fd_size(fd, offset + strlen(msg)) < 0) {
+        printf("ERROR: failed to write beyond the end of the file\n");
+        return -1;
+    }
+    memset(write_buf, 0, offset);
+    memcpy(write_buf + offset, msg, strlen(msg));
+    if (check_fd_content(fd, 0, write_buf, offset + strlen(msg)) < 0) {
+        return -1;
+    }
+
+    // The data truncated reads as zeros after the file is extended again
+    if (ftruncate(fd, PAGE_SIZE) < 0 || check_fd_size(fd, PAGE_SIZE) < 0 ||
+        pread(fd, read_buf, sizeof(read_buf), 0) != PAGE_SIZE ||
+        ftruncate(fd, offset + strlen(msg)) < 0 ||
+        check_fd_content(fd, 0, write_buf, offset) < 0) {
+        printf("ERROR: the truncated data is not zeros\n");
+        return -1;
+    }
+    close(fd);
+    unlink(file_name);
+    return 0;
+}
+
+static int create_filled_file(const char* path, char fill) {
+    memset(write_buf, fill, FILE_SIZE);
+    return write_file(path, write_buf, FILE_SIZE);
+}
+
+static int test_eviction(void) {
+    for (int i = 0; i < FILE_SIZE; i++) {
+        write_buf[i] = (char)(i * 3 + i / PAGE_SIZE);
+    }
+    int fd = open(file_name, O_RDWR | O_CREAT | O_TRUNC, 0666);
+    if (fd < 0) {
+        printf("ERROR: failed to open the file\n");
+        return -1;
+    }
+    // The pages are written backwards, so the first dirty pages evicted are
+    // beyond the end of the file on the host, where zeros are filled before
+    for (int page_i = FILE_SIZE / PAGE_SIZE - 1; page_i >= 0; page_i--) {
+        off_t offset = page_i * PAGE_SIZE;
+        if (pwrite(fd, write_buf + offset, PAGE_SIZE, offset) != PAGE_SIZE) {
+            printf("ERROR: failed to write the page %d\n", page_i);
+            return -1;
+        }
+    }
+    if (check_fd_size(fd, FILE_SIZE) < 0 || check_fd_content(fd, 0, write_buf, FILE_SIZE) < 0) {
+        return -1;
+    }
+    close(fd);
+
+    // The data is the same whether the pages are still cached or not
+    if (check_file(file_name, write_buf, FILE_SIZE) < 0 ||
+        check_file(file_name, write_buf, FILE_SIZE) < 0) {
+        return -1;
+    }
+    return 0;
+}
+
+// The pages kept after a file is closed are not used for the file that
+// replaces it
+static int test_closed_file_replaced(void) {
+    if (create_filled_file(file_name, 'a') < 0 ||
+        check_file(file_name, write_buf, FILE_SIZE) < 0) {
+        return -1;
+    }
+
+    if (create_filled_file(other_file_name, 'b') < 0 || rename(other_file_name, file_name) < 0) {
+        printf("ERROR: failed to replace the file by rename\n");
+        return -1;
+    }
+    if (check_file(file_name, write_buf, FILE_SIZE) < 0) {
+        printf("ERROR: the data of the file replaced by rename is read\n");
+        return -1;
+    }
+
+    if (unlink(file_name) < 0 || create_filled_file(file_name, 'c') < 0 ||
+        check_file(file_name, write_buf, FILE_SIZE) < 0) {
+        printf("ERROR: the data of the file unlinked is read\n");
+        return -1;
+    }
+
+    int fd = open(file_name, O_WRONLY | O_TRUNC);
+    if (fd < 0) {
+        printf("ERROR: failed to truncate the file\n");
+        return -1;
+    }
+    close(fd);
+    if (check_file(file_name, write_buf, 0) < 0) {
+        printf("ERROR: the data of the file truncated is read\n");
+        return -1;
+    }
+    unlink(file_name);
+    return 0;
+}
+
+int main(int argc, const char* argv[]) {
+    if (test_read_write() < 0 ||
+        test_sparse_write() < 0 ||
+        test_eviction() < 0 ||
+        test_closed_file_replaced() < 0) {
+        return -1;
+    }
+    printf("Page cache works successfully\n");
+    return 0;
+}
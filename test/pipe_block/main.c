@@ -0,0 +1,177 @@
+#include <sys/wait.h>
+#include <unistd.h>
+#include <stdlib.h>
+#include <stdio.h>
+#include <spawn.h>
+#include <string.h>
+#include <errno.h>
+
+static int spawn_echo_child(int pipe_rd_fd, int pipe_wr_fd, const char* msg,
+                            int* child_pid) {
+    posix_spawn_file_actions_t file_actions;
+    posix_spawn_file_actions_init(&file_actions);
+    posix_spawn_file_actions_adddup2(&file_actions, pipe_wr_fd, STDOUT_FILENO);
+    posix_spawn_file_actions_addclose(&file_actions, pipe_rd_fd);
+
+    const char* child_prog = "hello_world/bin.encrypted";
+    const char* child_argv[3] = { child_prog, msg, NULL };
+    if (posix_spawn(child_pid, child_prog, &file_actions,
+            NULL, (char* const*)child_argv, NULL) < 0) {
+        printf("ERROR: failed to spawn a child process\n");
+        return -1;
+    }
+    return 0;
+}
+
+static int test_read_blocks_until_eof(void) {
+    int pipe_fds[2];
+    if (pipe(pipe_fds) < 0) {
+        printf("ERROR: failed to create a pipe\n");
+        return -1;
+    }
+    int pipe_rd_fd = pipe_fds[0];
+    int pipe_wr_fd = pipe_fds[1];
+
+    const char* msg = "Block until the child writes";
+    int child_pid;
+    if (spawn_echo_child(pipe_rd_fd, pipe_wr_fd, msg, &child_pid) < 0) {
+        return -1;
+    }
+    close(pipe_wr_fd);
+
+    // Each read sleeps until some data is written and no read returns 0
+    // before the child exits and closes its end of the pipe
+    char actual_str[64] = {0};
+    size_t actual_len = 0;
+    ssize_t this_len;
+    while ((this_len = read(pipe_rd_fd, actual_str + actual_len,
+                            sizeof(actual_str) - 1 - actual_len)) > 0) {
+        actual_len += this_len;
+    }
+    if (this_len < 0) {
+        printf("ERROR: failed to read from the pipe\n");
+        return -1;
+    }
+    if (actual_len != strlen(msg) + 1 ||
+            strncmp(actual_str, msg, strlen(msg)) != 0) {
+        printf("ERROR: received string is not as expected\n");
+        return -1;
+    }
+    // Read again at EOF
+    if (read(pipe_rd_fd, actual_str, sizeof(actual_str)) != 0) {
+        printf("ERROR: read after EOF should return 0\n");
+        return -1;
+    }
+
+    int status = 0;
+    if (wait4(child_pid, &status, 0, NULL) < 0) {
+        printf("ERROR: failed to wait4 the child process\n");
+        return -1;
+    }
+    close(pipe_rd_fd);
+    return 0;
+}
+
+// The child locks its parent in getppid while the parent is blocked reading
+// the pipe, before it writes to the pipe
+static int test_getppid_while_parent_blocks(void) {
+    int pipe_fds[2];
+    if (pipe(pipe_fds) < 0) {
+        printf("ERROR: failed to create a pipe\n");
+        return -1;
+    }
+    posix_spawn_file_actions_t file_actions;
+    posix_spawn_file_actions_init(&file_actions);
+    posix_spawn_file_actions_adddup2(&file_actions, pipe_fds[1], STDOUT_FILENO);
+    posix_spawn_file_actions_addclose(&file_actions, pipe_fds[0]);
+
+    const char* child_prog = "pipe_block/bin.encrypted";
+    const char* child_argv[3] = { child_prog, "getppid", NULL };
+    int child_pid;
+    if (posix_spawn(&child_pid, child_prog, &file_actions,
+            NULL, (char* const*)child_argv, NULL) < 0) {
+        printf("ERROR: failed to spawn a child process\n");
+        return -1;
+    }
+    close(pipe_fds[1]);
+
+    int parent_pid = 0;
+    if (read(pipe_fds[0], &parent_pid, sizeof(parent_pid)) != sizeof(parent_pid) ||
+            parent_pid != getpid()) {
+        printf("ERROR: failed to read the parent pid from the child\n");
+        return -1;
+    }
+    int status = 0;
+    if (wait4(child_pid, &status, 0, NULL) < 0) {
+        printf("ERROR: failed to wait4 the child process\n");
+        return -1;
+    }
+    close(pipe_fds[0]);
+    return 0;
+}
+
+static int test_read_buffered_data_after_writer_closed(void) {
+    int pipe_fds[2];
+    if (pipe(pipe_fds) < 0) {
+        printf("ERROR: failed to create a pipe\n");
+        return -1;
+    }
+    int pipe_rd_fd = pipe_fds[0];
+    int pipe_wr_fd = pipe_fds[1];
+
+    const char* msg = "Data before close";
+    if (write(pipe_wr_fd, msg, strlen(msg)) != (ssize_t)strlen(msg)) {
+        printf("ERROR: failed to write to the pipe\n");
+        return -1;
+    }
+    close(pipe_wr_fd);
+
+    char buf[64] = {0};
+    if (read(pipe_rd_fd, buf, sizeof(buf)) != (ssize_t)strlen(msg) ||
+            strcmp(buf, msg) != 0) {
+        printf("ERROR: failed to read the data written before close\n");
+        return -1;
+    }
+    if (read(pipe_rd_fd, buf, sizeof(buf)) != 0) {
+        printf("ERROR: read after all writers are closed should return 0\n");
+        return -1;
+    }
+    close(pipe_rd_fd);
+    return 0;
+}
+
+static int test_write_without_reader(void) {
+    int pipe_fds[2];
+    if (pipe(pipe_fds) < 0) {
+        printf("ERROR: failed to create a pipe\n");
+        return -1;
+    }
+    int pipe_rd_fd = pipe_fds[0];
+    int pipe_wr_fd = pipe_fds[1];
+    close(pipe_rd_fd);
+
+    const char* msg = "Nobody reads this";
+    if (write(pipe_wr_fd, msg, strlen(msg)) >= 0 || errno != EPIPE) {
+        printf("ERROR: write without a reader should fail with EPIPE\n");
+        return -1;
+    }
+    close(pipe_wr_fd);
+    return 0;
+}
+
+int main(int argc, const char* argv[]) {
+    if (argc > 1 && strcmp(argv[1], "getppid") == 0) {
+        int parent_pid = getppid();
+        write(STDOUT_FILENO, &parent_pid, sizeof(parent_pid));
+        return 0;
+    }
+
+    if (test_read_blocks_until_eof() < 0 ||
+        test_getppid_while_parent_blocks() < 0 ||
+        test_read_buffered_data_after_writer_closed() < 0 ||
+        test_write_without_reader() < 0) {
+        return -1;
+    }
+    printf("Blocking pipes work successfully\n");
+    return 0;
+}
@@ -0,0 +1,117 @@
+#define _GNU_SOURCE
+#include <fcntl.h>
+#include <unistd.h>
+#include <string.h>
+#include <errno.h>
+#include <stdio.h>
+
+#define CHUNK_SIZE      (64 * 1024)
+
+static char chunk[CHUNK_SIZE];
+
+static int test_pipe2_nonblock(void) {
+    int pipe_fds[2];
+    if (pipe2(pipe_fds, O_NONBLOCK | O_CLOEXEC) < 0) {
+        printf("ERROR: failed to create a non-blocking pipe\n");
+        return -1;
+    }
+    int pipe_rd_fd = pipe_fds[0];
+    int pipe_wr_fd = pipe_fds[1];
+    if ((fcntl(pipe_rd_fd, F_GETFL) & O_NONBLOCK) == 0 ||
+        (fcntl(pipe_wr_fd, F_GETFL) & O_NONBLOCK) == 0) {
+        printf("ERROR: pipe2 does not set O_NONBLOCK\n");
+        return -1;
+    }
+
+    // Reading an empty pipe would block
+    if (read(pipe_rd_fd, chunk, sizeof(chunk)) >= 0 || errno != EAGAIN) {
+        printf("ERROR: read from an empty pipe does not fail with EAGAIN\n");
+        return -1;
+    }
+
+    // Fill the pipe until writing would block
+    size_t total_len = 0;
+    ssize_t this_len;
+    while ((this_len = write(pipe_wr_fd, chunk, sizeof(chunk))) > 0) {
+        total_len += this_len;
+    }
+    if (errno != EAGAIN || total_len == 0) {
+        printf("ERROR: write to a full pipe does not fail with EAGAIN\n");
+        return -1;
+    }
+
+    // Make the reader blocking, which does not block as the pipe is full
+    if (fcntl(pipe_rd_fd, F_SETFL, 0) < 0 ||
+        (fcntl(pipe_rd_fd, F_GETFL) & O_NONBLOCK) != 0) {
+        printf("ERROR: failed to clear O_NONBLOCK\n");
+        return -1;
+    }
+    while (total_len > 0) {
+        if ((this_len = read(pipe_rd_fd, chunk, sizeof(chunk))) <= 0) {
+            printf("ERROR: failed to read the data in the pipe\n");
+            return -1;
+        }
+        total_len -= this_len;
+    }
+
+    close(pipe_rd_fd);
+    close(pipe_wr_fd);
+    return 0;
+}
+
+static int test_fcntl_nonblock(void) {
+    int pipe_fds[2];
+    if (pipe(pipe_fds) < 0) {
+        printf("ERROR: failed to create a pipe\n");
+        return -1;
+    }
+    int pipe_rd_fd = pipe_fds[0];
+    int pipe_wr_fd = pipe_fds[1];
+
+    if (fcntl(pipe_rd_fd, F_SETFL, O_NONBLOCK) < 0) {
+        printf("ERROR: failed to set O_NONBLOCK\n");
+        return -1;
+    }
+    if (read(pipe_rd_fd, chunk, sizeof(chunk)) >= 0 || errno != EAGAIN) {
+        printf("ERROR: read from an empty pipe does not fail with EAGAIN\n");
+        return -1;
+    }
+
+    // Data can be read without blocking
+    const char* msg = "Hello World\n";
+    char buf[32] = {0};
+    if (write(pipe_wr_fd, msg, strlen(msg)) != (ssize_t)strlen(msg) ||
+        read(pipe_rd_fd, buf, sizeof(buf)) != (ssize_t)strlen(msg) ||
+        strcmp(buf, msg) != 0) {
+        printf("ERROR: failed to read the data written to the pipe\n");
+        return -1;
+    }
+
+    // The end of file is not an error
+    close(pipe_wr_fd);
+    if (read(pipe_rd_fd, buf, sizeof(buf)) != 0) {
+        printf("ERROR: read after the writer is closed does not return 0\n");
+        return -1;
+    }
+    close(pipe_rd_fd);
+    return 0;
+}
+
+static int test_pipe2_invalid_flags(void) {
+    int pipe_fds[2];
+    if (pipe2(pipe_fds, O_APPEND) >= 0 || errno != EINVAL) {
+        printf("ERROR: pipe2 with invalid flags does not fail with EINVAL\n");
+        return -1;
+    }
+    return 0;
+}
+
+int main(int argc, const char* argv[]) {
+    if (test_pipe2_nonblock() < 0 ||
+        test_fcntl_nonblock() < 0 ||
+        test_pipe2_invalid_flags() < 0) {
+        return -1;
+    }
+    printf("Non-blocking pipes work successfully\n");
+    return 0;
+}
@@ -0,0 +1,205 @@
+#define _GNU_SOURCE
+#include <sys/select.h>
+#include <sys/time.h>
+#include <sys/wait.h>
+#include <poll.h>
+#include <fcntl.h>
+#include <spawn.h>
+#include <unistd.h>
+#include <string.h>
+#include <stdio.h>
+
+static long elapsed_ms(struct timeval* start) {
+    struct timeval now;
+    gettimeofday(&now, NULL);
+    return (now.tv_sec - start->tv_sec) * 1000 + (now.tv_usec - start->tv_usec) / 1000;
+}
+
+static int test_poll_pipe(void) {
+    int pipe_fds[2];
+    if (pipe(pipe_fds) < 0) {
+        printf("ERROR: failed to create a pipe\n");
+        return -1;
+    }
+
+    // Nothing to read yet
+    struct pollfd poll_fds[2] = {
+        { .fd = pipe_fds[0], .events = POLLIN },
+        { .fd = pipe_fds[1], .events = POLLOUT },
+    };
+    if (poll(poll_fds, 2, 0) != 1 ||
+        poll_fds[0].revents != 0 || poll_fds[1].revents != POLLOUT) {
+        printf("ERROR: poll reports wrong events on an empty pipe\n");
+        return -1;
+    }
+
+    // Sleep until the timeout elapses
+    struct timeval start;
+    gettimeofday(&start, NULL);
+    if (poll(poll_fds, 1, 100) != 0 || poll_fds[0].revents != 0) {
+        printf("ERROR: poll on an empty pipe does not time out\n");
+        return -1;
+    }
+    if (elapsed_ms(&start) < 90) {
+        printf("ERROR: poll returns before the timeout elapses\n");
+        return -1;
+    }
+
+    const char* msg = "Hello World\n";
+    write(pipe_fds[1], msg, strlen(msg));
+    if (poll(poll_fds, 1, -1) != 1 || poll_fds[0].revents != POLLIN) {
+        printf("ERROR: poll does not report POLLIN\n");
+        return -1;
+    }
+
+    // POLLHUP is reported without being requested
+    close(pipe_fds[1]);
+    char buf[32];
+    read(pipe_fds[0], buf, sizeof(buf));
+    poll_fds[0].events = 0;
+    if (poll(poll_fds, 1, -1) != 1 || poll_fds[0].revents != POLLHUP) {
+        printf("ERROR: poll does not report POLLHUP\n");
+        return -1;
+    }
+    close(pipe_fds[0]);
+    return 0;
+}
+
+static int test_poll_wakeup(void) {
+    int pipe_fds[2];
+    if (pipe(pipe_fds) < 0) {
+        printf("ERROR: failed to create a pipe\n");
+        return -1;
+    }
+
+    // The child writes to the pipe while the parent sleeps in poll
+    posix_spawn_file_actions_t file_actions;
+    posix_spawn_file_actions_init(&file_actions);
+    posix_spawn_file_actions_adddup2(&file_actions, pipe_fds[1], STDOUT_FILENO);
+    posix_spawn_file_actions_addclose(&file_actions, pipe_fds[0]);
+    const char* child_prog = "hello_world/bin.encrypted";
+    const char* child_argv[2] = { child_prog, NULL };
+    int child_pid;
+    if (posix_spawn(&child_pid, child_prog, &file_actions,
+            NULL, (char* const*)child_argv, NULL) < 0) {
+        printf("ERROR: failed to spawn a child process\n");
+        return -1;
+    }
+    close(pipe_fds[1]);
+
+    struct pollfd poll_fd = { .fd = pipe_fds[0], .events = POLLIN };
+    if (poll(&poll_fd, 1, -1) != 1 || (poll_fd.revents & POLLIN) == 0) {
+        printf("ERROR: poll is not woken by the child\n");
+        return -1;
+    }
+
+    int status;
+    if (wait4(child_pid, &status, 0, NULL) < 0) {
+        printf("ERROR: failed to wait4 the child process\n");
+        return -1;
+    }
+    close(pipe_fds[0]);
+    return 0;
+}
+
+static int test_poll_file(void) {
+    const char* file_path = "/tmp/poll_test.txt";
+    int fd = open(file_path, O_RDWR | O_CREAT | O_TRUNC, 0666);
+    if (fd < 0) {
+        printf("ERROR: failed to create %s\n", file_path);
+        return -1;
+    }
+
+    // Regular files are always ready, while invalid fds get POLLNVAL and
+    // negative fds are ignored
+    struct pollfd poll_fds[3] = {
+        { .fd = fd, .events = POLLIN | POLLOUT },
+        { .fd = 1000, .events = POLLIN },
+        { .fd = -1, .events = POLLIN },
+    };
+    if (poll(poll_fds, 3, -1) != 2 ||
+        poll_fds[0].revents != (POLLIN | POLLOUT) ||
+        poll_fds[1].revents != POLLNVAL ||
+        poll_fds[2].revents != 0) {
+        printf("ERROR: poll reports wrong events on files\n");
+        return -1;
+    }
+    close(fd);
+    unlink(file_path);
+    return 0;
+}
+
+static int test_ppoll(void) {
+    int pipe_fds[2];
+    if (pipe(pipe_fds) < 0) {
+        printf("ERROR: failed to create a pipe\n");
+        return -1;
+    }
+
+    struct pollfd poll_fd = { .fd = pipe_fds[0], .events = POLLIN };
+    struct timespec timeout = { .tv_sec = 0, .tv_nsec = 10 * 1000 * 1000 };
+    if (ppoll(&poll_fd, 1, &timeout, NULL) != 0) {
+        printf("ERROR: ppoll on an empty pipe does not time out\n");
+        return -1;
+    }
+    timeout.tv_nsec = 1000 * 1000 * 1000;
+    if (ppoll(&poll_fd, 1, &timeout, NULL) >= 0) {
+        printf("ERROR: ppoll accepts an invalid timeout\n");
+        return -1;
+    }
+    close(pipe_fds[0]);
+    close(pipe_fds[1]);
+    return 0;
+}
+
+static int test_select(void) {
+    int pipe_fds[2];
+    if (pipe(pipe_fds) < 0) {
+        printf("ERROR: failed to create a pipe\n");
+        return -1;
+    }
+    int nfds = (pipe_fds[0] > pipe_fds[1] ? pipe_fds[0] : pipe_fds[1]) + 1;
+
+    fd_set read_fds, write_fds;
+    FD_ZERO(&read_fds);
+    FD_ZERO(&write_fds);
+    FD_SET(pipe_fds[0], &read_fds);
+    FD_SET(pipe_fds[1], &write_fds);
+    if (select(nfds, &read_fds, &write_fds, NULL, NULL) != 1 ||
+        FD_ISSET(pipe_fds[0], &read_fds) || !FD_ISSET(pipe_fds[1], &write_fds)) {
+        printf("ERROR: select reports wrong fds on an empty pipe\n");
+        return -1;
+    }
+
+    struct timeval timeout = { .tv_sec = 0, .tv_usec = 10 * 1000 };
+    FD_SET(pipe_fds[0], &read_fds);
+    if (select(nfds, &read_fds, NULL, NULL, &timeout) != 0 ||
+        FD_ISSET(pipe_fds[0], &read_fds)) {
+        printf("ERROR: select on an empty pipe does not time out\n");
+        return -1;
+    }
+
+    const char* msg = "Hello World\n";
+    write(pipe_fds[1], msg, strlen(msg));
+    FD_SET(pipe_fds[0], &read_fds);
+    if (select(nfds, &read_fds, NULL, NULL, NULL) != 1 ||
+        !FD_ISSET(pipe_fds[0], &read_fds)) {
+        printf("ERROR: select does not report a readable pipe\n");
+        return -1;
+    }
+    close(pipe_fds[0]);
+    close(pipe_fds[1]);
+    return 0;
+}
+
+int main(int argc, const char* argv[]) {
+    if (test_poll_pipe() < 0 ||
+        test_poll_wakeup() < 0 ||
+        test_poll_file() < 0 ||
+        test_ppoll() < 0 ||
+        test_select() < 0) {
+        return -1;
+    }
+    printf("Poll and select work successfully\n");
+    return 0;
+}
@@ -0,0 +1,111 @@
+#include <sys/types.h>
+#include <sys/stat.h>
+#include <sys/uio.h>
+#include <fcntl.h>
+#include <unistd.h>
+#include <string.h>
+#include <errno.h>
+#include <stdio.h>
+
+static int test_positional_io(const char* file_name) {
+    const char* write_msg = "Hello World\n";
+    char read_buf[128] = {0};
+    char iov_buf0[6] = {0}, iov_buf1[6] = {0};
+    struct iovec iov[2];
+    int fd;
+
+    if ((fd = open(file_name, O_RDWR | O_CREAT | O_TRUNC, 0666)) < 0) {
+        printf("ERROR: failed to create %s\n", file_name);
+        return -1;
+    }
+    if (pwrite(fd, write_msg, strlen(write_msg), 0) != strlen(write_msg)) {
+        printf("ERROR: failed to pwrite to %s\n", file_name);
+        return -1;
+    }
+    if (lseek(fd, 0, SEEK_CUR) != 0) {
+        printf("ERROR: pwrite changes the file offset of %s\n", file_name);
+        return -1;
+    }
+    if (pread(fd, read_buf, 5, 6) != 5 || strcmp(read_buf, "World") != 0) {
+        printf("ERROR: pread returns wrong data from %s\n", file_name);
+        return -1;
+    }
+    if (lseek(fd, 0, SEEK_CUR) != 0) {
+        printf("ERROR: pread changes the file offset of %s\n", file_name);
+        return -1;
+    }
+
+    iov[0].iov_base = "Howdy ";
+    iov[0].iov_len = 6;
+    iov[1].iov_base = "Earth\n";
+    iov[1].iov_len = 6;
+    if (pwritev(fd, iov, 2, 0) != 12) {
+        printf("ERROR: failed to pwritev to %s\n", file_name);
+        return -1;
+    }
+    iov[0].iov_base = iov_buf0;
+    iov[1].iov_base = iov_buf1;
+    iov[1].iov_len = 5;
+    if (preadv(fd, iov, 2, 0) != 11 || strncmp(iov_buf0, "Howdy ", 6) != 0 ||
+        strcmp(iov_buf1, "Earth") != 0) {
+        printf("ERROR: preadv returns wrong data from %s\n", file_name);
+        return -1;
+    }
+    if (lseek(fd, 0, SEEK_CUR) != 0) {
+        printf("ERROR: preadv or pwritev changes the file offset of %s\n", file_name);
+        return -1;
+    }
+
+    if (pread(fd, read_buf, sizeof(read_buf), -1) >= 0) {
+        printf("ERROR: pread succeeds with a negative offset\n");
+        return -1;
+    }
+    close(fd);
+
+    // Reading from a write-only fd or writing to a read-only one
+    if ((fd = open(file_name, O_WRONLY)) < 0) {
+        printf("ERROR: failed to open %s for writing\n", file_name);
+        return -1;
+    }
+    if (pread(fd, read_buf, sizeof(read_buf), 0) >= 0 || errno != EBADF ||
+        read(fd, read_buf, sizeof(read_buf)) >= 0 || errno != EBADF) {
+        printf("ERROR: reading from a write-only fd does not fail with EBADF\n");
+        close(fd);
+        return -1;
+    }
+    close(fd);
+    if ((fd = open(file_name, O_RDONLY)) < 0) {
+        printf("ERROR: failed to open %s for reading\n", file_name);
+        return -1;
+    }
+    if (pwrite(fd, write_msg, strlen(write_msg), 0) >= 0 || errno != EBADF ||
+        write(fd, write_msg, strlen(write_msg)) >= 0 || errno != EBADF) {
+        printf("ERROR: writing to a read-only fd does not fail with EBADF\n");
+        close(fd);
+        return -1;
+    }
+    close(fd);
+    return 0;
+}
+
+int main(int argc, const char* argv[]) {
+    int pipe_fds[2];
+    char buf[1];
+
+    // A file in tmpfs and a protected file
+    if (test_positional_io("/tmp/pread_test.txt") < 0 || test_positional_io("pread_test.txt") < 0) {
+        return -1;
+    }
+
+    if (pipe(pipe_fds) < 0) {
+        printf("ERROR: failed to create a pipe\n");
+        return -1;
+    }
+    if (pread(pipe_fds[0], buf, sizeof(buf), 0) >= 0) {
+        printf("ERROR: pread succeeds on a pipe\n");
+        return -1;
+    }
+
+    printf("Positional I/O works successfully\n");
+    return 0;
+}
@@ -0,0 +1,69 @@
+#include <sys/stat.h>
+#include <fcntl.h>
+#include <unistd.h>
+#include <string.h>
+#include <stdio.h>
+
+// The files under /data are sealed with the MRSIGNER key by libos.conf
+static const char* file_name = "/data/sgxfs_key_test.txt";
+static const char* renamed_file_name = "/data/sgxfs_key_test_renamed.txt";
+static const char* write_msg = "Hello World\n";
+
+static int check_file(const char* path, const char* expected) {
+    char read_buf[128] = {0};
+    int fd = open(path, O_RDONLY);
+    if (fd < 0) {
+        printf("ERROR: failed to open %s\n", path);
+        return -1;
+    }
+    if (read(fd, read_buf, sizeof(read_buf) - 1) != strlen(expected) ||
+        strcmp(read_buf, expected) != 0) {
+        printf("ERROR: the content of %s is not as it was written\n", path);
+        return -1;
+    }
+    close(fd);
+    return 0;
+}
+
+static int test_write_and_read(void) {
+    int fd = open(file_name, O_WRONLY | O_CREAT | O_TRUNC, 0666);
+    if (fd < 0) {
+        printf("ERROR: failed to create %s\n", file_name);
+        return -1;
+    }
+    if (write(fd, write_msg, strlen(write_msg)) != strlen(write_msg)) {
+        printf("ERROR: failed to write to %s\n", file_name);
+        return -1;
+    }
+    close(fd);
+    return check_file(file_name, write_msg);
+}
+
+static int test_rename(void) {
+    // The file is re-encrypted under the new name with the same key
+    if (rename(file_name, renamed_file_name) < 0) {
+        printf("ERROR: failed to rename %s\n", file_name);
+        return -1;
+    }
+    if (check_file(renamed_file_name, write_msg) < 0) {
+        return -1;
+    }
+    if (truncate(renamed_file_name, 5) < 0 || check_file(renamed_file_name, "Hello") < 0) {
+        printf("ERROR: failed to truncate %s\n", renamed_file_name);
+        return -1;
+    }
+    if (unlink(renamed_file_name) < 0) {
+        printf("ERROR: failed to unlink %s\n", renamed_file_name);
+        return -1;
+    }
+    return 0;
+}
+
+int main(int argc, const char* argv[]) {
+    if (test_write_and_read() < 0 ||
+        test_rename() < 0) {
+        return -1;
+    }
+    printf("Protected files with a sealing key work successfully\n");
+    return 0;
+}
@@ -0,0 +1,78 @@
+#include <sys/types.h>
+#include <sys/stat.h>
+#include <fcntl.h>
+#include <unistd.h>
+#include <string.h>
+#include <stdio.h>
+
+static const char* write_msg = "Hello World\n";
+
+static int check_file_size(const char* file_name, off_t expected_size) {
+    struct stat stat_buf;
+    if (stat(file_name, &stat_buf) < 0 || stat_buf.st_size != expected_size) {
+        printf("ERROR: the size of %s is not %ld\n", file_name, (long)expected_size);
+        return -1;
+    }
+    return 0;
+}
+
+static int test_shared_file(const char* file_name) {
+    char read_buf[128] = {0};
+    int write_fd, read_fd, trunc_fd;
+
+    if ((write_fd = open(file_name, O_WRONLY | O_CREAT | O_TRUNC, 0666)) < 0) {
+        printf("ERROR: failed to create %s\n", file_name);
+        return -1;
+    }
+    // Open the file again while it is opened for write
+    if ((read_fd = open(file_name, O_RDONLY)) < 0) {
+        printf("ERROR: failed to open %s twice\n", file_name);
+        return -1;
+    }
+
+    // The data written by one open file can be read by another
+    if (write(write_fd, write_msg, strlen(write_msg)) != strlen(write_msg)) {
+        printf("ERROR: failed to write to %s\n", file_name);
+        return -1;
+    }
+    if (read(read_fd, read_buf, 5) != 5 || strncmp(read_buf, write_msg, 5) != 0) {
+        printf("ERROR: the data written to %s is not read by another fd\n", file_name);
+        return -1;
+    }
+    // Each open file has its own offset
+    if (lseek(write_fd, 0, SEEK_CUR) != strlen(write_msg) || lseek(read_fd, 0, SEEK_CUR) != 5) {
+        printf("ERROR: the offsets of the open files of %s are not independent\n", file_name);
+        return -1;
+    }
+    if (check_file_size(file_name, strlen(write_msg)) < 0) {
+        return -1;
+    }
+
+    // Truncating the file by another open is seen by all open files
+    if ((trunc_fd = open(file_name, O_WRONLY | O_TRUNC)) < 0) {
+        printf("ERROR: failed to open %s with O_TRUNC\n", file_name);
+        return -1;
+    }
+    if (read(read_fd, read_buf, sizeof(read_buf)) != 0 || check_file_size(file_name, 0) < 0) {
+        printf("ERROR: %s is not truncated for all open files\n", file_name);
+        return -1;
+    }
+
+    close(trunc_fd);
+    close(read_fd);
+    close(write_fd);
+    if (unlink(file_name) < 0) {
+        printf("ERROR: failed to remove %s\n", file_name);
+        return -1;
+    }
+    return 0;
+}
+
+int main(int argc, const char* argv[]) {
+    if (test_shared_file("/tmp/test_shared_file.txt") < 0 ||
+        test_shared_file("test_shared_file.txt") < 0) {
+        return -1;
+    }
+    printf("Opening a file multiple times works successfully\n");
+    return 0;
+}
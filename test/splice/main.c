@@ -0,0 +1,205 @@
+#define _GNU_SOURCE
+#include <sys/sendfile.h>
+#include <sys/stat.h>
+#include <fcntl.h>
+#include <unistd.h>
+#include <string.h>
+#include <errno.h>
+#include <stdio.h>
+#include "test_fs.h"
+
+static const char* write_msg = "Hello World\n";
+
+static int check_pipe(int fd, const char* content) {
+    char buf[64] = { 0 };
+    if (read(fd, buf, sizeof(buf)) != strlen(content) || strcmp(buf, content) != 0) {
+        printf("ERROR: the data in the pipe is wrong\n");
+        return -1;
+    }
+    return 0;
+}
+
+static int test_sendfile(void) {
+    int in_fd = create_file("/tmp/splice_in.txt", write_msg, strlen(write_msg));
+    if (in_fd < 0) {
+        return -1;
+    }
+    int pipe_fds[2];
+    if (pipe(pipe_fds) < 0) {
+        printf("ERROR: failed to create a pipe\n");
+        close(in_fd);
+        return -1;
+    }
+
+    // The offset is advanced while the file position is not changed
+    off_t offset = 6;
+    if (sendfile(pipe_fds[1], in_fd, &offset, 64) != strlen(write_msg) - 6 ||
+        offset != strlen(write_msg) || lseek(in_fd, 0, SEEK_CUR) != 0 ||
+        check_pipe(pipe_fds[0], write_msg + 6) < 0) {
+        printf("ERROR: failed to sendfile with an offset\n");
+        return -1;
+    }
+    // Without the offset, the file position is used
+    if (sendfile(pipe_fds[1], in_fd, NULL, 5) != 5 ||
+        lseek(in_fd, 0, SEEK_CUR) != 5 || check_pipe(pipe_fds[0], "Hello") < 0) {
+        printf("ERROR: failed to sendfile from the file position\n");
+        return -1;
+    }
+    // The input must be a regular file
+    if (sendfile(in_fd, pipe_fds[0], NULL, 5) >= 0 || errno != EINVAL) {
+        printf("ERROR: sendfile from a pipe does not fail with EINVAL\n");
+        return -1;
+    }
+    offset = -1;
+    if (sendfile(pipe_fds[1], in_fd, &offset, 5) >= 0 || errno != EINVAL) {
+        printf("ERROR: sendfile with a negative offset does not fail with EINVAL\n");
+        return -1;
+    }
+    close(in_fd);
+    close(pipe_fds[0]);
+    close(pipe_fds[1]);
+    return 0;
+}
+
+static int test_splice(void) {
+    int in_fd = create_file("/tmp/splice_in.txt", write_msg, strlen(write_msg));
+    if (in_fd < 0) {
+        return -1;
+    }
+    int out_fd = create_file("/tmp/splice_out.txt", "", 0);
+    if (out_fd < 0) {
+        close(in_fd);
+        return -1;
+    }
+    int pipe_fds[2];
+    if (pipe(pipe_fds) < 0) {
+        printf("ERROR: failed to create a pipe\n");
+        close(in_fd);
+        close(out_fd);
+        return -1;
+    }
+
+    off_t off_in = 0;
+    if (splice(in_fd, &off_in, pipe_fds[1], NULL, 64, 0) != strlen(write_msg) ||
+        off_in != strlen(write_msg)) {
+        printf("ERROR: failed to splice from a file to a pipe\n");
+        return -1;
+    }
+    off_t off_out = 4;
+    if (splice(pipe_fds[0], NULL, out_fd, &off_out, 64, 0) != strlen(write_msg) ||
+        off_out != 4 + strlen(write_msg) ||
+        check_fd_content(out_fd, 4, write_msg, strlen(write_msg)) < 0) {
+        printf("ERROR: failed to splice from a pipe to a file\n");
+        return -1;
+    }
+
+    // The pipe is empty now
+    if (splice(pipe_fds[0], NULL, out_fd, NULL, 64, SPLICE_F_NONBLOCK) >= 0 ||
+        errno != EAGAIN) {
+        printf("ERROR: splice from an empty pipe does not fail with EAGAIN\n");
+        return -1;
+    }
+    // A pipe has no offset
+    off_t offset = 0;
+    if (splice(pipe_fds[0], &offset, out_fd, NULL, 64, 0) >= 0 || errno != ESPIPE) {
+        printf("ERROR: splice with the offset of a pipe does not fail with ESPIPE\n");
+        return -1;
+    }
+    // One of the files must be a pipe
+    if (splice(in_fd, NULL, out_fd, NULL, 64, 0) >= 0 || errno != EINVAL) {
+        printf("ERROR: splice between files does not fail with EINVAL\n");
+        return -1;
+    }
+    offset = -1;
+    if (splice(in_fd, &offset, pipe_fds[1], NULL, 64, 0) >= 0 || errno != EINVAL) {
+        printf("ERROR: splice with a negative offset does not fail with EINVAL\n");
+        return -1;
+    }
+
+    // EOF after the writer is closed
+    close(pipe_fds[1]);
+    if (splice(pipe_fds[0], NULL, out_fd, NULL, 64, 0) != 0) {
+        printf("ERROR: splice from a closed pipe does not return EOF\n");
+        return -1;
+    }
+    close(in_fd);
+    close(out_fd);
+    close(pipe_fds[0]);
+    return 0;
+}
+
+static int test_tee(void) {
+    int pipe_fds0[2], pipe_fds1[2];
+    if (pipe(pipe_fds0) < 0 || pipe(pipe_fds1) < 0) {
+        printf("ERROR: failed to create pipes\n");
+        return -1;
+    }
+    write(pipe_fds0[1], write_msg, strlen(write_msg));
+    if (tee(pipe_fds0[0], pipe_fds1[1], 5, 0) != 5 ||
+        check_pipe(pipe_fds1[0], "Hello") < 0) {
+        printf("ERROR: failed to tee\n");
+        return -1;
+    }
+    // The data in the input pipe is not consumed
+    if (check_pipe(pipe_fds0[0], write_msg) < 0) {
+        return -1;
+    }
+    if (tee(pipe_fds0[0], pipe_fds1[1], 5, SPLICE_F_NONBLOCK) >= 0 || errno != EAGAIN) {
+        printf("ERROR: tee from an empty pipe does not fail with EAGAIN\n");
+        return -1;
+    }
+    if (tee(pipe_fds0[0], pipe_fds0[1], 5, SPLICE_F_NONBLOCK) >= 0 || errno != EINVAL) {
+        printf("ERROR: tee from a pipe to itself does not fail with EINVAL\n");
+        return -1;
+    }
+    close(pipe_fds0[0]);
+    close(pipe_fds0[1]);
+    close(pipe_fds1[0]);
+    close(pipe_fds1[1]);
+    return 0;
+}
+
+static int test_copy_file_range(void) {
+    int in_fd = create_file("/tmp/splice_in.txt", write_msg, strlen(write_msg));
+    if (in_fd < 0) {
+        return -1;
+    }
+    int out_fd = create_file("/tmp/splice_out.txt", "", 0);
+    if (out_fd < 0) {
+        close(in_fd);
+        return -1;
+    }
+    off_t off_in = 6, off_out = 0;
+    if (copy_file_range(in_fd, &off_in, out_fd, &off_out, 64, 0) != strlen(write_msg) - 6 ||
+        off_in != strlen(write_msg) || off_out != strlen(write_msg) - 6 ||
+        check_fd_content(out_fd, 0, write_msg + 6, strlen(write_msg) - 6) < 0) {
+        printf("ERROR: failed to copy_file_range with offsets\n");
+        return -1;
+    }
+    // Without the offsets, the file positions are used
+    if (copy_file_range(in_fd, NULL, out_fd, NULL, 5, 0) != 5 ||
+        lseek(in_fd, 0, SEEK_CUR) != 5 || lseek(out_fd, 0, SEEK_CUR) != 5 ||
+        check_fd_content(out_fd, 0, "Hello\n", 6) < 0) {
+        printf("ERROR: failed to copy_file_range from the file positions\n");
+        return -1;
+    }
+    off_in = -1;
+    if (copy_file_range(in_fd, &off_in, out_fd, NULL, 5, 0) >= 0 || errno != EINVAL) {
+        printf("ERROR: copy_file_range with a negative offset does not fail with EINVAL\n");
+        return -1;
+    }
+    close(in_fd);
+    close(out_fd);
+    return 0;
+}
+
+int main(int argc, const char* argv[]) {
+    if (test_sendfile() < 0 ||
+        test_splice() < 0 ||
+        test_tee() < 0 ||
+        test_copy_file_range() < 0) {
+        return -1;
+    }
+    printf("Splice works successfully\n");
+    return 0;
+}
@@ -0,0 +1,76 @@
+#include <sys/types.h>
+#include <sys/stat.h>
+#include <fcntl.h>
+#include <unistd.h>
+#include <string.h>
+#include <stdio.h>
+
+static int test_file_stat(const char* file_name) {
+    const char* write_msg = "Hello World\n";
+    struct stat stat_buf;
+    int fd;
+
+    if ((fd = open(file_name, O_WRONLY | O_CREAT | O_TRUNC, 0666)) < 0) {
+        printf("ERROR: failed to create %s\n", file_name);
+        return -1;
+    }
+    if (write(fd, write_msg, strlen(write_msg)) != strlen(write_msg)) {
+        printf("ERROR: failed to write to %s\n", file_name);
+        return -1;
+    }
+    if (fstat(fd, &stat_buf) < 0 || !S_ISREG(stat_buf.st_mode) ||
+        stat_buf.st_size != strlen(write_msg)) {
+        printf("ERROR: fstat returns wrong metadata for %s\n", file_name);
+        return -1;
+    }
+    close(fd);
+
+    if (stat(file_name, &stat_buf) < 0 || !S_ISREG(stat_buf.st_mode) ||
+        stat_buf.st_size != strlen(write_msg)) {
+        printf("ERROR: stat returns wrong metadata for %s\n", file_name);
+        return -1;
+    }
+    if (fstatat(AT_FDCWD, file_name, &stat_buf, 0) < 0 || stat_buf.st_size != strlen(write_msg)) {
+        printf("ERROR: fstatat returns wrong metadata for %s\n", file_name);
+        return -1;
+    }
+    return 0;
+}
+
+int main(int argc, const char* argv[]) {
+    struct stat stat_buf;
+    int pipe_fds[2];
+
+    // A file in tmpfs and a protected file
+    if (test_file_stat("/tmp/stat_test.txt") < 0 || test_file_stat("stat_test.txt") < 0) {
+        return -1;
+    }
+
+    if (stat("/tmp", &stat_buf) < 0 || !S_ISDIR(stat_buf.st_mode)) {
+        printf("ERROR: stat returns wrong metadata for a directory\n");
+        return -1;
+    }
+    if (stat("/tmp/non_existing.txt", &stat_buf) == 0) {
+        printf("ERROR: stat succeeds on a non-existing file\n");
+        return -1;
+    }
+
+    if (pipe(pipe_fds) < 0) {
+        printf("ERROR: failed to create a pipe\n");
+        return -1;
+    }
+    if (fstat(pipe_fds[0], &stat_buf) < 0 || !S_ISFIFO(stat_buf.st_mode)) {
+        printf("ERROR: fstat returns wrong metadata for a pipe\n");
+        return -1;
+    }
+    close(pipe_fds[0]);
+    close(pipe_fds[1]);
+
+    if (fstat(STDOUT_FILENO, &stat_buf) < 0 || !S_ISCHR(stat_buf.st_mode)) {
+        printf("ERROR: fstat returns wrong metadata for stdout\n");
+        return -1;
+    }
+
+    printf("Stat succeeds\n");
+    return 0;
+}
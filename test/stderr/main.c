@@ -0,0 +1,48 @@
+#include <sys/types.h>
+#include <sys/stat.h>
+#include <sys/uio.h>
+#include <unistd.h>
+#include <string.h>
+#include <errno.h>
+#include <stdio.h>
+
+int main(int argc, const char* argv[]) {
+    const char* msg = "This message is written to stderr\n";
+    char part0[] = "This message is written to ";
+    char part1[] = "stderr by writev\n";
+    struct iovec iov[2] = {
+        { .iov_base = part0, .iov_len = strlen(part0) },
+        { .iov_base = part1, .iov_len = strlen(part1) },
+    };
+    struct stat stat_buf;
+    char buf[16];
+
+    if (write(STDERR_FILENO, msg, strlen(msg)) != strlen(msg)) {
+        printf("ERROR: failed to write to stderr\n");
+        return -1;
+    }
+    if (writev(STDERR_FILENO, iov, 2) != strlen(part0) + strlen(part1)) {
+        printf("ERROR: failed to writev to stderr\n");
+        return -1;
+    }
+    if (fprintf(stderr, "This message is written to stderr by %s\n", "fprintf") < 0) {
+        printf("ERROR: failed to fprintf to stderr\n");
+        return -1;
+    }
+
+    if (read(STDERR_FILENO, buf, sizeof(buf)) >= 0 || errno != EBADF) {
+        printf("ERROR: read from stderr does not fail with EBADF\n");
+        return -1;
+    }
+    if (lseek(STDERR_FILENO, 0, SEEK_SET) >= 0 || errno != ESPIPE) {
+        printf("ERROR: lseek on stderr does not fail with ESPIPE\n");
+        return -1;
+    }
+    if (fstat(STDERR_FILENO, &stat_buf) < 0 || !S_ISCHR(stat_buf.st_mode)) {
+        printf("ERROR: stderr is not a character device\n");
+        return -1;
+    }
+
+    printf("Stderr works successfully\n");
+    return 0;
+}
@@ -15,7 +15,7 @@ READELF_FILE := bin.readelf
 CLANG_BIN_PATH := $(shell clang -print-prog-name=clang)
 LLVM_PATH := $(abspath $(dir $(CLANG_BIN_PATH))../)
 
-C_FLAGS = -Wall -O0 $(EXTRA_C_FLAGS)
+C_FLAGS = -Wall -O0 -I$(CUR_DIR)/../include $(EXTRA_C_FLAGS)
 C_FLAGS += -Xclang -load -Xclang $(LLVM_PATH)/lib/LLVMMDSFIIRInserter.so
 LINK_FLAGS = $(C_FLAGS) $(EXTRA_LINK_FLAGS)
 
@@ -0,0 +1,74 @@
+#include <sys/types.h>
+#include <sys/stat.h>
+#include <fcntl.h>
+#include <unistd.h>
+#include <string.h>
+#include <stdio.h>
+
+int main(int argc, const char* argv[]) {
+    const char* file_name = "/tmp/tmpfs_test.txt";
+    int fd, flags, mode, len;
+    const char* write_msg = "Hello World\n";
+    const char* append_msg = "Hello Again\n";
+    char read_buf[128] = {0};
+
+    flags = O_WRONLY | O_CREAT| O_TRUNC;
+    mode = 00666;
+    if ((fd = open(file_name, flags, mode)) < 0) {
+        printf("ERROR: failed to open a file in tmpfs for write\n");
+        return -1;
+    }
+    if ((len = write(fd, write_msg, strlen(write_msg))) != strlen(write_msg)) {
+        printf("ERROR: failed to write to the file\n");
+        return -1;
+    }
+    close(fd);
+
+    flags = O_WRONLY | O_APPEND;
+    if ((fd = open(file_name, flags)) < 0) {
+        printf("ERROR: failed to open a file in tmpfs for append\n");
+        return -1;
+    }
+    if ((len = write(fd, append_msg, strlen(append_msg))) != strlen(append_msg)) {
+        printf("ERROR: failed to append to the file\n");
+        return -1;
+    }
+    close(fd);
+
+    flags = O_RDONLY;
+    if ((fd = open(file_name, flags)) < 0) {
+        printf("ERROR: failed to open a file in tmpfs for read\n");
+        return -1;
+    }
+    if ((len = read(fd, read_buf, sizeof(read_buf) - 1)) <= 0) {
+        printf("ERROR: failed to read from the file\n");
+        return -1;
+    }
+    close(fd);
+
+    if (strncmp(write_msg, read_buf, strlen(write_msg)) != 0 ||
+        strcmp(append_msg, read_buf + strlen(write_msg)) != 0) {
+        printf("ERROR: the message read from the file is not as it was written\n");
+        return -1;
+    }
+
+    flags = O_WRONLY | O_TRUNC;
+    if ((fd = open(file_name, flags)) < 0) {
+        printf("ERROR: failed to open a file in tmpfs for truncate\n");
+        return -1;
+    }
+    close(fd);
+    if ((fd = open(file_name, O_RDONLY)) < 0 || read(fd, read_buf, 1) != 0) {
+        printf("ERROR: the file is not truncated\n");
+        return -1;
+    }
+    close(fd);
+
+    if (open("/tmp/non_existing.txt", O_RDONLY) >= 0) {
+        printf("ERROR: opened a non-existing file in tmpfs\n");
+        return -1;
+    }
+
+    printf("TmpFS write and read succesfully\n");
+    return 0;
+}
@@ -0,0 +1,106 @@
+#define _GNU_SOURCE
+#include <sys/types.h>
+#include <sys/stat.h>
+#include <fcntl.h>
+#include <unistd.h>
+#include <stdint.h>
+#include <string.h>
+#include <errno.h>
+#include <stdio.h>
+
+static const char* write_msg = "Hello World\n";
+
+static int check_file_size(const char* file_name, off_t expected_size) {
+    struct stat stat_buf;
+    if (stat(file_name, &stat_buf) < 0 || stat_buf.st_size != expected_size) {
+        printf("ERROR: the size of %s is not %ld\n", file_name, (long)expected_size);
+        return -1;
+    }
+    return 0;
+}
+
+static int check_fd_size(int fd, off_t expected_size) {
+    struct stat stat_buf;
+    if (fstat(fd, &stat_buf) < 0 || stat_buf.st_size != expected_size) {
+        printf("ERROR: the size of fd %d is not %ld\n", fd, (long)expected_size);
+        return -1;
+    }
+    return 0;
+}
+
+static int test_truncate(const char* file_name) {
+    char read_buf[128] = {0};
+    int fd;
+
+    if ((fd = open(file_name, O_RDWR | O_CREAT | O_TRUNC, 0666)) < 0) {
+        printf("ERROR: failed to create %s\n", file_name);
+        return -1;
+    }
+    if (write(fd, write_msg, strlen(write_msg)) != strlen(write_msg)) {
+        printf("ERROR: failed to write to %s\n", file_name);
+        return -1;
+    }
+
+    // Shrink the file
+    if (ftruncate(fd, 5) < 0 || check_fd_size(fd, 5) < 0) {
+        printf("ERROR: failed to shrink %s\n", file_name);
+        return -1;
+    }
+    // Extend the file, which should be filled with zeros
+    if (ftruncate(fd, 8) < 0 || check_fd_size(fd, 8) < 0) {
+        printf("ERROR: failed to extend %s\n", file_name);
+        return -1;
+    }
+    if (lseek(fd, 0, SEEK_SET) != 0 || read(fd, read_buf, sizeof(read_buf)) != 8 ||
+        strncmp(read_buf, write_msg, 5) != 0 || memcmp(read_buf + 5, "\0\0\0", 3) != 0) {
+        printf("ERROR: the content of %s is wrong after truncate\n", file_name);
+        return -1;
+    }
+
+    if (fallocate(fd, 0, 0, 16) < 0 || check_fd_size(fd, 16) < 0) {
+        printf("ERROR: failed to allocate space for %s\n", file_name);
+        return -1;
+    }
+    if (fallocate(fd, FALLOC_FL_KEEP_SIZE, 0, 32) < 0 || check_fd_size(fd, 16) < 0) {
+        printf("ERROR: fallocate with FALLOC_FL_KEEP_SIZE changes the size of %s\n", file_name);
+        return -1;
+    }
+    // A range that overflows or is beyond the max file size
+    if (fallocate(fd, 0, INT64_MAX - 8, 16) == 0 || errno != EFBIG ||
+        ftruncate(fd, (off_t)1 << 62) == 0 || errno != EFBIG || check_fd_size(fd, 16) < 0) {
+        printf("ERROR: extending %s beyond the max file size does not fail with EFBIG\n", file_name);
+        return -1;
+    }
+    close(fd);
+
+    // O_CREAT without O_TRUNC keeps the content
+    if ((fd = open(file_name, O_WRONLY | O_CREAT, 0666)) < 0 || check_fd_size(fd, 16) < 0) {
+        printf("ERROR: opening %s with O_CREAT changes its size\n", file_name);
+        return -1;
+    }
+    close(fd);
+    if ((fd = open(file_name, O_WRONLY | O_TRUNC)) < 0 || check_fd_size(fd, 0) < 0) {
+        printf("ERROR: opening %s with O_TRUNC does not truncate it\n", file_name);
+        return -1;
+    }
+    close(fd);
+
+    if (truncate(file_name, 4) < 0 || check_file_size(file_name, 4) < 0) {
+        printf("ERROR: failed to truncate %s by path\n", file_name);
+        return -1;
+    }
+    if (truncate(file_name, -1) == 0) {
+        printf("ERROR: truncated %s to a negative length\n", file_name);
+        return -1;
+    }
+    return 0;
+}
+
+int main(int argc, const char* argv[]) {
+    // A file in tmpfs and a protected file
+    if (test_truncate("/tmp/truncate_test.txt") < 0 || test_truncate("truncate_test.txt") < 0) {
+        return -1;
+    }
+    printf("Truncate and fallocate work successfully\n");
+    return 0;
+}
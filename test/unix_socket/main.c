@@ -0,0 +1,236 @@
+#define _GNU_SOURCE
+#include <sys/socket.h>
+#include <sys/un.h>
+#include <sys/wait.h>
+#include <poll.h>
+#include <spawn.h>
+#include <stddef.h>
+#include <unistd.h>
+#include <string.h>
+#include <errno.h>
+#include <stdio.h>
+
+static const char* write_msg = "Hello World\n";
+
+static int check_transfer(int sender_fd, int receiver_fd) {
+    char buf[32] = { 0 };
+    if (send(sender_fd, write_msg, strlen(write_msg), 0) != strlen(write_msg) ||
+        recv(receiver_fd, buf, sizeof(buf), 0) != strlen(write_msg) ||
+        strcmp(buf, write_msg) != 0) {
+        printf("ERROR: the message received is not the one sent\n");
+        return -1;
+    }
+    return 0;
+}
+
+static socklen_t init_addr(struct sockaddr_un* addr, const char* name, int is_abstract) {
+    memset(addr, 0, sizeof(*addr));
+    addr->sun_family = AF_UNIX;
+    // An abstract name starts with a null byte, and is not null-terminated
+    strcpy(addr->sun_path + is_abstract, name);
+    return offsetof(struct sockaddr_un, sun_path) + is_abstract + strlen(name) + !is_abstract;
+}
+
+static int test_stream_pair(void) {
+    int sv[2];
+    if (socketpair(AF_UNIX, SOCK_STREAM, 0, sv) < 0) {
+        printf("ERROR: failed to create a pair of stream sockets\n");
+        return -1;
+    }
+    if (check_transfer(sv[0], sv[1]) < 0 ||
+        check_transfer(sv[1], sv[0]) < 0) {
+        return -1;
+    }
+
+    // The peer reads EOF after shutdown, and EPIPE after close
+    char buf[32];
+    shutdown(sv[0], SHUT_WR);
+    if (read(sv[1], buf, sizeof(buf)) != 0) {
+        printf("ERROR: read does not return EOF after shutdown\n");
+        return -1;
+    }
+    close(sv[1]);
+    if (send(sv[0], write_msg, strlen(write_msg), MSG_NOSIGNAL) >= 0 || errno != EPIPE) {
+        printf("ERROR: send does not fail with EPIPE after the peer is closed\n");
+        return -1;
+    }
+    close(sv[0]);
+    return 0;
+}
+
+static int test_dgram_pair(void) {
+    int sv[2];
+    if (socketpair(AF_UNIX, SOCK_DGRAM | SOCK_NONBLOCK, 0, sv) < 0) {
+        printf("ERROR: failed to create a pair of datagram sockets\n");
+        return -1;
+    }
+
+    // The boundaries of the messages are kept, and the rest of a message that
+    // does not fit is discarded
+    char buf[32];
+    if (send(sv[0], "ab", 2, 0) != 2 ||
+        send(sv[0], "cdefg", 5, 0) != 5 ||
+        recv(sv[1], buf, sizeof(buf), 0) != 2 ||
+        recv(sv[1], buf, 3, 0) != 3 ||
+        memcmp(buf, "cde", 3) != 0) {
+        printf("ERROR: the messages received are not the ones sent\n");
+        return -1;
+    }
+    if (recv(sv[1], buf, sizeof(buf), 0) >= 0 || errno != EAGAIN) {
+        printf("ERROR: recv does not fail with EAGAIN without messages\n");
+        return -1;
+    }
+    close(sv[0]);
+    close(sv[1]);
+    return 0;
+}
+
+static int test_stream_connect(const char* name, int is_abstract) {
+    struct sockaddr_un addr;
+    socklen_t addr_len = init_addr(&addr, name, is_abstract);
+    int listen_fd = socket(AF_UNIX, SOCK_STREAM | SOCK_NONBLOCK, 0);
+    if (listen_fd < 0 ||
+        bind(listen_fd, (struct sockaddr*)&addr, addr_len) < 0 ||
+        listen(listen_fd, 2) < 0) {
+        printf("ERROR: failed to listen on a stream socket\n");
+        return -1;
+    }
+    if (accept(listen_fd, NULL, NULL) >= 0 || errno != EAGAIN) {
+        printf("ERROR: accept does not fail with EAGAIN without connections\n");
+        return -1;
+    }
+    int other_fd = socket(AF_UNIX, SOCK_STREAM, 0);
+    if (bind(other_fd, (struct sockaddr*)&addr, addr_len) >= 0 || errno != EADDRINUSE) {
+        printf("ERROR: binding the same name does not fail with EADDRINUSE\n");
+        return -1;
+    }
+    close(other_fd);
+
+    int client_fd = socket(AF_UNIX, SOCK_STREAM, 0);
+    if (client_fd < 0 || connect(client_fd, (struct sockaddr*)&addr, addr_len) < 0) {
+        printf("ERROR: failed to connect to the listening socket\n");
+        return -1;
+    }
+    struct pollfd poll_fd = { .fd = listen_fd, .events = POLLIN };
+    if (poll(&poll_fd, 1, -1) != 1 || (poll_fd.revents & POLLIN) == 0) {
+        printf("ERROR: poll does not report the pending connection\n");
+        return -1;
+    }
+    int server_fd = accept4(listen_fd, NULL, NULL, SOCK_CLOEXEC);
+    if (server_fd < 0) {
+        printf("ERROR: failed to accept the connection\n");
+        return -1;
+    }
+
+    struct sockaddr_un peer_addr;
+    socklen_t peer_addr_len = sizeof(peer_addr);
+    if (getpeername(client_fd, (struct sockaddr*)&peer_addr, &peer_addr_len) < 0 ||
+        peer_addr_len != addr_len || memcmp(&peer_addr, &addr, addr_len) != 0) {
+        printf("ERROR: getpeername does not return the address connected to\n");
+        return -1;
+    }
+    if (check_transfer(client_fd, server_fd) < 0 ||
+        check_transfer(server_fd, client_fd) < 0) {
+        return -1;
+    }
+    close(client_fd);
+    close(server_fd);
+
+    // The name is released once the socket is closed
+    close(listen_fd);
+    client_fd = socket(AF_UNIX, SOCK_STREAM, 0);
+    int expected_errno = is_abstract ? ECONNREFUSED : ENOENT;
+    if (connect(client_fd, (struct sockaddr*)&addr, addr_len) >= 0 || errno != expected_errno) {
+        printf("ERROR: connecting to a closed socket does not fail as expected\n");
+        return -1;
+    }
+    close(client_fd);
+    return 0;
+}
+
+static int test_dgram_sendto(void) {
+    struct sockaddr_un addr, sender_addr, src_addr;
+    socklen_t addr_len = init_addr(&addr, "unix_socket_receiver", 1);
+    socklen_t sender_addr_len = init_addr(&sender_addr, "unix_socket_sender", 1);
+    int receiver_fd = socket(AF_UNIX, SOCK_DGRAM, 0);
+    int sender_fd = socket(AF_UNIX, SOCK_DGRAM, 0);
+    if (receiver_fd < 0 || sender_fd < 0 ||
+        bind(receiver_fd, (struct sockaddr*)&addr, addr_len) < 0 ||
+        bind(sender_fd, (struct sockaddr*)&sender_addr, sender_addr_len) < 0) {
+        printf("ERROR: failed to bind the datagram sockets\n");
+        return -1;
+    }
+
+    char buf[32] = { 0 };
+    socklen_t src_addr_len = sizeof(src_addr);
+    if (sendto(sender_fd, write_msg, strlen(write_msg), 0,
+            (struct sockaddr*)&addr, addr_len) != strlen(write_msg) ||
+        recvfrom(receiver_fd, buf, sizeof(buf), 0,
+            (struct sockaddr*)&src_addr, &src_addr_len) != strlen(write_msg) ||
+        strcmp(buf, write_msg) != 0) {
+        printf("ERROR: the message received is not the one sent\n");
+        return -1;
+    }
+    if (src_addr_len != sender_addr_len ||
+        memcmp(&src_addr, &sender_addr, sender_addr_len) != 0) {
+        printf("ERROR: recvfrom does not return the address of the sender\n");
+        return -1;
+    }
+    close(sender_fd);
+    close(receiver_fd);
+    return 0;
+}
+
+static int test_spawn(void) {
+    int sv[2];
+    if (socketpair(AF_UNIX, SOCK_STREAM, 0, sv) < 0) {
+        printf("ERROR: failed to create a pair of stream sockets\n");
+        return -1;
+    }
+
+    // The child process writes to the socket as its stdout
+    posix_spawn_file_actions_t file_actions;
+    posix_spawn_file_actions_init(&file_actions);
+    posix_spawn_file_actions_adddup2(&file_actions, sv[1], STDOUT_FILENO);
+    posix_spawn_file_actions_addclose(&file_actions, sv[0]);
+    const char* child_prog = "hello_world/bin.encrypted";
+    const char* child_argv[2] = { child_prog, NULL };
+    int child_pid;
+    if (posix_spawn(&child_pid, child_prog, &file_actions,
+            NULL, (char* const*)child_argv, NULL) < 0) {
+        printf("ERROR: failed to spawn a child process\n");
+        return -1;
+    }
+    close(sv[1]);
+
+    const char* expected_msg = "Hello World!\n";
+    char buf[32] = { 0 };
+    int read_len = 0, this_len;
+    while ((this_len = read(sv[0], buf + read_len, sizeof(buf) - 1 - read_len)) > 0) {
+        read_len += this_len;
+    }
+    if (strcmp(buf, expected_msg) != 0) {
+        printf("ERROR: the output of the child process is not received\n");
+        return -1;
+    }
+    int status;
+    if (wait4(child_pid, &status, 0, NULL) < 0) {
+        printf("ERROR: failed to wait4 the child process\n");
+        return -1;
+    }
+    close(sv[0]);
+    return 0;
+}
+
+int main(int argc, const char* argv[]) {
+    if (test_stream_pair() < 0 ||
+        test_dgram_pair() < 0 ||
+        test_stream_connect("/tmp/unix_socket_test", 0) < 0 ||
+        test_stream_connect("unix_socket_test", 1) < 0 ||
+        test_dgram_sendto() < 0 ||
+        test_spawn() < 0) {
+        return -1;
+    }
+    printf("Unix socket works successfully\n");
+    return 0;
+}
@@ -0,0 +1,57 @@
+#!/usr/bin/env python3
+#
+# Generate the manifest of a HostFS, i.e., a read-only file system whose files
+# are stored as plain files on the host but checked against SHA-256 hashes
+# inside the enclave.
+#
+# Usage: gen_hostfs_manifest.py <host dir> <output manifest>
+#
+# The SHA-256 hash of the generated manifest is printed, which should be given
+# as the manifest_hash option of the hostfs mount in libos.conf.
+
+import hashlib
+import os
+import sys
+
+# Must be in sync with HOSTFS_BLOCK_SIZE in src/libos/src/fs/host_fs.rs
+BLOCK_SIZE = 4096
+
+
+def gen_manifest_line(root_dir, rel_path):
+    block_hashes = []
+    size = 0
+    with open(os.path.join(root_dir, rel_path), "rb") as f:
+        while True:
+            block = f.read(BLOCK_SIZE)
+            if not block:
+                break
+            size += len(block)
+            block_hashes.append(hashlib.sha256(block).hexdigest())
+    return " ".join([rel_path, str(size)] + block_hashes) + "\n"
+
+
+def main():
+    if len(sys.argv) != 3:
+        print("Usage: {} <host dir> <output manifest>".format(sys.argv[0]))
+        return 1
+    root_dir, manifest_path = sys.argv[1], sys.argv[2]
+
+    lines = []
+    for dir_path, dir_names, file_names in os.walk(root_dir):
+        dir_names.sort()
+        for file_name in sorted(file_names):
+            rel_path = os.path.relpath(os.path.join(dir_path, file_name), root_dir)
+            if " " in rel_path:
+                print("ERROR: file names with spaces are not supported: " + rel_path)
+                return 1
+            lines.append(gen_manifest_line(root_dir, rel_path))
+
+    manifest = "".join(lines).encode()
+    with open(manifest_path, "wb") as f:
+        f.write(manifest)
+    print(hashlib.sha256(manifest).hexdigest())
+    return 0
+
+
+if __name__ == "__main__":
+    sys.exit(main())